@@ -102,6 +102,7 @@ export default defineConfig({
               { text: "Type Aliases", link: "/reference/schema/type-aliases" },
               { text: "Modules", link: "/reference/schema/modules" },
               { text: "Imports", link: "/reference/schema/imports" },
+              { text: "Services", link: "/reference/schema/services" },
               // { text: "References", link: "/reference/schema/references" },
               { text: "Attributes", link: "/reference/schema/attributes" },
             ],
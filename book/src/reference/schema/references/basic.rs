@@ -0,0 +1,6 @@
+// #region snippet
+struct Sample<'a> {
+    name: &'a str,
+    payload: &'a [u8],
+}
+// #endregion snippet
@@ -0,0 +1,21 @@
+package main
+
+import "context"
+
+func main() {}
+
+type GetUser struct{}
+type User struct{}
+
+// #region snippet
+// Access to the stored users.
+type Users interface {
+	// Fetch a single user by its ID.
+	Get(ctx context.Context, request GetUser) (User, error)
+	// Watch for changes of any user.
+	Watch(ctx context.Context, request uint64) (<-chan User, error)
+	// Store many users at once, and return the amount.
+	Import(ctx context.Context, requests <-chan User) (uint64, error)
+}
+
+// #endregion snippet
@@ -0,0 +1,34 @@
+use std::{future::Future, marker::PhantomData};
+
+pub struct GetUser;
+pub struct User;
+pub struct Error;
+pub struct BoxStream<T>(PhantomData<T>);
+pub trait Transport {}
+pub trait Handler {}
+
+// #region snippet
+/// Access to the stored users.
+pub trait Users: Send + Sync {
+    /// Fetch a single user by its ID.
+    fn get(&self, request: GetUser) -> impl Future<Output = Result<User, Error>> + Send;
+    /// Watch for changes of any user.
+    fn watch(&self, request: u64) -> impl Future<Output = Result<BoxStream<User>, Error>> + Send;
+    /// Store many users at once, and return the amount.
+    fn import(&self, requests: BoxStream<User>) -> impl Future<Output = Result<u64, Error>> + Send;
+}
+
+/// Client for the [`Users`] service, that sends calls through a transport.
+pub struct UsersClient<T> {
+    transport: T,
+}
+
+// impl<T: Transport> Users for UsersClient<T> { ... }
+
+/// Server for the [`Users`] service, that dispatches incoming calls to an implementation of it.
+pub struct UsersServer<S> {
+    service: S,
+}
+
+// impl<S: Users> Handler for UsersServer<S> { ... }
+// #endregion snippet
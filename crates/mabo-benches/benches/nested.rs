@@ -0,0 +1,132 @@
+#![expect(missing_docs)]
+
+use divan::{Bencher, black_box};
+use mabo::{
+    Encode, FieldEncoding, FieldId,
+    buf::{self, Size, SizeCache},
+};
+
+#[global_allocator]
+static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+fn main() {
+    divan::main();
+}
+
+/// Recursive message, written the same way as the generated code does it.
+struct Node {
+    name: String,
+    children: Vec<Node>,
+}
+
+/// Chain of nodes, where each node holds exactly one child, except for the last one.
+fn chain(depth: u32) -> Node {
+    (0..depth).fold(
+        Node {
+            name: "leaf".to_owned(),
+            children: Vec::new(),
+        },
+        |child, i| Node {
+            name: format!("node-{i}"),
+            children: vec![child],
+        },
+    )
+}
+
+/// Size calculation without the cache, where each vector sizes all of its nested content again.
+fn size_uncached(node: &Node) -> usize {
+    buf::size_field(1, || buf::size_string(&node.name))
+        + buf::size_field(2, || buf::size_vec(&node.children, size_uncached))
+        + buf::END_MARKER_SIZE
+}
+
+fn encode_uncached<W: buf::BufMut>(w: &mut W, node: &Node) {
+    buf::encode_field(w, FieldId::new(1, FieldEncoding::LengthPrefixed), |w| {
+        buf::encode_string(w, &node.name);
+    });
+    buf::encode_field(w, FieldId::new(2, FieldEncoding::LengthPrefixed), |w| {
+        buf::encode_vec(w, &node.children, size_uncached, encode_uncached);
+    });
+    buf::encode_u32(w, buf::END_MARKER);
+}
+
+impl Size for Node {
+    fn size(&self) -> usize {
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        buf::size_field(1, || buf::size_string(&self.name))
+            + buf::size_field(2, || {
+                buf::size_vec_cached(sizes, &self.children, |sizes, v| v.size_cached(sizes))
+            })
+            + buf::END_MARKER_SIZE
+    }
+}
+
+impl Encode for Node {
+    fn encode(&self, w: &mut impl buf::BufMut) {
+        buf::encode_sized(w, self);
+    }
+
+    fn encode_cached(&self, w: &mut impl buf::BufMut, sizes: &mut SizeCache) {
+        buf::encode_field(w, FieldId::new(1, FieldEncoding::LengthPrefixed), |w| {
+            buf::encode_string(w, &self.name);
+        });
+        buf::encode_field(w, FieldId::new(2, FieldEncoding::LengthPrefixed), |w| {
+            buf::encode_vec_cached(w, sizes, &self.children, |w, sizes, v| {
+                v.encode_cached(w, sizes);
+            });
+        });
+        buf::encode_u32(w, buf::END_MARKER);
+    }
+}
+
+#[divan::bench(args = [4, 32, 256])]
+fn encode_uncached_chain(bencher: Bencher<'_, '_>, depth: u32) {
+    let node = chain(depth);
+    let mut out = Vec::with_capacity(node.size());
+
+    bencher.bench_local(|| {
+        out.clear();
+        encode_uncached(&mut out, black_box(&node));
+    });
+}
+
+#[divan::bench(args = [4, 32, 256])]
+fn encode_cached_chain(bencher: Bencher<'_, '_>, depth: u32) {
+    let node = chain(depth);
+    let mut out = Vec::with_capacity(node.size());
+
+    bencher.bench_local(|| {
+        out.clear();
+        black_box(&node).encode(&mut out);
+    });
+}
+
+#[divan::bench(args = [4, 32, 256])]
+fn encode_cached_chain_reused(bencher: Bencher<'_, '_>, depth: u32) {
+    let node = chain(depth);
+    let mut out = Vec::with_capacity(node.size());
+    let mut sizes = SizeCache::new();
+
+    bencher.bench_local(|| {
+        out.clear();
+        sizes.clear();
+        let node = black_box(&node);
+        node.size_cached(&mut sizes);
+        node.encode_cached(&mut out, &mut sizes);
+    });
+}
+
+#[divan::bench(args = [4, 32, 256])]
+fn size_uncached_chain(bencher: Bencher<'_, '_>, depth: u32) {
+    let node = chain(depth);
+    bencher.bench(|| size_uncached(black_box(&node)));
+}
+
+#[divan::bench(args = [4, 32, 256])]
+fn size_cached_chain(bencher: Bencher<'_, '_>, depth: u32) {
+    let node = chain(depth);
+    bencher.bench(|| black_box(&node).size());
+}
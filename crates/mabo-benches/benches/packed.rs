@@ -0,0 +1,88 @@
+#![expect(missing_docs)]
+
+use divan::{Bencher, black_box};
+use mabo::buf::{self, Limits};
+
+#[global_allocator]
+static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+fn main() {
+    divan::main();
+}
+
+fn floats(n: u32) -> Vec<f64> {
+    (0..n).map(|i| f64::from(i) * 0.5).collect()
+}
+
+fn integers(n: u32) -> Vec<u32> {
+    (0..n).map(|i| i.wrapping_mul(2_654_435_761)).collect()
+}
+
+#[divan::bench(args = [16, 1024, 65536])]
+fn encode_f64_generic(bencher: Bencher<'_, '_>, n: u32) {
+    let values = floats(n);
+    let mut out = Vec::with_capacity(values.len() * 8 + 10);
+
+    bencher.bench_local(|| {
+        out.clear();
+        buf::encode_vec(
+            &mut out,
+            black_box(&values),
+            |v| buf::size_f64(*v),
+            |w, v| buf::encode_f64(w, *v),
+        );
+    });
+}
+
+#[divan::bench(args = [16, 1024, 65536])]
+fn encode_f64_packed(bencher: Bencher<'_, '_>, n: u32) {
+    let values = floats(n);
+    let mut out = Vec::with_capacity(values.len() * 8 + 10);
+
+    bencher.bench_local(|| {
+        out.clear();
+        buf::encode_vec_packed(&mut out, black_box(&values));
+    });
+}
+
+#[divan::bench(args = [16, 1024, 65536])]
+fn decode_f64_generic(bencher: Bencher<'_, '_>, n: u32) {
+    let mut data = Vec::new();
+    buf::encode_vec_packed(&mut data, &floats(n));
+
+    bencher.bench(|| {
+        buf::decode_vec(&mut black_box(&*data), &mut Limits::default(), |r, _| {
+            buf::decode_f64(r)
+        })
+    });
+}
+
+#[divan::bench(args = [16, 1024, 65536])]
+fn decode_f64_packed(bencher: Bencher<'_, '_>, n: u32) {
+    let mut data = Vec::new();
+    buf::encode_vec_packed(&mut data, &floats(n));
+
+    bencher.bench(|| buf::decode_vec_packed::<f64>(&mut black_box(&*data), &mut Limits::default()));
+}
+
+#[divan::bench(args = [16, 1024, 65536])]
+fn decode_u32_generic(bencher: Bencher<'_, '_>, n: u32) {
+    let mut data = Vec::new();
+    buf::encode_vec_packed(&mut data, &integers(n));
+
+    bencher.bench(|| {
+        buf::decode_vec(
+            &mut black_box(&*data),
+            &mut Limits::default(),
+            |r, limits| buf::decode_u32(r, limits),
+        )
+    });
+}
+
+#[divan::bench(args = [16, 1024, 65536])]
+fn decode_u32_packed(bencher: Bencher<'_, '_>, n: u32) {
+    let mut data = Vec::new();
+    buf::encode_vec_packed(&mut data, &integers(n));
+
+    bencher.bench(|| buf::decode_vec_packed::<u32>(&mut black_box(&*data), &mut Limits::default()));
+}
@@ -1,13 +1,16 @@
 use mabo_compiler::simplify::{
-    Enum, ExternalType, Field, FieldKind, Fields, Struct, Type, Variant,
+    Enum, ExternalType, Field, FieldKind, Fields, Literal, Struct, Type, Variant,
 };
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
+use quote::{ToTokens, format_ident, quote};
 
-use crate::{BytesType, Opts};
+use crate::{BytesType, Opts, TimestampType, UuidType, lifetimes::Scope, validate};
 
 pub(super) fn compile_struct(
     opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    unknown: bool,
     Struct {
         name,
         generics,
@@ -16,35 +19,47 @@ pub(super) fn compile_struct(
     }: &Struct<'_>,
 ) -> TokenStream {
     let name = Ident::new(name, Span::call_site());
-    let (generics, generics_where) = compile_generics(generics);
-    let field_vars = compile_field_vars(opts, &fields.fields);
-    let field_matches = compile_field_matches(opts, fields);
-    let field_assigns = compile_field_assigns(fields);
+    let (generics, generics_where) = compile_generics(borrows, generics);
+    let field_vars = compile_field_vars(opts, scope, &fields.fields, unknown);
+    let field_matches = compile_field_matches(opts, scope, borrows, fields, None);
+    let field_assigns = compile_field_assigns(fields, unknown);
+    let skip = compile_skip(unknown);
+    // Unknown fields are allocated and therefore count towards the limits as well.
+    let limited = unknown || fields_use_limits(fields);
 
     let body = if fields.kind == FieldKind::Unit {
         quote! { Ok(Self) }
     } else {
-        quote! {
-            #field_vars
-
-            loop {
-                let id = ::mabo::buf::decode_id(r)?;
-                match id.value {
-                    ::mabo::buf::END_MARKER => break,
-                    #field_matches
-                    _ => ::mabo::buf::decode_skip(r, id.encoding)?,
+        compile_nested(
+            limited,
+            &quote! {
+                #field_vars
+
+                loop {
+                    let id = ::mabo::buf::decode_id(r, limits)?;
+                    match id.value {
+                        ::mabo::buf::END_MARKER => break,
+                        #field_matches
+                        _ => #skip,
+                    }
                 }
-            }
 
-            Ok(Self #field_assigns)
-        }
+                Ok(Self #field_assigns)
+            },
+        )
     };
 
+    let body = compile_validated(opts.validate_on_decode && validate::has_rules(fields), body);
+
+    // Fields always need the limits, to apply the strict decoding checks.
+    let (decode_trait, decode_fn) = compile_signature(borrows, fields.kind != FieldKind::Unit);
+
     quote! {
         #[automatically_derived]
-        impl #generics ::mabo::Decode for #name #generics #generics_where {
+        #[allow(deprecated)]
+        impl #generics #decode_trait for #name #generics #generics_where {
             #[allow(clippy::type_complexity, clippy::too_many_lines)]
-            fn decode(r: &mut impl ::mabo::Buf) -> ::mabo::buf::Result<Self> {
+            #decode_fn {
                 #body
             }
         }
@@ -53,6 +68,9 @@ pub(super) fn compile_struct(
 
 pub(super) fn compile_enum(
     opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    unknown: bool,
     Enum {
         name,
         generics,
@@ -61,25 +79,59 @@ pub(super) fn compile_enum(
     }: &Enum<'_>,
 ) -> TokenStream {
     let name = Ident::new(name, Span::call_site());
-    let (generics, generics_where) = compile_generics(generics);
-    let variants = variants.iter().map(|v| compile_variant(opts, v));
+    let (generics, generics_where) = compile_generics(borrows, generics);
+    let validated =
+        opts.validate_on_decode && variants.iter().any(|v| validate::has_rules(&v.fields));
+    let variants = variants
+        .iter()
+        .map(|v| compile_variant(opts, scope, borrows, v));
+    // The variant identifier always needs the limits, to apply the strict decoding checks.
+    let (decode_trait, decode_fn) = compile_signature(borrows, true);
+
+    let body = if unknown {
+        quote! {
+            let variant = ::mabo::buf::decode_variant_id(r, limits)?;
+            match variant.value {
+                #(#variants,)*
+                id => ::mabo::buf::decode_unknown_variant(r, limits, variant)
+                    .map(|data| Self::Unknown { id, data }),
+            }
+        }
+    } else {
+        quote! {
+            match ::mabo::buf::decode_variant_id(r, limits)?.value {
+                #(#variants,)*
+                id => Err(::mabo::buf::Error::UnknownVariant(id)),
+            }
+        }
+    };
+    let body = compile_validated(validated, body);
 
     quote! {
         #[automatically_derived]
-        impl #generics ::mabo::Decode for #name #generics #generics_where {
+        #[allow(deprecated)]
+        impl #generics #decode_trait for #name #generics #generics_where {
             #[allow(clippy::too_many_lines)]
-            fn decode(r: &mut impl ::mabo::Buf) -> ::mabo::buf::Result<Self> {
-                match ::mabo::buf::decode_variant_id(r)?.value {
-                    #(#variants,)*
-                    id => Err(::mabo::buf::Error::UnknownVariant(id)),
-                }
+            #decode_fn {
+                #body
             }
         }
     }
 }
 
+/// Check the constraints of the decoded value, before handing it out.
+fn compile_validated(validated: bool, body: TokenStream) -> TokenStream {
+    if validated {
+        quote! { ::mabo::validate::validate_decoded({ #body }?) }
+    } else {
+        body
+    }
+}
+
 fn compile_variant(
     opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
     Variant {
         comment: _,
         name,
@@ -89,41 +141,70 @@ fn compile_variant(
     }: &Variant<'_>,
 ) -> TokenStream {
     let id = proc_macro2::Literal::u32_unsuffixed(*id);
+    let field_matches = compile_field_matches(opts, scope, borrows, fields, Some(name));
     let name = Ident::new(name, Span::call_site());
-    let field_vars = compile_field_vars(opts, &fields.fields);
-    let field_matches = compile_field_matches(opts, fields);
-    let field_assigns = compile_field_assigns(fields);
+    let field_vars = compile_field_vars(opts, scope, &fields.fields, false);
+    let field_assigns = compile_field_assigns(fields, false);
 
     if fields.kind == FieldKind::Unit {
         quote! { #id => Ok(Self::#name) }
     } else {
-        quote! {
-            #id => {
+        let body = compile_nested(
+            fields_use_limits(fields),
+            &quote! {
                 #field_vars
 
                 loop {
-                    let id = ::mabo::buf::decode_id(r)?;
+                    let id = ::mabo::buf::decode_id(r, limits)?;
                     match id.value {
                         ::mabo::buf::END_MARKER => break,
                         #field_matches
-                        _ => ::mabo::buf::decode_skip(r, id.encoding)?,
+                        _ => ::mabo::buf::decode_skip_field(r, limits, id)?,
                     }
                 }
 
                 Ok(Self::#name #field_assigns)
-            }
-        }
+            },
+        );
+
+        quote! { #id => { #body } }
+    }
+}
+
+/// Wrap the decoding of struct or enum variant fields into a new nesting level, so the maximum
+/// depth from the `Limits` is checked. That is only needed if any of the fields use the limits, as
+/// all other fields can't contain nested values.
+pub(super) fn compile_nested(limited: bool, body: &TokenStream) -> TokenStream {
+    if limited {
+        quote! { limits.nested(|limits| { #body }) }
+    } else {
+        body.clone()
+    }
+}
+
+/// Handling of fields that are unknown to the schema, which are either kept or skipped over (unless
+/// decoding in strict mode).
+fn compile_skip(unknown: bool) -> TokenStream {
+    if unknown {
+        quote! { { ::mabo::buf::decode_unknown_field(r, limits, id, &mut unknown_fields)?; } }
+    } else {
+        quote! { ::mabo::buf::decode_skip_field(r, limits, id)? }
     }
 }
 
-fn compile_field_vars(opts: &Opts, fields: &[Field<'_>]) -> TokenStream {
+fn compile_field_vars(
+    opts: &Opts,
+    scope: Scope<'_>,
+    fields: &[Field<'_>],
+    unknown: bool,
+) -> TokenStream {
     let vars = fields.iter().map(|field| {
         let name = Ident::new(&field.name, Span::call_site());
         (name, &field.ty)
     });
 
     let vars = vars.map(|(name, ty)| {
-        let ty_ident = super::definition::compile_data_type(opts, ty);
+        let ty_ident = super::definition::compile_data_type(opts, scope, ty);
 
         if matches!(ty, Type::Option(_)) {
             quote! { let mut #name: #ty_ident = None; }
@@ -132,153 +213,390 @@ fn compile_field_vars(opts: &Opts, fields: &[Field<'_>]) -> TokenStream {
         }
     });
 
-    quote! { #(#vars)* }
+    let unknown =
+        unknown.then(|| quote! { let mut unknown_fields = ::mabo::UnknownFields::new(); });
+
+    quote! {
+        #(#vars)*
+        #unknown
+    }
 }
 
-fn compile_field_matches(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
+/// Decoding of each known field, which adds the field (and enum variant) to the path of any
+/// decoding error. Fields that were already decoded before are rejected in strict mode.
+fn compile_field_matches(
+    opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    fields: &Fields<'_>,
+    variant: Option<&str>,
+) -> TokenStream {
+    let variant = variant.map(|variant| quote! { .with_variant(#variant) });
     let calls = fields.fields.iter().map(|Field { name, ty, id, .. }| {
         let id = proc_macro2::Literal::u32_unsuffixed(*id);
-        let name = proc_macro2::Ident::new(name, Span::call_site());
-        let ty = compile_data_type(opts, if let Type::Option(ty) = &ty { ty } else { ty }, true);
-
-        quote! { #id => #name = Some(#ty?) }
-    });
-
-    quote! { #(#calls,)* }
-}
-
-fn compile_field_assigns(fields: &Fields<'_>) -> TokenStream {
-    let assigns = fields.fields.iter().map(|Field { name, ty, id, .. }| {
         let name_lit = if fields.kind == FieldKind::Named {
             let lit = proc_macro2::Literal::string(name);
-            quote! { Some(#lit)}
+            quote! { Some(#lit) }
         } else {
             quote! { None }
         };
-        let name = Ident::new(name, Span::call_site());
-        let id = proc_macro2::Literal::u32_unsuffixed(*id);
+        let name = proc_macro2::Ident::new(name, Span::call_site());
+        let ty = compile_data_type(
+            opts,
+            scope,
+            borrows,
+            if let Type::Option(ty) = &ty { ty } else { ty },
+            true,
+        );
 
-        if matches!(ty, Type::Option(_)) {
-            quote! { #name }
-        } else if fields.kind == FieldKind::Named {
-            quote! {
-                #name: #name.ok_or(::mabo::buf::Error::MissingField {
-                    id: #id,
-                    name: #name_lit,
-                })?
-            }
-        } else {
-            quote! {
-                #name.ok_or(::mabo::buf::Error::MissingField {
-                   id: #id,
-                   name: #name_lit,
-               })?
+        quote! {
+            #id => {
+                limits.ensure_new_field(#id, #name.is_some())?;
+                #name = Some(#ty.map_err(|e| e.with_field(#id, #name_lit)#variant)?);
             }
         }
     });
 
+    quote! { #(#calls,)* }
+}
+
+fn compile_field_assigns(fields: &Fields<'_>, unknown: bool) -> TokenStream {
+    let assigns = fields.fields.iter().map(
+        |Field {
+             name,
+             ty,
+             default,
+             id,
+             ..
+         }| {
+            let name_lit = if fields.kind == FieldKind::Named {
+                let lit = proc_macro2::Literal::string(name);
+                quote! { Some(#lit)}
+            } else {
+                quote! { None }
+            };
+            let name = Ident::new(name, Span::call_site());
+            let id = proc_macro2::Literal::u32_unsuffixed(*id);
+
+            if matches!(ty, Type::Option(_)) {
+                quote! { #name }
+            } else if let Some(default) = default {
+                let value = compile_default(&name, default);
+                if fields.kind == FieldKind::Named {
+                    quote! { #name: #value }
+                } else {
+                    value
+                }
+            } else if fields.kind == FieldKind::Named {
+                quote! {
+                    #name: #name.ok_or(::mabo::buf::Error::MissingField {
+                        id: #id,
+                        name: #name_lit,
+                    })?
+                }
+            } else {
+                quote! {
+                    #name.ok_or(::mabo::buf::Error::MissingField {
+                       id: #id,
+                       name: #name_lit,
+                   })?
+                }
+            }
+        },
+    );
+
+    let unknown = unknown.then(|| quote! { unknown_fields, });
+
     if fields.kind == FieldKind::Named {
-        quote! { { #(#assigns,)* } }
+        quote! { { #(#assigns,)* #unknown } }
     } else {
-        quote! { (#(#assigns,)*) }
+        quote! { (#(#assigns,)* #unknown) }
+    }
+}
+
+/// Fall back to the default value of a field, if it was missing. Strings and bytes are only
+/// converted when needed, as they might allocate.
+pub(super) fn compile_default(name: impl ToTokens, default: &Literal) -> TokenStream {
+    let value = super::definition::compile_default(default);
+
+    match default {
+        Literal::String(_) | Literal::Bytes(_) => quote! { #name.unwrap_or_else(|| #value) },
+        Literal::Bool(_) | Literal::Int(_) | Literal::Float(_) => {
+            quote! { #name.unwrap_or(#value) }
+        }
     }
 }
 
-fn compile_generics(types: &[&str]) -> (TokenStream, TokenStream) {
+fn compile_generics(borrows: bool, types: &[&str]) -> (TokenStream, TokenStream) {
     if types.is_empty() {
-        Default::default()
+        let generics = if borrows {
+            quote! { <'a> }
+        } else {
+            TokenStream::new()
+        };
+        (generics, TokenStream::new())
     } else {
         let types = types.iter().map(|ty| Ident::new(ty, Span::call_site()));
         let types2 = types.clone();
 
+        if borrows {
+            (
+                quote! { <'a, #(#types,)*> },
+                quote! { where #(#types2: ::core::fmt::Debug + ::mabo::buf::BorrowDecode<'a>,)* },
+            )
+        } else {
+            (
+                quote! { <#(#types,)*> },
+                quote! { where #(#types2: ::core::fmt::Debug + ::mabo::buf::Decode,)* },
+            )
+        }
+    }
+}
+
+/// Trait and method signature of the decode implementation, which either decodes from any buffer,
+/// or borrows data from a byte slice if the type contains any `&string` or `&bytes` fields.
+fn compile_signature(borrows: bool, limited: bool) -> (TokenStream, TokenStream) {
+    let limits = compile_limits(limited);
+
+    if borrows {
         (
-            quote! { <#(#types,)*> },
-            quote! { where #(#types2: ::std::fmt::Debug + ::mabo::buf::Decode,)* },
+            quote! { ::mabo::BorrowDecode<'a> },
+            quote! {
+                fn borrow_decode_with(
+                    r: &mut &'a [u8],
+                    #limits: &mut ::mabo::buf::Limits,
+                ) -> ::mabo::buf::Result<Self>
+            },
         )
+    } else {
+        (
+            quote! { ::mabo::Decode },
+            quote! {
+                fn decode_with(
+                    r: &mut impl ::mabo::Buf,
+                    #limits: &mut ::mabo::buf::Limits,
+                ) -> ::mabo::buf::Result<Self>
+            },
+        )
+    }
+}
+
+/// Binding for the decoding limits, which is left unnamed if it's not used.
+fn compile_limits(limited: bool) -> TokenStream {
+    if limited {
+        quote! { limits }
+    } else {
+        quote! { _ }
+    }
+}
+
+pub(super) fn fields_use_limits(fields: &Fields<'_>) -> bool {
+    fields.fields.iter().any(|field| uses_limits(&field.ty))
+}
+
+/// Check whether decoding the given type requires access to the decoding limits, which is the case
+/// for any type that allocates memory or may contain nested structs and enums.
+pub(super) fn uses_limits(ty: &Type<'_>) -> bool {
+    match ty {
+        Type::Bool
+        | Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::U128
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::I128
+        | Type::F32
+        | Type::F64
+        | Type::StringRef
+        | Type::BytesRef
+        | Type::Timestamp
+        | Type::Duration
+        | Type::Uuid
+        | Type::Decimal => false,
+        Type::NonZero(ty) => !matches!(
+            **ty,
+            Type::U8
+                | Type::U16
+                | Type::U32
+                | Type::U64
+                | Type::U128
+                | Type::I8
+                | Type::I16
+                | Type::I32
+                | Type::I64
+                | Type::I128
+        ),
+        Type::Tuple(types) => types.iter().any(uses_limits),
+        Type::String
+        | Type::Bytes
+        | Type::Vec(_)
+        | Type::HashMap(_)
+        | Type::HashSet(_)
+        | Type::BTreeMap(_)
+        | Type::BTreeSet(_)
+        | Type::Option(_)
+        | Type::BoxString
+        | Type::BoxBytes
+        | Type::Array(_, _)
+        | Type::External(_) => true,
+    }
+}
+
+/// Check whether the decoding of the given type refers to the decoding limits at all. Besides the
+/// types from [`uses_limits`], this includes any type that contains _Varint_ integers or markers,
+/// as they're checked in strict mode.
+pub(super) fn refers_to_limits(ty: &Type<'_>) -> bool {
+    match ty {
+        Type::U8 | Type::I8 | Type::F32 | Type::F64 => false,
+        Type::NonZero(ty) => !matches!(**ty, Type::U8 | Type::I8),
+        _ => true,
     }
 }
 
 #[expect(clippy::too_many_lines)]
-fn compile_data_type(opts: &Opts, ty: &Type<'_>, root: bool) -> TokenStream {
+pub(super) fn compile_data_type(
+    opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    ty: &Type<'_>,
+    root: bool,
+) -> TokenStream {
+    // Nested values must be decoded from a byte slice as well, in case they borrow from it.
+    let suffix = if borrows { "_borrowed" } else { "" };
+
     match ty {
-        Type::Bool => quote! { ::mabo::buf::decode_bool(r) },
+        Type::Bool => quote! { ::mabo::buf::decode_bool(r, limits) },
         Type::U8 => quote! { ::mabo::buf::decode_u8(r) },
-        Type::U16 => quote! { ::mabo::buf::decode_u16(r) },
-        Type::U32 => quote! { ::mabo::buf::decode_u32(r) },
-        Type::U64 => quote! { ::mabo::buf::decode_u64(r) },
-        Type::U128 => quote! { ::mabo::buf::decode_u128(r) },
+        Type::U16 => quote! { ::mabo::buf::decode_u16(r, limits) },
+        Type::U32 => quote! { ::mabo::buf::decode_u32(r, limits) },
+        Type::U64 => quote! { ::mabo::buf::decode_u64(r, limits) },
+        Type::U128 => quote! { ::mabo::buf::decode_u128(r, limits) },
         Type::I8 => quote! { ::mabo::buf::decode_i8(r) },
-        Type::I16 => quote! { ::mabo::buf::decode_i16(r) },
-        Type::I32 => quote! { ::mabo::buf::decode_i32(r) },
-        Type::I64 => quote! { ::mabo::buf::decode_i64(r) },
-        Type::I128 => quote! { ::mabo::buf::decode_i128(r) },
+        Type::I16 => quote! { ::mabo::buf::decode_i16(r, limits) },
+        Type::I32 => quote! { ::mabo::buf::decode_i32(r, limits) },
+        Type::I64 => quote! { ::mabo::buf::decode_i64(r, limits) },
+        Type::I128 => quote! { ::mabo::buf::decode_i128(r, limits) },
         Type::F32 => quote! { ::mabo::buf::decode_f32(r) },
         Type::F64 => quote! { ::mabo::buf::decode_f64(r) },
-        Type::String | Type::StringRef => quote! { ::mabo::buf::decode_string(r) },
-        Type::Bytes | Type::BytesRef => match opts.bytes_type {
-            BytesType::VecU8 => quote! { ::mabo::buf::decode_bytes_std(r) },
-            BytesType::Bytes => quote! { ::mabo::buf::decode_bytes_bytes(r) },
+        Type::String => quote! { ::mabo::buf::decode_string(r, limits) },
+        Type::StringRef => quote! { ::mabo::buf::decode_string_borrowed(r, limits) },
+        Type::Bytes => match opts.bytes_type {
+            BytesType::VecU8 => quote! { ::mabo::buf::decode_bytes_std(r, limits) },
+            BytesType::Bytes => quote! { ::mabo::buf::decode_bytes_bytes(r, limits) },
+        },
+        Type::BytesRef => quote! { ::mabo::buf::decode_bytes_borrowed(r, limits) },
+        Type::Timestamp => match opts.timestamp_type {
+            TimestampType::SystemTime if opts.no_std => {
+                quote! { ::mabo::buf::decode_timestamp(r, limits) }
+            }
+            TimestampType::SystemTime => quote! { ::mabo::buf::decode_timestamp_std(r, limits) },
+            TimestampType::Chrono => quote! { ::mabo::buf::decode_timestamp_chrono(r, limits) },
+        },
+        Type::Duration => quote! { ::mabo::buf::decode_duration(r, limits) },
+        Type::Uuid => match opts.uuid_type {
+            UuidType::Bytes => quote! { ::mabo::buf::decode_uuid(r, limits) },
+            UuidType::Uuid => {
+                quote! { ::mabo::buf::decode_uuid(r, limits).map(::mabo::uuid::Uuid::from_bytes) }
+            }
         },
+        Type::Decimal => quote! { ::mabo::buf::decode_decimal(r, limits) },
+        Type::Vec(ty) if super::definition::is_packed(ty) => {
+            quote! { ::mabo::buf::decode_vec_packed(r, limits) }
+        }
         Type::Vec(ty) => {
-            let ty = compile_data_type(opts, ty, false);
-            quote! { ::mabo::buf::decode_vec(r, |r| { #ty }) }
+            let limits = compile_limits(refers_to_limits(ty));
+            let ty = compile_data_type(opts, scope, borrows, ty, false);
+            let decode = format_ident!("decode_vec{suffix}");
+            quote! { ::mabo::buf::#decode(r, limits, |r, #limits| { #ty }) }
         }
-        Type::HashMap(kv) => {
-            let ty_k = compile_data_type(opts, &kv.0, false);
-            let ty_v = compile_data_type(opts, &kv.1, false);
-            quote! { ::mabo::buf::decode_hash_map(r, |r| { #ty_k }, |r| { #ty_v }) }
+        Type::HashMap(kv) | Type::BTreeMap(kv) => {
+            let limits_k = compile_limits(refers_to_limits(&kv.0));
+            let limits_v = compile_limits(refers_to_limits(&kv.1));
+            let ty_k = compile_data_type(opts, scope, borrows, &kv.0, false);
+            let ty_v = compile_data_type(opts, scope, borrows, &kv.1, false);
+            let decode = format_ident!("decode_{}{suffix}", super::definition::collection_kind(ty));
+            quote! {
+                ::mabo::buf::#decode(r, limits, |r, #limits_k| { #ty_k }, |r, #limits_v| { #ty_v })
+            }
         }
-        Type::HashSet(ty) => {
-            let ty = compile_data_type(opts, ty, false);
-            quote! { ::mabo::buf::decode_hash_set(r, |r| { #ty }) }
+        Type::HashSet(inner) | Type::BTreeSet(inner) => {
+            let limits = compile_limits(refers_to_limits(inner));
+            let decode = format_ident!("decode_{}{suffix}", super::definition::collection_kind(ty));
+            let ty = compile_data_type(opts, scope, borrows, inner, false);
+            quote! { ::mabo::buf::#decode(r, limits, |r, #limits| { #ty }) }
         }
         Type::Option(ty) => {
-            let ty = compile_data_type(opts, ty, false);
-            quote! { ::mabo::buf::decode_option(r, |r| { #ty }) }
+            let limits = compile_limits(refers_to_limits(ty));
+            let ty = compile_data_type(opts, scope, borrows, ty, false);
+            quote! { ::mabo::buf::decode_option(r, limits, |r, #limits| { #ty }) }
         }
         Type::NonZero(ty) => match &**ty {
             Type::U8 => quote! { ::mabo::buf::decode_non_zero_u8(r) },
-            Type::U16 => quote! { ::mabo::buf::decode_non_zero_u16(r) },
-            Type::U32 => quote! { ::mabo::buf::decode_non_zero_u32(r) },
-            Type::U64 => quote! { ::mabo::buf::decode_non_zero_u64(r) },
-            Type::U128 => quote! { ::mabo::buf::decode_non_zero_u128(r) },
+            Type::U16 => quote! { ::mabo::buf::decode_non_zero_u16(r, limits) },
+            Type::U32 => quote! { ::mabo::buf::decode_non_zero_u32(r, limits) },
+            Type::U64 => quote! { ::mabo::buf::decode_non_zero_u64(r, limits) },
+            Type::U128 => quote! { ::mabo::buf::decode_non_zero_u128(r, limits) },
             Type::I8 => quote! { ::mabo::buf::decode_non_zero_i8(r) },
-            Type::I16 => quote! { ::mabo::buf::decode_non_zero_i16(r) },
-            Type::I32 => quote! { ::mabo::buf::decode_non_zero_i32(r) },
-            Type::I64 => quote! { ::mabo::buf::decode_non_zero_i64(r) },
-            Type::I128 => quote! { ::mabo::buf::decode_non_zero_i128(r) },
+            Type::I16 => quote! { ::mabo::buf::decode_non_zero_i16(r, limits) },
+            Type::I32 => quote! { ::mabo::buf::decode_non_zero_i32(r, limits) },
+            Type::I64 => quote! { ::mabo::buf::decode_non_zero_i64(r, limits) },
+            Type::I128 => quote! { ::mabo::buf::decode_non_zero_i128(r, limits) },
             Type::String | Type::StringRef => {
-                quote! { ::mabo::buf::decode_non_zero_string(r) }
+                quote! { ::mabo::buf::decode_non_zero_string(r, limits) }
             }
             Type::Bytes | Type::BytesRef => match opts.bytes_type {
                 BytesType::VecU8 => {
-                    quote! { ::mabo::buf::decode_non_zero_bytes_std(r) }
+                    quote! { ::mabo::buf::decode_non_zero_bytes_std(r, limits) }
                 }
                 BytesType::Bytes => {
-                    quote! { ::mabo::buf::decode_non_zero_bytes_bytes(r) }
+                    quote! { ::mabo::buf::decode_non_zero_bytes_bytes(r, limits) }
                 }
             },
+            Type::Vec(ty) if super::definition::is_packed(ty) => {
+                quote! { ::mabo::buf::decode_non_zero_vec_packed(r, limits) }
+            }
             Type::Vec(ty) => {
-                let ty = compile_data_type(opts, ty, false);
-                quote! { ::mabo::buf::decode_non_zero_vec(r, |r| { #ty }) }
+                let limits = compile_limits(refers_to_limits(ty));
+                let ty = compile_data_type(opts, scope, borrows, ty, false);
+                let decode = format_ident!("decode_non_zero_vec{suffix}");
+                quote! { ::mabo::buf::#decode(r, limits, |r, #limits| { #ty }) }
             }
-            Type::HashMap(kv) => {
-                let ty_k = compile_data_type(opts, &kv.0, false);
-                let ty_v = compile_data_type(opts, &kv.1, false);
-                quote! { ::mabo::buf::decode_non_zero_hash_map(r, |r| { #ty_k }, |r| { #ty_v }) }
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
+                let limits_k = compile_limits(refers_to_limits(&kv.0));
+                let limits_v = compile_limits(refers_to_limits(&kv.1));
+                let ty_k = compile_data_type(opts, scope, borrows, &kv.0, false);
+                let ty_v = compile_data_type(opts, scope, borrows, &kv.1, false);
+                let decode = format_ident!(
+                    "decode_non_zero_{}{suffix}",
+                    super::definition::collection_kind(ty)
+                );
+                quote! {
+                    ::mabo::buf::#decode(r, limits, |r, #limits_k| { #ty_k }, |r, #limits_v| { #ty_v })
+                }
             }
-            Type::HashSet(ty) => {
-                let ty = compile_data_type(opts, ty, false);
-                quote! { ::mabo::buf::decode_non_zero_hash_set(r, |r| { #ty }) }
+            Type::HashSet(inner) | Type::BTreeSet(inner) => {
+                let limits = compile_limits(refers_to_limits(inner));
+                let decode = format_ident!(
+                    "decode_non_zero_{}{suffix}",
+                    super::definition::collection_kind(ty)
+                );
+                let ty = compile_data_type(opts, scope, borrows, inner, false);
+                quote! { ::mabo::buf::#decode(r, limits, |r, #limits| { #ty }) }
             }
             ty => todo!("compiler should catch invalid {ty:?} type"),
         },
-        Type::BoxString => quote! { Box::<str>::decode(r) },
-        Type::BoxBytes => quote! { Box::<[u8]>::decode(r) },
+        Type::BoxString => quote! { Box::<str>::decode_with(r, limits) },
+        Type::BoxBytes => quote! { Box::<[u8]>::decode_with(r, limits) },
         Type::Tuple(types) => match types.len() {
             2..=12 => {
-                let types = types.iter().map(|ty| compile_data_type(opts, ty, false));
-                let length = root.then_some(quote! { ::mabo::buf::decode_u64(r)?; });
+                let types = types
+                    .iter()
+                    .map(|ty| compile_data_type(opts, scope, borrows, ty, false));
+                let length = root.then_some(quote! { ::mabo::buf::decode_u64(r, limits)?; });
                 quote! { {
                     #length
                     Ok::<_, ::mabo::buf::Error>((#(#types?,)*))
@@ -286,9 +604,17 @@ fn compile_data_type(opts: &Opts, ty: &Type<'_>, root: bool) -> TokenStream {
             }
             n => todo!("compiler should catch invalid tuple with {n} elements"),
         },
+        Type::Array(ty, _size) if super::definition::is_packed(ty) => {
+            quote! { ::mabo::buf::decode_array_packed(r, limits) }
+        }
         Type::Array(ty, _size) => {
-            let ty = compile_data_type(opts, ty, false);
-            quote! { ::mabo::buf::decode_array(r, |r| { #ty }) }
+            let limits = compile_limits(refers_to_limits(ty));
+            let ty = compile_data_type(opts, scope, borrows, ty, false);
+            let decode = format_ident!("decode_array{suffix}");
+            quote! { ::mabo::buf::#decode(r, limits, |r, #limits| { #ty }) }
+        }
+        Type::External(_) if borrows => {
+            quote! { ::mabo::BorrowDecode::borrow_decode_with(r, limits) }
         }
         Type::External(ExternalType {
             path,
@@ -300,10 +626,10 @@ fn compile_data_type(opts: &Opts, ty: &Type<'_>, root: bool) -> TokenStream {
             let generics = (!generics.is_empty()).then(|| {
                 let types = generics
                     .iter()
-                    .map(|ty| super::definition::compile_data_type(opts, ty));
+                    .map(|ty| super::definition::compile_data_type(opts, scope, ty));
                 quote! { ::<#(#types,)*> }
             });
-            quote! { #(#path::)* #ty #generics::decode(r) }
+            quote! { #(#path::)* #ty #generics::decode_with(r, limits) }
         }
     }
 }
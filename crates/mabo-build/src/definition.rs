@@ -1,79 +1,133 @@
 use mabo_compiler::simplify::{
-    Const, Definition, Enum, ExternalType, Field, FieldKind, Fields, Import, Literal, Module,
-    Schema, Struct, Type, TypeAlias, Variant,
+    Const, Definition, Deprecated, Enum, ExternalType, Field, FieldKind, Fields, Import, Literal,
+    Module, Schema, Struct, Type, TypeAlias, Variant,
 };
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{ToTokens, quote};
 
-use super::{decode, encode, size};
-use crate::{BytesType, Opts};
+use super::{decode, encode, merge, service, size, validate, view};
+use crate::{BytesType, Opts, TimestampType, UuidType, lifetimes::Scope};
 
 /// Take a single schema and convert it into Rust source code.
+///
+/// The `schemas` are all schemas that are compiled together, by name, which are needed to look up
+/// the definitions of imported types.
 #[must_use]
-pub fn compile_schema(opts: &Opts, Schema { definitions, .. }: &Schema<'_>) -> TokenStream {
-    let definitions = definitions.iter().map(|def| compile_definition(opts, def));
+pub fn compile_schema<'a>(
+    opts: &Opts,
+    Schema { definitions, .. }: &'a Schema<'a>,
+    schemas: &'a [(&'a str, Schema<'a>)],
+) -> TokenStream {
+    let scope = Scope::new(definitions, schemas);
+    let definitions = definitions
+        .iter()
+        .map(|def| compile_definition(opts, scope, def));
+
+    let imports = compile_imports(opts);
 
     quote! {
-        #[allow(unused_imports)]
-        use ::mabo::buf::{Decode, Encode, Size};
+        #imports
 
         #(#definitions)*
     }
 }
 
-fn compile_definition(opts: &Opts, definition: &Definition<'_>) -> TokenStream {
+fn compile_imports(opts: &Opts) -> TokenStream {
+    let alloc = opts.no_std.then(|| {
+        quote! {
+            #[allow(unused_imports)]
+            use ::alloc::{boxed::Box, string::String, vec::Vec};
+        }
+    });
+
+    quote! {
+        #alloc
+        #[allow(unused_imports)]
+        use ::mabo::buf::{Decode, Encode, Size};
+    }
+}
+
+fn compile_definition(opts: &Opts, scope: Scope<'_>, definition: &Definition<'_>) -> TokenStream {
     match definition {
-        Definition::Module(m) => compile_module(opts, m),
+        Definition::Module(m) => compile_module(opts, scope, m),
         Definition::Struct(s) => {
-            let def = compile_struct(opts, s);
-            let encode = encode::compile_struct(opts, s);
-            let decode = decode::compile_struct(opts, s);
-            let size = size::compile_struct(opts, s);
+            let scope = scope.with_generics(&s.generics);
+            let borrows = scope.borrows_fields(&s.fields);
+            let unknown = opts.preserves_unknown_fields(s);
+            let def = compile_struct(opts, scope, borrows, unknown, s);
+            let default = compile_struct_default(opts, scope, borrows, unknown, s);
+            let encode = encode::compile_struct(opts, borrows, unknown, s);
+            let decode = decode::compile_struct(opts, scope, borrows, unknown, s);
+            let size = size::compile_struct(opts, borrows, unknown, s);
+            let view = (opts.views && s.generics.is_empty() && !s.fields.fields.is_empty())
+                .then(|| view::compile_struct(opts, scope, s));
+            let merge =
+                (opts.merge && !borrows).then(|| merge::compile_struct(opts, scope, unknown, s));
+            let validate = validate::compile_struct(borrows, s);
 
             quote! {
                 #def
+                #default
                 #encode
                 #decode
                 #size
+                #view
+                #merge
+                #validate
             }
         }
         Definition::Enum(e) => {
-            let def = compile_enum(opts, e);
-            let encode = encode::compile_enum(opts, e);
-            let decode = decode::compile_enum(opts, e);
-            let size = size::compile_enum(opts, e);
+            let scope = scope.with_generics(&e.generics);
+            let borrows = e
+                .variants
+                .iter()
+                .any(|variant| scope.borrows_fields(&variant.fields));
+            let unknown = opts.preserves_unknown_variants(e);
+            let def = compile_enum(opts, scope, borrows, unknown, e);
+            let encode = encode::compile_enum(opts, borrows, unknown, e);
+            let decode = decode::compile_enum(opts, scope, borrows, unknown, e);
+            let size = size::compile_enum(opts, borrows, unknown, e);
+            let merge = (opts.merge && !borrows).then(|| merge::compile_enum(e));
+            let validate = validate::compile_enum(borrows, unknown, e);
 
             quote! {
                 #def
                 #encode
                 #decode
                 #size
+                #merge
+                #validate
             }
         }
-        Definition::TypeAlias(a) => compile_alias(opts, a),
+        Definition::TypeAlias(a) => compile_alias(opts, scope, a),
         Definition::Const(c) => compile_const(c),
         Definition::Import(i) => compile_import(i),
+        Definition::Service(s) => service::compile_service(opts, scope, s),
     }
 }
 
-fn compile_module(
+fn compile_module<'a>(
     opts: &Opts,
+    scope: Scope<'a>,
     Module {
         comment,
         name,
         definitions,
         ..
-    }: &Module<'_>,
+    }: &'a Module<'a>,
 ) -> TokenStream {
     let comment = compile_comment(comment);
     let name = Ident::new(name, Span::call_site());
-    let definitions = definitions.iter().map(|def| compile_definition(opts, def));
+    let scope = scope.with_definitions(definitions);
+    let definitions = definitions
+        .iter()
+        .map(|def| compile_definition(opts, scope, def));
+    let imports = compile_imports(opts);
 
     quote! {
         #comment
         pub mod #name {
-            #[allow(unused_imports)]
-            use ::mabo::buf::{Decode, Encode, Size};
+            #imports
 
             #(#definitions)*
         }
@@ -82,74 +136,276 @@ fn compile_module(
 
 fn compile_struct(
     opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    unknown: bool,
     Struct {
         comment,
         name,
         generics,
         fields,
+        deprecated,
         ..
     }: &Struct<'_>,
 ) -> TokenStream {
     let comment = compile_comment(comment);
+    let deprecated = compile_deprecated(deprecated.as_ref());
     let name = Ident::new(name, Span::call_site());
-    let generics = compile_generics(generics);
+    let generics = compile_generics(borrows, generics);
     let semicolon = (fields.kind != FieldKind::Named).then_some(quote! {;});
-    let fields = compile_fields(opts, fields, true);
+    let fields = compile_fields(opts, scope, fields, true, unknown);
 
     quote! {
         #comment
+        #deprecated
         #[derive(Clone, Debug, PartialEq)]
-        #[allow(dead_code, clippy::module_name_repetitions, clippy::option_option)]
+        #[allow(
+            dead_code,
+            deprecated,
+            clippy::module_name_repetitions,
+            clippy::option_option
+        )]
         pub struct #name #generics #fields #semicolon
     }
 }
 
+/// Implement [`Default`] for structs, that declare a default value for any of their fields. All
+/// other fields take the default value of their type, so the implementation is skipped if any of
+/// these types doesn't implement [`Default`] itself.
+fn compile_struct_default(
+    opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    unknown: bool,
+    Struct {
+        name,
+        generics,
+        fields,
+        ..
+    }: &Struct<'_>,
+) -> Option<TokenStream> {
+    if !has_default(opts, scope, fields, &mut Vec::new()) {
+        return None;
+    }
+
+    let name = Ident::new(name, Span::call_site());
+    let generics_where = (!generics.is_empty()).then(|| {
+        let types = generics.iter().map(|ty| Ident::new(ty, Span::call_site()));
+        quote! { where #(#types: ::core::default::Default,)* }
+    });
+    let generics = compile_generics(borrows, generics);
+    let values = fields.fields.iter().map(|field| {
+        let value = field.default.as_ref().map_or_else(
+            || quote! { ::core::default::Default::default() },
+            compile_default,
+        );
+
+        if fields.kind == FieldKind::Named {
+            let name = Ident::new(&field.name, Span::call_site());
+            quote! { #name: #value }
+        } else {
+            value
+        }
+    });
+    let unknown = unknown.then(|| {
+        if fields.kind == FieldKind::Named {
+            quote! { unknown_fields: ::mabo::UnknownFields::new(), }
+        } else {
+            quote! { ::mabo::UnknownFields::new(), }
+        }
+    });
+    let values = if fields.kind == FieldKind::Named {
+        quote! { { #(#values,)* #unknown } }
+    } else {
+        quote! { (#(#values,)* #unknown) }
+    };
+
+    Some(quote! {
+        #[automatically_derived]
+        #[allow(deprecated)]
+        impl #generics ::core::default::Default for #name #generics #generics_where {
+            fn default() -> Self {
+                Self #values
+            }
+        }
+    })
+}
+
+/// Check whether a [`Default`] implementation is generated for the struct with the given fields.
+fn has_default<'a>(
+    opts: &Opts,
+    scope: Scope<'a>,
+    fields: &Fields<'_>,
+    visited: &mut Vec<*const Definition<'a>>,
+) -> bool {
+    fields.fields.iter().any(|field| field.default.is_some())
+        && fields.fields.iter().all(|field| {
+            field.default.is_some() || implements_default(opts, scope, &field.ty, visited)
+        })
+}
+
+/// Check whether the Rust type, that the given type is mapped to, implements [`Default`].
+fn implements_default<'a>(
+    opts: &Opts,
+    scope: Scope<'a>,
+    ty: &Type<'_>,
+    visited: &mut Vec<*const Definition<'a>>,
+) -> bool {
+    match ty {
+        Type::Bool
+        | Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::U128
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::I128
+        | Type::F32
+        | Type::F64
+        | Type::String
+        | Type::StringRef
+        | Type::Bytes
+        | Type::BytesRef
+        | Type::BoxString
+        | Type::BoxBytes
+        | Type::Duration
+        | Type::Uuid
+        | Type::Decimal
+        | Type::Vec(_)
+        | Type::HashMap(_)
+        | Type::HashSet(_)
+        | Type::BTreeMap(_)
+        | Type::BTreeSet(_)
+        | Type::Option(_) => true,
+        Type::Timestamp => match opts.timestamp_type {
+            TimestampType::SystemTime => opts.no_std,
+            TimestampType::Chrono => true,
+        },
+        Type::NonZero(_) => false,
+        // The standard library only implements `Default` for arrays up to 32 elements, and
+        // tuples up to 12 elements.
+        Type::Array(ty, size) => *size <= 32 && implements_default(opts, scope, ty, visited),
+        Type::Tuple(types) => {
+            types.len() <= 12
+                && types
+                    .iter()
+                    .all(|ty| implements_default(opts, scope, ty, visited))
+        }
+        Type::External(ty) => {
+            if scope.is_generic(ty) {
+                return true;
+            }
+            if !ty
+                .generics
+                .iter()
+                .all(|ty| implements_default(opts, scope, ty, visited))
+            {
+                return false;
+            }
+
+            let Some((scope, definition)) = scope.resolve(ty) else {
+                return false;
+            };
+
+            // Types that contain themselves can't be constructed from defaults.
+            if visited.contains(&std::ptr::from_ref(definition)) {
+                return false;
+            }
+            visited.push(definition);
+
+            let result = match definition {
+                Definition::Struct(s) => {
+                    has_default(opts, scope.with_generics(&s.generics), &s.fields, visited)
+                }
+                Definition::TypeAlias(a) => {
+                    implements_default(opts, scope.with_generics(&a.generics), &a.target, visited)
+                }
+                _ => false,
+            };
+
+            visited.pop();
+            result
+        }
+    }
+}
+
 fn compile_enum(
     opts: &Opts,
+    scope: Scope<'_>,
+    borrows: bool,
+    unknown: bool,
     Enum {
         comment,
         name,
         generics,
         variants,
+        deprecated,
         ..
     }: &Enum<'_>,
 ) -> TokenStream {
     let comment = compile_comment(comment);
+    let deprecated = compile_deprecated(deprecated.as_ref());
     let name = Ident::new(name, Span::call_site());
-    let generics = compile_generics(generics);
-    let variants = variants.iter().map(|v| compile_variant(opts, v));
+    let generics = compile_generics(borrows, generics);
+    let variants = variants.iter().map(|v| compile_variant(opts, scope, v));
+    let non_exhaustive = unknown.then(|| quote! { #[non_exhaustive] });
+    let unknown = unknown.then(|| {
+        quote! {
+            /// Variant that is unknown to the schema, kept from decoding.
+            Unknown {
+                id: u32,
+                data: ::mabo::buf::Bytes,
+            },
+        }
+    });
 
     quote! {
         #comment
+        #deprecated
         #[derive(Clone, Debug, PartialEq)]
-        #[allow(dead_code, clippy::module_name_repetitions, clippy::option_option)]
+        #[allow(
+            dead_code,
+            deprecated,
+            clippy::module_name_repetitions,
+            clippy::option_option
+        )]
+        #non_exhaustive
         pub enum #name #generics {
             #(#variants,)*
+            #unknown
         }
     }
 }
 
 fn compile_variant(
     opts: &Opts,
+    scope: Scope<'_>,
     Variant {
         comment,
         name,
         fields,
+        deprecated,
         ..
     }: &Variant<'_>,
 ) -> TokenStream {
     let comment = compile_comment(comment);
+    let deprecated = compile_deprecated(deprecated.as_ref());
     let name = Ident::new(name, Span::call_site());
-    let fields = compile_fields(opts, fields, false);
+    let fields = compile_fields(opts, scope, fields, false, false);
 
     quote! {
         #comment
+        #deprecated
         #name #fields
     }
 }
 
 fn compile_alias(
     opts: &Opts,
+    scope: Scope<'_>,
     TypeAlias {
         comment,
         name,
@@ -160,12 +416,18 @@ fn compile_alias(
 ) -> TokenStream {
     let comment = compile_comment(comment);
     let name = Ident::new(name, Span::call_site());
-    let generics = compile_generics(generics);
-    let target = compile_data_type(opts, target);
+    let scope = scope.with_generics(generics);
+    let generics = compile_generics(scope.borrows(target), generics);
+    let target = compile_data_type(opts, scope, target);
 
     quote! {
         #comment
-        #[allow(dead_code, clippy::module_name_repetitions, clippy::option_option)]
+        #[allow(
+            dead_code,
+            deprecated,
+            clippy::module_name_repetitions,
+            clippy::option_option
+        )]
         pub type #name #generics = #target;
     }
 }
@@ -176,16 +438,19 @@ fn compile_const(
         name,
         ty,
         value,
+        deprecated,
         ..
     }: &Const<'_>,
 ) -> TokenStream {
     let comment = compile_comment(comment);
+    let deprecated = compile_deprecated(deprecated.as_ref());
     let name = Ident::new(name, Span::call_site());
     let ty = compile_const_data_type(ty);
     let value = compile_literal(value);
 
     quote! {
         #comment
+        #deprecated
         #[allow(dead_code)]
         pub const #name: #ty = #value;
     }
@@ -215,32 +480,53 @@ fn compile_import(
     }
 }
 
-fn compile_comment(lines: &[&str]) -> TokenStream {
+pub(super) fn compile_comment(lines: &[&str]) -> TokenStream {
     let lines = lines.iter().map(|line| format!(" {line}"));
     quote! { #(#[doc = #lines])* }
 }
 
-fn compile_generics(types: &[&str]) -> Option<TokenStream> {
-    (!types.is_empty()).then(|| {
+/// Mark the element as deprecated, with the optional note as explanation.
+pub(super) fn compile_deprecated(deprecated: Option<&Deprecated>) -> Option<TokenStream> {
+    deprecated.map(|Deprecated { note }| {
+        let note = note.as_deref().map(|note| quote! { (note = #note) });
+        quote! { #[deprecated #note] }
+    })
+}
+
+pub(super) fn compile_generics(borrows: bool, types: &[&str]) -> Option<TokenStream> {
+    (borrows || !types.is_empty()).then(|| {
+        let lifetime = borrows.then(|| quote! { 'a, });
         let types = types.iter().map(|ty| Ident::new(ty, Span::call_site()));
-        quote! { <#(#types,)*> }
+        quote! { <#lifetime #(#types,)*> }
     })
 }
 
-fn compile_fields(opts: &Opts, fields: &Fields<'_>, for_struct: bool) -> TokenStream {
+fn compile_fields(
+    opts: &Opts,
+    scope: Scope<'_>,
+    fields: &Fields<'_>,
+    for_struct: bool,
+    unknown: bool,
+) -> TokenStream {
     let values = fields.fields.iter().map(
         |Field {
-             comment, name, ty, ..
+             comment,
+             name,
+             ty,
+             deprecated,
+             ..
          }| {
             let public = for_struct.then(|| quote! { pub });
-            let ty = compile_data_type(opts, ty);
+            let ty = compile_data_type(opts, scope, ty);
 
             if fields.kind == FieldKind::Named {
                 let comment = compile_comment(comment);
+                let deprecated = compile_deprecated(deprecated.as_ref());
                 let name = Ident::new(name, Span::call_site());
 
                 quote! {
                     #comment
+                    #deprecated
                     #public #name: #ty
                 }
             } else {
@@ -249,14 +535,26 @@ fn compile_fields(opts: &Opts, fields: &Fields<'_>, for_struct: bool) -> TokenSt
         },
     );
 
+    let unknown = unknown.then(|| {
+        if fields.kind == FieldKind::Named {
+            quote! {
+                /// Fields that are unknown to the schema, kept from decoding.
+                pub unknown_fields: ::mabo::UnknownFields,
+            }
+        } else {
+            quote! { pub ::mabo::UnknownFields, }
+        }
+    });
+
     match fields.kind {
-        FieldKind::Named => quote! { {#(#values,)*} },
-        FieldKind::Unnamed => quote! { (#(#values,)*) },
+        FieldKind::Named => quote! { {#(#values,)* #unknown} },
+        FieldKind::Unnamed => quote! { (#(#values,)* #unknown) },
         FieldKind::Unit => quote! {},
     }
 }
 
-pub(super) fn compile_data_type(opts: &Opts, ty: &Type<'_>) -> TokenStream {
+#[expect(clippy::too_many_lines)]
+pub(super) fn compile_data_type(opts: &Opts, scope: Scope<'_>, ty: &Type<'_>) -> TokenStream {
     match &ty {
         Type::Bool => quote! { bool },
         Type::U8 => quote! { u8 },
@@ -271,80 +569,108 @@ pub(super) fn compile_data_type(opts: &Opts, ty: &Type<'_>) -> TokenStream {
         Type::I128 => quote! { i128 },
         Type::F32 => quote! { f32 },
         Type::F64 => quote! { f64 },
-        Type::String | Type::StringRef => quote! { String },
-        Type::Bytes | Type::BytesRef => match opts.bytes_type {
+        Type::String => quote! { String },
+        Type::StringRef => quote! { &'a str },
+        Type::Bytes => match opts.bytes_type {
             BytesType::VecU8 => quote! { Vec<u8> },
             BytesType::Bytes => quote! { ::mabo::buf::Bytes },
         },
+        Type::BytesRef => quote! { &'a [u8] },
         Type::Vec(ty) => {
-            let ty = compile_data_type(opts, ty);
+            let ty = compile_data_type(opts, scope, ty);
             quote! { Vec<#ty> }
         }
-        Type::HashMap(kv) => {
-            let k = compile_data_type(opts, &kv.0);
-            let v = compile_data_type(opts, &kv.1);
-            quote! { ::std::collections::HashMap<#k, #v> }
+        Type::HashMap(kv) | Type::BTreeMap(kv) => {
+            let k = compile_data_type(opts, scope, &kv.0);
+            let v = compile_data_type(opts, scope, &kv.1);
+            let map = collection_path(opts, ty);
+            quote! { #map<#k, #v> }
         }
-        Type::HashSet(ty) => {
-            let ty = compile_data_type(opts, ty);
-            quote! { ::std::collections::HashSet<#ty> }
+        Type::HashSet(inner) | Type::BTreeSet(inner) => {
+            let inner = compile_data_type(opts, scope, inner);
+            let set = collection_path(opts, ty);
+            quote! { #set<#inner> }
         }
         Type::Option(ty) => {
-            let ty = compile_data_type(opts, ty);
+            let ty = compile_data_type(opts, scope, ty);
             quote! { Option<#ty> }
         }
         Type::NonZero(ty) => match &**ty {
-            Type::U8 => quote! { ::std::num::NonZeroU8 },
-            Type::U16 => quote! { ::std::num::NonZeroU16 },
-            Type::U32 => quote! { ::std::num::NonZeroU32 },
-            Type::U64 => quote! { ::std::num::NonZeroU64 },
-            Type::U128 => quote! { ::std::num::NonZeroU128 },
-            Type::I8 => quote! { ::std::num::NonZeroI8 },
-            Type::I16 => quote! { ::std::num::NonZeroI16 },
-            Type::I32 => quote! { ::std::num::NonZeroI32 },
-            Type::I64 => quote! { ::std::num::NonZeroI64 },
-            Type::I128 => quote! { ::std::num::NonZeroI128 },
+            Type::U8 => quote! { ::core::num::NonZeroU8 },
+            Type::U16 => quote! { ::core::num::NonZeroU16 },
+            Type::U32 => quote! { ::core::num::NonZeroU32 },
+            Type::U64 => quote! { ::core::num::NonZeroU64 },
+            Type::U128 => quote! { ::core::num::NonZeroU128 },
+            Type::I8 => quote! { ::core::num::NonZeroI8 },
+            Type::I16 => quote! { ::core::num::NonZeroI16 },
+            Type::I32 => quote! { ::core::num::NonZeroI32 },
+            Type::I64 => quote! { ::core::num::NonZeroI64 },
+            Type::I128 => quote! { ::core::num::NonZeroI128 },
             Type::String | Type::StringRef => quote! { ::mabo::NonZeroString },
             Type::Bytes | Type::BytesRef => match opts.bytes_type {
                 BytesType::VecU8 => quote! { ::mabo::NonZeroBytes },
                 BytesType::Bytes => quote! { ::mabo::NonZero<::mabo::buf::Bytes> },
             },
             Type::Vec(ty) => {
-                let ty = compile_data_type(opts, ty);
+                let ty = compile_data_type(opts, scope, ty);
                 quote! { ::mabo::NonZeroVec<#ty> }
             }
             Type::HashMap(kv) => {
-                let k = compile_data_type(opts, &kv.0);
-                let v = compile_data_type(opts, &kv.1);
+                let k = compile_data_type(opts, scope, &kv.0);
+                let v = compile_data_type(opts, scope, &kv.1);
                 quote! { ::mabo::NonZeroHashMap<#k, #v> }
             }
             Type::HashSet(ty) => {
-                let ty = compile_data_type(opts, ty);
+                let ty = compile_data_type(opts, scope, ty);
                 quote! { ::mabo::NonZeroHashSet<#ty> }
             }
+            Type::BTreeMap(kv) => {
+                let k = compile_data_type(opts, scope, &kv.0);
+                let v = compile_data_type(opts, scope, &kv.1);
+                quote! { ::mabo::NonZeroBTreeMap<#k, #v> }
+            }
+            Type::BTreeSet(ty) => {
+                let ty = compile_data_type(opts, scope, ty);
+                quote! { ::mabo::NonZeroBTreeSet<#ty> }
+            }
             ty => todo!("compiler should catch invalid {ty:?} type"),
         },
         Type::BoxString => quote! { Box<str> },
         Type::BoxBytes => quote! { Box<[u8]> },
+        Type::Timestamp => match opts.timestamp_type {
+            TimestampType::SystemTime if opts.no_std => quote! { (i64, u32) },
+            TimestampType::SystemTime => quote! { ::std::time::SystemTime },
+            TimestampType::Chrono => quote! { ::mabo::chrono::DateTime<::mabo::chrono::Utc> },
+        },
+        Type::Duration => quote! { ::core::time::Duration },
+        Type::Uuid => match opts.uuid_type {
+            UuidType::Bytes => quote! { [u8; 16] },
+            UuidType::Uuid => quote! { ::mabo::uuid::Uuid },
+        },
+        Type::Decimal => quote! { ::mabo::Decimal },
         Type::Tuple(types) => {
-            let types = types.iter().map(|ty| compile_data_type(opts, ty));
+            let types = types.iter().map(|ty| compile_data_type(opts, scope, ty));
             quote! { (#(#types,)*) }
         }
         Type::Array(ty, size) => {
-            let ty = compile_data_type(opts, ty);
+            let ty = compile_data_type(opts, scope, ty);
             let size = proc_macro2::Literal::u32_unsuffixed(*size);
             quote! { [#ty; #size] }
         }
-        Type::External(ExternalType {
-            path,
-            name,
-            generics,
-        }) => {
+        Type::External(
+            ext @ ExternalType {
+                path,
+                name,
+                generics,
+            },
+        ) => {
             let path = path.iter().map(|part| Ident::new(part, Span::call_site()));
             let name = Ident::new(name, Span::call_site());
-            let generics = (!generics.is_empty()).then(|| {
-                let types = generics.iter().map(|ty| compile_data_type(opts, ty));
-                quote! { <#(#types,)*> }
+            let borrows = scope.borrows_external(ext);
+            let generics = (borrows || !generics.is_empty()).then(|| {
+                let lifetime = borrows.then(|| quote! { 'a, });
+                let types = generics.iter().map(|ty| compile_data_type(opts, scope, ty));
+                quote! { <#lifetime #(#types,)*> }
             });
 
             quote! {
@@ -354,6 +680,59 @@ pub(super) fn compile_data_type(opts: &Opts, ty: &Type<'_>) -> TokenStream {
     }
 }
 
+/// Path to a map or set type, which comes from the standard library, or from the `mabo` runtime
+/// crate when generating code for `no_std` environments.
+fn collection_path(opts: &Opts, ty: &Type<'_>) -> TokenStream {
+    let name = match ty {
+        Type::HashMap(_) => "HashMap",
+        Type::HashSet(_) => "HashSet",
+        Type::BTreeMap(_) => "BTreeMap",
+        Type::BTreeSet(_) => "BTreeSet",
+        _ => unreachable!("only maps and sets have a collection path"),
+    };
+    let name = Ident::new(name, Span::call_site());
+
+    if opts.no_std {
+        quote! { ::mabo::collections::#name }
+    } else {
+        quote! { ::std::collections::#name }
+    }
+}
+
+/// Name of a map or set type in the schema, which is also the name used by the runtime functions
+/// that en- and decode it.
+pub(super) fn collection_kind(ty: &Type<'_>) -> &'static str {
+    match ty {
+        Type::HashMap(_) => "hash_map",
+        Type::HashSet(_) => "hash_set",
+        Type::BTreeMap(_) => "btree_map",
+        Type::BTreeSet(_) => "btree_set",
+        _ => unreachable!("only maps and sets have a collection kind"),
+    }
+}
+
+/// Whether the element type of a vector or array can be en- and decoded through the runtime's
+/// `Packed` trait, which handles the whole slice in one call, instead of passing a closure for the
+/// elements.
+pub(super) fn is_packed(ty: &Type<'_>) -> bool {
+    matches!(
+        ty,
+        Type::Bool
+            | Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::U128
+            | Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::I128
+            | Type::F32
+            | Type::F64
+    )
+}
+
 fn compile_const_data_type(ty: &Type<'_>) -> TokenStream {
     match &ty {
         Type::Bool => quote! { bool },
@@ -375,6 +754,17 @@ fn compile_const_data_type(ty: &Type<'_>) -> TokenStream {
     }
 }
 
+/// Default value of a field, where string and byte literals are converted into the field's type.
+pub(super) fn compile_default(literal: &Literal) -> TokenStream {
+    let value = compile_literal(literal);
+
+    match literal {
+        Literal::String(_) => quote! { #value.into() },
+        Literal::Bytes(_) => quote! { (&#value[..]).into() },
+        Literal::Bool(_) | Literal::Int(_) | Literal::Float(_) => value,
+    }
+}
+
 fn compile_literal(literal: &Literal) -> TokenStream {
     match &literal {
         Literal::Bool(b) => quote! { #b },
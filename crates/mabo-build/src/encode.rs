@@ -1,11 +1,13 @@
 use mabo_compiler::simplify::{Enum, Field, FieldKind, Fields, Struct, Type, Variant};
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{ToTokens, quote};
+use quote::{ToTokens, format_ident, quote};
 
-use crate::{BytesType, Opts};
+use crate::{BytesType, Opts, TimestampType, UuidType};
 
 pub(super) fn compile_struct(
     opts: &Opts,
+    borrows: bool,
+    unknown: bool,
     Struct {
         name,
         generics,
@@ -17,36 +19,45 @@ pub(super) fn compile_struct(
         .fields
         .iter()
         .map(|field| Ident::new(&field.name, Span::call_site()));
+    let unknown_name = unknown.then(|| quote! { unknown_fields, });
     let names = match fields.kind {
-        FieldKind::Named => quote! { {#(#names,)*} },
-        FieldKind::Unnamed => quote! { (#(#names,)*) },
+        FieldKind::Named => quote! { {#(#names,)* #unknown_name} },
+        FieldKind::Unnamed => quote! { (#(#names,)* #unknown_name) },
         FieldKind::Unit => quote! {},
     };
 
     let name = Ident::new(name, Span::call_site());
-    let (generics, generics_where) = compile_generics(generics);
-    let fields = compile_fields(opts, fields);
+    let (generics, type_generics, generics_where) = compile_generics(borrows, generics);
+    let cached = super::size::fields_use_sizes(fields);
+    let body = compile_fields(opts, fields, unknown);
+    let body = compile_body(
+        cached,
+        &quote! {
+            clippy::borrow_deref_ref,
+            clippy::deref_addrof,
+            clippy::explicit_auto_deref,
+            clippy::needless_borrow,
+            clippy::too_many_lines,
+        },
+        &quote! {
+            let Self #names = self;
+            #body
+        },
+    );
 
     quote! {
         #[automatically_derived]
-        impl #generics ::mabo::Encode for #name #generics #generics_where {
-            #[allow(
-                clippy::borrow_deref_ref,
-                clippy::deref_addrof,
-                clippy::explicit_auto_deref,
-                clippy::needless_borrow,
-                clippy::too_many_lines,
-            )]
-            fn encode(&self, w: &mut impl ::mabo::BufMut) {
-                let Self #names = self;
-                #fields
-            }
+        #[allow(deprecated)]
+        impl #generics ::mabo::Encode for #name #type_generics #generics_where {
+            #body
         }
     }
 }
 
 pub(super) fn compile_enum(
     opts: &Opts,
+    borrows: bool,
+    unknown: bool,
     Enum {
         name,
         generics,
@@ -55,22 +66,62 @@ pub(super) fn compile_enum(
     }: &Enum<'_>,
 ) -> TokenStream {
     let name = Ident::new(name, Span::call_site());
-    let (generics, generics_where) = compile_generics(generics);
+    let (generics, type_generics, generics_where) = compile_generics(borrows, generics);
+    let cached = variants
+        .iter()
+        .any(|v| super::size::fields_use_sizes(&v.fields));
     let variants = variants.iter().map(|v| compile_variant(opts, v));
+    let unknown = unknown.then(|| {
+        quote! { Self::Unknown { id, data } => ::mabo::buf::encode_unknown_variant(w, *id, data), }
+    });
+    let body = compile_body(
+        cached,
+        &quote! {
+            clippy::borrow_deref_ref,
+            clippy::deref_addrof,
+            clippy::semicolon_if_nothing_returned,
+            clippy::too_many_lines,
+        },
+        &quote! {
+            match self {
+                #(#variants,)*
+                #unknown
+            }
+        },
+    );
 
     quote! {
         #[automatically_derived]
-        impl #generics ::mabo::Encode for #name #generics #generics_where {
-            #[allow(
-                clippy::borrow_deref_ref,
-                clippy::deref_addrof,
-                clippy::semicolon_if_nothing_returned,
-                clippy::too_many_lines,
-            )]
+        #[allow(deprecated)]
+        impl #generics ::mabo::Encode for #name #type_generics #generics_where {
+            #body
+        }
+    }
+}
+
+/// Either the plain encoding, or the cached one, together with a plain encoding that calculates
+/// the sizes up front and forwards to it.
+fn compile_body(cached: bool, lints: &TokenStream, body: &TokenStream) -> TokenStream {
+    if cached {
+        quote! {
             fn encode(&self, w: &mut impl ::mabo::BufMut) {
-                match self {
-                    #(#variants,)*
-                }
+                ::mabo::buf::encode_sized(w, self);
+            }
+
+            #[allow(#lints)]
+            fn encode_cached(
+                &self,
+                w: &mut impl ::mabo::BufMut,
+                sizes: &mut ::mabo::buf::SizeCache,
+            ) {
+                #body
+            }
+        }
+    } else {
+        quote! {
+            #[allow(#lints)]
+            fn encode(&self, w: &mut impl ::mabo::BufMut) {
+                #body
             }
         }
     }
@@ -83,9 +134,14 @@ fn compile_variant(
     }: &Variant<'_>,
 ) -> TokenStream {
     let id = proc_macro2::Literal::u32_unsuffixed(*id);
-    let id = quote! { ::mabo::VariantId::new(#id) };
+    let encoding = if fields.kind == FieldKind::Unit {
+        quote! { ::mabo::VariantEncoding::Unit }
+    } else {
+        quote! { ::mabo::VariantEncoding::Fields }
+    };
+    let id = quote! { ::mabo::VariantId::new(#id, #encoding) };
     let name = Ident::new(name, Span::call_site());
-    let fields_body = compile_fields(opts, fields);
+    let fields_body = compile_fields(opts, fields, false);
     let field_names = fields
         .fields
         .iter()
@@ -112,7 +168,7 @@ fn compile_variant(
     }
 }
 
-fn compile_fields(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
+fn compile_fields(opts: &Opts, fields: &Fields<'_>, unknown: bool) -> TokenStream {
     if fields.kind == FieldKind::Unit {
         quote! {}
     } else {
@@ -131,23 +187,40 @@ fn compile_fields(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
             }
         });
 
+        let unknown = unknown.then(|| {
+            quote! { ::mabo::buf::encode_unknown_fields(w, unknown_fields); }
+        });
+
         quote! {
            #(#calls)*
+           #unknown
            ::mabo::buf::encode_u32(w, ::mabo::buf::END_MARKER);
         }
     }
 }
 
-fn compile_generics(types: &[&str]) -> (TokenStream, TokenStream) {
+/// Generics for the implementation block and the type itself. Lifetimes are elided, as the
+/// implementation doesn't depend on it.
+fn compile_generics(borrows: bool, types: &[&str]) -> (TokenStream, TokenStream, TokenStream) {
+    let lifetime = borrows.then(|| quote! { '_, });
+
     if types.is_empty() {
-        Default::default()
+        (
+            TokenStream::new(),
+            lifetime
+                .map(|lifetime| quote! { <#lifetime> })
+                .unwrap_or_default(),
+            TokenStream::new(),
+        )
     } else {
         let types = types.iter().map(|ty| Ident::new(ty, Span::call_site()));
         let types2 = types.clone();
+        let types3 = types.clone();
 
         (
             quote! { <#(#types,)*> },
-            quote! { where #(#types2: ::mabo::buf::Encode + ::mabo::buf::Size,)* },
+            quote! { <#lifetime #(#types2,)*> },
+            quote! { where #(#types3: ::mabo::buf::Encode + ::mabo::buf::Size,)* },
         )
     }
 }
@@ -216,7 +289,11 @@ fn compile_data_type(
             quote! { ::mabo::FieldEncoding::LengthPrefixed },
             quote! { ::mabo::buf::encode_string(w, #name) },
         ),
-        Type::Bytes | Type::BytesRef | Type::BoxBytes => match opts.bytes_type {
+        Type::BytesRef => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            quote! { ::mabo::buf::encode_bytes_std(w, #name) },
+        ),
+        Type::Bytes | Type::BoxBytes => match opts.bytes_type {
             BytesType::VecU8 => (
                 quote! { ::mabo::FieldEncoding::LengthPrefixed },
                 quote! { ::mabo::buf::encode_bytes_std(w, #name) },
@@ -226,38 +303,70 @@ fn compile_data_type(
                 quote! { ::mabo::buf::encode_bytes_bytes(w, #name) },
             ),
         },
+        Type::Timestamp => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            match opts.timestamp_type {
+                TimestampType::SystemTime if opts.no_std => {
+                    quote! { ::mabo::buf::encode_timestamp(w, #name.0, #name.1) }
+                }
+                TimestampType::SystemTime => {
+                    quote! { ::mabo::buf::encode_timestamp_std(w, #name) }
+                }
+                TimestampType::Chrono => quote! { ::mabo::buf::encode_timestamp_chrono(w, #name) },
+            },
+        ),
+        Type::Duration => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            quote! { ::mabo::buf::encode_duration(w, #name) },
+        ),
+        Type::Uuid => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            match opts.uuid_type {
+                UuidType::Bytes => quote! { ::mabo::buf::encode_uuid(w, #name) },
+                UuidType::Uuid => quote! { ::mabo::buf::encode_uuid(w, #name.as_bytes()) },
+            },
+        ),
+        Type::Decimal => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            quote! { ::mabo::buf::encode_decimal(w, #name) },
+        ),
+        Type::Vec(ty) if super::definition::is_packed(ty) => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            quote! { ::mabo::buf::encode_vec_packed(w, #name) },
+        ),
         Type::Vec(ty) => {
-            let size = super::size::compile_data_type(opts, ty, quote! { v });
+            let sizes = super::size::sizes_param(ty);
             let (_, encode) = compile_data_type(opts, ty, quote! { v }, false);
             (quote! { ::mabo::FieldEncoding::LengthPrefixed }, {
-                quote! { ::mabo::buf::encode_vec(w, #name, |v| { #size }, |w, v| { #encode; }) }
+                quote! { ::mabo::buf::encode_vec_cached(w, sizes, #name, |w, #sizes, v| { #encode; }) }
             })
         }
-        Type::HashMap(kv) => {
-            let size_k = super::size::compile_data_type(opts, &kv.0, quote! { k });
-            let size_v = super::size::compile_data_type(opts, &kv.1, quote! { v });
+        Type::HashMap(kv) | Type::BTreeMap(kv) => {
+            let sizes_k = super::size::sizes_param(&kv.0);
+            let sizes_v = super::size::sizes_param(&kv.1);
             let (_, encode_k) = compile_data_type(opts, &kv.0, quote! { k }, false);
             let (_, encode_v) = compile_data_type(opts, &kv.1, quote! { v }, false);
+            let func = compile_collection_func(opts, ty);
             (
                 quote! { ::mabo::FieldEncoding::LengthPrefixed },
                 quote! {
-                    ::mabo::buf::encode_hash_map(
+                    ::mabo::buf::#func(
                         w,
+                        sizes,
                         #name,
-                        |k| { #size_k },
-                        |v| { #size_v },
-                        |w, k| { #encode_k; },
-                        |w, v| { #encode_v; },
+                        |w, #sizes_k, k| { #encode_k; },
+                        |w, #sizes_v, v| { #encode_v; },
                     )
                 },
             )
         }
-        Type::HashSet(ty) => {
-            let size = super::size::compile_data_type(opts, ty, quote! { v });
-            let (_, encode) = compile_data_type(opts, ty, quote! { v }, false);
+        Type::HashSet(inner) | Type::BTreeSet(inner) => {
+            let sizes = super::size::sizes_param(inner);
+            let (_, encode) = compile_data_type(opts, inner, quote! { v }, false);
+            let func = compile_collection_func(opts, ty);
             (
                 quote! { ::mabo::FieldEncoding::LengthPrefixed },
-                quote! { ::mabo::buf::encode_hash_set(w, #name, |v| { #size }, |w, v| { #encode; }) },
+                quote! { ::mabo::buf::#func(w, sizes, #name, |w, #sizes, v| { #encode; }) },
             )
         }
         Type::Option(ty) => {
@@ -314,7 +423,9 @@ fn compile_data_type(
             | Type::BytesRef
             | Type::Vec(_)
             | Type::HashMap(_)
-            | Type::HashSet(_) => compile_data_type(opts, ty, quote! { #name.get() }, false),
+            | Type::HashSet(_)
+            | Type::BTreeMap(_)
+            | Type::BTreeSet(_) => compile_data_type(opts, ty, quote! { #name.get() }, false),
             ty => todo!("compiler should catch invalid {ty:?} type"),
         },
         Type::Tuple(types) => match types.len() {
@@ -326,12 +437,10 @@ fn compile_data_type(
                 (
                     quote! { ::mabo::FieldEncoding::LengthPrefixed },
                     if root {
-                        let size = types.iter().enumerate().map(|(idx, ty)| {
-                            let idx = proc_macro2::Literal::usize_unsuffixed(idx);
-                            super::size::compile_data_type(opts, ty, quote! { &#name.#idx })
-                        });
-
-                        quote! { ::mabo::buf::encode_tuple(w, || { #(#size)+* }, |w| { #(#encode;)* }) }
+                        let sizes = super::size::sizes_param(ty);
+                        quote! {
+                            ::mabo::buf::encode_tuple_cached(w, sizes, |w, #sizes| { #(#encode;)* })
+                        }
                     } else {
                         quote! { #(#encode;)* }
                     },
@@ -339,17 +448,35 @@ fn compile_data_type(
             }
             n => todo!("compiler should catch invalid tuple with {n} elements"),
         },
+        Type::Array(ty, _size) if super::definition::is_packed(ty) => (
+            quote! { ::mabo::FieldEncoding::LengthPrefixed },
+            quote! { ::mabo::buf::encode_array_packed(w, #name) },
+        ),
         Type::Array(ty, _size) => {
-            let size = super::size::compile_data_type(opts, ty, quote! { v });
+            let sizes = super::size::sizes_param(ty);
             let (_, encode) = compile_data_type(opts, ty, quote! { v }, false);
             (
                 quote! { ::mabo::FieldEncoding::LengthPrefixed },
-                quote! { ::mabo::buf::encode_array(w, #name, |v| { #size }, |w, v| { #encode; }) },
+                quote! {
+                    ::mabo::buf::encode_array_cached(w, sizes, #name, |w, #sizes, v| { #encode; })
+                },
             )
         }
         Type::External(_) => (
             quote! { ::mabo::FieldEncoding::LengthPrefixed },
-            quote! { #name.encode(w) },
+            quote! { #name.encode_cached(w, sizes) },
         ),
     }
 }
+
+/// Name of the function that encodes the given map or set type. B-tree collections are already
+/// ordered by their elements, so only hash collections need the extra sorting step for the
+/// canonical encoding.
+fn compile_collection_func(opts: &Opts, ty: &Type<'_>) -> Ident {
+    let kind = super::definition::collection_kind(ty);
+    if opts.canonical && matches!(ty, Type::HashMap(_) | Type::HashSet(_)) {
+        format_ident!("encode_{kind}_canonical_cached")
+    } else {
+        format_ident!("encode_{kind}_cached")
+    }
+}
@@ -1,8 +1,17 @@
 //! Code generator crate for Rust projects that can be used in `build.rs` build scripts.
 
-use std::{env, fmt::Debug, fs, path::PathBuf};
-
-use mabo_parser::Schema;
+use std::{
+    env,
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use mabo_compiler::{
+    lint::Warning,
+    simplify::{Enum, FieldKind, Struct},
+};
+use mabo_parser::{Attributes, Schema};
 use miette::Report;
 use thiserror::Error;
 
@@ -11,7 +20,12 @@ pub use self::definition::compile_schema;
 mod decode;
 mod definition;
 mod encode;
+mod lifetimes;
+mod merge;
+mod service;
 mod size;
+mod validate;
+mod view;
 
 /// Shorthand for the standard result type, that defaults to the crate level's [`Error`](enum@Error)
 /// type.
@@ -104,9 +118,26 @@ impl Debug for Error {
 
 /// Instance of the compiler, which is responsible to generate Rust source code from schema files.
 #[derive(Default)]
+#[expect(clippy::struct_excessive_bools)]
 pub struct Compiler {
     /// The data type to use for Mabo's `bytes` type.
     bytes_type: BytesType,
+    /// The data type to use for Mabo's `timestamp` type.
+    timestamp_type: TimestampType,
+    /// The data type to use for Mabo's `uuid` type.
+    uuid_type: UuidType,
+    /// Whether to keep unknown fields for all structs.
+    preserve_unknown: bool,
+    /// Whether to generate code for `no_std` environments.
+    no_std: bool,
+    /// Whether to encode maps and sets in their canonical form.
+    canonical: bool,
+    /// Whether to generate lazy view types for structs.
+    views: bool,
+    /// Whether to generate implementations for merge decoding.
+    merge: bool,
+    /// Whether to validate the declared field constraints right after decoding.
+    validate_on_decode: bool,
 }
 
 /// The data type to use for Mabo's `bytes` type, that is used throughout all generated schemas.
@@ -119,10 +150,82 @@ pub enum BytesType {
     Bytes,
 }
 
+/// The data type to use for Mabo's `timestamp` type, that is used throughout all generated schemas.
+#[derive(Clone, Copy, Default)]
+pub enum TimestampType {
+    /// Use the default `SystemTime` type from Rust's stdlib. As it is not available in `no_std`
+    /// environments, an `(i64, u32)` tuple of seconds and nanoseconds since the Unix epoch is used
+    /// instead when generating code for them.
+    #[default]
+    SystemTime,
+    /// Use the [`chrono::DateTime<Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)
+    /// type, which needs the `chrono` feature of the `mabo` crate.
+    Chrono,
+}
+
+/// The data type to use for Mabo's `uuid` type, that is used throughout all generated schemas.
+#[derive(Clone, Copy, Default)]
+pub enum UuidType {
+    /// Use a plain `[u8; 16]` byte array.
+    #[default]
+    Bytes,
+    /// Use the [`uuid::Uuid`](https://docs.rs/uuid/latest/uuid/struct.Uuid.html) type, which
+    /// needs the `uuid` feature of the `mabo` crate.
+    Uuid,
+}
+
 /// Additional options to adjust the behavior of the Rust code generator.
 #[derive(Default)]
+#[expect(clippy::struct_excessive_bools)]
 pub struct Opts {
     bytes_type: BytesType,
+    timestamp_type: TimestampType,
+    uuid_type: UuidType,
+    preserve_unknown: bool,
+    no_std: bool,
+    canonical: bool,
+    views: bool,
+    merge: bool,
+    validate_on_decode: bool,
+}
+
+impl From<&Compiler> for Opts {
+    fn from(compiler: &Compiler) -> Self {
+        Self {
+            bytes_type: compiler.bytes_type,
+            timestamp_type: compiler.timestamp_type,
+            uuid_type: compiler.uuid_type,
+            preserve_unknown: compiler.preserve_unknown,
+            no_std: compiler.no_std,
+            canonical: compiler.canonical,
+            views: compiler.views,
+            merge: compiler.merge,
+            validate_on_decode: compiler.validate_on_decode,
+        }
+    }
+}
+
+impl Opts {
+    /// Whether the given struct keeps unknown fields while decoding. Unit structs don't have any
+    /// fields and therefore never keep unknown ones.
+    fn preserves_unknown_fields(&self, s: &Struct<'_>) -> bool {
+        s.fields.kind != FieldKind::Unit && self.preserves_unknown(&s.source.attributes)
+    }
+
+    /// Whether the given enum keeps unknown variants while decoding.
+    fn preserves_unknown_variants(&self, e: &Enum<'_>) -> bool {
+        self.preserves_unknown(&e.source.attributes)
+    }
+
+    /// Preservation of unknown data is enabled either for all elements, or individually with the
+    /// `#[preserve_unknown]` attribute.
+    fn preserves_unknown(&self, attributes: &Attributes<'_>) -> bool {
+        self.preserve_unknown
+            || attributes
+                .0
+                .iter()
+                .any(|attr| attr.name == "preserve_unknown")
+    }
 }
 
 impl Compiler {
@@ -133,6 +236,104 @@ impl Compiler {
         self
     }
 
+    /// Change the type that is used to represent Mabo `timestamp` points in time.
+    #[must_use]
+    pub fn with_timestamp_type(mut self, value: TimestampType) -> Self {
+        self.timestamp_type = value;
+        self
+    }
+
+    /// Change the type that is used to represent Mabo `uuid` identifiers.
+    #[must_use]
+    pub fn with_uuid_type(mut self, value: UuidType) -> Self {
+        self.uuid_type = value;
+        self
+    }
+
+    /// Keep fields and enum variants that are unknown to the schema while decoding any struct or
+    /// enum, and write them back when encoding it again. This can also be enabled for individual
+    /// structs and enums, with the `#[preserve_unknown]` attribute.
+    ///
+    /// Enums gain an additional `Unknown` variant for this purpose, and are marked as
+    /// `#[non_exhaustive]`.
+    ///
+    /// This is useful for services that pass data through, while only looking at or modifying
+    /// parts of it, as data from newer schema versions is otherwise lost.
+    #[must_use]
+    pub fn with_preserve_unknown(mut self, value: bool) -> Self {
+        self.preserve_unknown = value;
+        self
+    }
+
+    /// Generate code that compiles in `no_std` environments, as long as an allocator is available.
+    ///
+    /// The generated code refers to `String`, `Vec` and `Box` through the `alloc` crate, which
+    /// must be declared with `extern crate alloc;` at the root of the crate that includes it. Maps
+    /// and sets are taken from the `mabo::collections` module, so the `mabo` runtime crate can be
+    /// used with its default `std` feature disabled.
+    #[must_use]
+    pub fn with_no_std(mut self, value: bool) -> Self {
+        self.no_std = value;
+        self
+    }
+
+    /// Encode `hash_map` and `hash_set` values in their canonical form, with all elements sorted
+    /// by their encoded bytes. Equal values then always result in the exact same bytes, which is
+    /// needed when encoded payloads are hashed or signed.
+    ///
+    /// Such payloads can be checked with
+    /// [`mabo::buf::verify_canonical`](https://docs.rs/mabo/latest/mabo/buf/fn.verify_canonical.html).
+    #[must_use]
+    pub fn with_canonical_encoding(mut self, value: bool) -> Self {
+        self.canonical = value;
+        self
+    }
+
+    /// Generate a lazy view type next to each struct, named after the struct with a `View`
+    /// suffix (for example `SampleView<'a>` for a `Sample` struct).
+    ///
+    /// A view scans an encoded payload once, only remembering where each field is located, and
+    /// then decodes single fields when they're accessed. That avoids decoding (and allocating) the
+    /// whole struct, if only a few of its fields are needed.
+    ///
+    /// Nested structs and enums are written without their length, so fields of these types are
+    /// still decoded once while scanning, to find where they end.
+    ///
+    /// Views are not generated for generic structs and structs without fields.
+    #[must_use]
+    pub fn with_views(mut self, value: bool) -> Self {
+        self.views = value;
+        self
+    }
+
+    /// Implement `mabo::buf::DecodeMerge` for all structs and enums, which decodes a payload into
+    /// an existing value, instead of creating a new one.
+    ///
+    /// Present fields replace the current value, vectors, maps and sets get the decoded elements
+    /// appended, and nested structs are merged recursively. Absent fields are left untouched.
+    /// Enums are always replaced as a whole.
+    ///
+    /// All nested types must implement the trait as well, so schemas that import each other
+    /// should be compiled with the same setting. It is not implemented for types that borrow from
+    /// the payload.
+    #[must_use]
+    pub fn with_decode_merge(mut self, value: bool) -> Self {
+        self.merge = value;
+        self
+    }
+
+    /// Check the constraints of the `#[validate(...)]` attributes right after decoding a struct
+    /// or enum, failing with `mabo::buf::Error::Validation` if any of them are violated.
+    ///
+    /// The `mabo::validate::Validate` trait is implemented for all structs and enums with
+    /// validation rules regardless of this setting, so values can be checked manually at any
+    /// time. Merge decoding doesn't run the checks.
+    #[must_use]
+    pub fn with_validate_on_decode(mut self, value: bool) -> Self {
+        self.validate_on_decode = value;
+        self
+    }
+
     /// Compile the given list of Mabo schema files (glob patterns) into Rust source code.
     ///
     /// # Errors
@@ -179,6 +380,10 @@ impl Compiler {
                 file: path.clone(),
             })?;
 
+            for warning in mabo_compiler::lint_schema(&schema) {
+                print_warning(path, input, &warning);
+            }
+
             validated.push((stem, schema));
         }
 
@@ -192,13 +397,15 @@ impl Compiler {
             file: PathBuf::new(),
         })?;
 
-        let opts = Opts {
-            bytes_type: self.bytes_type,
-        };
+        let opts = Opts::from(self);
+
+        let schemas = validated
+            .into_iter()
+            .map(|(stem, schema)| (stem, mabo_compiler::simplify_schema(schema)))
+            .collect::<Vec<_>>();
 
-        for (stem, schema) in validated {
-            let schema = mabo_compiler::simplify_schema(schema);
-            let code = definition::compile_schema(&opts, &schema);
+        for (stem, schema) in &schemas {
+            let code = definition::compile_schema(&opts, schema, &schemas);
             let code = prettyplease::unparse(&syn::parse2(code.clone()).map_err(|source| {
                 Error::InvalidCode {
                     source,
@@ -218,6 +425,20 @@ impl Compiler {
     }
 }
 
+/// Report a warning to Cargo, which shows it at the end of the build script output.
+fn print_warning(file: &Path, input: &str, warning: &Warning) {
+    let Warning::DeprecatedUsage(usage) = warning;
+
+    let before = &input[..usage.used.start];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+
+    println!("cargo:warning={}:{line}:{column}: {usage}", file.display());
+    if let Some(note) = &usage.note {
+        println!("cargo:warning=note: {note}");
+    }
+}
+
 fn init_miette() {
     miette::set_hook(Box::new(|_| {
         Box::new(
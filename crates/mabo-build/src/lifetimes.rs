@@ -0,0 +1,213 @@
+use mabo_compiler::simplify::{Definition, ExternalType, Fields, Import, Schema, Type};
+
+/// View on the definitions of a single module, that allows to find out whether a type contains
+/// borrowed data (`&string` or `&bytes`), either directly or through any of the types it refers to.
+///
+/// Types that borrow data from the decoded input are generated with an additional `'a` lifetime.
+#[derive(Clone, Copy)]
+pub(crate) struct Scope<'a> {
+    definitions: &'a [Definition<'a>],
+    generics: &'a [&'a str],
+    /// All schemas that are compiled together, by name, to look up types from imported schemas.
+    schemas: &'a [(&'a str, Schema<'a>)],
+}
+
+impl<'a> Scope<'a> {
+    pub(crate) fn new(
+        definitions: &'a [Definition<'a>],
+        schemas: &'a [(&'a str, Schema<'a>)],
+    ) -> Self {
+        Self {
+            definitions,
+            generics: &[],
+            schemas,
+        }
+    }
+
+    /// Scope for the content of a definition, where the generic type arguments shadow any
+    /// definitions of the same name.
+    pub(crate) fn with_generics(self, generics: &'a [&'a str]) -> Self {
+        Self { generics, ..self }
+    }
+
+    /// Scope of a module with the given definitions, that can see the same schemas.
+    pub(crate) fn with_definitions(self, definitions: &'a [Definition<'a>]) -> Self {
+        Self::new(definitions, self.schemas)
+    }
+
+    /// Scope of a direct child module.
+    pub(crate) fn module(self, name: &str) -> Option<Self> {
+        self.definitions.iter().find_map(|def| match def {
+            Definition::Module(m) if m.name == name => Some(self.with_definitions(&m.definitions)),
+            _ => None,
+        })
+    }
+
+    /// Scope of the root of another schema.
+    fn schema(self, name: &str) -> Option<Self> {
+        self.schemas.iter().find_map(|(n, schema)| {
+            (*n == name).then(|| self.with_definitions(&schema.definitions))
+        })
+    }
+
+    /// Check whether any of the fields contain borrowed data.
+    pub(crate) fn borrows_fields(self, fields: &Fields<'_>) -> bool {
+        self.visit_fields(fields, &mut Vec::new())
+    }
+
+    /// Check whether the given type contains borrowed data.
+    pub(crate) fn borrows(self, ty: &Type<'_>) -> bool {
+        self.visit(ty, &mut Vec::new())
+    }
+
+    /// Check whether the definition, that the external type refers to, contains borrowed data.
+    /// Other than [`Self::borrows`], this doesn't consider the generic type arguments.
+    pub(crate) fn borrows_external(self, ty: &ExternalType<'_>) -> bool {
+        self.visit_external(ty, &mut Vec::new())
+    }
+
+    /// Find the definition that the external type refers to, together with the scope of the
+    /// module it is defined in. Types from imported schemas are resolved through the imports of
+    /// the current module.
+    pub(crate) fn resolve(self, ty: &ExternalType<'_>) -> Option<(Self, &'a Definition<'a>)> {
+        self.resolve_local(ty).or_else(|| self.resolve_import(ty))
+    }
+
+    fn resolve_local(self, ty: &ExternalType<'_>) -> Option<(Self, &'a Definition<'a>)> {
+        let scope = ty
+            .path
+            .iter()
+            .try_fold(Self::new(self.definitions, self.schemas), |scope, name| {
+                scope.module(name)
+            })?;
+
+        scope.find(ty.name).map(|definition| (scope, definition))
+    }
+
+    /// Resolve the type through the imports of the current module. The type is either imported
+    /// directly (`use other::Type;`), or one of its parent modules is (`use other::module;`).
+    fn resolve_import(self, ty: &ExternalType<'_>) -> Option<(Self, &'a Definition<'a>)> {
+        self.definitions.iter().find_map(|def| {
+            let Definition::Import(Import {
+                segments, element, ..
+            }) = def
+            else {
+                return None;
+            };
+
+            let path = match (ty.path.split_first(), element) {
+                (None, Some(element)) if **element == *ty.name => &[][..],
+                (Some((first, rest)), None) if segments.last() == Some(first) => rest,
+                _ => return None,
+            };
+
+            let (root, modules) = segments.split_first()?;
+            let scope = modules
+                .iter()
+                .chain(path)
+                .try_fold(self.schema(root)?, |scope, name| scope.module(name))?;
+
+            scope.find(ty.name).map(|definition| (scope, definition))
+        })
+    }
+
+    /// Find a type definition with the given name, directly in this module.
+    fn find(self, name: &str) -> Option<&'a Definition<'a>> {
+        self.definitions.iter().find(|def| match def {
+            Definition::Struct(s) => s.name == name,
+            Definition::Enum(e) => e.name == name,
+            Definition::TypeAlias(a) => a.name == name,
+            _ => false,
+        })
+    }
+
+    /// Check whether the name refers to one of the generic type arguments of the current
+    /// definition.
+    pub(crate) fn is_generic(self, ty: &ExternalType<'_>) -> bool {
+        ty.path.is_empty() && self.generics.contains(&ty.name)
+    }
+
+    fn visit_fields(self, fields: &Fields<'_>, visited: &mut Vec<*const Definition<'a>>) -> bool {
+        fields
+            .fields
+            .iter()
+            .any(|field| self.visit(&field.ty, visited))
+    }
+
+    fn visit(self, ty: &Type<'_>, visited: &mut Vec<*const Definition<'a>>) -> bool {
+        match ty {
+            Type::Bool
+            | Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::U128
+            | Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::I128
+            | Type::F32
+            | Type::F64
+            | Type::String
+            | Type::Bytes
+            | Type::BoxString
+            | Type::BoxBytes
+            | Type::Timestamp
+            | Type::Duration
+            | Type::Uuid
+            | Type::Decimal => false,
+            Type::StringRef | Type::BytesRef => true,
+            // Non-zero strings and byte arrays are always owned.
+            Type::NonZero(ty) => {
+                !matches!(**ty, Type::StringRef | Type::BytesRef) && self.visit(ty, visited)
+            }
+            Type::Vec(ty)
+            | Type::HashSet(ty)
+            | Type::BTreeSet(ty)
+            | Type::Option(ty)
+            | Type::Array(ty, _) => self.visit(ty, visited),
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
+                self.visit(&kv.0, visited) || self.visit(&kv.1, visited)
+            }
+            Type::Tuple(types) => types.iter().any(|ty| self.visit(ty, visited)),
+            Type::External(ty) => {
+                ty.generics.iter().any(|ty| self.visit(ty, visited))
+                    || self.visit_external(ty, visited)
+            }
+        }
+    }
+
+    fn visit_external(
+        self,
+        ty: &ExternalType<'_>,
+        visited: &mut Vec<*const Definition<'a>>,
+    ) -> bool {
+        if self.is_generic(ty) {
+            return false;
+        }
+
+        let Some((scope, definition)) = self.resolve(ty) else {
+            return false;
+        };
+
+        // Recursive types don't add any borrowed data by themselves.
+        if visited.contains(&std::ptr::from_ref(definition)) {
+            return false;
+        }
+        visited.push(definition);
+
+        match definition {
+            Definition::Struct(s) => scope
+                .with_generics(&s.generics)
+                .visit_fields(&s.fields, visited),
+            Definition::Enum(e) => e.variants.iter().any(|variant| {
+                scope
+                    .with_generics(&e.generics)
+                    .visit_fields(&variant.fields, visited)
+            }),
+            Definition::TypeAlias(a) => scope.with_generics(&a.generics).visit(&a.target, visited),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use mabo_compiler::simplify::{Enum, Field, FieldKind, Struct, Type};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::{Opts, lifetimes::Scope};
+
+/// Implement merge decoding for the struct, which decodes the fields in place, instead of
+/// collecting them into a new instance.
+pub(super) fn compile_struct(
+    opts: &Opts,
+    scope: Scope<'_>,
+    unknown: bool,
+    Struct {
+        name,
+        generics,
+        fields,
+        ..
+    }: &Struct<'_>,
+) -> TokenStream {
+    let name = Ident::new(name, Span::call_site());
+    let (generics, generics_where) = compile_generics(generics);
+
+    if fields.kind == FieldKind::Unit {
+        return quote! {
+            #[automatically_derived]
+            #[allow(deprecated)]
+            impl #generics ::mabo::buf::DecodeMerge for #name #generics #generics_where {
+                fn decode_merge_with(
+                    &mut self,
+                    _: &mut impl ::mabo::Buf,
+                    _: &mut ::mabo::buf::Limits,
+                ) -> ::mabo::buf::Result<()> {
+                    Ok(())
+                }
+            }
+        };
+    }
+
+    let names = fields
+        .fields
+        .iter()
+        .map(|field| Ident::new(&field.name, Span::call_site()));
+    let unknown_name = unknown.then(|| quote! { unknown_fields, });
+    let names = if fields.kind == FieldKind::Named {
+        quote! { {#(#names,)* #unknown_name} }
+    } else {
+        quote! { (#(#names,)* #unknown_name) }
+    };
+    let field_matches = fields
+        .fields
+        .iter()
+        .map(|field| compile_field(opts, scope, fields.kind == FieldKind::Named, field));
+    let skip = if unknown {
+        quote! { { ::mabo::buf::decode_unknown_field(r, limits, id, unknown_fields)?; } }
+    } else {
+        quote! { ::mabo::buf::decode_skip_field(r, limits, id)? }
+    };
+    // Unknown fields are allocated and therefore count towards the limits as well.
+    let limited = unknown || super::decode::fields_use_limits(fields);
+    let body = super::decode::compile_nested(
+        limited,
+        &quote! {
+            loop {
+                let id = ::mabo::buf::decode_id(r, limits)?;
+                match id.value {
+                    ::mabo::buf::END_MARKER => break,
+                    #(#field_matches,)*
+                    _ => #skip,
+                }
+            }
+
+            Ok(())
+        },
+    );
+
+    quote! {
+        #[automatically_derived]
+        #[allow(deprecated)]
+        impl #generics ::mabo::buf::DecodeMerge for #name #generics #generics_where {
+            #[allow(clippy::too_many_lines)]
+            fn decode_merge_with(
+                &mut self,
+                r: &mut impl ::mabo::Buf,
+                limits: &mut ::mabo::buf::Limits,
+            ) -> ::mabo::buf::Result<()> {
+                let Self #names = self;
+                #body
+            }
+        }
+    }
+}
+
+/// Implement merge decoding for the enum, which always replaces the whole value, as the decoded
+/// variant may be a different one.
+pub(super) fn compile_enum(Enum { name, generics, .. }: &Enum<'_>) -> TokenStream {
+    let name = Ident::new(name, Span::call_site());
+    let (generics, generics_where) = compile_generics(generics);
+
+    quote! {
+        #[automatically_derived]
+        #[allow(deprecated)]
+        impl #generics ::mabo::buf::DecodeMerge for #name #generics #generics_where {
+            fn decode_merge_with(
+                &mut self,
+                r: &mut impl ::mabo::Buf,
+                limits: &mut ::mabo::buf::Limits,
+            ) -> ::mabo::buf::Result<()> {
+                *self = <Self as ::mabo::Decode>::decode_with(r, limits)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Merge a single field into its current value. Optional fields that are not set yet, are
+/// decoded as usual.
+fn compile_field(
+    opts: &Opts,
+    scope: Scope<'_>,
+    named: bool,
+    Field { name, ty, id, .. }: &Field<'_>,
+) -> TokenStream {
+    let id = proc_macro2::Literal::u32_unsuffixed(*id);
+    let name_lit = if named {
+        let lit = proc_macro2::Literal::string(name);
+        quote! { Some(#lit) }
+    } else {
+        quote! { None }
+    };
+    let name = Ident::new(name, Span::call_site());
+
+    let merge = if let Type::Option(ty) = ty {
+        let decode = super::decode::compile_data_type(opts, scope, false, ty, true);
+        if merges(ty) {
+            let merge = compile_merge(opts, scope, ty, &name);
+            quote! {
+                match #name {
+                    Some(#name) => #merge,
+                    None => #decode.map(|v| *#name = Some(v)),
+                }
+            }
+        } else {
+            quote! { #decode.map(|v| *#name = Some(v)) }
+        }
+    } else {
+        compile_merge(opts, scope, ty, &name)
+    };
+
+    quote! { #id => { #merge.map_err(|e| e.with_field(#id, #name_lit))?; } }
+}
+
+/// Whether the current value of a type is kept and extended, instead of being replaced.
+fn merges(ty: &Type<'_>) -> bool {
+    matches!(
+        ty,
+        Type::Vec(_)
+            | Type::HashMap(_)
+            | Type::HashSet(_)
+            | Type::BTreeMap(_)
+            | Type::BTreeSet(_)
+            | Type::External(_)
+    )
+}
+
+fn compile_merge(opts: &Opts, scope: Scope<'_>, ty: &Type<'_>, name: &Ident) -> TokenStream {
+    match ty {
+        Type::External(_) => {
+            quote! { ::mabo::buf::DecodeMerge::decode_merge_with(#name, r, limits) }
+        }
+        ty => {
+            let decode = super::decode::compile_data_type(opts, scope, false, ty, true);
+            if merges(ty) {
+                quote! { #decode.map(|v| ::mabo::buf::merge_extend(#name, v)) }
+            } else {
+                quote! { #decode.map(|v| *#name = v) }
+            }
+        }
+    }
+}
+
+fn compile_generics(types: &[&str]) -> (TokenStream, TokenStream) {
+    if types.is_empty() {
+        (TokenStream::new(), TokenStream::new())
+    } else {
+        let types = types.iter().map(|ty| Ident::new(ty, Span::call_site()));
+        let types2 = types.clone();
+
+        (
+            quote! { <#(#types,)*> },
+            quote! { where #(#types2: ::core::fmt::Debug + ::mabo::buf::DecodeMerge,)* },
+        )
+    }
+}
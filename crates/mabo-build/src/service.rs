@@ -0,0 +1,259 @@
+use mabo_compiler::simplify::{Method, MethodType, Service};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::{
+    Opts,
+    definition::{compile_comment, compile_data_type, compile_deprecated},
+    lifetimes::Scope,
+};
+
+/// Generate a trait for the service, together with a client that implements it on top of any
+/// [`Transport`](mabo::service::Transport), and a server that dispatches incoming calls to any
+/// implementation of the trait.
+pub(super) fn compile_service(opts: &Opts, scope: Scope<'_>, service: &Service<'_>) -> TokenStream {
+    let def = compile_trait(opts, scope, service);
+    let client = compile_client(opts, scope, service);
+    let server = compile_server(service);
+
+    quote! {
+        #def
+        #client
+        #server
+    }
+}
+
+fn compile_trait(
+    opts: &Opts,
+    scope: Scope<'_>,
+    Service {
+        comment,
+        name,
+        methods,
+        deprecated,
+        ..
+    }: &Service<'_>,
+) -> TokenStream {
+    let comment = compile_comment(comment);
+    let deprecated = compile_deprecated(deprecated.as_ref());
+    let name = Ident::new(name, Span::call_site());
+    let methods = methods.iter().map(|method| {
+        let comment = compile_comment(&method.comment);
+        let deprecated = compile_deprecated(method.deprecated.as_ref());
+        let signature = compile_signature(opts, scope, method);
+
+        quote! {
+            #comment
+            #deprecated
+            #signature;
+        }
+    });
+
+    quote! {
+        #comment
+        #deprecated
+        #[allow(clippy::module_name_repetitions)]
+        pub trait #name: Send + Sync {
+            #(#methods)*
+        }
+    }
+}
+
+fn compile_client(
+    opts: &Opts,
+    scope: Scope<'_>,
+    Service { name, methods, .. }: &Service<'_>,
+) -> TokenStream {
+    let service = *name;
+    let client = format_ident!("{name}Client");
+    let name = Ident::new(name, Span::call_site());
+    let docs =
+        format!(" Client for the [`{service}`] service, that sends calls through a transport.");
+    let descriptions = methods.iter().map(|method| {
+        let method_name = method.name;
+        let id = proc_macro2::Literal::u32_unsuffixed(method.id);
+        let request_stream = method.request.stream;
+        let response_stream = method.response.stream;
+
+        quote! {
+            ::mabo::service::Method {
+                service: #service,
+                name: #method_name,
+                id: #id,
+                request_stream: #request_stream,
+                response_stream: #response_stream,
+            }
+        }
+    });
+    let calls = methods.iter().enumerate().map(|(i, method)| {
+        let i = proc_macro2::Literal::usize_unsuffixed(i);
+        let signature = compile_signature(opts, scope, method);
+        let requests = if method.request.stream {
+            quote! { ::mabo::service::encode_stream(requests) }
+        } else {
+            quote! { ::mabo::service::once(::mabo::service::encode(&request)) }
+        };
+        let response = if method.response.stream {
+            quote! { Ok(::mabo::service::decode_stream(responses)) }
+        } else {
+            quote! { ::mabo::service::decode(::mabo::service::single(responses).await?) }
+        };
+
+        quote! {
+            #signature {
+                async move {
+                    let responses = self
+                        .transport
+                        .call(&Self::METHODS[#i], #requests)
+                        .await?;
+                    #response
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #docs]
+        #[allow(clippy::module_name_repetitions)]
+        pub struct #client<T> {
+            transport: T,
+        }
+
+        #[allow(deprecated)]
+        impl<T> #client<T> {
+            /// Descriptions of all methods that the service provides.
+            pub const METHODS: &'static [::mabo::service::Method] = &[#(#descriptions,)*];
+
+            /// Create a new client, that sends all calls through the given transport.
+            pub const fn new(transport: T) -> Self {
+                Self { transport }
+            }
+
+            /// Get back the underlying transport.
+            pub fn into_inner(self) -> T {
+                self.transport
+            }
+        }
+
+        #[automatically_derived]
+        #[allow(deprecated, clippy::manual_async_fn)]
+        impl<T: ::mabo::service::Transport> #name for #client<T> {
+            #(#calls)*
+        }
+    }
+}
+
+fn compile_server(Service { name, methods, .. }: &Service<'_>) -> TokenStream {
+    let client = format_ident!("{name}Client");
+    let server = format_ident!("{name}Server");
+    let docs = format!(
+        " Server for the [`{name}`] service, that dispatches incoming calls to an implementation \
+         of it."
+    );
+    let name = Ident::new(name, Span::call_site());
+    let calls = methods.iter().map(|method| {
+        let id = proc_macro2::Literal::u32_unsuffixed(method.id);
+        let method_name = Ident::new(method.name, Span::call_site());
+        let request = if method.request.stream {
+            quote! { ::mabo::service::decode_stream(requests) }
+        } else {
+            quote! { ::mabo::service::decode(::mabo::service::single(requests).await?)? }
+        };
+        let response = if method.response.stream {
+            quote! { ::mabo::service::encode_stream(response) }
+        } else {
+            quote! { ::mabo::service::once(::mabo::service::encode(&response)) }
+        };
+
+        quote! {
+            #id => {
+                let response = self.service.#method_name(#request).await?;
+                Ok(#response)
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #docs]
+        #[allow(clippy::module_name_repetitions)]
+        pub struct #server<S> {
+            service: S,
+        }
+
+        impl<S> #server<S> {
+            /// Create a new server, that dispatches all calls to the given service implementation.
+            pub const fn new(service: S) -> Self {
+                Self { service }
+            }
+
+            /// Get back the underlying service implementation.
+            pub fn into_inner(self) -> S {
+                self.service
+            }
+        }
+
+        #[automatically_derived]
+        #[allow(deprecated, clippy::manual_async_fn)]
+        impl<S: #name> ::mabo::service::Handler for #server<S> {
+            const METHODS: &'static [::mabo::service::Method] = #client::<()>::METHODS;
+
+            fn handle(
+                &self,
+                method: u32,
+                requests: ::mabo::service::BoxStream<::mabo::buf::Bytes>,
+            ) -> impl ::core::future::Future<
+                Output = ::core::result::Result<
+                    ::mabo::service::BoxStream<::mabo::buf::Bytes>,
+                    ::mabo::service::Error,
+                >,
+            > + Send {
+                async move {
+                    match method {
+                        #(#calls)*
+                        _ => Err(::mabo::service::Error::UnknownMethod(method)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Signature of a method, that is shared between the trait declaration and the client.
+fn compile_signature(opts: &Opts, scope: Scope<'_>, method: &Method<'_>) -> TokenStream {
+    let name = Ident::new(method.name, Span::call_site());
+    let request = if method.request.stream {
+        quote! { requests }
+    } else {
+        quote! { request }
+    };
+    let request_ty = compile_type(opts, scope, &method.request);
+    let response = compile_type(opts, scope, &method.response);
+
+    quote! {
+        fn #name(
+            &self,
+            #request: #request_ty,
+        ) -> impl ::core::future::Future<
+            Output = ::core::result::Result<#response, ::mabo::service::Error>,
+        > + Send
+    }
+}
+
+/// Type of a request or response, which is wrapped in a stream if it carries multiple values.
+fn compile_type(
+    opts: &Opts,
+    scope: Scope<'_>,
+    MethodType { ty, stream }: &MethodType<'_>,
+) -> TokenStream {
+    assert!(
+        !scope.borrows(ty),
+        "service methods can't use types that borrow data"
+    );
+
+    let ty = compile_data_type(opts, scope, ty);
+    if *stream {
+        quote! { ::mabo::service::BoxStream<#ty> }
+    } else {
+        ty
+    }
+}
@@ -1,11 +1,13 @@
 use mabo_compiler::simplify::{Enum, Field, FieldKind, Fields, Struct, Type, Variant};
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{ToTokens, quote};
+use quote::{ToTokens, format_ident, quote};
 
-use crate::{BytesType, Opts};
+use crate::{BytesType, Opts, TimestampType, UuidType};
 
 pub(super) fn compile_struct(
     opts: &Opts,
+    borrows: bool,
+    unknown: bool,
     Struct {
         name,
         generics,
@@ -17,55 +19,118 @@ pub(super) fn compile_struct(
         .fields
         .iter()
         .map(|field| Ident::new(&field.name, Span::call_site()));
+    let unknown_name = unknown.then(|| quote! { unknown_fields, });
     let names = match fields.kind {
-        FieldKind::Named => quote! { {#(#names,)*} },
-        FieldKind::Unnamed => quote! { (#(#names,)*) },
+        FieldKind::Named => quote! { {#(#names,)* #unknown_name} },
+        FieldKind::Unnamed => quote! { (#(#names,)* #unknown_name) },
         FieldKind::Unit => quote! {},
     };
 
     let name = Ident::new(name, Span::call_site());
-    let (generics, generics_where) = compile_generics(generics);
-    let fields = compile_struct_fields(opts, fields);
+    let (generics, type_generics, generics_where) = compile_generics(borrows, generics);
+    let cached = fields_use_sizes(fields);
+    let body = compile_struct_fields(opts, fields, unknown);
+    let body = quote! {
+        let Self #names = self;
+        #body
+    };
+    let body = compile_body(
+        cached,
+        &quote! {
+            clippy::borrow_deref_ref,
+            clippy::deref_addrof,
+            clippy::explicit_auto_deref,
+            clippy::needless_borrow,
+            clippy::too_many_lines,
+        },
+        &body,
+    );
 
     quote! {
         #[automatically_derived]
-        impl #generics ::mabo::buf::Size for #name #generics #generics_where {
-            #[allow(
-                clippy::borrow_deref_ref,
-                clippy::deref_addrof,
-                clippy::explicit_auto_deref,
-                clippy::needless_borrow,
-                clippy::too_many_lines,
-            )]
+        #[allow(deprecated)]
+        impl #generics ::mabo::buf::Size for #name #type_generics #generics_where {
+            #body
+        }
+    }
+}
+
+/// Whether any of the fields depends on the [`SizeCache`](mabo::buf::SizeCache), in which case
+/// the generated type implements the cached calculation and encoding.
+pub(super) fn fields_use_sizes(fields: &Fields<'_>) -> bool {
+    fields.fields.iter().any(|field| {
+        let ty = if let Type::Option(ty) = &field.ty {
+            ty
+        } else {
+            &field.ty
+        };
+        uses_sizes(ty, true)
+    })
+}
+
+/// Whether the size of the type is recorded in the size cache, which is the case for all
+/// length-prefixed values, except for packed vectors and arrays, as their content size is cheap
+/// to calculate. Nested structs and enums might have such values inside of them.
+pub(super) fn uses_sizes(ty: &Type<'_>, root: bool) -> bool {
+    match ty {
+        Type::Vec(ty) | Type::Array(ty, _) => !super::definition::is_packed(ty),
+        Type::HashMap(_)
+        | Type::HashSet(_)
+        | Type::BTreeMap(_)
+        | Type::BTreeSet(_)
+        | Type::External(_) => true,
+        Type::Option(ty) | Type::NonZero(ty) => uses_sizes(ty, false),
+        Type::Tuple(types) => root || types.iter().any(|ty| uses_sizes(ty, false)),
+        _ => false,
+    }
+}
+
+/// Name for the size cache parameter of a closure, that calculates or encodes a value of the given
+/// type. It is left unnamed if the type doesn't need it, to not trigger any unused warnings.
+pub(super) fn sizes_param(ty: &Type<'_>) -> TokenStream {
+    if uses_sizes(ty, false) {
+        quote! { sizes }
+    } else {
+        quote! { _ }
+    }
+}
+
+/// Either the plain size calculation, or the cached one, together with a plain calculation that
+/// forwards to it.
+fn compile_body(cached: bool, lints: &TokenStream, body: &TokenStream) -> TokenStream {
+    if cached {
+        quote! {
             fn size(&self) -> usize {
-                let Self #names = self;
-                #fields
+                self.size_cached(&mut ::mabo::buf::SizeCache::disabled())
+            }
+
+            #[allow(#lints)]
+            fn size_cached(&self, sizes: &mut ::mabo::buf::SizeCache) -> usize {
+                #body
+            }
+        }
+    } else {
+        quote! {
+            #[allow(#lints)]
+            fn size(&self) -> usize {
+                #body
             }
         }
     }
 }
 
-fn compile_struct_fields(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
+fn compile_struct_fields(opts: &Opts, fields: &Fields<'_>, unknown: bool) -> TokenStream {
     if fields.kind == FieldKind::Unit {
         quote! { 0 }
     } else {
-        let calls = fields.fields.iter().map(|Field { name, ty, id, .. }| {
-            let id = proc_macro2::Literal::u32_unsuffixed(*id);
-            let name = proc_macro2::Ident::new(name, Span::call_site());
+        let calls = fields.fields.iter().map(|field| compile_field(opts, field));
 
-            if let Type::Option(ty) = &ty {
-                let ty = compile_data_type(opts, ty, quote! { v });
-                quote! {
-                    ::mabo::buf::size_field_option(#id, #name.as_ref(), |v| { #ty })
-                }
-            } else {
-                let ty = compile_data_type(opts, ty, name.into_token_stream());
-                quote! { ::mabo::buf::size_field(#id, || { #ty }) }
-            }
-        });
+        let unknown =
+            unknown.then(|| quote! { ::mabo::buf::size_unknown_fields(unknown_fields) + });
 
         quote! {
             #(#calls +)*
+            #unknown
             ::mabo::buf::END_MARKER_SIZE
         }
     }
@@ -73,6 +138,8 @@ fn compile_struct_fields(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
 
 pub(super) fn compile_enum(
     opts: &Opts,
+    borrows: bool,
+    unknown: bool,
     Enum {
         name,
         generics,
@@ -81,23 +148,33 @@ pub(super) fn compile_enum(
     }: &Enum<'_>,
 ) -> TokenStream {
     let name = Ident::new(name, Span::call_site());
-    let (generics, generics_where) = compile_generics(generics);
+    let (generics, type_generics, generics_where) = compile_generics(borrows, generics);
+    let cached = variants.iter().any(|v| fields_use_sizes(&v.fields));
     let variants = variants.iter().map(|v| compile_variant(opts, v));
+    let unknown = unknown.then(|| {
+        quote! { Self::Unknown { id, data } => ::mabo::buf::size_unknown_variant(*id, data), }
+    });
+    let body = compile_body(
+        cached,
+        &quote! {
+            clippy::borrow_deref_ref,
+            clippy::deref_addrof,
+            clippy::semicolon_if_nothing_returned,
+            clippy::too_many_lines,
+        },
+        &quote! {
+            match self {
+                #(#variants,)*
+                #unknown
+            }
+        },
+    );
 
     quote! {
         #[automatically_derived]
-        impl #generics ::mabo::buf::Size for #name #generics #generics_where {
-            #[allow(
-                clippy::borrow_deref_ref,
-                clippy::deref_addrof,
-                clippy::semicolon_if_nothing_returned,
-                clippy::too_many_lines,
-            )]
-            fn size(&self) -> usize {
-                match self {
-                    #(#variants,)*
-                }
-            }
+        #[allow(deprecated)]
+        impl #generics ::mabo::buf::Size for #name #type_generics #generics_where {
+            #body
         }
     }
 }
@@ -145,20 +222,7 @@ fn compile_variant_fields(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
     if fields.kind == FieldKind::Unit {
         quote! { 0 }
     } else {
-        let calls = fields.fields.iter().map(|Field { name, ty, id, .. }| {
-            let id = proc_macro2::Literal::u32_unsuffixed(*id);
-            let name = proc_macro2::Ident::new(name, Span::call_site());
-
-            if let Type::Option(ty) = &ty {
-                let ty = compile_data_type(opts, ty, quote! { v });
-                quote! {
-                    ::mabo::buf::size_field_option(#id, #name.as_ref(), |v| { #ty })
-                }
-            } else {
-                let ty = compile_data_type(opts, ty, name.into_token_stream());
-                quote! { ::mabo::buf::size_field(#id, || { #ty }) }
-            }
-        });
+        let calls = fields.fields.iter().map(|field| compile_field(opts, field));
 
         quote! {
            #(#calls +)*
@@ -167,22 +231,49 @@ fn compile_variant_fields(opts: &Opts, fields: &Fields<'_>) -> TokenStream {
     }
 }
 
-fn compile_generics(types: &[&str]) -> (TokenStream, TokenStream) {
+fn compile_field(opts: &Opts, Field { name, ty, id, .. }: &Field<'_>) -> TokenStream {
+    let id = proc_macro2::Literal::u32_unsuffixed(*id);
+    let name = proc_macro2::Ident::new(name, Span::call_site());
+
+    if let Type::Option(ty) = &ty {
+        let ty = compile_data_type(opts, ty, quote! { v }, true);
+        quote! {
+            ::mabo::buf::size_field_option(#id, #name.as_ref(), |v| { #ty })
+        }
+    } else {
+        let ty = compile_data_type(opts, ty, name.into_token_stream(), true);
+        quote! { ::mabo::buf::size_field(#id, || { #ty }) }
+    }
+}
+
+/// Generics for the implementation block and the type itself. Lifetimes are elided, as the
+/// implementation doesn't depend on it.
+fn compile_generics(borrows: bool, types: &[&str]) -> (TokenStream, TokenStream, TokenStream) {
+    let lifetime = borrows.then(|| quote! { '_, });
+
     if types.is_empty() {
-        Default::default()
+        (
+            TokenStream::new(),
+            lifetime
+                .map(|lifetime| quote! { <#lifetime> })
+                .unwrap_or_default(),
+            TokenStream::new(),
+        )
     } else {
         let types = types.iter().map(|ty| Ident::new(ty, Span::call_site()));
         let types2 = types.clone();
+        let types3 = types.clone();
 
         (
             quote! { <#(#types,)*> },
-            quote! { where #(#types2: ::mabo::buf::Size,)* },
+            quote! { <#lifetime #(#types2,)*> },
+            quote! { where #(#types3: ::mabo::buf::Size,)* },
         )
     }
 }
 
-#[expect(clippy::needless_pass_by_value)]
-pub(crate) fn compile_data_type(opts: &Opts, ty: &Type<'_>, name: TokenStream) -> TokenStream {
+#[expect(clippy::needless_pass_by_value, clippy::too_many_lines)]
+fn compile_data_type(opts: &Opts, ty: &Type<'_>, name: TokenStream, root: bool) -> TokenStream {
     match &ty {
         Type::Bool => quote! { ::mabo::buf::size_bool(*#name) },
         Type::U8 => quote! { ::mabo::buf::size_u8(*#name) },
@@ -200,25 +291,49 @@ pub(crate) fn compile_data_type(opts: &Opts, ty: &Type<'_>, name: TokenStream) -
         Type::String | Type::StringRef | Type::BoxString => {
             quote! { ::mabo::buf::size_string(#name) }
         }
-        Type::Bytes | Type::BytesRef | Type::BoxBytes => match opts.bytes_type {
+        Type::BytesRef => quote! { ::mabo::buf::size_bytes_std(#name) },
+        Type::Bytes | Type::BoxBytes => match opts.bytes_type {
             BytesType::VecU8 => quote! { ::mabo::buf::size_bytes_std(#name) },
             BytesType::Bytes => quote! { ::mabo::buf::size_bytes_bytes(#name) },
         },
+        Type::Timestamp => match opts.timestamp_type {
+            TimestampType::SystemTime if opts.no_std => {
+                quote! { ::mabo::buf::size_timestamp(#name.0, #name.1) }
+            }
+            TimestampType::SystemTime => quote! { ::mabo::buf::size_timestamp_std(#name) },
+            TimestampType::Chrono => quote! { ::mabo::buf::size_timestamp_chrono(#name) },
+        },
+        Type::Duration => quote! { ::mabo::buf::size_duration(#name) },
+        Type::Uuid => match opts.uuid_type {
+            UuidType::Bytes => quote! { ::mabo::buf::size_uuid(#name) },
+            UuidType::Uuid => quote! { ::mabo::buf::size_uuid(#name.as_bytes()) },
+        },
+        Type::Decimal => quote! { ::mabo::buf::size_decimal(#name) },
+        Type::Vec(ty) if super::definition::is_packed(ty) => {
+            quote! { ::mabo::buf::size_vec_packed(#name) }
+        }
         Type::Vec(ty) => {
-            let ty = compile_data_type(opts, ty, quote! { v });
-            quote! { ::mabo::buf::size_vec(#name, |v| { #ty }) }
+            let sizes = sizes_param(ty);
+            let ty = compile_data_type(opts, ty, quote! { v }, false);
+            quote! { ::mabo::buf::size_vec_cached(sizes, #name, |#sizes, v| { #ty }) }
         }
-        Type::HashMap(kv) => {
-            let ty_k = compile_data_type(opts, &kv.0, quote! { k });
-            let ty_v = compile_data_type(opts, &kv.1, quote! { v });
-            quote! { ::mabo::buf::size_hash_map(#name, |k| { #ty_k }, |v| { #ty_v }) }
+        Type::HashMap(kv) | Type::BTreeMap(kv) => {
+            let (sizes_k, sizes_v) = (sizes_param(&kv.0), sizes_param(&kv.1));
+            let ty_k = compile_data_type(opts, &kv.0, quote! { k }, false);
+            let ty_v = compile_data_type(opts, &kv.1, quote! { v }, false);
+            let size = format_ident!("size_{}_cached", super::definition::collection_kind(ty));
+            quote! {
+                ::mabo::buf::#size(sizes, #name, |#sizes_k, k| { #ty_k }, |#sizes_v, v| { #ty_v })
+            }
         }
-        Type::HashSet(ty) => {
-            let ty = compile_data_type(opts, ty, quote! { v });
-            quote! { ::mabo::buf::size_hash_set(#name, |v| { #ty }) }
+        Type::HashSet(inner) | Type::BTreeSet(inner) => {
+            let sizes = sizes_param(inner);
+            let inner = compile_data_type(opts, inner, quote! { v }, false);
+            let size = format_ident!("size_{}_cached", super::definition::collection_kind(ty));
+            quote! { ::mabo::buf::#size(sizes, #name, |#sizes, v| { #inner }) }
         }
         Type::Option(ty) => {
-            let ty = compile_data_type(opts, ty, quote! { v });
+            let ty = compile_data_type(opts, ty, quote! { v }, false);
             quote! { ::mabo::buf::size_option(#name.as_ref(), |v| { #ty }) }
         }
         Type::NonZero(ty) => match &**ty {
@@ -238,25 +353,37 @@ pub(crate) fn compile_data_type(opts: &Opts, ty: &Type<'_>, name: TokenStream) -
             | Type::BytesRef
             | Type::Vec(_)
             | Type::HashMap(_)
-            | Type::HashSet(_) => compile_data_type(opts, ty, quote! { #name.get() }),
+            | Type::HashSet(_)
+            | Type::BTreeMap(_)
+            | Type::BTreeSet(_) => compile_data_type(opts, ty, quote! { #name.get() }, false),
             ty => todo!("compiler should catch invalid {ty:?} type"),
         },
         Type::Tuple(types) => match types.len() {
             2..=12 => {
+                let sizes = sizes_param(ty);
                 let types = types.iter().enumerate().map(|(idx, ty)| {
                     let idx = proc_macro2::Literal::usize_unsuffixed(idx);
-                    compile_data_type(opts, ty, quote! { &#name.#idx })
+                    compile_data_type(opts, ty, quote! { &#name.#idx }, false)
                 });
-                quote! { #(#types)+* }
+
+                if root {
+                    quote! { ::mabo::buf::size_tuple_cached(sizes, |#sizes| { #(#types)+* }) }
+                } else {
+                    quote! { #(#types)+* }
+                }
             }
             n => todo!("compiler should catch invalid tuple with {n} elements"),
         },
+        Type::Array(ty, _size) if super::definition::is_packed(ty) => {
+            quote! { ::mabo::buf::size_array_packed(#name) }
+        }
         Type::Array(ty, _size) => {
-            let ty = compile_data_type(opts, ty, quote! { v });
-            quote! { ::mabo::buf::size_array(#name, |v| { #ty }) }
+            let sizes = sizes_param(ty);
+            let ty = compile_data_type(opts, ty, quote! { v }, false);
+            quote! { ::mabo::buf::size_array_cached(sizes, #name, |#sizes, v| { #ty }) }
         }
         Type::External(_) => {
-            quote! { #name.size() }
+            quote! { #name.size_cached(sizes) }
         }
     }
 }
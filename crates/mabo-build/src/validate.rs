@@ -0,0 +1,236 @@
+use mabo_compiler::simplify::{Bound, Enum, Field, FieldKind, Fields, Struct, Type, Validation};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{ToTokens, quote};
+
+use crate::definition::compile_generics;
+
+/// Whether any of the fields declares validation rules.
+pub(super) fn has_rules(fields: &Fields<'_>) -> bool {
+    fields
+        .fields
+        .iter()
+        .any(|field| !field.validations.is_empty())
+}
+
+/// Implement [`Validate`](mabo::validate::Validate) for structs, that declare validation rules for
+/// any of their fields.
+pub(super) fn compile_struct(
+    borrows: bool,
+    Struct {
+        name,
+        generics,
+        fields,
+        ..
+    }: &Struct<'_>,
+) -> Option<TokenStream> {
+    if !has_rules(fields) {
+        return None;
+    }
+
+    let name = Ident::new(name, Span::call_site());
+    let generics = compile_generics(borrows, generics);
+    let (names, checks) = compile_fields(fields, &quote! { None });
+
+    Some(quote! {
+        #[automatically_derived]
+        #[allow(deprecated)]
+        impl #generics ::mabo::validate::Validate for #name #generics {
+            #[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]
+            fn validate(&self) -> ::core::result::Result<(), ::mabo::validate::Violations> {
+                let mut violations = ::mabo::validate::Violations::new();
+                let Self { #(#names,)* .. } = self;
+                #(#checks)*
+                violations.into_result()
+            }
+        }
+    })
+}
+
+/// Implement [`Validate`](mabo::validate::Validate) for enums, that declare validation rules for
+/// any fields of their variants.
+pub(super) fn compile_enum(
+    borrows: bool,
+    unknown: bool,
+    Enum {
+        name,
+        generics,
+        variants,
+        ..
+    }: &Enum<'_>,
+) -> Option<TokenStream> {
+    if !variants.iter().any(|variant| has_rules(&variant.fields)) {
+        return None;
+    }
+
+    let name = Ident::new(name, Span::call_site());
+    let generics = compile_generics(borrows, generics);
+    let others = (unknown || !variants.iter().all(|variant| has_rules(&variant.fields)))
+        .then(|| quote! { _ => {} });
+    let variants = variants
+        .iter()
+        .filter(|variant| has_rules(&variant.fields))
+        .map(|variant| {
+            let variant_lit = proc_macro2::Literal::string(variant.name);
+            let (names, checks) = compile_fields(&variant.fields, &quote! { Some(#variant_lit) });
+            let variant = Ident::new(variant.name, Span::call_site());
+
+            quote! {
+                Self::#variant { #(#names,)* .. } => {
+                    #(#checks)*
+                }
+            }
+        });
+
+    Some(quote! {
+        #[automatically_derived]
+        #[allow(deprecated)]
+        impl #generics ::mabo::validate::Validate for #name #generics {
+            #[allow(unused_comparisons, clippy::absurd_extreme_comparisons)]
+            fn validate(&self) -> ::core::result::Result<(), ::mabo::validate::Violations> {
+                let mut violations = ::mabo::validate::Violations::new();
+                #[allow(clippy::match_wildcard_for_single_variants, clippy::single_match)]
+                match self {
+                    #(#variants)*
+                    #others
+                }
+                violations.into_result()
+            }
+        }
+    })
+}
+
+/// Bind the fields that have rules, and check each of them. Only named fields can declare rules.
+fn compile_fields(fields: &Fields<'_>, variant: &TokenStream) -> (Vec<Ident>, Vec<TokenStream>) {
+    debug_assert!(fields.kind == FieldKind::Named);
+
+    fields
+        .fields
+        .iter()
+        .filter(|field| !field.validations.is_empty())
+        .map(|field| {
+            let name = Ident::new(&field.name, Span::call_site());
+            let check = compile_field(&name, variant, field);
+            (name, check)
+        })
+        .unzip()
+}
+
+fn compile_field(
+    binding: &Ident,
+    variant: &TokenStream,
+    Field {
+        name,
+        ty,
+        validations,
+        ..
+    }: &Field<'_>,
+) -> TokenStream {
+    // Rules of optional fields apply to the value, if present.
+    let (ty, optional) = match ty {
+        Type::Option(ty) => (&**ty, true),
+        ty => (ty, false),
+    };
+    let name = proc_macro2::Literal::string(name);
+    let checks = validations.iter().map(|validation| {
+        let (valid, rule) = compile_rule(ty, validation);
+        quote! { violations.check(#valid, #name, #variant, #rule); }
+    });
+
+    if optional {
+        quote! {
+            if let Some(v) = #binding {
+                #(#checks)*
+            }
+        }
+    } else {
+        quote! {
+            {
+                let v = #binding;
+                #(#checks)*
+            }
+        }
+    }
+}
+
+/// Condition that is `true` for valid values of `v`, together with the rule it represents.
+fn compile_rule(ty: &Type<'_>, validation: &Validation) -> (TokenStream, TokenStream) {
+    match validation {
+        Validation::Min(bound) => {
+            let (value, text) = compile_bound(ty, *bound);
+            (
+                quote! { *v >= #value },
+                quote! { ::mabo::validate::Rule::Min(#text) },
+            )
+        }
+        Validation::Max(bound) => {
+            let (value, text) = compile_bound(ty, *bound);
+            (
+                quote! { *v <= #value },
+                quote! { ::mabo::validate::Rule::Max(#text) },
+            )
+        }
+        Validation::MinLen(len) => {
+            let len_expr = compile_len(ty);
+            let len = proc_macro2::Literal::u64_unsuffixed(*len);
+            (
+                quote! { #len_expr >= #len },
+                quote! { ::mabo::validate::Rule::MinLen(#len) },
+            )
+        }
+        Validation::MaxLen(len) => {
+            let len_expr = compile_len(ty);
+            let len = proc_macro2::Literal::u64_unsuffixed(*len);
+            (
+                quote! { #len_expr <= #len },
+                quote! { ::mabo::validate::Rule::MaxLen(#len) },
+            )
+        }
+        Validation::Pattern(pattern) => {
+            let pattern = proc_macro2::Literal::string(pattern);
+            (
+                quote! {{
+                    static PATTERN: ::mabo::validate::Pattern =
+                        ::mabo::validate::Pattern::new(#pattern);
+                    PATTERN.is_match(v)
+                }},
+                quote! { ::mabo::validate::Rule::Pattern(#pattern) },
+            )
+        }
+        Validation::NonEmpty => (
+            quote! { !v.is_empty() },
+            quote! { ::mabo::validate::Rule::NonEmpty },
+        ),
+    }
+}
+
+/// Numeric bound as literal of the field's type, and the way it is shown in violations.
+#[expect(clippy::cast_precision_loss)]
+fn compile_bound(ty: &Type<'_>, bound: Bound) -> (TokenStream, proc_macro2::Literal) {
+    let (value, text) = match (ty, bound) {
+        (Type::F32 | Type::F64, Bound::Int(value)) => (
+            proc_macro2::Literal::f64_unsuffixed(value as f64),
+            value.to_string(),
+        ),
+        (_, Bound::Int(value)) => (
+            proc_macro2::Literal::i128_unsuffixed(value),
+            value.to_string(),
+        ),
+        (_, Bound::Float(value)) => (
+            proc_macro2::Literal::f64_unsuffixed(value),
+            value.to_string(),
+        ),
+    };
+
+    (
+        value.into_token_stream(),
+        proc_macro2::Literal::string(&text),
+    )
+}
+
+/// Length of the value, where strings are measured in characters.
+fn compile_len(ty: &Type<'_>) -> TokenStream {
+    match ty {
+        Type::String | Type::StringRef | Type::BoxString => quote! { v.chars().count() },
+        _ => quote! { v.len() },
+    }
+}
@@ -0,0 +1,217 @@
+use mabo_compiler::simplify::{Field, FieldKind, Struct, Type};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::{Opts, lifetimes::Scope};
+
+/// Generate a view type for the struct, which indexes the location of all fields in an encoded
+/// payload once, and only decodes individual fields when they're accessed.
+///
+/// Views always borrow from the payload, so any nested value is decoded the same way as it is for
+/// types that contain `&string` or `&bytes` fields.
+pub(super) fn compile_struct(
+    opts: &Opts,
+    scope: Scope<'_>,
+    Struct { name, fields, .. }: &Struct<'_>,
+) -> TokenStream {
+    let doc = format!(" Lazy view on an encoded [`{name}`], that decodes fields on access.");
+    let view = format_ident!("{name}View");
+    let named = fields.kind == FieldKind::Named;
+    let fields = &fields.fields;
+    let count = proc_macro2::Literal::usize_unsuffixed(fields.len());
+    let field_matches = fields.iter().enumerate().map(|(idx, field)| {
+        let idx = proc_macro2::Literal::usize_unsuffixed(idx);
+        let id = proc_macro2::Literal::u32_unsuffixed(field.id);
+        quote! { #id => view.fields[#idx] = Some(field) }
+    });
+    let skips = fields
+        .iter()
+        .filter(|field| !self_delimiting(&field.ty))
+        .map(|field| compile_skip(opts, scope, named, field))
+        .collect::<Vec<_>>();
+    let skip = if skips.is_empty() {
+        quote! { ::mabo::buf::decode_skip(r, limits, id.encoding)?; }
+    } else {
+        quote! {
+            match id.value {
+                #(#skips,)*
+                _ => ::mabo::buf::decode_skip(r, limits, id.encoding)?,
+            }
+        }
+    };
+    let accessors = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| compile_accessor(opts, scope, named, idx, field));
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug)]
+        #[allow(dead_code, clippy::module_name_repetitions)]
+        pub struct #view<'a> {
+            options: ::mabo::buf::DecodeOptions,
+            fields: [Option<&'a [u8]>; #count],
+        }
+
+        #[allow(
+            dead_code,
+            deprecated,
+            clippy::missing_errors_doc,
+            clippy::option_option,
+            clippy::single_match,
+            clippy::single_match_else,
+            clippy::type_complexity,
+        )]
+        impl<'a> #view<'a> {
+            /// Scan the encoded struct once, to find the location of each of its fields.
+            ///
+            /// # Errors
+            ///
+            /// Will return `Err` if the payload is not a valid encoded struct. The content of the
+            /// fields is only checked once it is accessed.
+            pub fn new(data: &'a [u8]) -> ::mabo::buf::Result<Self> {
+                Self::new_with(data, ::mabo::buf::DecodeOptions::new())
+            }
+
+            /// Same as [`Self::new`], but applies the given limits whenever a field is decoded.
+            ///
+            /// # Errors
+            ///
+            /// Will return `Err` if the payload is not a valid encoded struct.
+            pub fn new_with(
+                mut data: &'a [u8],
+                options: ::mabo::buf::DecodeOptions,
+            ) -> ::mabo::buf::Result<Self> {
+                let r = &mut data;
+                let limits = &mut ::mabo::buf::Limits::new(options);
+                let mut view = Self {
+                    options,
+                    fields: [None; #count],
+                };
+
+                loop {
+                    let id = ::mabo::buf::decode_id(r, limits)?;
+                    if id.value == ::mabo::buf::END_MARKER {
+                        break;
+                    }
+
+                    let field = *r;
+                    #skip
+                    let field = &field[..field.len() - r.len()];
+
+                    match id.value {
+                        #(#field_matches,)*
+                        _ => {}
+                    }
+                }
+
+                Ok(view)
+            }
+
+            #(#accessors)*
+        }
+    }
+}
+
+/// Whether the extent of the field's value can be found from the field encoding alone.
+///
+/// Nested structs and enums, as well as options inside optional fields, are marked as length
+/// prefixed, but are written without the length. The view has to decode them to skip over them.
+fn self_delimiting(ty: &Type<'_>) -> bool {
+    match ty {
+        Type::Option(ty) => !matches!(**ty, Type::Option(_) | Type::External(_)),
+        Type::External(_) => false,
+        _ => true,
+    }
+}
+
+/// Skipping of a field, that isn't [`self_delimiting`], by decoding and dropping its value.
+fn compile_skip(opts: &Opts, scope: Scope<'_>, named: bool, field: &Field<'_>) -> TokenStream {
+    let id = proc_macro2::Literal::u32_unsuffixed(field.id);
+    let name_lit = compile_name(named, &field.name);
+    let ty = if let Type::Option(ty) = &field.ty {
+        ty
+    } else {
+        &field.ty
+    };
+    let ty_ident = super::definition::compile_data_type(opts, scope, ty);
+    let decode = super::decode::compile_data_type(opts, scope, true, ty, true);
+
+    quote! {
+        #id => {
+            let _: #ty_ident = #decode.map_err(|e| e.with_field(#id, #name_lit))?;
+        }
+    }
+}
+
+fn compile_name(named: bool, name: &str) -> TokenStream {
+    if named {
+        let lit = proc_macro2::Literal::string(name);
+        quote! { Some(#lit) }
+    } else {
+        quote! { None }
+    }
+}
+
+fn compile_accessor(
+    opts: &Opts,
+    scope: Scope<'_>,
+    named: bool,
+    idx: usize,
+    Field {
+        comment,
+        name,
+        ty,
+        default,
+        id,
+        ..
+    }: &Field<'_>,
+) -> TokenStream {
+    let idx = proc_macro2::Literal::usize_unsuffixed(idx);
+    let comment = super::definition::compile_comment(comment);
+    let name_lit = compile_name(named, name);
+    let name = Ident::new(name, Span::call_site());
+    let id = proc_macro2::Literal::u32_unsuffixed(*id);
+    let ty_ident = super::definition::compile_data_type(opts, scope, ty);
+
+    let inner = if let Type::Option(ty) = &ty { ty } else { ty };
+    let limits = super::decode::refers_to_limits(inner)
+        .then(|| quote! { let limits = &mut ::mabo::buf::Limits::new(self.options); });
+    let decode = super::decode::compile_data_type(opts, scope, true, inner, true);
+    let decode = quote! {
+        let r = &mut data;
+        #limits
+        #decode.map_err(|e| e.with_field(#id, #name_lit))
+    };
+
+    if matches!(ty, Type::Option(_)) {
+        quote! {
+            #comment
+            pub fn #name(&self) -> ::mabo::buf::Result<#ty_ident> {
+                self.fields[#idx].map(|mut data| { #decode }).transpose()
+            }
+        }
+    } else if let Some(default) = default {
+        let value = super::definition::compile_default(default);
+        quote! {
+            #comment
+            pub fn #name(&self) -> ::mabo::buf::Result<#ty_ident> {
+                let Some(mut data) = self.fields[#idx] else {
+                    return Ok(#value);
+                };
+                #decode
+            }
+        }
+    } else {
+        quote! {
+            #comment
+            pub fn #name(&self) -> ::mabo::buf::Result<#ty_ident> {
+                let mut data = self.fields[#idx].ok_or(::mabo::buf::Error::MissingField {
+                    id: #id,
+                    name: #name_lit,
+                })?;
+                #decode
+            }
+        }
+    }
+}
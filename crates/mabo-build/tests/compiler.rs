@@ -6,7 +6,7 @@ use std::{
 };
 
 use insta::{assert_snapshot, glob, with_settings};
-use mabo_build::Opts;
+use mabo_build::{Compiler, Opts};
 use mabo_parser::Schema;
 
 fn strip_path(path: &Path) -> PathBuf {
@@ -16,38 +16,63 @@ fn strip_path(path: &Path) -> PathBuf {
         .to_owned()
 }
 
-#[test]
-fn compile_schema() {
-    glob!("inputs/*.mabo", |path| {
+/// Compile all schemas that match the pattern with the given options, and compare the generated
+/// code against the snapshot with the given name.
+fn compile(pattern: &str, opts: &Opts, name: &str) {
+    glob!(pattern, |path| {
         let input = fs::read_to_string(path).unwrap();
         let value = Schema::parse(input.as_str(), Some(&strip_path(path))).unwrap();
         let value = mabo_compiler::simplify_schema(&value);
-        let value = mabo_build::compile_schema(&Opts::default(), &value);
+        let value = mabo_build::compile_schema(opts, &value, &[]);
         let value = prettyplease::unparse(&syn::parse2(value.clone()).unwrap());
 
         with_settings!({
             description => input.trim(),
             omit_expression => true,
         }, {
-            assert_snapshot!("compile", value);
+            assert_snapshot!(name, value);
         });
     });
 }
 
+#[test]
+fn compile_schema() {
+    compile("inputs/*.mabo", &Opts::default(), "compile");
+}
+
 #[test]
 fn compile_schema_extra() {
-    glob!("inputs_extra/*.mabo", |path| {
-        let input = fs::read_to_string(path).unwrap();
-        let value = Schema::parse(input.as_str(), Some(&strip_path(path))).unwrap();
-        let value = mabo_compiler::simplify_schema(&value);
-        let value = mabo_build::compile_schema(&Opts::default(), &value);
-        let value = prettyplease::unparse(&syn::parse2(value.clone()).unwrap());
+    compile("inputs_extra/*.mabo", &Opts::default(), "compile_extra");
+}
 
-        with_settings!({
-            description => input.trim(),
-            omit_expression => true,
-        }, {
-            assert_snapshot!("compile_extra", value);
-        });
-    });
+#[test]
+fn compile_schema_no_std() {
+    let opts = Opts::from(&Compiler::default().with_no_std(true));
+    compile("inputs/types_*.mabo", &opts, "compile_no_std");
+}
+
+#[test]
+fn compile_schema_canonical() {
+    let opts = Opts::from(&Compiler::default().with_canonical_encoding(true));
+    compile("inputs/types_generic.mabo", &opts, "compile_canonical");
+}
+
+#[test]
+fn compile_schema_views() {
+    let opts = Opts::from(&Compiler::default().with_views(true));
+    compile(
+        "inputs/{types_basic,types_ref,struct_tuple}.mabo",
+        &opts,
+        "compile_views",
+    );
+}
+
+#[test]
+fn compile_schema_decode_merge() {
+    let opts = Opts::from(&Compiler::default().with_decode_merge(true));
+    compile(
+        "inputs/{types_basic,types_nested,struct_generics,enum_basic}.mabo",
+        &opts,
+        "compile_decode_merge",
+    );
 }
@@ -16,16 +16,20 @@
 //! // Resolve all types used in the schema, both in the schema itself and its submodules, and in
 //! // potentially types from external schemas that are referenced in it.
 //! mabo_compiler::resolve_schemas(&[("test", &schema)]).unwrap();
+//! // Look for questionable, but valid constructs, like the use of deprecated types.
+//! assert!(mabo_compiler::lint_schema(&schema).is_empty());
 //! ```
 
 #![expect(clippy::module_name_repetitions)]
 
+pub use lint::schema as lint_schema;
 use mabo_parser::Spanned;
 pub use resolve::schemas as resolve_schemas;
 pub use simplify::schema as simplify_schema;
 pub use validate::schema as validate_schema;
 
 mod highlight;
+pub mod lint;
 pub mod resolve;
 pub mod simplify;
 pub mod validate;
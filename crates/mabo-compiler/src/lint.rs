@@ -0,0 +1,165 @@
+//! Detect questionable constructs in a schema, which are still valid and therefore only reported as
+//! warnings instead of failing the compilation.
+
+use std::ops::Range;
+
+use mabo_parser::{Definition, ExternalType, Fields, Generics, Schema, Spanned, Type};
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    highlight,
+    resolve::{is_generic, visit_externals},
+    simplify::simplify_deprecated,
+};
+
+/// Reason why a schema might need attention, although it is valid.
+#[derive(Debug, Diagnostic, Error)]
+pub enum Warning {
+    /// Deprecated type used by an element that isn't deprecated itself.
+    #[error("deprecated type used")]
+    #[diagnostic(transparent)]
+    DeprecatedUsage(#[from] DeprecatedUsage),
+}
+
+/// Struct or enum that is marked as deprecated, but still used by another element.
+#[derive(Debug, Diagnostic, Error)]
+#[error("use of deprecated {kind} {}", highlight::value(name))]
+#[diagnostic(severity(Warning))]
+pub struct DeprecatedUsage {
+    /// Kind of the deprecated type, like `struct` or `enum`.
+    pub kind: &'static str,
+    /// Name of the deprecated type.
+    pub name: String,
+    /// Optional explanation from the `#[deprecated = "..."]` attribute.
+    #[help]
+    pub note: Option<String>,
+    /// Source location of the type declaration.
+    #[label("declared here")]
+    pub declared: Range<usize>,
+    /// Source location of the type usage.
+    #[label("used here")]
+    pub used: Range<usize>,
+}
+
+/// Find questionable constructs in the schema, like usages of deprecated types from elements that
+/// aren't deprecated themselves.
+///
+/// Only types declared within the same schema are considered, as imported types from external
+/// schemas are not known at this point.
+#[must_use]
+pub fn schema(value: &Schema<'_>) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    lint_definitions(&value.definitions, &mut warnings);
+    warnings
+}
+
+fn lint_definitions(definitions: &[Definition<'_>], warnings: &mut Vec<Warning>) {
+    for def in definitions {
+        match def {
+            Definition::Module(m) => lint_definitions(&m.definitions, warnings),
+            Definition::Struct(s) if simplify_deprecated(&s.attributes).is_none() => {
+                lint_fields(definitions, &s.fields, s.generics.as_ref(), warnings);
+            }
+            Definition::Enum(e) if simplify_deprecated(&e.attributes).is_none() => {
+                for variant in e
+                    .variants
+                    .values()
+                    .filter(|variant| simplify_deprecated(&variant.attributes).is_none())
+                {
+                    lint_fields(definitions, &variant.fields, e.generics.as_ref(), warnings);
+                }
+            }
+            Definition::TypeAlias(a) => {
+                lint_type(definitions, &a.target, a.generics.as_ref(), warnings);
+            }
+            Definition::Service(s) if simplify_deprecated(&s.attributes).is_none() => {
+                for method in s
+                    .methods
+                    .iter()
+                    .filter(|method| simplify_deprecated(&method.attributes).is_none())
+                {
+                    lint_type(definitions, &method.request.ty, None, warnings);
+                    lint_type(definitions, &method.response.ty, None, warnings);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn lint_fields(
+    definitions: &[Definition<'_>],
+    fields: &Fields<'_>,
+    generics: Option<&Generics<'_>>,
+    warnings: &mut Vec<Warning>,
+) {
+    match fields {
+        Fields::Named(_, _, named) => {
+            for field in named
+                .values()
+                .filter(|field| simplify_deprecated(&field.attributes).is_none())
+            {
+                lint_type(definitions, &field.ty, generics, warnings);
+            }
+        }
+        Fields::Unnamed(_, unnamed) => {
+            for field in unnamed.values() {
+                lint_type(definitions, &field.ty, generics, warnings);
+            }
+        }
+        Fields::Unit => {}
+    }
+}
+
+fn lint_type(
+    definitions: &[Definition<'_>],
+    ty: &Type<'_>,
+    generics: Option<&Generics<'_>>,
+    warnings: &mut Vec<Warning>,
+) {
+    visit_externals(ty, &mut |external| {
+        if !is_generic(external, generics)
+            && let Some(usage) = find_deprecated(definitions, external)
+        {
+            warnings.push(usage.into());
+        }
+    });
+}
+
+/// Locate the struct or enum that the external type refers to, relative to the module it's used
+/// in, and check whether it's deprecated.
+fn find_deprecated(
+    definitions: &[Definition<'_>],
+    ty: &ExternalType<'_>,
+) -> Option<DeprecatedUsage> {
+    let definitions = ty
+        .path
+        .iter()
+        .try_fold(definitions, |definitions, (name, _)| {
+            definitions.iter().find_map(|def| match def {
+                Definition::Module(m) if m.name.get() == name.get() => {
+                    Some(m.definitions.as_slice())
+                }
+                _ => None,
+            })
+        })?;
+
+    let (kind, name, attributes) = definitions.iter().find_map(|def| match def {
+        Definition::Struct(s) if s.name.get() == ty.name.get() => {
+            Some(("struct", &s.name, &s.attributes))
+        }
+        Definition::Enum(e) if e.name.get() == ty.name.get() => {
+            Some(("enum", &e.name, &e.attributes))
+        }
+        _ => None,
+    })?;
+
+    simplify_deprecated(attributes).map(|deprecated| DeprecatedUsage {
+        kind,
+        name: name.get().to_owned(),
+        note: deprecated.note.map(Into::into),
+        declared: name.span().into(),
+        used: ty.name.span().into(),
+    })
+}
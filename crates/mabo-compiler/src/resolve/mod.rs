@@ -107,6 +107,7 @@ enum DeclarationKind {
     Enum { generics: usize },
     Alias,
     Const,
+    Service,
 }
 
 impl DeclarationKind {
@@ -116,6 +117,7 @@ impl DeclarationKind {
             DeclarationKind::Enum { .. } => "enum",
             DeclarationKind::Alias => "type alias",
             DeclarationKind::Const => "constant",
+            DeclarationKind::Service => "service",
         }
     }
 }
@@ -165,12 +167,14 @@ impl Module<'_> {
                 }
                 .into())
             }
-            DeclarationKind::Alias | DeclarationKind::Const => Err(InvalidKind {
-                kind: definition.kind.as_str(),
-                declared: definition.name.span().into(),
-                used: ty.name.span().into(),
+            DeclarationKind::Alias | DeclarationKind::Const | DeclarationKind::Service => {
+                Err(InvalidKind {
+                    kind: definition.kind.as_str(),
+                    declared: definition.name.span().into(),
+                    used: ty.name.span().into(),
+                }
+                .into())
             }
-            .into()),
             _ => Ok(()),
         }
     }
@@ -216,6 +220,12 @@ impl Module<'_> {
                     used: element.span().into(),
                 }
                 .into()),
+                DeclarationKind::Service => Err(InvalidKind {
+                    kind: "service",
+                    declared: definition.name.span().into(),
+                    used: element.span().into(),
+                }
+                .into()),
                 DeclarationKind::Struct { generics } | DeclarationKind::Enum { generics } => {
                     Ok(ResolvedImport::Type {
                         schema: self.schema,
@@ -309,6 +319,22 @@ impl Module<'_> {
                 }],
             }
             .into()),
+            DeclarationKind::Service => Err(RemoteInvalidKind {
+                kind: "service",
+                used: ty.name.span().into(),
+                declaration: [RemoteInvalidKindDeclaration {
+                    kind: "service",
+                    source_code: NamedSource::new(
+                        self.schema
+                            .path
+                            .as_ref()
+                            .map_or_else(|| "<unknown>".to_owned(), |p| p.display().to_string()),
+                        self.schema.source.to_owned(),
+                    ),
+                    used: definition.name.span().into(),
+                }],
+            }
+            .into()),
             _ => Ok(()),
         }
     }
@@ -323,14 +349,6 @@ pub(crate) fn resolve_module_types<'a>(
     module: &'a Module<'_>,
     missing: &mut Vec<LocallyMissingType<'a>>,
 ) {
-    fn is_generic(external: &ExternalType<'_>, generics: Option<&Generics<'_>>) -> bool {
-        external.generics.is_none()
-            && external.path.is_empty()
-            && generics
-                .as_ref()
-                .is_some_and(|g| g.types.values().any(|g| g.get() == external.name.get()))
-    }
-
     fn resolve<'a>(
         missing: &mut Vec<LocallyMissingType<'a>>,
         ty: &'a Type<'_>,
@@ -353,7 +371,7 @@ pub(crate) fn resolve_module_types<'a>(
         module: &Module<'_>,
     ) {
         match fields {
-            Fields::Named(_, named) => {
+            Fields::Named(_, _, named) => {
                 for field in named.values() {
                     resolve(missing, &field.ty, generics, module);
                 }
@@ -377,6 +395,12 @@ pub(crate) fn resolve_module_types<'a>(
                     resolve_fields(missing, &variant.fields, e.generics.as_ref(), module);
                 }
             }
+            Definition::Service(s) => {
+                for method in &s.methods {
+                    resolve(missing, &method.request.ty, None, module);
+                    resolve(missing, &method.response.ty, None, module);
+                }
+            }
             _ => {}
         }
     }
@@ -445,6 +469,10 @@ fn visit_module_tree<'a>(
                 kind: DeclarationKind::Const,
                 name: c.name.clone(),
             }),
+            Definition::Service(s) => module.types.push(Declaration {
+                kind: DeclarationKind::Service,
+                name: s.name.clone(),
+            }),
             Definition::Import(i) => module.imports.push(i),
         }
     }
@@ -452,9 +480,21 @@ fn visit_module_tree<'a>(
     module
 }
 
+/// Whether the external type actually refers to one of the generic type parameters.
+pub(crate) fn is_generic(external: &ExternalType<'_>, generics: Option<&Generics<'_>>) -> bool {
+    external.generics.is_none()
+        && external.path.is_empty()
+        && generics
+            .as_ref()
+            .is_some_and(|g| g.types.values().any(|g| g.get() == external.name.get()))
+}
+
 /// Walk through the type and any type arguments, calling the visitor whenever an external type is
 /// encountered.
-fn visit_externals<'a>(value: &'a Type<'_>, visit: &mut impl FnMut(&'a ExternalType<'_>)) {
+pub(crate) fn visit_externals<'a>(
+    value: &'a Type<'_>,
+    visit: &mut impl FnMut(&'a ExternalType<'_>),
+) {
     match &value.value {
         DataType::Bool
         | DataType::U8
@@ -475,14 +515,19 @@ fn visit_externals<'a>(value: &'a Type<'_>, visit: &mut impl FnMut(&'a ExternalT
         | DataType::BytesRef
         | DataType::NonZero { .. }
         | DataType::BoxString
-        | DataType::BoxBytes => {}
+        | DataType::BoxBytes
+        | DataType::Timestamp
+        | DataType::Duration
+        | DataType::Uuid
+        | DataType::Decimal => {}
         DataType::Vec { ty, .. }
         | DataType::HashSet { ty, .. }
+        | DataType::BTreeSet { ty, .. }
         | DataType::Option { ty, .. }
         | DataType::Array { ty, .. } => {
             visit_externals(ty, visit);
         }
-        DataType::HashMap { key, value, .. } => {
+        DataType::HashMap { key, value, .. } | DataType::BTreeMap { key, value, .. } => {
             visit_externals(key, visit);
             visit_externals(value, visit);
         }
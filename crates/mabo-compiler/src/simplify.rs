@@ -48,6 +48,8 @@ pub enum Definition<'a> {
     Const(Const<'a>),
     /// Import declaration of other schemas.
     Import(Import<'a>),
+    /// Service declaration with remote procedures.
+    Service(Service<'a>),
 }
 
 /// Scoping mechanism to categorize elements.
@@ -78,6 +80,8 @@ pub struct Struct<'a> {
     pub generics: Box<[&'a str]>,
     /// Fields of the struct, if any.
     pub fields: Fields<'a>,
+    /// Marker that the struct shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
 }
 
 /// Rust-ish enum.
@@ -94,6 +98,8 @@ pub struct Enum<'a> {
     pub generics: Box<[&'a str]>,
     /// List of possible variants that the enum can represent.
     pub variants: Vec<Variant<'a>>,
+    /// Marker that the enum shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
 }
 
 /// Single variant of an enum.
@@ -110,6 +116,8 @@ pub struct Variant<'a> {
     pub fields: Fields<'a>,
     /// Identifier for this variant, that must be unique within the current enum.
     pub id: u32,
+    /// Marker that the variant shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
 }
 
 /// Fields of a struct or enum that define its structure.
@@ -136,8 +144,49 @@ pub struct Field<'a> {
     pub name: Cow<'a, str>,
     /// Data type that defines the shape of the contained data.
     pub ty: Type<'a>,
+    /// Optional default value, that is used when the field is missing.
+    pub default: Option<Literal>,
+    /// Constraints on the field value, declared with the `#[validate(...)]` attribute.
+    pub validations: Box<[Validation]>,
     /// Identifier for this field, that must be unique within the current element.
     pub id: u32,
+    /// Marker that the field shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
+}
+
+/// Single constraint on the value of a field, that is checked by the generated validation code.
+#[cfg_attr(feature = "json", derive(schemars::JsonSchema, serde::Serialize))]
+pub enum Validation {
+    /// Smallest allowed value of a number (inclusive).
+    Min(Bound),
+    /// Largest allowed value of a number (inclusive).
+    Max(Bound),
+    /// Smallest allowed length of a string (in characters), bytes or collection.
+    MinLen(u64),
+    /// Largest allowed length of a string (in characters), bytes or collection.
+    MaxLen(u64),
+    /// Regular expression that a string must match.
+    Pattern(Box<str>),
+    /// String, bytes or collection must contain at least one element.
+    NonEmpty,
+}
+
+/// Numeric bound of a `min` or `max` validation rule.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "json", derive(schemars::JsonSchema, serde::Serialize))]
+pub enum Bound {
+    /// Integer number.
+    Int(i128),
+    /// Floating point number.
+    Float(f64),
+}
+
+/// Deprecation of an element, declared with the `#[deprecated]` or `#[deprecated = "..."]`
+/// attribute.
+#[cfg_attr(feature = "json", derive(schemars::JsonSchema, serde::Serialize))]
+pub struct Deprecated {
+    /// Optional explanation, like the replacement that should be used instead.
+    pub note: Option<Box<str>>,
 }
 
 /// Field from the [`mabo_parser`] create, where a [`Field`] structure originates from.
@@ -190,6 +239,53 @@ pub struct Const<'a> {
     pub ty: Type<'a>,
     /// Literal value that this declaration represents.
     pub value: Literal,
+    /// Marker that the constant shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
+}
+
+/// Collection of remote procedures, that can be called with a request and answer with a response.
+#[cfg_attr(feature = "json", derive(schemars::JsonSchema, serde::Serialize))]
+pub struct Service<'a> {
+    /// Original parser element.
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub source: &'a mabo_parser::Service<'a>,
+    /// Optional service-level comment.
+    pub comment: Box<[&'a str]>,
+    /// Unique name for this service, within its current scope.
+    pub name: &'a str,
+    /// List of methods that the service provides.
+    pub methods: Box<[Method<'a>]>,
+    /// Marker that the service shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
+}
+
+/// Single remote procedure of a service.
+#[cfg_attr(feature = "json", derive(schemars::JsonSchema, serde::Serialize))]
+pub struct Method<'a> {
+    /// Original parser element.
+    #[cfg_attr(feature = "json", serde(skip))]
+    pub source: &'a mabo_parser::Method<'a>,
+    /// Optional method-level comment.
+    pub comment: Box<[&'a str]>,
+    /// Unique name for this method, within the service it belongs to.
+    pub name: &'a str,
+    /// Type of the request that the method is called with.
+    pub request: MethodType<'a>,
+    /// Type of the response that the method answers with.
+    pub response: MethodType<'a>,
+    /// Identifier for this method, that must be unique within the current service.
+    pub id: u32,
+    /// Marker that the method shouldn't be used anymore.
+    pub deprecated: Option<Deprecated>,
+}
+
+/// Request or response type of a service method.
+#[cfg_attr(feature = "json", derive(schemars::JsonSchema, serde::Serialize))]
+pub struct MethodType<'a> {
+    /// Data type of the (individual) values.
+    pub ty: Type<'a>,
+    /// Whether zero or more values are sent as a stream, instead of exactly one.
+    pub stream: bool,
 }
 
 /// In-schema definition of a literal value
@@ -264,6 +360,10 @@ pub enum Type<'a> {
     HashMap(Box<(Type<'a>, Type<'a>)>),
     /// Hash set of data types (each entry is unique).
     HashSet(Box<Type<'a>>),
+    /// Key-value map of data types, ordered by its keys.
+    BTreeMap(Box<(Type<'a>, Type<'a>)>),
+    /// Set of data types (each entry is unique), ordered by its entries.
+    BTreeSet(Box<Type<'a>>),
     /// Optional value.
     Option(Box<Type<'a>>),
     /// Non-zero value.
@@ -274,6 +374,14 @@ pub enum Type<'a> {
     BoxString,
     /// Boxed version of a byte vector that is immutable.
     BoxBytes,
+    /// Point in time, as seconds and nanoseconds since the Unix epoch.
+    Timestamp,
+    /// Span of time, as seconds and nanoseconds.
+    Duration,
+    /// Universally unique identifier of 16 bytes.
+    Uuid,
+    /// Decimal number with a fixed amount of fractional digits.
+    Decimal,
     /// Fixed size list of up to 12 types.
     Tuple(Box<[Type<'a>]>),
     /// Continuous list of values with a single time and known length.
@@ -330,6 +438,7 @@ fn definition<'a>(item: &'a mabo_parser::Definition<'_>) -> Definition<'a> {
         mabo_parser::Definition::TypeAlias(a) => Definition::TypeAlias(simplify_alias(a)),
         mabo_parser::Definition::Const(c) => Definition::Const(simplify_const(c)),
         mabo_parser::Definition::Import(i) => Definition::Import(simplify_import(i)),
+        mabo_parser::Definition::Service(s) => Definition::Service(simplify_service(s)),
     }
 }
 
@@ -349,6 +458,7 @@ fn simplify_struct<'a>(item: &'a mabo_parser::Struct<'_>) -> Struct<'a> {
         name: item.name.get(),
         generics: generics(item.generics.as_ref()),
         fields: simplify_fields(&item.fields),
+        deprecated: simplify_deprecated(&item.attributes),
     }
 }
 
@@ -365,6 +475,7 @@ fn simplify_enum<'a>(item: &'a mabo_parser::Enum<'_>) -> Enum<'a> {
             .values()
             .map(|variant| simplify_variant(variant, &mut id_gen))
             .collect(),
+        deprecated: simplify_deprecated(&item.attributes),
     }
 }
 
@@ -378,6 +489,7 @@ fn simplify_variant<'a>(
         name: item.name.get(),
         fields: simplify_fields(&item.fields),
         id: id_gen.next(item.id.as_ref()),
+        deprecated: simplify_deprecated(&item.attributes),
     }
 }
 
@@ -385,7 +497,7 @@ fn simplify_fields<'a>(item: &'a mabo_parser::Fields<'_>) -> Fields<'a> {
     let mut id_gen = IdGenerator::new();
 
     match item {
-        mabo_parser::Fields::Named(_, named) => Fields {
+        mabo_parser::Fields::Named(_, _, named) => Fields {
             source: item,
             fields: named
                 .values()
@@ -394,7 +506,13 @@ fn simplify_fields<'a>(item: &'a mabo_parser::Fields<'_>) -> Fields<'a> {
                     comment: comment(&field.comment),
                     name: field.name.get().into(),
                     ty: simplify_type(&field.ty),
+                    default: field
+                        .default
+                        .as_ref()
+                        .map(|(_, value)| simplify_literal(value)),
+                    validations: simplify_validations(&field.attributes),
                     id: id_gen.next(field.id.as_ref()),
+                    deprecated: simplify_deprecated(&field.attributes),
                 })
                 .collect(),
             kind: FieldKind::Named,
@@ -409,7 +527,13 @@ fn simplify_fields<'a>(item: &'a mabo_parser::Fields<'_>) -> Fields<'a> {
                     comment: Box::default(),
                     name: format!("n{i}").into(),
                     ty: simplify_type(&field.ty),
+                    default: field
+                        .default
+                        .as_ref()
+                        .map(|(_, value)| simplify_literal(value)),
+                    validations: Box::default(),
                     id: id_gen.next(field.id.as_ref()),
+                    deprecated: None,
                 })
                 .collect(),
             kind: FieldKind::Unnamed,
@@ -446,10 +570,18 @@ fn simplify_type<'a>(item: &'a mabo_parser::Type<'_>) -> Type<'a> {
             ref key, ref value, ..
         } => Type::HashMap((simplify_type(key), simplify_type(value)).into()),
         mabo_parser::DataType::HashSet { ref ty, .. } => Type::HashSet(simplify_type(ty).into()),
+        mabo_parser::DataType::BTreeMap {
+            ref key, ref value, ..
+        } => Type::BTreeMap((simplify_type(key), simplify_type(value)).into()),
+        mabo_parser::DataType::BTreeSet { ref ty, .. } => Type::BTreeSet(simplify_type(ty).into()),
         mabo_parser::DataType::Option { ref ty, .. } => Type::Option(simplify_type(ty).into()),
         mabo_parser::DataType::NonZero { ref ty, .. } => Type::NonZero(simplify_type(ty).into()),
         mabo_parser::DataType::BoxString => Type::BoxString,
         mabo_parser::DataType::BoxBytes => Type::BoxBytes,
+        mabo_parser::DataType::Timestamp => Type::Timestamp,
+        mabo_parser::DataType::Duration => Type::Duration,
+        mabo_parser::DataType::Uuid => Type::Uuid,
+        mabo_parser::DataType::Decimal => Type::Decimal,
         mabo_parser::DataType::Tuple { ref types, .. } => {
             Type::Tuple(types.values().map(|ty| simplify_type(ty)).collect())
         }
@@ -483,6 +615,7 @@ fn simplify_const<'a>(item: &'a mabo_parser::Const<'_>) -> Const<'a> {
         name: item.name.get(),
         ty: simplify_type(&item.ty),
         value: simplify_literal(&item.value),
+        deprecated: simplify_deprecated(&item.attributes),
     }
 }
 
@@ -496,6 +629,103 @@ fn simplify_literal(item: &mabo_parser::Literal) -> Literal {
     }
 }
 
+/// Collect the rules of all `#[validate(...)]` attributes. The attributes are already checked by
+/// the validation step, so anything unexpected is skipped here.
+fn simplify_validations(item: &mabo_parser::Attributes<'_>) -> Box<[Validation]> {
+    use mabo_parser::{AttributeValue, LiteralValue};
+
+    item.0
+        .iter()
+        .filter(|attr| attr.name == "validate")
+        .filter_map(|attr| match &attr.value {
+            AttributeValue::Multi(rules) => Some(rules),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|rule| {
+            Some(match (rule.name, &rule.value) {
+                ("min", AttributeValue::Single(literal)) => {
+                    Validation::Min(simplify_bound(literal)?)
+                }
+                ("max", AttributeValue::Single(literal)) => {
+                    Validation::Max(simplify_bound(literal)?)
+                }
+                ("min_len", AttributeValue::Single(literal)) => match literal.value {
+                    LiteralValue::Int(value) => Validation::MinLen(value.try_into().ok()?),
+                    _ => return None,
+                },
+                ("max_len", AttributeValue::Single(literal)) => match literal.value {
+                    LiteralValue::Int(value) => Validation::MaxLen(value.try_into().ok()?),
+                    _ => return None,
+                },
+                ("pattern", AttributeValue::Single(literal)) => match &literal.value {
+                    LiteralValue::String(value) => Validation::Pattern(value.as_str().into()),
+                    _ => return None,
+                },
+                ("non_empty", AttributeValue::Unit) => Validation::NonEmpty,
+                _ => return None,
+            })
+        })
+        .collect()
+}
+
+fn simplify_bound(item: &mabo_parser::Literal) -> Option<Bound> {
+    match item.value {
+        mabo_parser::LiteralValue::Int(i) => Some(Bound::Int(i)),
+        mabo_parser::LiteralValue::Float(f) => Some(Bound::Float(f)),
+        _ => None,
+    }
+}
+
+/// Find the `#[deprecated]` attribute, with its optional note given as string value.
+pub(crate) fn simplify_deprecated(item: &mabo_parser::Attributes<'_>) -> Option<Deprecated> {
+    use mabo_parser::{AttributeValue, Literal, LiteralValue};
+
+    item.0
+        .iter()
+        .find(|attr| attr.name == "deprecated")
+        .map(|attr| Deprecated {
+            note: match &attr.value {
+                AttributeValue::Single(Literal {
+                    value: LiteralValue::String(note),
+                    ..
+                }) => Some(note.as_str().into()),
+                _ => None,
+            },
+        })
+}
+
+fn simplify_service<'a>(item: &'a mabo_parser::Service<'_>) -> Service<'a> {
+    let mut id_gen = IdGenerator::new();
+
+    Service {
+        source: item,
+        comment: comment(&item.comment),
+        name: item.name.get(),
+        methods: item
+            .methods
+            .iter()
+            .map(|method| Method {
+                source: method,
+                comment: comment(&method.comment),
+                name: method.name.get(),
+                request: simplify_method_type(&method.request),
+                response: simplify_method_type(&method.response),
+                id: id_gen.next(method.id.as_ref()),
+                deprecated: simplify_deprecated(&method.attributes),
+            })
+            .collect(),
+        deprecated: simplify_deprecated(&item.attributes),
+    }
+}
+
+fn simplify_method_type<'a>(item: &'a mabo_parser::MethodType<'_>) -> MethodType<'a> {
+    MethodType {
+        ty: simplify_type(&item.ty),
+        stream: item.stream.is_some(),
+    }
+}
+
 fn simplify_import<'a>(item: &'a mabo_parser::Import<'_>) -> Import<'a> {
     Import {
         source: item,
@@ -0,0 +1,143 @@
+use std::ops::{Range, RangeInclusive};
+
+use mabo_parser::{DataType, Enum, Fields, Literal, LiteralValue, Spanned, Struct, Type};
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::highlight;
+
+/// Default value of a field that can't be used for the type of the field.
+#[derive(Debug, Diagnostic, Error)]
+#[error(
+    "the default value {}",
+    highlight::focus(match kind {
+        InvalidDefaultKind::Unsupported => "isn't supported for this type",
+        InvalidDefaultKind::Mismatch => "doesn't match the field type",
+        InvalidDefaultKind::OutOfRange => "is out of range for the field type",
+    })
+)]
+#[diagnostic(help(
+    "defaults are only supported for booleans, numbers, strings and bytes, and must be a literal \
+     of the same type"
+))]
+pub struct InvalidDefault {
+    /// The reason why the value is invalid.
+    pub kind: InvalidDefaultKind,
+    /// Source location of the default value.
+    #[label("declared here")]
+    pub declared: Range<usize>,
+    /// Source location of the field type.
+    #[label("for this type")]
+    pub ty: Range<usize>,
+}
+
+/// Possible reasons for a default value to be invalid.
+#[derive(Debug)]
+pub enum InvalidDefaultKind {
+    /// The field type can't have a default value.
+    Unsupported,
+    /// The literal is of a different kind than the field type.
+    Mismatch,
+    /// The integer doesn't fit into the field type.
+    OutOfRange,
+}
+
+/// Ensure all default values of the struct's fields match their type.
+pub fn validate_struct_defaults(value: &Struct<'_>) -> Result<(), InvalidDefault> {
+    validate_field_defaults(&value.fields)
+}
+
+/// Ensure all default values of the enum variants' fields match their type.
+pub fn validate_enum_defaults(value: &Enum<'_>) -> Result<(), InvalidDefault> {
+    value
+        .variants
+        .values()
+        .try_for_each(|variant| validate_field_defaults(&variant.fields))
+}
+
+fn validate_field_defaults(value: &Fields<'_>) -> Result<(), InvalidDefault> {
+    match value {
+        Fields::Named(_, _, named) => named.values().try_for_each(|field| {
+            field
+                .default
+                .as_ref()
+                .map_or(Ok(()), |(_, literal)| validate_default(&field.ty, literal))
+        }),
+        Fields::Unnamed(_, unnamed) => unnamed.values().try_for_each(|field| {
+            field
+                .default
+                .as_ref()
+                .map_or(Ok(()), |(_, literal)| validate_default(&field.ty, literal))
+        }),
+        Fields::Unit => Ok(()),
+    }
+}
+
+fn validate_default(ty: &Type<'_>, literal: &Literal) -> Result<(), InvalidDefault> {
+    let kind = match (&ty.value, &literal.value) {
+        (DataType::Bool, LiteralValue::Bool(_))
+        | (DataType::F32 | DataType::F64, LiteralValue::Float(_))
+        | (DataType::String | DataType::StringRef | DataType::BoxString, LiteralValue::String(_))
+        | (DataType::Bytes | DataType::BytesRef | DataType::BoxBytes, LiteralValue::Bytes(_)) => {
+            return Ok(());
+        }
+        (value_ty, LiteralValue::Int(value)) => match int_range(value_ty) {
+            Some(range) if range.contains(value) => return Ok(()),
+            Some(_) => InvalidDefaultKind::OutOfRange,
+            None if supports_default(value_ty) => InvalidDefaultKind::Mismatch,
+            None => InvalidDefaultKind::Unsupported,
+        },
+        (value_ty, _) if supports_default(value_ty) => InvalidDefaultKind::Mismatch,
+        _ => InvalidDefaultKind::Unsupported,
+    };
+
+    Err(InvalidDefault {
+        kind,
+        declared: literal.span().into(),
+        ty: ty.span().into(),
+    })
+}
+
+/// Whether the type can have a default value at all, which is limited to the types that can be
+/// expressed as a literal.
+fn supports_default(ty: &DataType<'_>) -> bool {
+    matches!(
+        ty,
+        DataType::Bool
+            | DataType::U8
+            | DataType::U16
+            | DataType::U32
+            | DataType::U64
+            | DataType::U128
+            | DataType::I8
+            | DataType::I16
+            | DataType::I32
+            | DataType::I64
+            | DataType::I128
+            | DataType::F32
+            | DataType::F64
+            | DataType::String
+            | DataType::StringRef
+            | DataType::BoxString
+            | DataType::Bytes
+            | DataType::BytesRef
+            | DataType::BoxBytes
+    )
+}
+
+/// Range of values that an integer type can hold, or `None` if the type isn't an integer.
+pub(super) fn int_range(ty: &DataType<'_>) -> Option<RangeInclusive<i128>> {
+    Some(match ty {
+        DataType::U8 => 0..=u8::MAX.into(),
+        DataType::U16 => 0..=u16::MAX.into(),
+        DataType::U32 => 0..=u32::MAX.into(),
+        DataType::U64 => 0..=u64::MAX.into(),
+        DataType::U128 => 0..=i128::MAX,
+        DataType::I8 => i8::MIN.into()..=i8::MAX.into(),
+        DataType::I16 => i16::MIN.into()..=i16::MAX.into(),
+        DataType::I32 => i32::MIN.into()..=i32::MAX.into(),
+        DataType::I64 => i64::MIN.into()..=i64::MAX.into(),
+        DataType::I128 => i128::MIN..=i128::MAX,
+        _ => return None,
+    })
+}
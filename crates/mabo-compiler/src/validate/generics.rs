@@ -119,7 +119,7 @@ fn validate_duplicate_generics(value: &Generics<'_>) -> Result<(), DuplicateGene
 /// field.
 fn validate_field_generics(value: &Fields<'_>, unvisited: &mut FxHashMap<&str, Span>) {
     match &value {
-        Fields::Named(_, named) => {
+        Fields::Named(_, _, named) => {
             for field in named.values() {
                 visit_externals(&field.ty, &mut |external| {
                     if external.path.is_empty() && external.generics.is_none() {
@@ -164,12 +164,17 @@ fn visit_externals(value: &Type<'_>, visit: &mut impl FnMut(&ExternalType<'_>))
         | DataType::BytesRef
         | DataType::NonZero { .. }
         | DataType::BoxString
-        | DataType::BoxBytes => {}
+        | DataType::BoxBytes
+        | DataType::Timestamp
+        | DataType::Duration
+        | DataType::Uuid
+        | DataType::Decimal => {}
         DataType::Vec { ty, .. }
         | DataType::HashSet { ty, .. }
+        | DataType::BTreeSet { ty, .. }
         | DataType::Option { ty, .. }
         | DataType::Array { ty, .. } => visit_externals(ty, visit),
-        DataType::HashMap { key, value, .. } => {
+        DataType::HashMap { key, value, .. } | DataType::BTreeMap { key, value, .. } => {
             visit_externals(key, visit);
             visit_externals(value, visit);
         }
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use mabo_parser::{Enum, Fields, Id, Spanned, Struct};
+use mabo_parser::{Enum, Fields, Id, Reserved, Service, Spanned, Struct};
 use miette::Diagnostic;
 use rustc_hash::{FxBuildHasher, FxHashMap};
 use thiserror::Error;
@@ -18,6 +18,10 @@ pub enum DuplicateId {
     #[error("duplicate ID in a field")]
     #[diagnostic(transparent)]
     Field(#[from] DuplicateFieldId),
+    /// Two service methods use the same ID.
+    #[error("duplicate ID in a service method")]
+    #[diagnostic(transparent)]
+    Method(#[from] DuplicateMethodId),
 }
 
 /// Duplicate ID for enum variants.
@@ -39,6 +43,25 @@ pub struct DuplicateVariantId {
     pub second: Range<usize>,
 }
 
+/// Duplicate ID for service methods.
+#[derive(Debug, Diagnostic, Error)]
+#[error("duplicate ID {} in service method `{name}`, already used in `{other_name}`", id.get())]
+#[diagnostic(help("the IDs for each method of a service must be unique"))]
+pub struct DuplicateMethodId {
+    /// The duplicate ID.
+    pub id: Id,
+    /// Name of the method that tries to use the same ID again.
+    pub name: String,
+    /// Name of the method that used the ID for the first time.
+    pub other_name: String,
+    /// Source location of the first occurrence.
+    #[label("first declared here")]
+    pub first: Range<usize>,
+    /// Source location of the duplicate.
+    #[label("used here again")]
+    pub second: Range<usize>,
+}
+
 /// Duplicate ID for fields of a struct or enum variant.
 #[derive(Debug, Diagnostic, Error)]
 pub enum DuplicateFieldId {
@@ -90,6 +113,25 @@ pub struct DuplicateUnnamedFieldId {
     pub second: Range<usize>,
 }
 
+/// ID that was explicitly reserved, but is used by a field or enum variant anyway.
+#[derive(Debug, Diagnostic, Error)]
+#[error("ID {} in `{name}` is reserved", id.get())]
+#[diagnostic(help(
+    "reserved IDs must not be used by any field or variant, including implicitly assigned IDs"
+))]
+pub struct ReservedId {
+    /// The reserved ID.
+    pub id: Id,
+    /// Name of the field or variant that uses the ID.
+    pub name: String,
+    /// Source location of the reservation.
+    #[label("reserved here")]
+    pub reserved: Range<usize>,
+    /// Source location of the usage.
+    #[label("used here")]
+    pub used: Range<usize>,
+}
+
 /// Ensure all IDs inside a struct are unique (which are the field IDs).
 pub(crate) fn validate_struct_ids(value: &Struct<'_>) -> Result<(), DuplicateFieldId> {
     validate_field_ids(&value.fields)
@@ -128,10 +170,34 @@ pub(crate) fn validate_enum_ids(value: &Enum<'_>) -> Result<(), DuplicateId> {
         .map_or(Ok(()), Err)
 }
 
+/// Ensure all method IDs inside a service are unique.
+pub(crate) fn validate_service_ids(value: &Service<'_>) -> Result<(), DuplicateMethodId> {
+    let mut visited = FxHashMap::with_capacity_and_hasher(value.methods.len(), FxBuildHasher);
+    let mut id_gen = IdGenerator::new();
+
+    value
+        .methods
+        .iter()
+        .find_map(|method| {
+            let id = id_gen.next_with_span(method.id.as_ref(), || method.span());
+
+            visited
+                .insert(id.get(), (method.name.get(), id.span()))
+                .map(|(other_name, other_span)| DuplicateMethodId {
+                    name: method.name.get().to_owned(),
+                    other_name: other_name.to_owned(),
+                    first: other_span.into(),
+                    second: id.span().into(),
+                    id,
+                })
+        })
+        .map_or(Ok(()), Err)
+}
+
 /// Ensure all field IDs of a struct or enum are unique.
 fn validate_field_ids(value: &Fields<'_>) -> Result<(), DuplicateFieldId> {
     match value {
-        Fields::Named(_, named) => {
+        Fields::Named(_, _, named) => {
             let mut visited = FxHashMap::with_capacity_and_hasher(named.len(), FxBuildHasher);
             let mut id_gen = IdGenerator::new();
 
@@ -179,3 +245,50 @@ fn validate_field_ids(value: &Fields<'_>) -> Result<(), DuplicateFieldId> {
 
     Ok(())
 }
+
+/// Ensure none of the struct's fields uses a reserved ID, be it explicitly or implicitly.
+pub(crate) fn validate_struct_reserved_ids(value: &Struct<'_>) -> Result<(), ReservedId> {
+    validate_field_reserved_ids(&value.fields)
+}
+
+/// Ensure none of the enum's variants uses a reserved ID, be it explicitly or implicitly. The same
+/// applies to the fields of each variant.
+pub(crate) fn validate_enum_reserved_ids(value: &Enum<'_>) -> Result<(), ReservedId> {
+    let mut id_gen = IdGenerator::new();
+
+    value.variants.values().try_for_each(|variant| {
+        let id = id_gen.next_with_span(variant.id.as_ref(), || variant.span());
+
+        check_reserved_id(&value.reserved, id, variant.name.get())?;
+        validate_field_reserved_ids(&variant.fields)
+    })
+}
+
+/// Ensure none of the named fields uses a reserved ID. Only named fields can declare reservations.
+fn validate_field_reserved_ids(value: &Fields<'_>) -> Result<(), ReservedId> {
+    let Fields::Named(_, reserved, named) = value else {
+        return Ok(());
+    };
+
+    let mut id_gen = IdGenerator::new();
+
+    named.values().try_for_each(|field| {
+        let id = id_gen.next_with_span(field.id.as_ref(), || field.span());
+        check_reserved_id(reserved, id, field.name.get())
+    })
+}
+
+fn check_reserved_id(reserved: &[Reserved<'_>], id: Id, name: &str) -> Result<(), ReservedId> {
+    reserved
+        .iter()
+        .flat_map(|reserved| reserved.values.values())
+        .find(|value| value.contains_id(id.get()))
+        .map_or(Ok(()), |value| {
+            Err(ReservedId {
+                name: name.to_owned(),
+                reserved: value.span().into(),
+                used: id.span().into(),
+                id,
+            })
+        })
+}
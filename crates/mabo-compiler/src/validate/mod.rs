@@ -6,19 +6,26 @@ use miette::Diagnostic;
 use thiserror::Error;
 
 pub use self::{
+    defaults::{InvalidDefault, InvalidDefaultKind},
     generics::{DuplicateGenericName, InvalidGenericType, UnusedGeneric},
     ids::{
-        DuplicateFieldId, DuplicateId, DuplicateNamedFieldId, DuplicateUnnamedFieldId,
-        DuplicateVariantId,
+        DuplicateFieldId, DuplicateId, DuplicateMethodId, DuplicateNamedFieldId,
+        DuplicateUnnamedFieldId, DuplicateVariantId, ReservedId,
+    },
+    names::{
+        DuplicateFieldName, DuplicateMethodName, DuplicateName, DuplicateNameInModule,
+        DuplicateVariantName, ReservedName,
     },
-    names::{DuplicateFieldName, DuplicateName, DuplicateNameInModule, DuplicateVariantName},
     tuples::{InvalidTupleAmount, TupleSize},
+    validations::{InvalidValidation, InvalidValidationKind},
 };
 
+mod defaults;
 mod generics;
 mod ids;
 mod names;
 mod tuples;
+mod validations;
 
 /// Reason why a schema was invalid.
 #[derive(Debug, Diagnostic, Error)]
@@ -39,6 +46,22 @@ pub enum Error {
     #[error("invalid tuple element size found")]
     #[diagnostic(transparent)]
     TupleSize(#[from] TupleSize),
+    /// Reserved ID was used by a field or enum variant.
+    #[error("reserved ID used")]
+    #[diagnostic(transparent)]
+    ReservedId(#[from] ReservedId),
+    /// Reserved name was used by a field or enum variant.
+    #[error("reserved name used")]
+    #[diagnostic(transparent)]
+    ReservedName(#[from] ReservedName),
+    /// Default value of a field doesn't fit the field type.
+    #[error("invalid default value found")]
+    #[diagnostic(transparent)]
+    InvalidDefault(#[from] InvalidDefault),
+    /// Validation rule of a field is unknown or doesn't fit the field type.
+    #[error("invalid validation rule found")]
+    #[diagnostic(transparent)]
+    InvalidValidation(#[from] InvalidValidation),
 }
 
 impl From<DuplicateFieldId> for Error {
@@ -53,16 +76,31 @@ impl From<DuplicateFieldName> for Error {
     }
 }
 
+impl From<DuplicateMethodId> for Error {
+    fn from(v: DuplicateMethodId) -> Self {
+        Self::DuplicateId(v.into())
+    }
+}
+
+impl From<DuplicateMethodName> for Error {
+    fn from(v: DuplicateMethodName) -> Self {
+        Self::DuplicateName(v.into())
+    }
+}
+
 /// Ensure the schema doesn't include invalid definitions, which would be difficult to validate
 /// during the parsing step.
 ///
 /// Currently, it checks that:
 /// - All definitions (struct, enums, modules, ...) have a unique name within their module
 ///   namespace.
-/// - IDs in field names or enum variant names are unique.
-/// - Fields names in structs or enum variants are unique.
+/// - IDs in field names, enum variant names or service method names are unique.
+/// - Fields names in structs or enum variants, and method names in services are unique.
+/// - Fields and enum variants don't use any reserved IDs or names, including implicit IDs.
 /// - Generic type parameters in a struct or enum are unique.
 /// - All generic type parameters are used.
+/// - Default values of fields match the field type.
+/// - Validation rules of fields are known and match the field type.
 ///
 /// # Errors
 ///
@@ -81,14 +119,22 @@ fn definition(value: &Definition<'_>) -> Result<(), Error> {
         Definition::Struct(s) => {
             ids::validate_struct_ids(s)?;
             names::validate_struct_names(s)?;
+            ids::validate_struct_reserved_ids(s)?;
+            names::validate_struct_reserved_names(s)?;
             generics::validate_struct_generics(s)?;
             tuples::validate_struct_tuples(s)?;
+            defaults::validate_struct_defaults(s)?;
+            validations::validate_struct_validations(s)?;
         }
         Definition::Enum(e) => {
             ids::validate_enum_ids(e)?;
             names::validate_enum_names(e)?;
+            ids::validate_enum_reserved_ids(e)?;
+            names::validate_enum_reserved_names(e)?;
             generics::validate_enum_generics(e)?;
             tuples::validate_enum_tuples(e)?;
+            defaults::validate_enum_defaults(e)?;
+            validations::validate_enum_validations(e)?;
         }
         Definition::TypeAlias(a) => {
             tuples::validate_alias_tuples(a)?;
@@ -96,6 +142,11 @@ fn definition(value: &Definition<'_>) -> Result<(), Error> {
         Definition::Const(c) => {
             tuples::validate_const_tuples(c)?;
         }
+        Definition::Service(s) => {
+            ids::validate_service_ids(s)?;
+            names::validate_service_names(s)?;
+            tuples::validate_service_tuples(s)?;
+        }
         Definition::Import(_) => {}
     }
 
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use mabo_parser::{Definition, Enum, Fields, Import, Spanned, Struct};
+use mabo_parser::{Definition, Enum, Fields, Import, Name, Reserved, Service, Spanned, Struct};
 use miette::Diagnostic;
 use rustc_hash::{FxBuildHasher, FxHashMap};
 use thiserror::Error;
@@ -20,6 +20,10 @@ pub enum DuplicateName {
     #[error("duplicate name in the scope of a module")]
     #[diagnostic(transparent)]
     InModule(#[from] DuplicateNameInModule),
+    /// Two methods of a service have the same name.
+    #[error("duplicate name in a service method")]
+    #[diagnostic(transparent)]
+    Method(#[from] DuplicateMethodName),
 }
 
 /// Duplicate name for enum variants.
@@ -52,6 +56,21 @@ pub struct DuplicateFieldName {
     pub second: Range<usize>,
 }
 
+/// Duplicate name for service methods.
+#[derive(Debug, Diagnostic, Error)]
+#[error("duplicate method name `{name}` in service")]
+#[diagnostic(help("the names of each method must be unique"))]
+pub struct DuplicateMethodName {
+    /// Name of the method.
+    pub name: String,
+    /// Source location of the first occurrence.
+    #[label("first declared here")]
+    pub first: Range<usize>,
+    /// Source location of the duplicate.
+    #[label("used here again")]
+    pub second: Range<usize>,
+}
+
 /// Duplicate name for definitions inside a module.
 #[derive(Debug, Diagnostic, Error)]
 #[error("duplicate definition name `{name}`")]
@@ -69,6 +88,21 @@ pub struct DuplicateNameInModule {
     pub second: Range<usize>,
 }
 
+/// Name that was explicitly reserved, but is used by a field or enum variant anyway.
+#[derive(Debug, Diagnostic, Error)]
+#[error("the name `{name}` is reserved")]
+#[diagnostic(help("reserved names must not be used by any field or variant"))]
+pub struct ReservedName {
+    /// The reserved name.
+    pub name: String,
+    /// Source location of the reservation.
+    #[label("reserved here")]
+    pub reserved: Range<usize>,
+    /// Source location of the usage.
+    #[label("used here")]
+    pub used: Range<usize>,
+}
+
 /// Ensure all field names inside a struct are unique.
 pub(crate) fn validate_struct_names(value: &Struct<'_>) -> Result<(), DuplicateFieldName> {
     validate_field_names(&value.fields)
@@ -101,10 +135,28 @@ pub(crate) fn validate_enum_names(value: &Enum<'_>) -> Result<(), DuplicateName>
         .map_or(Ok(()), Err)
 }
 
+/// Ensure all method names inside a service are unique.
+pub(crate) fn validate_service_names(value: &Service<'_>) -> Result<(), DuplicateMethodName> {
+    let mut visited = FxHashMap::with_capacity_and_hasher(value.methods.len(), FxBuildHasher);
+    value
+        .methods
+        .iter()
+        .find_map(|method| {
+            visited
+                .insert(method.name.get(), method.name.span())
+                .map(|first| DuplicateMethodName {
+                    name: method.name.get().to_owned(),
+                    first: first.into(),
+                    second: method.name.span().into(),
+                })
+        })
+        .map_or(Ok(()), Err)
+}
+
 /// Ensure all field names of a struct or enum are unique.
 fn validate_field_names(value: &Fields<'_>) -> Result<(), DuplicateFieldName> {
     match value {
-        Fields::Named(_, named) => {
+        Fields::Named(_, _, named) => {
             let mut visited = FxHashMap::with_capacity_and_hasher(named.len(), FxBuildHasher);
             named
                 .values()
@@ -125,6 +177,46 @@ fn validate_field_names(value: &Fields<'_>) -> Result<(), DuplicateFieldName> {
     Ok(())
 }
 
+/// Ensure none of the struct's fields uses a reserved name.
+pub(crate) fn validate_struct_reserved_names(value: &Struct<'_>) -> Result<(), ReservedName> {
+    validate_field_reserved_names(&value.fields)
+}
+
+/// Ensure none of the enum's variants uses a reserved name. The same applies to the fields of each
+/// variant.
+pub(crate) fn validate_enum_reserved_names(value: &Enum<'_>) -> Result<(), ReservedName> {
+    value.variants.values().try_for_each(|variant| {
+        check_reserved_name(&value.reserved, &variant.name)?;
+        validate_field_reserved_names(&variant.fields)
+    })
+}
+
+/// Ensure none of the named fields uses a reserved name. Only named fields can declare
+/// reservations.
+fn validate_field_reserved_names(value: &Fields<'_>) -> Result<(), ReservedName> {
+    let Fields::Named(_, reserved, named) = value else {
+        return Ok(());
+    };
+
+    named
+        .values()
+        .try_for_each(|field| check_reserved_name(reserved, &field.name))
+}
+
+fn check_reserved_name(reserved: &[Reserved<'_>], name: &Name<'_>) -> Result<(), ReservedName> {
+    reserved
+        .iter()
+        .flat_map(|reserved| reserved.values.values())
+        .find(|value| value.contains_name(name.get()))
+        .map_or(Ok(()), |value| {
+            Err(ReservedName {
+                name: name.get().to_owned(),
+                reserved: value.span().into(),
+                used: name.span().into(),
+            })
+        })
+}
+
 pub(crate) fn validate_names_in_module(value: &[Definition<'_>]) -> Result<(), DuplicateName> {
     let mut visited = FxHashMap::with_capacity_and_hasher(value.len(), FxBuildHasher);
     value
@@ -136,6 +228,7 @@ pub(crate) fn validate_names_in_module(value: &[Definition<'_>]) -> Result<(), D
                 Definition::Enum(e) => &e.name,
                 Definition::TypeAlias(a) => &a.name,
                 Definition::Const(c) => &c.name,
+                Definition::Service(s) => &s.name,
                 Definition::Import(Import {
                     element: Some((_, name)),
                     ..
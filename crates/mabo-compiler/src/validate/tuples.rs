@@ -1,7 +1,8 @@
 use std::ops::Range;
 
 use mabo_parser::{
-    Const, DataType, Enum, Fields, Spanned, Struct, Type, TypeAlias, punctuated::ValuesIter,
+    Const, DataType, Enum, Fields, Service, Spanned, Struct, Type, TypeAlias,
+    punctuated::ValuesIter,
 };
 use miette::Diagnostic;
 use thiserror::Error;
@@ -51,7 +52,7 @@ pub(crate) fn validate_enum_tuples(value: &Enum<'_>) -> Result<(), TupleSize> {
 
 fn validate_field_tuples(value: &Fields<'_>) -> Result<(), TupleSize> {
     match value {
-        Fields::Named(_, named) => named
+        Fields::Named(_, _, named) => named
             .values()
             .try_for_each(|field| validate_tuple_size(&field.ty)),
         Fields::Unnamed(_, unnamed) => unnamed
@@ -69,6 +70,13 @@ pub(crate) fn validate_const_tuples(value: &Const<'_>) -> Result<(), TupleSize>
     validate_tuple_size(&value.ty)
 }
 
+pub(crate) fn validate_service_tuples(value: &Service<'_>) -> Result<(), TupleSize> {
+    value.methods.iter().try_for_each(|method| {
+        validate_tuple_size(&method.request.ty)?;
+        validate_tuple_size(&method.response.ty)
+    })
+}
+
 fn validate_tuple_size(value: &Type<'_>) -> Result<(), TupleSize> {
     visit_tuples(value, &mut |tuples| {
         let amount = match tuples.len() {
@@ -110,12 +118,17 @@ fn visit_tuples<E>(
         | DataType::BytesRef
         | DataType::NonZero { .. }
         | DataType::BoxString
-        | DataType::BoxBytes => Ok(()),
+        | DataType::BoxBytes
+        | DataType::Timestamp
+        | DataType::Duration
+        | DataType::Uuid
+        | DataType::Decimal => Ok(()),
         DataType::Vec { ty, .. }
         | DataType::HashSet { ty, .. }
+        | DataType::BTreeSet { ty, .. }
         | DataType::Option { ty, .. }
         | DataType::Array { ty, .. } => visit_tuples(ty, visit),
-        DataType::HashMap { key, value, .. } => {
+        DataType::HashMap { key, value, .. } | DataType::BTreeMap { key, value, .. } => {
             visit_tuples(key, visit)?;
             visit_tuples(value, visit)
         }
@@ -0,0 +1,229 @@
+use std::ops::Range;
+
+use mabo_parser::{
+    Attribute, AttributeValue, DataType, Enum, Fields, Literal, LiteralValue, NamedField, Spanned,
+    Struct, Type,
+};
+use miette::Diagnostic;
+use thiserror::Error;
+
+use super::defaults::int_range;
+use crate::highlight;
+
+/// Validation rule of a field that is unknown or can't be used for the type of the field.
+#[derive(Debug, Diagnostic, Error)]
+#[error(
+    "the validation rule {}",
+    highlight::focus(match kind {
+        InvalidValidationKind::Unknown => "isn't known",
+        InvalidValidationKind::InvalidValue => "has an invalid value",
+        InvalidValidationKind::Unsupported => "isn't supported for this type",
+        InvalidValidationKind::OutOfRange => "is out of range for the field type",
+        InvalidValidationKind::Unsatisfiable => "can never be satisfied",
+    })
+)]
+#[diagnostic(help(
+    "known rules are `min` and `max` for numbers, `min_len`, `max_len` and `non_empty` for \
+     strings, bytes and collections, and `pattern` for strings"
+))]
+pub struct InvalidValidation {
+    /// The reason why the rule is invalid.
+    pub kind: InvalidValidationKind,
+    /// Source location of the validation rule.
+    #[label("declared here")]
+    pub declared: Range<usize>,
+    /// Source location of the field type.
+    #[label("for this type")]
+    pub ty: Range<usize>,
+}
+
+/// Possible reasons for a validation rule to be invalid.
+#[derive(Debug)]
+pub enum InvalidValidationKind {
+    /// The rule name isn't one of the known rules.
+    Unknown,
+    /// The value is missing or of the wrong kind, like a string for a `min` rule, or a regular
+    /// expression that fails to compile.
+    InvalidValue,
+    /// The field type can't be checked with this rule.
+    Unsupported,
+    /// The number doesn't fit into the field type.
+    OutOfRange,
+    /// The lower bound is greater than the upper bound.
+    Unsatisfiable,
+}
+
+/// Ensure all validation rules of the struct's fields are known and match their type.
+pub fn validate_struct_validations(value: &Struct<'_>) -> Result<(), InvalidValidation> {
+    validate_field_validations(&value.fields)
+}
+
+/// Ensure all validation rules of the enum variants' fields are known and match their type.
+pub fn validate_enum_validations(value: &Enum<'_>) -> Result<(), InvalidValidation> {
+    value
+        .variants
+        .values()
+        .try_for_each(|variant| validate_field_validations(&variant.fields))
+}
+
+fn validate_field_validations(value: &Fields<'_>) -> Result<(), InvalidValidation> {
+    match value {
+        Fields::Named(_, _, named) => named.values().try_for_each(validate_field),
+        Fields::Unnamed(..) | Fields::Unit => Ok(()),
+    }
+}
+
+fn validate_field(field: &NamedField<'_>) -> Result<(), InvalidValidation> {
+    // Rules of optional fields apply to the value, if present.
+    let ty = match &field.ty.value {
+        DataType::Option { ty, .. } => ty,
+        _ => &field.ty,
+    };
+
+    let mut bounds = Bounds::default();
+
+    for attr in field
+        .attributes
+        .0
+        .iter()
+        .filter(|attr| attr.name == "validate")
+    {
+        let AttributeValue::Multi(rules) = &attr.value else {
+            return Err(error(InvalidValidationKind::InvalidValue, attr, ty));
+        };
+
+        for rule in rules {
+            validate_rule(rule, ty, &mut bounds)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lower and upper bounds of the rules seen so far, to detect ranges that can't be satisfied.
+#[derive(Default)]
+struct Bounds<'a> {
+    min: Option<f64>,
+    max: Option<(f64, &'a Attribute<'a>)>,
+    min_len: Option<i128>,
+    max_len: Option<(i128, &'a Attribute<'a>)>,
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn validate_rule<'a>(
+    rule: &'a Attribute<'a>,
+    ty: &Type<'_>,
+    bounds: &mut Bounds<'a>,
+) -> Result<(), InvalidValidation> {
+    let fail = |kind| Err(error(kind, rule, ty));
+
+    match (rule.name, &rule.value) {
+        ("min" | "max", AttributeValue::Single(literal)) => {
+            let value = match (&ty.value, &literal.value) {
+                (DataType::F32 | DataType::F64, LiteralValue::Float(value)) => *value,
+                (DataType::F32 | DataType::F64, LiteralValue::Int(value)) => *value as f64,
+                (value_ty, LiteralValue::Int(value)) => match int_range(value_ty) {
+                    Some(range) if range.contains(value) => *value as f64,
+                    Some(_) => return fail(InvalidValidationKind::OutOfRange),
+                    None => return fail(InvalidValidationKind::Unsupported),
+                },
+                (value_ty, _) if is_number(value_ty) => {
+                    return fail(InvalidValidationKind::InvalidValue);
+                }
+                _ => return fail(InvalidValidationKind::Unsupported),
+            };
+
+            if rule.name == "min" {
+                bounds.min = Some(value);
+            } else {
+                bounds.max = Some((value, rule));
+            }
+        }
+        ("min_len" | "max_len", AttributeValue::Single(Literal { value, .. })) => {
+            let LiteralValue::Int(value) = value else {
+                return fail(InvalidValidationKind::InvalidValue);
+            };
+            if u64::try_from(*value).is_err() {
+                return fail(InvalidValidationKind::InvalidValue);
+            }
+            if !has_length(&ty.value) {
+                return fail(InvalidValidationKind::Unsupported);
+            }
+
+            if rule.name == "min_len" {
+                bounds.min_len = Some(*value);
+            } else {
+                bounds.max_len = Some((*value, rule));
+            }
+        }
+        ("pattern", AttributeValue::Single(Literal { value, .. })) => {
+            let LiteralValue::String(pattern) = value else {
+                return fail(InvalidValidationKind::InvalidValue);
+            };
+            if regex_syntax::Parser::new().parse(pattern).is_err() {
+                return fail(InvalidValidationKind::InvalidValue);
+            }
+            if !is_string(&ty.value) {
+                return fail(InvalidValidationKind::Unsupported);
+            }
+        }
+        ("non_empty", AttributeValue::Unit) => {
+            if !has_length(&ty.value) {
+                return fail(InvalidValidationKind::Unsupported);
+            }
+        }
+        ("min" | "max" | "min_len" | "max_len" | "pattern" | "non_empty", _) => {
+            return fail(InvalidValidationKind::InvalidValue);
+        }
+        _ => return fail(InvalidValidationKind::Unknown),
+    }
+
+    match (bounds.min, bounds.max) {
+        (Some(min), Some((max, attr))) if min > max => {
+            return Err(error(InvalidValidationKind::Unsatisfiable, attr, ty));
+        }
+        _ => {}
+    }
+
+    match (bounds.min_len, bounds.max_len) {
+        (Some(min), Some((max, attr))) if min > max => {
+            Err(error(InvalidValidationKind::Unsatisfiable, attr, ty))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn error(kind: InvalidValidationKind, attr: &Attribute<'_>, ty: &Type<'_>) -> InvalidValidation {
+    InvalidValidation {
+        kind,
+        declared: attr.span().into(),
+        ty: ty.span().into(),
+    }
+}
+
+fn is_number(ty: &DataType<'_>) -> bool {
+    matches!(ty, DataType::F32 | DataType::F64) || int_range(ty).is_some()
+}
+
+fn is_string(ty: &DataType<'_>) -> bool {
+    matches!(
+        ty,
+        DataType::String | DataType::StringRef | DataType::BoxString
+    )
+}
+
+/// Whether the type has a length, that can be checked by the length rules.
+fn has_length(ty: &DataType<'_>) -> bool {
+    is_string(ty)
+        || matches!(
+            ty,
+            DataType::Bytes
+                | DataType::BytesRef
+                | DataType::BoxBytes
+                | DataType::Vec { .. }
+                | DataType::HashMap { .. }
+                | DataType::HashSet { .. }
+                | DataType::BTreeMap { .. }
+                | DataType::BTreeSet { .. }
+        )
+}
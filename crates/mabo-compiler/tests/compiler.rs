@@ -137,3 +137,24 @@ fn resolve_schema_remote() {
         });
     });
 }
+
+#[test]
+fn lint_schema() {
+    glob!("inputs/lint/*.mabo", |path| {
+        let input = fs::read_to_string(path).unwrap();
+        let schema = Schema::parse(input.as_str(), Some(&strip_path(path))).unwrap();
+        let mut result = mabo_compiler::lint_schema(&schema);
+        assert_eq!(1, result.len());
+        let report = Report::new(result.remove(0)).with_source_code(NamedSource::new(
+            path.file_name().unwrap().to_string_lossy(),
+            input.clone(),
+        ));
+
+        with_settings!({
+            description => input.trim(),
+            omit_expression => true,
+        }, {
+            assert_snapshot!("warning_lint", Wrapper::new(&*report).to_string());
+        });
+    });
+}
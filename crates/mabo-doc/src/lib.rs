@@ -4,7 +4,9 @@ use std::rc::Rc;
 
 use anyhow::Result;
 use askama::Template;
-use mabo_compiler::simplify::{Const, Definition, Enum, Module, Schema, Struct, TypeAlias};
+use mabo_compiler::simplify::{
+    Const, Definition, Enum, Module, Schema, Service, Struct, TypeAlias,
+};
 
 mod templates;
 
@@ -80,6 +82,7 @@ fn render_definition<'a>(
         Definition::TypeAlias(a) => render_alias(a, path),
         Definition::Const(c) => render_const(c, path),
         Definition::Import(_) => return None,
+        Definition::Service(s) => render_service(s, path),
     })
 }
 
@@ -142,3 +145,13 @@ fn render_const<'a>(item: &'a Const<'_>, path: &Rc<[Rc<str>]>) -> Result<Output<
         modules: Vec::new(),
     })
 }
+
+fn render_service<'a>(item: &'a Service<'_>, path: &Rc<[Rc<str>]>) -> Result<Output<'a>> {
+    Ok(Output {
+        name: item.name,
+        path: Rc::clone(path),
+        file: format!("service.{}.html", item.name),
+        content: templates::ServiceDetail { path, item }.render()?,
+        modules: Vec::new(),
+    })
+}
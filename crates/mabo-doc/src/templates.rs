@@ -5,8 +5,8 @@ use std::{
 
 use askama::Template;
 use mabo_compiler::simplify::{
-    Const, Definition, Enum, ExternalType, Field, FieldKind, Literal, Module, Struct, Type,
-    TypeAlias,
+    Const, Definition, Enum, ExternalType, Field, FieldKind, Literal, Method, MethodType, Module,
+    Service, Struct, Type, TypeAlias,
 };
 use mabo_meta::WireSize;
 
@@ -211,6 +211,26 @@ impl ConstDetail<'_> {
     }
 }
 
+#[derive(Template)]
+#[template(path = "detail/service.html")]
+pub struct ServiceDetail<'a> {
+    pub path: &'a [Rc<str>],
+    pub item: &'a Service<'a>,
+}
+
+impl ServiceDetail<'_> {
+    fn print_schema(&self) -> String {
+        let mut buf = format!("service {} {{\n", self.item.name);
+
+        for method in &*self.item.methods {
+            let _ = writeln!(&mut buf, "    {};", PrintMethod(method));
+        }
+
+        buf.push('}');
+        buf
+    }
+}
+
 fn render_wire_size(size: &WireSize) -> String {
     let mut buf = String::new();
     size.print(&mut buf, 0);
@@ -258,16 +278,42 @@ struct PrintField<'a>(&'a Field<'a>, FieldKind);
 impl Display for PrintField<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.1 {
-            FieldKind::Named => write!(
-                f,
-                "{}: {} @{}",
-                self.0.name,
-                PrintType(&self.0.ty),
-                self.0.id
-            ),
-            FieldKind::Unnamed => write!(f, "{} @{}", PrintType(&self.0.ty), self.0.id),
-            FieldKind::Unit => Ok(()),
+            FieldKind::Named => write!(f, "{}: {}", self.0.name, PrintType(&self.0.ty))?,
+            FieldKind::Unnamed => write!(f, "{}", PrintType(&self.0.ty))?,
+            FieldKind::Unit => return Ok(()),
+        }
+
+        if let Some(default) = &self.0.default {
+            write!(f, " = {}", PrintLiteral(default))?;
+        }
+
+        write!(f, " @{}", self.0.id)
+    }
+}
+
+struct PrintMethod<'a>(&'a Method<'a>);
+
+impl Display for PrintMethod<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fn {}({}) -> {} @{}",
+            self.0.name,
+            PrintMethodType(&self.0.request),
+            PrintMethodType(&self.0.response),
+            self.0.id,
+        )
+    }
+}
+
+struct PrintMethodType<'a>(&'a MethodType<'a>);
+
+impl Display for PrintMethodType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.stream {
+            f.write_str("stream ")?;
         }
+        PrintType(&self.0.ty).fmt(f)
     }
 }
 
@@ -296,10 +342,16 @@ impl Display for PrintType<'_> {
             Type::Vec(t) => write!(f, "vec<{}>", Self(t)),
             Type::HashMap(kv) => write!(f, "hash_map<{}, {}>", Self(&kv.0), Self(&kv.1)),
             Type::HashSet(t) => write!(f, "hash_set<{}>", Self(t)),
+            Type::BTreeMap(kv) => write!(f, "btree_map<{}, {}>", Self(&kv.0), Self(&kv.1)),
+            Type::BTreeSet(t) => write!(f, "btree_set<{}>", Self(t)),
             Type::Option(t) => write!(f, "option<{}>", Self(t)),
             Type::NonZero(t) => write!(f, "non_zero<{}>", Self(t)),
             Type::BoxString => f.write_str("box<string>"),
             Type::BoxBytes => f.write_str("box<bytes>"),
+            Type::Timestamp => f.write_str("timestamp"),
+            Type::Duration => f.write_str("duration"),
+            Type::Uuid => f.write_str("uuid"),
+            Type::Decimal => f.write_str("decimal"),
             Type::Tuple(types) => {
                 f.write_char('(')?;
                 for (i, ty) in types.iter().enumerate() {
@@ -11,6 +11,8 @@ pub struct Cli {
     pub out_dir: Option<PathBuf>,
     #[arg(long)]
     pub no_fmt: bool,
+    #[arg(long)]
+    pub validate_on_decode: bool,
 }
 
 impl Cli {
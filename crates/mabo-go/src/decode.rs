@@ -9,16 +9,20 @@ use crate::{
     definition::{self, RenderGenericNames},
 };
 
-pub(super) struct RenderStruct<'a>(pub(super) &'a Struct<'a>);
+pub(super) struct RenderStruct<'a> {
+    pub(super) def: &'a Struct<'a>,
+    /// Whether to check the validation rules after decoding.
+    pub(super) validated: bool,
+}
 
 impl Display for RenderStruct<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
             "var _ buf.Decode = (*{}{})(nil)\n",
-            heck::AsUpperCamelCase(&self.0.name),
+            heck::AsUpperCamelCase(&self.def.name),
             RenderGenericNames {
-                generics: &self.0.generics,
+                generics: &self.def.generics,
                 fields_filter: None,
             }
         )?;
@@ -26,13 +30,13 @@ impl Display for RenderStruct<'_> {
         writeln!(
             f,
             "func (v *{}{}) Decode(r []byte) ([]byte, error) {{",
-            heck::AsUpperCamelCase(&self.0.name),
+            heck::AsUpperCamelCase(&self.def.name),
             RenderGenericNames {
-                generics: &self.0.generics,
+                generics: &self.def.generics,
                 fields_filter: None,
             }
         )?;
-        writeln!(f, "{}", RenderFieldVars(&self.0.fields))?;
+        writeln!(f, "{}", RenderFieldVars(&self.def.fields))?;
         writeln!(f, "\tfor len(r) > 0 {{")?;
         writeln!(f, "\t\tr2, id, err := buf.DecodeID(r)")?;
         writeln!(f, "\t\tif err != nil {{")?;
@@ -40,12 +44,13 @@ impl Display for RenderStruct<'_> {
         writeln!(f, "\t\t}}")?;
         writeln!(f, "\t\tr = r2\n")?;
         writeln!(f, "\t\tswitch id {{")?;
-        write!(f, "{}", RenderFields(&self.0.fields))?;
+        write!(f, "{}", RenderFields(&self.def.fields))?;
         writeln!(f, "\t\t\tcase buf.EndMarker:")?;
         writeln!(f, "\t\t\t\tbreak")?;
         writeln!(f, "\t\t}}")?;
         writeln!(f, "\t}}\n")?;
-        write!(f, "{}", RenderFoundChecks(&self.0.fields))?;
+        write!(f, "{}", RenderFoundChecks(&self.def.fields))?;
+        write!(f, "{}", RenderValidated(self.validated))?;
         writeln!(f, "\n\treturn r, nil\n}}")
     }
 }
@@ -54,6 +59,8 @@ pub(super) struct RenderEnumVariant<'a> {
     pub(super) enum_name: &'a str,
     pub(super) generics: &'a [&'a str],
     pub(super) variant: &'a Variant<'a>,
+    /// Whether to check the validation rules after decoding.
+    pub(super) validated: bool,
 }
 
 impl Display for RenderEnumVariant<'_> {
@@ -93,10 +100,32 @@ impl Display for RenderEnumVariant<'_> {
         writeln!(f, "\t\t}}")?;
         writeln!(f, "\t}}\n")?;
         write!(f, "{}", RenderFoundChecks(&self.variant.fields))?;
+        write!(f, "{}", RenderValidated(self.validated))?;
         writeln!(f, "\n\treturn r, nil\n}}")
     }
 }
 
+/// Check of the validation rules, that is run once all fields are decoded.
+struct RenderValidated(bool);
+
+impl Display for RenderValidated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 {
+            writeln!(
+                f,
+                "\n\tif violations := v.Validate(); len(violations) > 0 {{"
+            )?;
+            writeln!(
+                f,
+                "\t\treturn nil, mabo.ValidationError{{Violations: violations}}"
+            )?;
+            writeln!(f, "\t}}")?;
+        }
+
+        Ok(())
+    }
+}
+
 struct RenderFieldVars<'a>(&'a Fields<'a>);
 
 impl Display for RenderFieldVars<'_> {
@@ -115,6 +144,19 @@ impl Display for RenderFoundChecks<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for field in &*self.0.fields {
             writeln!(f, "\tif !found{} {{", heck::AsUpperCamelCase(&field.name))?;
+            if let Some(default) = &field.default {
+                writeln!(
+                    f,
+                    "\t\tv.{} = {}",
+                    heck::AsUpperCamelCase(&field.name),
+                    definition::RenderDefault {
+                        ty: &field.ty,
+                        value: default,
+                    },
+                )?;
+                writeln!(f, "\t}}")?;
+                continue;
+            }
             writeln!(f, "\t\treturn nil, buf.MissingFieldError{{")?;
             writeln!(f, "\t\t\tID:    {},", field.id)?;
             writeln!(
@@ -195,6 +237,10 @@ impl Display for RenderType<'_> {
             Type::Bytes | Type::BytesRef | Type::BoxBytes => {
                 write!(f, "buf.DecodeBytes(r)")
             }
+            Type::Timestamp => write!(f, "buf.DecodeTimestamp(r)"),
+            Type::Duration => write!(f, "buf.DecodeDuration(r)"),
+            Type::Uuid => write!(f, "buf.DecodeUUID(r)"),
+            Type::Decimal => write!(f, "buf.DecodeDecimal(r)"),
             Type::Vec(ty) => {
                 write!(
                     f,
@@ -206,7 +252,7 @@ impl Display for RenderType<'_> {
                     }
                 )
             }
-            Type::HashMap(kv) => {
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
                 write!(
                     f,
                     "{}",
@@ -217,7 +263,7 @@ impl Display for RenderType<'_> {
                     }
                 )
             }
-            Type::HashSet(ty) => {
+            Type::HashSet(ty) | Type::BTreeSet(ty) => {
                 write!(
                     f,
                     "{}",
@@ -263,7 +309,7 @@ impl Display for RenderType<'_> {
                         }
                     )
                 }
-                Type::HashMap(kv) => {
+                Type::HashMap(kv) | Type::BTreeMap(kv) => {
                     write!(
                         f,
                         "{}",
@@ -274,7 +320,7 @@ impl Display for RenderType<'_> {
                         }
                     )
                 }
-                Type::HashSet(ty) => {
+                Type::HashSet(ty) | Type::BTreeSet(ty) => {
                     write!(
                         f,
                         "{}",
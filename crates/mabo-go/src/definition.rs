@@ -1,11 +1,11 @@
 use std::fmt::{self, Display, Write};
 
 use mabo_compiler::simplify::{
-    Const, Definition, Enum, ExternalType, Fields, Literal, Schema, Struct, Type, TypeAlias,
-    Variant,
+    Const, Definition, Deprecated, Enum, ExternalType, Fields, Literal, MethodType, Schema,
+    Service, Struct, Type, TypeAlias, Validation, Variant,
 };
 
-use crate::{Indent, Opts, Output, decode, encode, size};
+use crate::{Indent, Opts, Output, decode, encode, size, validate};
 
 /// Take a single schema and convert it into Go source code (which can result in multiple files).
 #[must_use]
@@ -17,12 +17,12 @@ pub fn render_schema<'a>(
         "{}{}{}",
         RenderHeader,
         RenderPackage(opts.package, None),
-        RenderImports,
+        RenderImports(definitions),
     );
 
     let modules = definitions
         .iter()
-        .filter_map(|def| render_definition(&mut content, def))
+        .filter_map(|def| render_definition(opts, &mut content, def))
         .collect();
 
     Output {
@@ -32,20 +32,24 @@ pub fn render_schema<'a>(
     }
 }
 
-fn render_definition<'a>(buf: &mut String, definition: &'a Definition<'_>) -> Option<Output<'a>> {
+fn render_definition<'a>(
+    opts: &Opts<'_>,
+    buf: &mut String,
+    definition: &'a Definition<'_>,
+) -> Option<Output<'a>> {
     match definition {
         Definition::Module(m) => {
             let mut content = format!(
                 "{}{}{}",
                 RenderHeader,
                 RenderPackage(m.name, Some(&m.comment)),
-                RenderImports,
+                RenderImports(&m.definitions),
             );
 
             let modules = m
                 .definitions
                 .iter()
-                .filter_map(|def| render_definition(&mut content, def))
+                .filter_map(|def| render_definition(opts, &mut content, def))
                 .collect();
 
             return Some(Output {
@@ -67,19 +71,37 @@ fn render_definition<'a>(buf: &mut String, definition: &'a Definition<'_>) -> Op
                 }
             )
             .unwrap();
+            if s.fields.fields.iter().any(|field| field.default.is_some()) {
+                writeln!(buf, "\n{}", RenderDefaultFunc(s)).unwrap();
+            }
             writeln!(
                 buf,
                 "\n{}\n{}\n{}",
                 encode::RenderStruct(s),
-                decode::RenderStruct(s),
+                decode::RenderStruct {
+                    def: s,
+                    validated: opts.validate_on_decode && validate::has_rules(&s.fields),
+                },
                 size::RenderStruct(s),
             )
             .unwrap();
+            if validate::has_rules(&s.fields) {
+                writeln!(buf, "{}", validate::RenderStruct(s)).unwrap();
+            }
         }
-        Definition::Enum(e) => writeln!(buf, "{}", RenderEnum(e)).unwrap(),
+        Definition::Enum(e) => writeln!(
+            buf,
+            "{}",
+            RenderEnum {
+                def: e,
+                validate_on_decode: opts.validate_on_decode,
+            }
+        )
+        .unwrap(),
         Definition::TypeAlias(a) => writeln!(buf, "{}", RenderAlias(a)).unwrap(),
         Definition::Const(c) => write!(buf, "{}", RenderConst(c)).unwrap(),
         Definition::Import(_) => {}
+        Definition::Service(s) => writeln!(buf, "{}", RenderService(s)).unwrap(),
     }
 
     None
@@ -108,7 +130,8 @@ impl Display for RenderPackage<'_> {
                 "{}",
                 RenderComment {
                     indent: Indent(0),
-                    comment
+                    comment,
+                    deprecated: None,
                 }
             )?;
         }
@@ -117,11 +140,43 @@ impl Display for RenderPackage<'_> {
     }
 }
 
-struct RenderImports;
+struct RenderImports<'a>(&'a [Definition<'a>]);
 
-impl Display for RenderImports {
+impl Display for RenderImports<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "import (")?;
+        let mut std = Vec::new();
+        if self
+            .0
+            .iter()
+            .any(|def| matches!(def, Definition::Service(_)))
+        {
+            std.push("context");
+        }
+        if self
+            .0
+            .iter()
+            .any(|def| uses_validation(def, |_, rule| matches!(rule, Validation::Pattern(_))))
+        {
+            std.push("regexp");
+        }
+        if self.0.iter().any(uses_time) {
+            std.push("time");
+        }
+        if self.0.iter().any(|def| {
+            uses_validation(def, |ty, rule| {
+                matches!(rule, Validation::MinLen(_) | Validation::MaxLen(_))
+                    && matches!(ty, Type::String | Type::StringRef | Type::BoxString)
+            })
+        }) {
+            std.push("unicode/utf8");
+        }
+        for package in &std {
+            writeln!(f, "\t\"{package}\"")?;
+        }
+        if !std.is_empty() {
+            writeln!(f)?;
+        }
         writeln!(f, "\tmabo \"github.com/dnaka91/mabo-go\"")?;
         writeln!(f, "\tbuf \"github.com/dnaka91/mabo-go/buf\"")?;
         writeln!(f, ")\n")
@@ -137,7 +192,8 @@ impl Display for RenderStruct<'_> {
             "{}type {}{} {}",
             RenderComment {
                 indent: Indent(0),
-                comment: &self.0.comment
+                comment: &self.0.comment,
+                deprecated: self.0.deprecated.as_ref(),
             },
             heck::AsUpperCamelCase(&self.0.name),
             RenderGenerics {
@@ -149,6 +205,59 @@ impl Display for RenderStruct<'_> {
     }
 }
 
+/// Services become an interface, which is left to be implemented on top of a transport.
+struct RenderService<'a>(&'a Service<'a>);
+
+impl Display for RenderService<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}type {} interface {{",
+            RenderComment {
+                indent: Indent(0),
+                comment: &self.0.comment,
+                deprecated: self.0.deprecated.as_ref(),
+            },
+            heck::AsUpperCamelCase(&self.0.name),
+        )?;
+
+        for method in &*self.0.methods {
+            let request = if method.request.stream {
+                "requests"
+            } else {
+                "request"
+            };
+
+            writeln!(
+                f,
+                "{}\t{}(ctx context.Context, {request} {}) ({}, error)",
+                RenderComment {
+                    indent: Indent(1),
+                    comment: &method.comment,
+                    deprecated: method.deprecated.as_ref(),
+                },
+                heck::AsUpperCamelCase(&method.name),
+                RenderMethodType(&method.request),
+                RenderMethodType(&method.response),
+            )?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+/// Streams of values are passed as receive-only channels.
+struct RenderMethodType<'a>(&'a MethodType<'a>);
+
+impl Display for RenderMethodType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.stream {
+            f.write_str("<-chan ")?;
+        }
+        RenderType(&self.0.ty).fmt(f)
+    }
+}
+
 struct RenderNewFunc<'a, T> {
     name: T,
     generics: &'a [&'a str],
@@ -189,6 +298,53 @@ where
     }
 }
 
+/// Constructor for structs that declare default values for any of their fields. All other fields
+/// keep Go's zero value.
+struct RenderDefaultFunc<'a>(&'a Struct<'a>);
+
+impl Display for RenderDefaultFunc<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let generics = RenderGenericNames {
+            generics: &self.0.generics,
+            fields_filter: None,
+        };
+
+        writeln!(
+            f,
+            "func Default{}{}() {0}{} {{",
+            heck::AsUpperCamelCase(&self.0.name),
+            RenderGenerics {
+                generics: &self.0.generics,
+                fields_filter: None,
+            },
+            generics,
+        )?;
+        writeln!(
+            f,
+            "\treturn {}{}{{",
+            heck::AsUpperCamelCase(&self.0.name),
+            generics,
+        )?;
+
+        for field in &*self.0.fields.fields {
+            if let Some(value) = &field.default {
+                writeln!(
+                    f,
+                    "\t\t{}: {},",
+                    heck::AsUpperCamelCase(&field.name),
+                    RenderDefault {
+                        ty: &field.ty,
+                        value,
+                    },
+                )?;
+            }
+        }
+
+        writeln!(f, "\t}}")?;
+        write!(f, "}}")
+    }
+}
+
 struct RenderGenerics<'a> {
     generics: &'a [&'a str],
     fields_filter: Option<&'a Fields<'a>>,
@@ -301,7 +457,8 @@ impl Display for RenderFields<'_> {
                     "{}\t{} {}",
                     RenderComment {
                         indent: Indent(1),
-                        comment: &field.comment
+                        comment: &field.comment,
+                        deprecated: field.deprecated.as_ref(),
                     },
                     heck::AsUpperCamelCase(&field.name),
                     RenderType(&field.ty)
@@ -367,6 +524,7 @@ impl Display for RenderAlias<'_> {
             RenderComment {
                 indent: Indent(0),
                 comment: &self.0.comment,
+                deprecated: None,
             },
             heck::AsUpperCamelCase(&self.0.name),
             RenderType(&self.0.target),
@@ -377,15 +535,35 @@ impl Display for RenderAlias<'_> {
 struct RenderComment<'a> {
     indent: Indent,
     comment: &'a [&'a str],
+    deprecated: Option<&'a Deprecated>,
 }
 
 impl Display for RenderComment<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self { indent, comment } = *self;
+        let Self {
+            indent,
+            comment,
+            deprecated,
+        } = *self;
         for line in comment {
             writeln!(f, "{indent}// {line}")?;
         }
 
+        // Go tooling picks up deprecations from a paragraph that starts with `Deprecated:`.
+        if let Some(deprecated) = deprecated {
+            if !comment.is_empty() {
+                writeln!(f, "{indent}//")?;
+            }
+            writeln!(
+                f,
+                "{indent}// Deprecated: {}",
+                deprecated
+                    .note
+                    .as_deref()
+                    .unwrap_or("This element shouldn't be used anymore.")
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -409,9 +587,19 @@ impl Display for RenderType<'_> {
             Type::F64 => write!(f, "float64"),
             Type::String | Type::StringRef | Type::BoxString => write!(f, "string"),
             Type::Bytes | Type::BytesRef | Type::BoxBytes => write!(f, "[]byte"),
+            Type::Timestamp => write!(f, "time.Time"),
+            Type::Duration => write!(f, "time.Duration"),
+            Type::Uuid => write!(f, "[16]byte"),
+            Type::Decimal => write!(f, "mabo.Decimal"),
             Type::Vec(ty) => write!(f, "[]{}", RenderType(ty)),
-            Type::HashMap(kv) => write!(f, "map[{}]{}", RenderType(&kv.0), RenderType(&kv.1)),
-            Type::HashSet(ty) => write!(f, "map[{}]struct{{}}", RenderType(ty)),
+            // Go has no ordered maps, so B-tree collections are plain maps as well, that are only
+            // sorted while encoding.
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
+                write!(f, "map[{}]{}", RenderType(&kv.0), RenderType(&kv.1))
+            }
+            Type::HashSet(ty) | Type::BTreeSet(ty) => {
+                write!(f, "map[{}]struct{{}}", RenderType(ty))
+            }
             Type::Option(ty) => write!(f, "*{}", RenderType(ty)),
             Type::NonZero(ty) => match &**ty {
                 Type::U8 => write!(f, "mabo.NonZeroU8"),
@@ -429,13 +617,15 @@ impl Display for RenderType<'_> {
                 Type::String | Type::StringRef => write!(f, "mabo.NonZeroString"),
                 Type::Bytes | Type::BytesRef => write!(f, "mabo.NonZeroBytes"),
                 Type::Vec(ty) => write!(f, "mabo.NonZeroVec[{}]", RenderType(ty)),
-                Type::HashMap(kv) => write!(
+                Type::HashMap(kv) | Type::BTreeMap(kv) => write!(
                     f,
                     "mabo.NonZeroHashMap[{}, {}]",
                     RenderType(&kv.0),
                     RenderType(&kv.1)
                 ),
-                Type::HashSet(ty) => write!(f, "mabo.NonZeroHashSet[{}]", RenderType(ty)),
+                Type::HashSet(ty) | Type::BTreeSet(ty) => {
+                    write!(f, "mabo.NonZeroHashSet[{}]", RenderType(ty))
+                }
                 ty => todo!("compiler should catch invalid {ty:?} type"),
             },
             Type::Tuple(types) => write!(f, "mabo.Tuple{}{}", types.len(), Concat(types)),
@@ -538,7 +728,8 @@ impl Display for RenderConst<'_> {
             "{}{kind} {} {} = {}",
             RenderComment {
                 indent: Indent(0),
-                comment: &self.0.comment
+                comment: &self.0.comment,
+                deprecated: self.0.deprecated.as_ref(),
             },
             heck::AsUpperCamelCase(&self.0.name),
             RenderConstType(&self.0.ty),
@@ -574,14 +765,40 @@ impl Display for RenderLiteral<'_> {
     }
 }
 
-struct RenderEnum<'a>(&'a Enum<'a>);
+/// Default value of a field. Big integers can't be expressed as literals and are constructed
+/// instead.
+pub(super) struct RenderDefault<'a> {
+    pub(super) ty: &'a Type<'a>,
+    pub(super) value: &'a Literal,
+}
+
+impl Display for RenderDefault<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.ty, self.value) {
+            (Type::U128 | Type::I128, Literal::Int(value)) => match i64::try_from(*value) {
+                Ok(value) => write!(f, "big.NewInt({value})"),
+                Err(_) => write!(
+                    f,
+                    "func() *big.Int {{ v, _ := new(big.Int).SetString(\"{value}\", 10); return v }}()"
+                ),
+            },
+            (_, Literal::Bytes(value)) if value.is_empty() => f.write_str("[]byte{}"),
+            (_, value) => RenderLiteral(value).fmt(f),
+        }
+    }
+}
+
+struct RenderEnum<'a> {
+    def: &'a Enum<'a>,
+    validate_on_decode: bool,
+}
 
 impl Display for RenderEnum<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
             "type {}Variant interface {{",
-            heck::AsUpperCamelCase(&self.0.name),
+            heck::AsUpperCamelCase(&self.def.name),
         )?;
         writeln!(f, "\t sealed()")?;
         writeln!(f, "}}")?;
@@ -591,19 +808,21 @@ impl Display for RenderEnum<'_> {
             "\n{}type {} {1}Variant",
             RenderComment {
                 indent: Indent(0),
-                comment: &self.0.comment
+                comment: &self.def.comment,
+                deprecated: self.def.deprecated.as_ref(),
             },
-            heck::AsUpperCamelCase(&self.0.name),
+            heck::AsUpperCamelCase(&self.def.name),
         )?;
 
-        for variant in &self.0.variants {
+        for variant in &self.def.variants {
             write!(
                 f,
                 "\n{}",
                 RenderEnumVariant {
-                    enum_name: self.0.name,
-                    generics: &self.0.generics,
-                    variant
+                    enum_name: self.def.name,
+                    generics: &self.def.generics,
+                    variant,
+                    validate_on_decode: self.validate_on_decode,
                 }
             )?;
         }
@@ -616,6 +835,7 @@ struct RenderEnumVariant<'a> {
     enum_name: &'a str,
     generics: &'a [&'a str],
     variant: &'a Variant<'a>,
+    validate_on_decode: bool,
 }
 
 impl Display for RenderEnumVariant<'_> {
@@ -625,7 +845,8 @@ impl Display for RenderEnumVariant<'_> {
             "{}type {}_{}{} {}",
             RenderComment {
                 indent: Indent(0),
-                comment: &self.variant.comment
+                comment: &self.variant.comment,
+                deprecated: self.variant.deprecated.as_ref(),
             },
             heck::AsUpperCamelCase(self.enum_name),
             heck::AsUpperCamelCase(&self.variant.name),
@@ -674,13 +895,88 @@ impl Display for RenderEnumVariant<'_> {
                 enum_name: self.enum_name,
                 generics: self.generics,
                 variant: self.variant,
+                validated: self.validate_on_decode && validate::has_rules(&self.variant.fields),
             },
             size::RenderEnumVariant {
                 enum_name: self.enum_name,
                 generics: self.generics,
                 variant: self.variant,
             },
-        )
+        )?;
+
+        if validate::has_rules(&self.variant.fields) {
+            write!(
+                f,
+                "\n{}",
+                validate::RenderEnumVariant {
+                    enum_name: self.enum_name,
+                    generics: self.generics,
+                    variant: self.variant,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the definition refers to any type from Go's `time` package, which then must be
+/// imported. Nested modules are rendered into their own file and not considered.
+fn uses_time(definition: &Definition<'_>) -> bool {
+    fn visit(ty: &Type<'_>) -> bool {
+        match ty {
+            Type::Timestamp | Type::Duration => true,
+            Type::Vec(ty)
+            | Type::HashSet(ty)
+            | Type::BTreeSet(ty)
+            | Type::Option(ty)
+            | Type::NonZero(ty)
+            | Type::Array(ty, _) => visit(ty),
+            Type::HashMap(kv) | Type::BTreeMap(kv) => visit(&kv.0) || visit(&kv.1),
+            Type::Tuple(types) => types.iter().any(visit),
+            Type::External(ty) => ty.generics.iter().any(visit),
+            _ => false,
+        }
+    }
+
+    let fields = |fields: &Fields<'_>| fields.fields.iter().any(|field| visit(&field.ty));
+
+    match definition {
+        Definition::Struct(s) => fields(&s.fields),
+        Definition::Enum(e) => e.variants.iter().any(|variant| fields(&variant.fields)),
+        Definition::TypeAlias(a) => visit(&a.target),
+        Definition::Service(s) => s
+            .methods
+            .iter()
+            .any(|method| visit(&method.request.ty) || visit(&method.response.ty)),
+        Definition::Module(_) | Definition::Const(_) | Definition::Import(_) => false,
+    }
+}
+
+/// Whether any field of the definition declares a validation rule, that matches the predicate.
+/// Optional field types are passed without the option wrapper, as the rules apply to the value.
+fn uses_validation(
+    definition: &Definition<'_>,
+    predicate: impl Fn(&Type<'_>, &Validation) -> bool,
+) -> bool {
+    let fields = |fields: &Fields<'_>| {
+        fields.fields.iter().any(|field| {
+            let ty = match &field.ty {
+                Type::Option(ty) => ty,
+                ty => ty,
+            };
+            field.validations.iter().any(|rule| predicate(ty, rule))
+        })
+    };
+
+    match definition {
+        Definition::Struct(s) => fields(&s.fields),
+        Definition::Enum(e) => e.variants.iter().any(|variant| fields(&variant.fields)),
+        Definition::Module(_)
+        | Definition::TypeAlias(_)
+        | Definition::Const(_)
+        | Definition::Import(_)
+        | Definition::Service(_) => false,
     }
 }
 
@@ -705,13 +1001,20 @@ fn uses_generic(generic: &str, fields: &Fields<'_>) -> bool {
             | Type::Bytes
             | Type::BytesRef
             | Type::BoxString
-            | Type::BoxBytes => false,
+            | Type::BoxBytes
+            | Type::Timestamp
+            | Type::Duration
+            | Type::Uuid
+            | Type::Decimal => false,
             Type::Vec(ty)
             | Type::HashSet(ty)
+            | Type::BTreeSet(ty)
             | Type::Option(ty)
             | Type::NonZero(ty)
             | Type::Array(ty, _) => visit_external(ty, visit),
-            Type::HashMap(kv) => visit_external(&kv.0, visit) || visit_external(&kv.1, visit),
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
+                visit_external(&kv.0, visit) || visit_external(&kv.1, visit)
+            }
             Type::Tuple(types) => types.iter().any(|ty| visit_external(ty, visit)),
             Type::External(ty) => visit(ty),
         }
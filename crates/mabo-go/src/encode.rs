@@ -142,6 +142,10 @@ where
             Type::Bytes | Type::BytesRef | Type::BoxBytes => {
                 write!(f, "buf.EncodeBytes(w, {name})")
             }
+            Type::Timestamp => write!(f, "buf.EncodeTimestamp(w, {name})"),
+            Type::Duration => write!(f, "buf.EncodeDuration(w, {name})"),
+            Type::Uuid => write!(f, "buf.EncodeUUID(w, {name})"),
+            Type::Decimal => write!(f, "buf.EncodeDecimal(w, {name})"),
             Type::Vec(ty) => {
                 writeln!(
                     f,
@@ -160,10 +164,15 @@ where
                 )?;
                 write!(f, "{indent}}})")
             }
-            Type::HashMap(kv) => {
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
                 writeln!(
                     f,
-                    "buf.EncodeHashMap[{}, {}](",
+                    "buf.{}[{}, {}](",
+                    if matches!(ty, Type::HashMap(_)) {
+                        "EncodeHashMap"
+                    } else {
+                        "EncodeSortedMap"
+                    },
                     definition::RenderType(&kv.0),
                     definition::RenderType(&kv.1)
                 )?;
@@ -206,18 +215,23 @@ where
                 writeln!(f, "{}}},", indent + 1)?;
                 write!(f, "{indent})")
             }
-            Type::HashSet(ty) => {
+            Type::HashSet(inner) | Type::BTreeSet(inner) => {
                 writeln!(
                     f,
-                    "buf.EncodeHashSet[{}](w, {name}, func(w []byte, v {0}) []byte {{",
-                    definition::RenderType(ty),
+                    "buf.{}[{}](w, {name}, func(w []byte, v {1}) []byte {{",
+                    if matches!(ty, Type::HashSet(_)) {
+                        "EncodeHashSet"
+                    } else {
+                        "EncodeSortedSet"
+                    },
+                    definition::RenderType(inner),
                 )?;
                 writeln!(
                     f,
                     "{}return {}",
                     indent + 1,
                     RenderType {
-                        ty,
+                        ty: inner,
                         name: "v",
                         indent: indent + 1,
                     },
@@ -259,7 +273,9 @@ where
                 | Type::BytesRef
                 | Type::Vec(_)
                 | Type::HashMap(_)
-                | Type::HashSet(_) => write!(
+                | Type::HashSet(_)
+                | Type::BTreeMap(_)
+                | Type::BTreeSet(_) => write!(
                     f,
                     "{}",
                     RenderType {
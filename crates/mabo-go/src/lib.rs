@@ -11,6 +11,7 @@ mod decode;
 mod definition;
 mod encode;
 mod size;
+mod validate;
 
 /// Options for the code generator that can modify the way the code is generated.
 #[derive(Default)]
@@ -18,6 +19,9 @@ pub struct Opts<'a> {
     /// Name of the package for the root schema. Eventual sub-modules will have their package name
     /// the schema's module name.
     pub package: &'a str,
+    /// Check the constraints of the `#[validate(...)]` attributes at the end of each `Decode`
+    /// method, failing with a `mabo.ValidationError` if any of them are violated.
+    pub validate_on_decode: bool,
 }
 
 /// The output of generating converting a schema file into one or more Go source code files. The
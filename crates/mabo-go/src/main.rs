@@ -5,6 +5,7 @@ use std::{env, fs, path::Path, process::Command};
 use anyhow::{Context, Result, bail, ensure};
 use mabo_go::{Opts, Output};
 use mabo_parser::Schema;
+use miette::{NamedSource, Report};
 
 use self::cli::Cli;
 
@@ -55,6 +56,16 @@ fn main() -> Result<()> {
             let schema = Schema::parse(input, Some(path))?;
             mabo_compiler::validate_schema(&schema)?;
 
+            for warning in mabo_compiler::lint_schema(&schema) {
+                eprintln!(
+                    "{:?}",
+                    Report::new(warning).with_source_code(NamedSource::new(
+                        path.display().to_string(),
+                        input.clone()
+                    ))
+                );
+            }
+
             Ok((stem, schema))
         })
         .collect::<Result<Vec<_>>>()?;
@@ -68,6 +79,7 @@ fn main() -> Result<()> {
 
     let opts = Opts {
         package: &project.project_file.package.name,
+        validate_on_decode: cli.validate_on_decode,
     };
 
     for (_, schema) in validated {
@@ -144,6 +144,10 @@ where
             Type::Bytes | Type::BytesRef | Type::BoxBytes => {
                 write!(f, "buf.SizeBytes({name})")
             }
+            Type::Timestamp => write!(f, "buf.SizeTimestamp({name})"),
+            Type::Duration => write!(f, "buf.SizeDuration({name})"),
+            Type::Uuid => write!(f, "buf.SizeUUID({name})"),
+            Type::Decimal => write!(f, "buf.SizeDecimal({name})"),
             Type::Vec(ty) => {
                 writeln!(
                     f,
@@ -162,7 +166,7 @@ where
                 )?;
                 write!(f, "{indent}}})")
             }
-            Type::HashMap(kv) => {
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
                 writeln!(
                     f,
                     "buf.SizeHashMap[{}, {}](",
@@ -208,7 +212,7 @@ where
                 writeln!(f, "{}}},", indent + 1)?;
                 write!(f, "{indent})")
             }
-            Type::HashSet(ty) => {
+            Type::HashSet(ty) | Type::BTreeSet(ty) => {
                 writeln!(
                     f,
                     "buf.SizeHashSet[{}]({name}, func(v {0}) int {{",
@@ -261,7 +265,9 @@ where
                 | Type::BytesRef
                 | Type::Vec(_)
                 | Type::HashMap(_)
-                | Type::HashSet(_) => write!(
+                | Type::HashSet(_)
+                | Type::BTreeMap(_)
+                | Type::BTreeSet(_) => write!(
                     f,
                     "{}",
                     RenderType {
@@ -0,0 +1,257 @@
+use std::fmt::{self, Display};
+
+use mabo_compiler::simplify::{Bound, Field, Fields, Literal, Struct, Type, Validation, Variant};
+
+use crate::definition::{self, RenderGenericNames};
+
+/// Whether any of the fields declares validation rules.
+pub(super) fn has_rules(fields: &Fields<'_>) -> bool {
+    fields
+        .fields
+        .iter()
+        .any(|field| !field.validations.is_empty())
+}
+
+pub(super) struct RenderStruct<'a>(pub(super) &'a Struct<'a>);
+
+impl Display for RenderStruct<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = heck::AsUpperCamelCase(&self.0.name).to_string();
+
+        write!(f, "{}", RenderPatterns(&prefix, &self.0.fields))?;
+        writeln!(
+            f,
+            "func (v *{}{}) Validate() []mabo.Violation {{",
+            prefix,
+            RenderGenericNames {
+                generics: &self.0.generics,
+                fields_filter: None,
+            }
+        )?;
+        writeln!(f, "\tvar violations []mabo.Violation")?;
+        write!(
+            f,
+            "{}",
+            RenderFields {
+                prefix: &prefix,
+                variant: None,
+                fields: &self.0.fields,
+            }
+        )?;
+        writeln!(f, "\treturn violations\n}}")
+    }
+}
+
+pub(super) struct RenderEnumVariant<'a> {
+    pub(super) enum_name: &'a str,
+    pub(super) generics: &'a [&'a str],
+    pub(super) variant: &'a Variant<'a>,
+}
+
+impl Display for RenderEnumVariant<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = format!(
+            "{}_{}",
+            heck::AsUpperCamelCase(self.enum_name),
+            heck::AsUpperCamelCase(&self.variant.name),
+        );
+
+        write!(f, "{}", RenderPatterns(&prefix, &self.variant.fields))?;
+        writeln!(
+            f,
+            "func (v *{}{}) Validate() []mabo.Violation {{",
+            prefix,
+            RenderGenericNames {
+                generics: self.generics,
+                fields_filter: Some(&self.variant.fields),
+            }
+        )?;
+        writeln!(f, "\tvar violations []mabo.Violation")?;
+        write!(
+            f,
+            "{}",
+            RenderFields {
+                prefix: &prefix,
+                variant: Some(self.variant.name),
+                fields: &self.variant.fields,
+            }
+        )?;
+        writeln!(f, "\treturn violations\n}}")
+    }
+}
+
+/// Package-level variables for the compiled regular expressions, so they are only compiled once.
+struct RenderPatterns<'a>(&'a str, &'a Fields<'a>);
+
+impl Display for RenderPatterns<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for field in &*self.1.fields {
+            for (i, validation) in field.validations.iter().enumerate() {
+                if let Validation::Pattern(pattern) = validation {
+                    writeln!(
+                        f,
+                        "var {} = regexp.MustCompile({pattern:?})\n",
+                        PatternName(self.0, field, i),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct PatternName<'a>(&'a str, &'a Field<'a>, usize);
+
+impl Display for PatternName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pattern{}{}{}",
+            self.0,
+            heck::AsUpperCamelCase(&self.1.name),
+            self.2,
+        )
+    }
+}
+
+struct RenderFields<'a> {
+    prefix: &'a str,
+    variant: Option<&'a str>,
+    fields: &'a Fields<'a>,
+}
+
+impl Display for RenderFields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for field in &*self.fields.fields {
+            if field.validations.is_empty() {
+                continue;
+            }
+
+            // Rules of optional fields apply to the value, if present.
+            let ty = if let Type::Option(ty) = &field.ty {
+                writeln!(
+                    f,
+                    "\tif v.{} != nil {{",
+                    heck::AsUpperCamelCase(&field.name)
+                )?;
+                writeln!(f, "\t\tx := *v.{}", heck::AsUpperCamelCase(&field.name))?;
+                &**ty
+            } else {
+                writeln!(f, "\t{{")?;
+                writeln!(f, "\t\tx := v.{}", heck::AsUpperCamelCase(&field.name))?;
+                &field.ty
+            };
+
+            for (i, validation) in field.validations.iter().enumerate() {
+                let (rule, value) = match validation {
+                    Validation::Min(value) => ("RuleMin", RuleValue::Bound(*value)),
+                    Validation::Max(value) => ("RuleMax", RuleValue::Bound(*value)),
+                    Validation::MinLen(len) => ("RuleMinLen", RuleValue::Len(*len)),
+                    Validation::MaxLen(len) => ("RuleMaxLen", RuleValue::Len(*len)),
+                    Validation::Pattern(pattern) => ("RulePattern", RuleValue::Pattern(pattern)),
+                    Validation::NonEmpty => ("RuleNonEmpty", RuleValue::None),
+                };
+
+                writeln!(
+                    f,
+                    "\t\tif {} {{",
+                    RenderViolated {
+                        ty,
+                        validation,
+                        pattern: PatternName(self.prefix, field, i),
+                    }
+                )?;
+                write!(
+                    f,
+                    "\t\t\tviolations = append(violations, mabo.Violation{{Field: \"{}\"",
+                    field.name
+                )?;
+                if let Some(variant) = self.variant {
+                    write!(f, ", Variant: \"{variant}\"")?;
+                }
+                writeln!(f, ", Rule: mabo.{rule}{value}}})")?;
+                writeln!(f, "\t\t}}")?;
+            }
+
+            writeln!(f, "\t}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Condition that is `true`, if the value `x` violates the rule.
+struct RenderViolated<'a> {
+    ty: &'a Type<'a>,
+    validation: &'a Validation,
+    pattern: PatternName<'a>,
+}
+
+impl Display for RenderViolated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.validation {
+            Validation::Min(value) => RenderCompare(self.ty, *value, "<").fmt(f),
+            Validation::Max(value) => RenderCompare(self.ty, *value, ">").fmt(f),
+            Validation::MinLen(len) => write!(f, "{} < {len}", RenderLen(self.ty)),
+            Validation::MaxLen(len) => write!(f, "{} > {len}", RenderLen(self.ty)),
+            Validation::Pattern(_) => write!(f, "!{}.MatchString(x)", self.pattern),
+            Validation::NonEmpty => f.write_str("len(x) == 0"),
+        }
+    }
+}
+
+/// Comparison of a number against a bound. Big integers can't use the regular operators.
+struct RenderCompare<'a>(&'a Type<'a>, Bound, &'a str);
+
+impl Display for RenderCompare<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let literal = match self.1 {
+            Bound::Int(value) => Literal::Int(value),
+            Bound::Float(value) => Literal::Float(value),
+        };
+        let value = definition::RenderDefault {
+            ty: self.0,
+            value: &literal,
+        };
+
+        if matches!(self.0, Type::U128 | Type::I128) {
+            write!(f, "x.Cmp({value}) {} 0", self.2)
+        } else {
+            write!(f, "x {} {value}", self.2)
+        }
+    }
+}
+
+/// Length of the value `x`, where strings are measured in characters.
+struct RenderLen<'a>(&'a Type<'a>);
+
+impl Display for RenderLen<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if matches!(self.0, Type::String | Type::StringRef | Type::BoxString) {
+            f.write_str("utf8.RuneCountInString(x)")
+        } else {
+            f.write_str("len(x)")
+        }
+    }
+}
+
+/// The bound, length or pattern of a rule, as shown in the violation.
+enum RuleValue<'a> {
+    Bound(Bound),
+    Len(u64),
+    Pattern(&'a str),
+    None,
+}
+
+impl Display for RuleValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bound(Bound::Int(value)) => write!(f, ", Value: \"{value}\""),
+            Self::Bound(Bound::Float(value)) => write!(f, ", Value: \"{value}\""),
+            Self::Len(len) => write!(f, ", Value: \"{len}\""),
+            Self::Pattern(pattern) => write!(f, ", Value: {pattern:?}"),
+            Self::None => Ok(()),
+        }
+    }
+}
@@ -32,7 +32,13 @@ fn render_schema() {
         let input = fs::read_to_string(path).unwrap();
         let value = Schema::parse(input.as_str(), Some(&strip_path(path))).unwrap();
         let value = mabo_compiler::simplify_schema(&value);
-        let value = mabo_go::render_schema(&Opts { package: "sample" }, &value);
+        let value = mabo_go::render_schema(
+            &Opts {
+                package: "sample",
+                validate_on_decode: true,
+            },
+            &value,
+        );
 
         let mut merged = String::new();
         merge_output(&mut merged, value, Path::new(""));
@@ -1,14 +1,15 @@
 use std::ops::Range;
 
 use lsp_types::{self as lsp, Diagnostic, Uri};
-use mabo_compiler::validate;
+use mabo_compiler::{lint, validate};
 use mabo_parser::{
     Schema,
     error::{
-        ParseAliasCause, ParseAttributeCause, ParseCommentError, ParseConstCause,
-        ParseDefinitionError, ParseEnumCause, ParseFieldsCause, ParseFieldsError,
-        ParseGenericsError, ParseIdError, ParseImportCause, ParseLiteralCause, ParseModuleCause,
-        ParseSchemaCause, ParseSchemaError, ParseStructCause, ParseTypeCause, ParseTypeError,
+        ParseAliasCause, ParseAttributeCause, ParseAttributeError, ParseCommentError,
+        ParseConstCause, ParseDefinitionError, ParseEnumCause, ParseFieldsCause, ParseFieldsError,
+        ParseGenericsError, ParseIdError, ParseImportCause, ParseLiteralCause, ParseLiteralError,
+        ParseModuleCause, ParseReservedCause, ParseReservedError, ParseSchemaCause,
+        ParseSchemaError, ParseServiceCause, ParseStructCause, ParseTypeCause, ParseTypeError,
     },
 };
 
@@ -30,6 +31,13 @@ pub fn simplify<'a>(
     result.as_ref().map(mabo_compiler::simplify_schema)
 }
 
+pub fn lint(file: &Uri, index: &Index, schema: &Schema<'_>) -> Vec<Diagnostic> {
+    mabo_compiler::lint_schema(schema)
+        .into_iter()
+        .map(|warning| lint_diagnostic(file.clone(), index, warning))
+        .collect()
+}
+
 fn parse_schema_diagnostic(index: &Index, e: &ParseSchemaError) -> Diagnostic {
     match &e.cause {
         ParseSchemaCause::Parser(at) => {
@@ -46,21 +54,7 @@ fn parse_definition_diagnostic(index: &Index, e: &ParseDefinitionError) -> Diagn
             Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
         }
         ParseDefinitionError::Comment(e) => parse_comment_diagnostic(index, e),
-        ParseDefinitionError::Attribute(e) => match &e.cause {
-            ParseAttributeCause::Parser(at) => {
-                Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
-            }
-            ParseAttributeCause::Literal(e) => match &e.cause {
-                ParseLiteralCause::Parser(at) => {
-                    Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
-                }
-                ParseLiteralCause::FoundReference { at }
-                | ParseLiteralCause::InvalidInt { at }
-                | ParseLiteralCause::ParseInt { at, .. } => {
-                    Diagnostic::new_simple(get_range(index, *at..*at), e.cause.to_string())
-                }
-            },
-        },
+        ParseDefinitionError::Attribute(e) => parse_attribute_diagnostic(index, e),
         ParseDefinitionError::Module(e) => match &e.cause {
             ParseModuleCause::Parser(at) => {
                 Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
@@ -91,6 +85,8 @@ fn parse_definition_diagnostic(index: &Index, e: &ParseDefinitionError) -> Diagn
             ParseEnumCause::Field(e) => parse_fields_diagnostic(index, e),
             ParseEnumCause::Comment(e) => parse_comment_diagnostic(index, e),
             ParseEnumCause::Id(e) => parse_id_diagnostic(index, e),
+            ParseEnumCause::Reserved(e) => parse_reserved_diagnostic(index, e),
+            ParseEnumCause::Attribute(e) => parse_attribute_diagnostic(index, e),
         },
         ParseDefinitionError::Const(e) => match &e.cause {
             ParseConstCause::Parser(at) => {
@@ -100,16 +96,7 @@ fn parse_definition_diagnostic(index: &Index, e: &ParseDefinitionError) -> Diagn
                 Diagnostic::new_simple(get_range(index, *at..*at), e.cause.to_string())
             }
             ParseConstCause::Type(e) => parse_type_diagnostic(index, e),
-            ParseConstCause::Literal(e) => match &e.cause {
-                ParseLiteralCause::Parser(at) => {
-                    Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
-                }
-                ParseLiteralCause::FoundReference { at }
-                | ParseLiteralCause::InvalidInt { at }
-                | ParseLiteralCause::ParseInt { at, .. } => {
-                    Diagnostic::new_simple(get_range(index, *at..*at), e.cause.to_string())
-                }
-            },
+            ParseConstCause::Literal(e) => parse_literal_diagnostic(index, e),
         },
         ParseDefinitionError::Alias(e) => match &e.cause {
             ParseAliasCause::Parser(at) => {
@@ -122,6 +109,18 @@ fn parse_definition_diagnostic(index: &Index, e: &ParseDefinitionError) -> Diagn
             ParseAliasCause::Type(e) => parse_type_diagnostic(index, e),
         },
         ParseDefinitionError::Import(e) => parse_import_cause_diagnostic(index, &e.cause),
+        ParseDefinitionError::Service(e) => match &e.cause {
+            ParseServiceCause::Parser(at) => {
+                Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
+            }
+            ParseServiceCause::InvalidName { at } | ParseServiceCause::InvalidMethodName { at } => {
+                Diagnostic::new_simple(get_range(index, *at..*at), e.cause.to_string())
+            }
+            ParseServiceCause::Comment(e) => parse_comment_diagnostic(index, e),
+            ParseServiceCause::Attribute(e) => parse_attribute_diagnostic(index, e),
+            ParseServiceCause::Type(e) => parse_type_diagnostic(index, e),
+            ParseServiceCause::Id(e) => parse_id_diagnostic(index, e),
+        },
     }
 }
 
@@ -135,6 +134,19 @@ fn parse_type_diagnostic(index: &Index, e: &ParseTypeError) -> Diagnostic {
     }
 }
 
+fn parse_literal_diagnostic(index: &Index, e: &ParseLiteralError) -> Diagnostic {
+    match &e.cause {
+        ParseLiteralCause::Parser(at) => {
+            Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
+        }
+        ParseLiteralCause::FoundReference { at }
+        | ParseLiteralCause::InvalidInt { at }
+        | ParseLiteralCause::ParseInt { at, .. } => {
+            Diagnostic::new_simple(get_range(index, *at..*at), e.cause.to_string())
+        }
+    }
+}
+
 fn parse_comment_diagnostic(index: &Index, e: &ParseCommentError) -> Diagnostic {
     Diagnostic::new_simple(get_range(index, e.at.clone()), e.to_string())
 }
@@ -161,6 +173,8 @@ fn parse_import_cause_diagnostic(index: &Index, c: &ParseImportCause) -> Diagnos
             ParseEnumCause::Field(e) => parse_fields_diagnostic(index, e),
             ParseEnumCause::Comment(e) => parse_comment_diagnostic(index, e),
             ParseEnumCause::Id(e) => parse_id_diagnostic(index, e),
+            ParseEnumCause::Reserved(e) => parse_reserved_diagnostic(index, e),
+            ParseEnumCause::Attribute(e) => parse_attribute_diagnostic(index, e),
         },
     }
 }
@@ -186,7 +200,37 @@ fn parse_fields_diagnostic(index: &Index, e: &ParseFieldsError) -> Diagnostic {
         }
         ParseFieldsCause::Type(e) => parse_type_diagnostic(index, e),
         ParseFieldsCause::Id(e) => parse_id_diagnostic(index, e),
+        ParseFieldsCause::Literal(e) => parse_literal_diagnostic(index, e),
         ParseFieldsCause::Comment(e) => parse_comment_diagnostic(index, e),
+        ParseFieldsCause::Reserved(e) => parse_reserved_diagnostic(index, e),
+        ParseFieldsCause::UnnamedReserved { at } => {
+            Diagnostic::new_simple(get_range(index, at.clone()), e.cause.to_string())
+        }
+        ParseFieldsCause::Attribute(e) => parse_attribute_diagnostic(index, e),
+    }
+}
+
+fn parse_attribute_diagnostic(index: &Index, e: &ParseAttributeError) -> Diagnostic {
+    match &e.cause {
+        ParseAttributeCause::Parser(at) => {
+            Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
+        }
+        ParseAttributeCause::Literal(e) => parse_literal_diagnostic(index, e),
+    }
+}
+
+fn parse_reserved_diagnostic(index: &Index, e: &ParseReservedError) -> Diagnostic {
+    match &e.cause {
+        ParseReservedCause::Parser(at) => {
+            Diagnostic::new_simple(get_range(index, *at..*at), e.to_string())
+        }
+        ParseReservedCause::InvalidName { at } => {
+            Diagnostic::new_simple(get_range(index, *at..*at), e.cause.to_string())
+        }
+        ParseReservedCause::EmptyRange { at } => {
+            Diagnostic::new_simple(get_range(index, at.clone()), e.cause.to_string())
+        }
+        ParseReservedCause::Id(e) => parse_id_diagnostic(index, e),
     }
 }
 
@@ -204,11 +248,13 @@ fn validate_schema_diagnostic(file: Uri, index: &Index, e: validate::Error) -> D
                 DuplicateFieldId::Named(e) => (e.to_string(), e.first, e.second),
                 DuplicateFieldId::Unnamed(e) => (e.to_string(), e.first, e.second),
             },
+            DuplicateId::Method(e) => (e.to_string(), e.first, e.second),
         },
         Error::DuplicateName(e) => match e {
             DuplicateName::EnumVariant(e) => (e.to_string(), e.first, e.second),
             DuplicateName::Field(e) => (e.to_string(), e.first, e.second),
             DuplicateName::InModule(e) => (e.to_string(), e.first, e.second),
+            DuplicateName::Method(e) => (e.to_string(), e.first, e.second),
         },
         Error::InvalidGeneric(e) => match e {
             InvalidGenericType::Duplicate(e) => (e.to_string(), e.first, e.second),
@@ -221,6 +267,22 @@ fn validate_schema_diagnostic(file: Uri, index: &Index, e: validate::Error) -> D
             let message = e.to_string();
             return Diagnostic::new_simple(get_range(index, e.declared), message);
         }
+        Error::ReservedId(e) => {
+            let message = e.to_string();
+            return reserved_diagnostic(file, index, message, e.reserved, e.used);
+        }
+        Error::ReservedName(e) => {
+            let message = e.to_string();
+            return reserved_diagnostic(file, index, message, e.reserved, e.used);
+        }
+        Error::InvalidDefault(e) => {
+            let message = e.to_string();
+            return Diagnostic::new_simple(get_range(index, e.declared), message);
+        }
+        Error::InvalidValidation(e) => {
+            let message = e.to_string();
+            return Diagnostic::new_simple(get_range(index, e.declared), message);
+        }
     };
 
     diagnostic_with_related(
@@ -233,6 +295,45 @@ fn validate_schema_diagnostic(file: Uri, index: &Index, e: validate::Error) -> D
     )
 }
 
+fn lint_diagnostic(file: Uri, index: &Index, warning: lint::Warning) -> Diagnostic {
+    let lint::Warning::DeprecatedUsage(e) = warning;
+
+    let message = match &e.note {
+        Some(note) => format!("{e}: {note}"),
+        None => e.to_string(),
+    };
+
+    Diagnostic {
+        severity: Some(lsp::DiagnosticSeverity::WARNING),
+        tags: Some(vec![lsp::DiagnosticTag::DEPRECATED]),
+        ..diagnostic_with_related(
+            get_range(index, e.used),
+            message,
+            vec![lsp::DiagnosticRelatedInformation {
+                location: lsp::Location::new(file, get_range(index, e.declared)),
+                message: "declared here".to_owned(),
+            }],
+        )
+    }
+}
+
+fn reserved_diagnostic(
+    file: Uri,
+    index: &Index,
+    message: String,
+    reserved: Range<usize>,
+    used: Range<usize>,
+) -> Diagnostic {
+    diagnostic_with_related(
+        get_range(index, used),
+        message,
+        vec![lsp::DiagnosticRelatedInformation {
+            location: lsp::Location::new(file, get_range(index, reserved)),
+            message: "reserved here".to_owned(),
+        }],
+    )
+}
+
 fn diagnostic_with_related(
     range: lsp::Range,
     message: String,
@@ -3,8 +3,8 @@ use std::{fmt::Write, ops::Range};
 use anyhow::Result;
 use lsp_types::{self as lsp, DocumentSymbol, SymbolKind};
 use mabo_parser::{
-    Const, Definition, Enum, Fields, Import, Module, NamedField, Schema, Spanned, Struct,
-    TypeAlias, UnnamedField, Variant,
+    Const, Definition, Enum, Fields, Import, Method, Module, NamedField, Schema, Service, Spanned,
+    Struct, TypeAlias, UnnamedField, Variant,
 };
 
 use super::index::Index;
@@ -24,6 +24,7 @@ fn visit_definition(index: &Index, item: &Definition<'_>) -> Result<DocumentSymb
         Definition::TypeAlias(a) => visit_alias(index, a),
         Definition::Const(c) => visit_const(index, c),
         Definition::Import(i) => visit_import(index, i),
+        Definition::Service(s) => visit_service(index, s),
     }
 }
 
@@ -71,7 +72,7 @@ fn visit_variant(index: &Index, item: &Variant<'_>) -> Result<DocumentSymbol> {
 
 fn visit_fields(index: &Index, item: &Fields<'_>) -> Result<Vec<DocumentSymbol>> {
     match item {
-        Fields::Named(_, named) => named
+        Fields::Named(_, _, named) => named
             .values()
             .map(|field| visit_named_field(index, field))
             .collect(),
@@ -150,6 +151,27 @@ fn visit_import(index: &Index, item: &Import<'_>) -> Result<DocumentSymbol> {
     ))
 }
 
+fn visit_service(index: &Index, item: &Service<'_>) -> Result<DocumentSymbol> {
+    Ok(create_symbol(
+        item.name.get(),
+        SymbolKind::INTERFACE,
+        index.get_range(item.name.span())?,
+        item.methods
+            .iter()
+            .map(|method| visit_method(index, method))
+            .collect::<Result<_>>()?,
+    ))
+}
+
+fn visit_method(index: &Index, item: &Method<'_>) -> Result<DocumentSymbol> {
+    Ok(create_symbol(
+        item.name.get(),
+        SymbolKind::METHOD,
+        index.get_range(item.name.span())?,
+        vec![],
+    ))
+}
+
 #[expect(deprecated)]
 fn create_symbol(
     name: &str,
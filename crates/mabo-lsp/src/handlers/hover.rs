@@ -3,7 +3,8 @@ use std::{fmt::Write, ops::Range};
 use anyhow::Result;
 use lsp_types as lsp;
 use mabo_compiler::simplify::{
-    Const, Definition, Enum, Field, Fields, Module, ParserField, Schema, Struct, TypeAlias, Variant,
+    Const, Definition, Enum, Field, Fields, Method, Module, ParserField, Schema, Service, Struct,
+    TypeAlias, Variant,
 };
 use mabo_parser::{Span, Spanned};
 
@@ -37,6 +38,7 @@ fn visit_definition(
         Definition::TypeAlias(a) => visit_alias(a, position),
         Definition::Const(c) => visit_const(c, position),
         Definition::Import(_) => None,
+        Definition::Service(s) => visit_service(s, position),
     }
 }
 
@@ -165,6 +167,21 @@ fn visit_const(item: &Const<'_>, position: usize) -> Option<(String, Span)> {
         .then(|| (fold_comment(&item.comment), item.source.name.span()))
 }
 
+fn visit_service(item: &Service<'_>, position: usize) -> Option<(String, Span)> {
+    (Range::from(item.source.name.span()).contains(&position))
+        .then(|| (fold_comment(&item.comment), item.source.name.span()))
+        .or_else(|| {
+            item.methods
+                .iter()
+                .find_map(|method| visit_method(method, position))
+        })
+}
+
+fn visit_method(item: &Method<'_>, position: usize) -> Option<(String, Span)> {
+    (Range::from(item.source.name.span()).contains(&position))
+        .then(|| (fold_comment(&item.comment), item.source.name.span()))
+}
+
 fn fold_comment(comment: &[&str]) -> String {
     comment.iter().fold(String::new(), |mut acc, line| {
         acc.push_str(line);
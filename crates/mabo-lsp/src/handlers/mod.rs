@@ -6,7 +6,7 @@ use anyhow::{Context, Result};
 use line_index::{LineIndex, TextRange};
 use log::{debug, error, warn};
 use lsp_types::{
-    DeleteFilesParams, DidChangeConfigurationParams, DidChangeTextDocumentParams,
+    DeleteFilesParams, Diagnostic, DidChangeConfigurationParams, DidChangeTextDocumentParams,
     DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentSymbolParams,
     DocumentSymbolResponse, FileOperationFilter, FileOperationPattern, FileOperationPatternKind,
     FileOperationRegistrationOptions, Hover, HoverContents, HoverParams, HoverProviderCapability,
@@ -168,12 +168,8 @@ pub fn did_open(state: &mut GlobalState<'_>, params: DidOpenTextDocumentParams)
     };
 
     if let Err(e) = state.client.publish_diagnostics(
-        params.text_document.uri,
-        file.borrow_schema()
-            .as_ref()
-            .err()
-            .map(|diag| vec![diag.clone()])
-            .unwrap_or_default(),
+        params.text_document.uri.clone(),
+        diagnostics(&params.text_document.uri, file),
         None,
     ) {
         error!(error:err = *e; "failed publishing diagnostics");
@@ -220,11 +216,7 @@ pub fn did_change(state: &mut GlobalState<'_>, mut params: DidChangeTextDocument
 
     if let Err(e) = state.client.publish_diagnostics(
         params.text_document.uri.clone(),
-        file.borrow_schema()
-            .as_ref()
-            .err()
-            .map(|diag| vec![diag.clone()])
-            .unwrap_or_default(),
+        diagnostics(&params.text_document.uri, &file),
         None,
     ) {
         error!(error:err = *e; "failed publishing diagnostics");
@@ -329,6 +321,14 @@ pub fn did_change_configuration(
     }
 }
 
+/// Either the error that prevented the schema from compiling, or any warnings about it.
+fn diagnostics(uri: &Uri, file: &state::File) -> Vec<Diagnostic> {
+    match file.borrow_schema() {
+        Ok(schema) => compile::lint(uri, file.borrow_index(), schema),
+        Err(diag) => vec![diag.clone()],
+    }
+}
+
 fn convert_range(index: &Index, range: Option<lsp_types::Range>) -> Result<TextRange> {
     let range = range.context("incremental change misses range")?;
 
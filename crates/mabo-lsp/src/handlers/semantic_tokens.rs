@@ -1,10 +1,15 @@
+use std::ops::Range;
+
 use anyhow::{Result, ensure};
 use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType};
+use mabo_compiler::lint::Warning;
 use mabo_parser::{
-    Comment, Const, DataType, Definition, Enum, ExternalType, Fields, Generics, Id, Import,
-    Literal, LiteralValue, Module, NamedField, Schema, Span, Spanned, Struct, Type, TypeAlias,
-    UnnamedField, Variant, token::Delimiter,
+    Attributes, Comment, Const, DataType, Definition, Enum, ExternalType, Fields, Generics, Id,
+    Import, Literal, LiteralValue, Method, MethodType, Module, NamedField, Reserved, ReservedValue,
+    Schema, Service, Span, Spanned, Struct, Type, TypeAlias, UnnamedField, Variant,
+    token::{self, Delimiter},
 };
+use rustc_hash::FxHashSet;
 
 pub(crate) use self::{modifiers::TOKEN_MODIFIERS, types::TOKEN_TYPES};
 use super::index::Index;
@@ -43,6 +48,8 @@ define_semantic_token_types! {
         VARIABLE,
         PROPERTY,
         ENUM_MEMBER,
+        INTERFACE,
+        METHOD,
         KEYWORD,
         COMMENT,
         // STRING,
@@ -63,7 +70,9 @@ define_semantic_token_types! {
         (SEMICOLON, "semicolon"),
         (POUND, "pound"),
         (DOUBLE_COLON, "doubleColon"),
+        (DOUBLE_DOT, "doubleDot"),
         (EQUAL, "equal"),
+        (ARROW, "arrow"),
 
         // Delimiter tokens
         (BRACE, "brace"),
@@ -100,6 +109,7 @@ define_semantic_token_modifiers! {
     standard {
         DECLARATION,
         STATIC,
+        DEPRECATED,
         DOCUMENTATION,
     }
 
@@ -127,10 +137,25 @@ fn token_modifier_bitset(modifiers: &[SemanticTokenModifier]) -> u32 {
         .fold(0, |acc, modifier| acc + (1 << modifier))
 }
 
+/// Modifiers of a declaration, with the deprecation added if the element carries a `#[deprecated]`
+/// attribute.
+fn declaration_modifiers(
+    attributes: &Attributes<'_>,
+    modifiers: &[SemanticTokenModifier],
+) -> Vec<SemanticTokenModifier> {
+    let mut modifiers = modifiers.to_vec();
+    if attributes.0.iter().any(|attr| attr.name == "deprecated") {
+        modifiers.push(modifiers::DEPRECATED);
+    }
+    modifiers
+}
+
 pub struct Visitor<'a> {
     index: &'a Index,
     tokens: Vec<SemanticToken>,
     delta: lsp_types::Position,
+    /// Locations of type usages that refer to a deprecated type.
+    deprecated: FxHashSet<Range<usize>>,
 }
 
 impl<'a> Visitor<'a> {
@@ -142,6 +167,7 @@ impl<'a> Visitor<'a> {
                 line: 0,
                 character: 0,
             },
+            deprecated: FxHashSet::default(),
         }
     }
 
@@ -194,6 +220,13 @@ impl<'a> Visitor<'a> {
     }
 
     pub fn visit_schema(mut self, item: &Schema<'_>) -> Result<Vec<SemanticToken>> {
+        self.deprecated = mabo_compiler::lint_schema(item)
+            .into_iter()
+            .map(|warning| match warning {
+                Warning::DeprecatedUsage(usage) => usage.used,
+            })
+            .collect();
+
         for def in &item.definitions {
             self.visit_definition(def)?;
         }
@@ -217,6 +250,7 @@ impl<'a> Visitor<'a> {
             Definition::TypeAlias(a) => self.visit_alias(a),
             Definition::Const(c) => self.visit_const(c),
             Definition::Import(i) => self.visit_import(i),
+            Definition::Service(s) => self.visit_service(s),
         }
     }
 
@@ -236,7 +270,11 @@ impl<'a> Visitor<'a> {
     fn visit_struct(&mut self, item: &Struct<'_>) -> Result<()> {
         self.visit_comment(&item.comment)?;
         self.add_span(&item.keyword, &types::KEYWORD, &[])?;
-        self.add_span(&item.name, &types::STRUCT, &[modifiers::DECLARATION])?;
+        self.add_span(
+            &item.name,
+            &types::STRUCT,
+            &declaration_modifiers(&item.attributes, &[modifiers::DECLARATION]),
+        )?;
         self.visit_generics(item.generics.as_ref())?;
         self.visit_fields(&item.fields)
     }
@@ -244,9 +282,14 @@ impl<'a> Visitor<'a> {
     fn visit_enum(&mut self, item: &Enum<'_>) -> Result<()> {
         self.visit_comment(&item.comment)?;
         self.add_span(&item.keyword, &types::KEYWORD, &[])?;
-        self.add_span(&item.name, &types::ENUM, &[modifiers::DECLARATION])?;
+        self.add_span(
+            &item.name,
+            &types::ENUM,
+            &declaration_modifiers(&item.attributes, &[modifiers::DECLARATION]),
+        )?;
         self.visit_generics(item.generics.as_ref())?;
         self.add_span(&item.brace.open(), &types::BRACE, &[])?;
+        self.visit_reserved(&item.reserved, &types::ENUM_MEMBER)?;
 
         for (variant, comma) in &item.variants {
             self.visit_variant(variant)?;
@@ -260,7 +303,11 @@ impl<'a> Visitor<'a> {
 
     fn visit_variant(&mut self, item: &Variant<'_>) -> Result<()> {
         self.visit_comment(&item.comment)?;
-        self.add_span(&item.name, &types::ENUM_MEMBER, &[modifiers::DECLARATION])?;
+        self.add_span(
+            &item.name,
+            &types::ENUM_MEMBER,
+            &declaration_modifiers(&item.attributes, &[modifiers::DECLARATION]),
+        )?;
         self.visit_fields(&item.fields)?;
         self.visit_id(item.id)?;
 
@@ -269,8 +316,9 @@ impl<'a> Visitor<'a> {
 
     fn visit_fields(&mut self, item: &Fields<'_>) -> Result<()> {
         match item {
-            Fields::Named(brace, named) => {
+            Fields::Named(brace, reserved, named) => {
                 self.add_span(&brace.open(), &types::BRACE, &[])?;
+                self.visit_reserved(reserved, &types::PROPERTY)?;
                 for (field, comma) in named {
                     self.visit_named_field(field)?;
                     if let Some(comma) = &comma {
@@ -295,20 +343,58 @@ impl<'a> Visitor<'a> {
         Ok(())
     }
 
+    fn visit_reserved(&mut self, items: &[Reserved<'_>], name: &SemanticTokenType) -> Result<()> {
+        for item in items {
+            self.add_span(&item.keyword, &types::KEYWORD, &[])?;
+            for (value, comma) in &item.values {
+                match value {
+                    ReservedValue::Id(id) => self.add_span(id, &types::IDENTIFIER, &[])?,
+                    ReservedValue::Range(start, dots, end) => {
+                        self.add_span(start, &types::IDENTIFIER, &[])?;
+                        self.add_span(dots, &types::DOUBLE_DOT, &[])?;
+                        self.add_span(end, &types::IDENTIFIER, &[])?;
+                    }
+                    ReservedValue::Name(value) => self.add_span(value, name, &[])?,
+                }
+                if let Some(comma) = &comma {
+                    self.add_span(comma, &types::COMMA, &[])?;
+                }
+            }
+            self.add_span(&item.semicolon, &types::SEMICOLON, &[])?;
+        }
+
+        Ok(())
+    }
+
     fn visit_named_field(&mut self, item: &NamedField<'_>) -> Result<()> {
         self.visit_comment(&item.comment)?;
-        self.add_span(&item.name, &types::PROPERTY, &[modifiers::DECLARATION])?;
+        self.add_span(
+            &item.name,
+            &types::PROPERTY,
+            &declaration_modifiers(&item.attributes, &[modifiers::DECLARATION]),
+        )?;
         self.add_span(&item.colon, &types::COLON, &[])?;
         self.visit_type(&item.ty)?;
+        self.visit_default(item.default.as_ref())?;
         self.visit_id(item.id)?;
         Ok(())
     }
 
     fn visit_unnamed_field(&mut self, item: &UnnamedField<'_>) -> Result<()> {
         self.visit_type(&item.ty)?;
+        self.visit_default(item.default.as_ref())?;
         self.visit_id(item.id)
     }
 
+    fn visit_default(&mut self, item: Option<&(token::Equal, Literal)>) -> Result<()> {
+        if let Some((equal, value)) = item {
+            self.add_span(equal, &types::EQUAL, &[])?;
+            self.visit_literal(value)?;
+        }
+
+        Ok(())
+    }
+
     fn visit_id(&mut self, item: Option<Id>) -> Result<()> {
         if let Some(id) = item {
             self.add_span(&id, &types::IDENTIFIER, &[])?;
@@ -333,11 +419,14 @@ impl<'a> Visitor<'a> {
         self.add_span(
             &item.name,
             &types::VARIABLE,
-            &[
-                modifiers::DECLARATION,
-                modifiers::STATIC,
-                modifiers::CONSTANT,
-            ],
+            &declaration_modifiers(
+                &item.attributes,
+                &[
+                    modifiers::DECLARATION,
+                    modifiers::STATIC,
+                    modifiers::CONSTANT,
+                ],
+            ),
         )?;
         self.add_span(&item.colon, &types::COLON, &[])?;
         self.visit_type(&item.ty)?;
@@ -346,6 +435,7 @@ impl<'a> Visitor<'a> {
         self.add_span(&item.semicolon, &types::SEMICOLON, &[])
     }
 
+    #[expect(clippy::too_many_lines)]
     fn visit_type(&mut self, item: &Type<'_>) -> Result<()> {
         match &item.value {
             DataType::Bool
@@ -366,9 +456,14 @@ impl<'a> Visitor<'a> {
             | DataType::BoxString
             | DataType::Bytes
             | DataType::BytesRef
-            | DataType::BoxBytes => self.add_span(item, &types::BUILTIN_TYPE, &[]),
+            | DataType::BoxBytes
+            | DataType::Timestamp
+            | DataType::Duration
+            | DataType::Uuid
+            | DataType::Decimal => self.add_span(item, &types::BUILTIN_TYPE, &[]),
             DataType::Vec { span, angle, ty }
             | DataType::HashSet { span, angle, ty }
+            | DataType::BTreeSet { span, angle, ty }
             | DataType::Option { span, angle, ty }
             | DataType::NonZero { span, angle, ty } => {
                 self.add_span(span, &types::BUILTIN_TYPE, &[])?;
@@ -382,6 +477,13 @@ impl<'a> Visitor<'a> {
                 key,
                 comma,
                 value,
+            }
+            | DataType::BTreeMap {
+                span,
+                angle,
+                key,
+                comma,
+                value,
             } => {
                 self.add_span(span, &types::BUILTIN_TYPE, &[])?;
                 self.add_span(&angle.open(), &types::ANGLE, &[])?;
@@ -421,7 +523,11 @@ impl<'a> Visitor<'a> {
                     self.add_span(name, &types::NAMESPACE, &[])?;
                     self.add_span(token, &types::DOUBLE_COLON, &[])?;
                 }
-                self.add_span(name, &types::TYPE, &[])?;
+                if self.deprecated.contains(&name.span().into()) {
+                    self.add_span(name, &types::TYPE, &[modifiers::DEPRECATED])?;
+                } else {
+                    self.add_span(name, &types::TYPE, &[])?;
+                }
                 if let Some(angle) = angle {
                     self.add_span(&angle.open(), &types::ANGLE, &[])?;
                 }
@@ -467,6 +573,48 @@ impl<'a> Visitor<'a> {
         Ok(())
     }
 
+    fn visit_service(&mut self, item: &Service<'_>) -> Result<()> {
+        self.visit_comment(&item.comment)?;
+        self.add_span(&item.keyword, &types::KEYWORD, &[])?;
+        self.add_span(
+            &item.name,
+            &types::INTERFACE,
+            &declaration_modifiers(&item.attributes, &[modifiers::DECLARATION]),
+        )?;
+        self.add_span(&item.brace.open(), &types::BRACE, &[])?;
+
+        for method in &item.methods {
+            self.visit_method(method)?;
+        }
+
+        self.add_span(&item.brace.close(), &types::BRACE, &[])
+    }
+
+    fn visit_method(&mut self, item: &Method<'_>) -> Result<()> {
+        self.visit_comment(&item.comment)?;
+        self.add_span(&item.keyword, &types::KEYWORD, &[])?;
+        self.add_span(
+            &item.name,
+            &types::METHOD,
+            &declaration_modifiers(&item.attributes, &[modifiers::DECLARATION]),
+        )?;
+        self.add_span(&item.paren.open(), &types::PARENTHESIS, &[])?;
+        self.visit_method_type(&item.request)?;
+        self.add_span(&item.paren.close(), &types::PARENTHESIS, &[])?;
+        self.add_span(&item.arrow, &types::ARROW, &[])?;
+        self.visit_method_type(&item.response)?;
+        self.visit_id(item.id)?;
+        self.add_span(&item.semicolon, &types::SEMICOLON, &[])
+    }
+
+    fn visit_method_type(&mut self, item: &MethodType<'_>) -> Result<()> {
+        if let Some(stream) = &item.stream {
+            self.add_span(stream, &types::KEYWORD, &[])?;
+        }
+
+        self.visit_type(&item.ty)
+    }
+
     fn visit_import(&mut self, item: &Import<'_>) -> Result<()> {
         self.add_span(&item.keyword, &types::KEYWORD, &[])?;
         for segment in &item.segments {
@@ -140,6 +140,25 @@ pub fn wire_size(ty: &Type<'_>) -> Option<WireSize> {
                 ("element".into(), wire_size(ty)),
             ],
         },
+        Type::BTreeMap(kv) => WireSize {
+            label: "btree_map".into(),
+            min: 1,
+            max: None,
+            inner: vec![
+                ("length".into(), wire_size(&Type::U64)),
+                ("key".into(), wire_size(&kv.0)),
+                ("value".into(), wire_size(&kv.1)),
+            ],
+        },
+        Type::BTreeSet(ty) => WireSize {
+            label: "btree_set".into(),
+            min: 1,
+            max: None,
+            inner: vec![
+                ("length".into(), wire_size(&Type::U64)),
+                ("element".into(), wire_size(ty)),
+            ],
+        },
         Type::Option(ty) => {
             let inner = wire_size(ty);
             WireSize {
@@ -160,6 +179,16 @@ pub fn wire_size(ty: &Type<'_>) -> Option<WireSize> {
         }
         Type::BoxString => WireSize::min("box<string>", 1),
         Type::BoxBytes => WireSize::min("box<bytes>", 1),
+        Type::Timestamp => {
+            wire_size_prefixed("timestamp", [("seconds", Type::I64), ("nanos", Type::U32)])
+        }
+        Type::Duration => {
+            wire_size_prefixed("duration", [("seconds", Type::U64), ("nanos", Type::U32)])
+        }
+        Type::Uuid => WireSize::fixed("uuid", 17),
+        Type::Decimal => {
+            wire_size_prefixed("decimal", [("mantissa", Type::I128), ("scale", Type::U32)])
+        }
         Type::Array(ty, size) => wire_size_array(ty, *size),
         Type::Tuple(types) => wire_size_tuple(types),
         Type::External(_) => return None,
@@ -206,6 +235,31 @@ fn wire_size_tuple(types: &[Type<'_>]) -> WireSize {
     }
 }
 
+/// Wire size of a well-known type, that is made up of a fixed list of values behind a length
+/// prefix, which always fits into a single byte.
+fn wire_size_prefixed<const N: usize>(
+    label: &'static str,
+    parts: [(&'static str, Type<'static>); N],
+) -> WireSize {
+    let inner = parts
+        .into_iter()
+        .map(|(label, ty)| (Cow::Borrowed(label), wire_size(&ty)))
+        .collect::<Vec<_>>();
+    let sizes = || inner.iter().filter_map(|(_, size)| size.as_ref());
+
+    WireSize {
+        label: label.into(),
+        min: 1 + sizes().map(|size| size.min).sum::<usize>(),
+        max: sizes()
+            .map(|size| size.max)
+            .sum::<Option<usize>>()
+            .map(|max| 1 + max),
+        inner: std::iter::once(("length".into(), Some(WireSize::fixed("u64", 1))))
+            .chain(inner)
+            .collect(),
+    }
+}
+
 fn varint_size(value: u32) -> usize {
     ((std::mem::size_of::<u32>() * 8 - value.leading_zeros() as usize).div_ceil(7)).max(1)
 }
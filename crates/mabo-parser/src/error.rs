@@ -18,8 +18,8 @@ pub use crate::parser::{
     ParseCommentError, ParseConstCause, ParseConstError, ParseEnumCause, ParseEnumError,
     ParseFieldsCause, ParseFieldsError, ParseGenericsCause, ParseGenericsError, ParseIdCause,
     ParseIdError, ParseImportCause, ParseImportError, ParseLiteralCause, ParseLiteralError,
-    ParseModuleCause, ParseModuleError, ParseStructCause, ParseStructError, ParseTypeCause,
-    ParseTypeError,
+    ParseModuleCause, ParseModuleError, ParseReservedCause, ParseReservedError, ParseServiceCause,
+    ParseServiceError, ParseStructCause, ParseStructError, ParseTypeCause, ParseTypeError,
 };
 
 /// Reason why a Mabo schema definition was invalid.
@@ -165,6 +165,9 @@ pub enum ParseDefinitionError {
     /// Invalid import definition.
     #[diagnostic(transparent)]
     Import(ParseImportError),
+    /// Invalid service definition.
+    #[diagnostic(transparent)]
+    Service(ParseServiceError),
 }
 
 impl Error for ParseDefinitionError {
@@ -179,6 +182,7 @@ impl Error for ParseDefinitionError {
             Self::Const(inner) => inner.source(),
             Self::Alias(inner) => inner.source(),
             Self::Import(inner) => inner.source(),
+            Self::Service(inner) => inner.source(),
         }
     }
 }
@@ -195,6 +199,7 @@ impl Display for ParseDefinitionError {
             Self::Const(inner) => inner.fmt(f),
             Self::Alias(inner) => inner.fmt(f),
             Self::Import(inner) => inner.fmt(f),
+            Self::Service(inner) => inner.fmt(f),
         }
     }
 }
@@ -247,6 +252,12 @@ impl From<ParseImportError> for ParseDefinitionError {
     }
 }
 
+impl From<ParseServiceError> for ParseDefinitionError {
+    fn from(value: ParseServiceError) -> Self {
+        Self::Service(value)
+    }
+}
+
 impl<I> winnow::error::ParserError<I> for ParseDefinitionError
 where
     I: winnow::stream::Location + winnow::stream::Stream,
@@ -202,6 +202,8 @@ pub enum Definition<'a> {
     Const(Const<'a>),
     /// Import declaration of other schemas.
     Import(Import<'a>),
+    /// Service declaration with remote procedures.
+    Service(Service<'a>),
 }
 
 impl Print for Definition<'_> {
@@ -213,6 +215,7 @@ impl Print for Definition<'_> {
             Definition::TypeAlias(v) => v.print(f, level),
             Definition::Const(v) => v.print(f, level),
             Definition::Import(v) => v.print(f, level),
+            Definition::Service(v) => v.print(f, level),
         }
     }
 }
@@ -231,6 +234,7 @@ impl<'a> Definition<'a> {
             Definition::Enum(e) => e.comment = comment,
             Definition::TypeAlias(a) => a.comment = comment,
             Definition::Const(c) => c.comment = comment,
+            Definition::Service(s) => s.comment = comment,
             Definition::Import(_) => {}
         }
         self
@@ -240,10 +244,9 @@ impl<'a> Definition<'a> {
         match &mut self {
             Definition::Struct(s) => s.attributes = attributes,
             Definition::Enum(e) => e.attributes = attributes,
-            Definition::Module(_)
-            | Definition::TypeAlias(_)
-            | Definition::Const(_)
-            | Definition::Import(_) => {}
+            Definition::Const(c) => c.attributes = attributes,
+            Definition::Service(s) => s.attributes = attributes,
+            Definition::Module(_) | Definition::TypeAlias(_) | Definition::Import(_) => {}
         }
         self
     }
@@ -369,6 +372,8 @@ impl Display for Struct<'_> {
 /// ```txt
 /// /// Optional comment
 /// enum Sample {
+///     /// Reserved IDs and names of removed variants
+///     reserved @4, "Four";
 ///     /// Unit variant
 ///     One @1,
 ///     /// Unnamed (tuple) variant
@@ -393,6 +398,8 @@ pub struct Enum<'a> {
     pub generics: Option<Generics<'a>>,
     /// Braces `{`...`}` around the variants.
     pub brace: token::Brace,
+    /// IDs and names that can't be used by any variant.
+    pub reserved: Vec<Reserved<'a>>,
     /// List of possible variants that the enum can represent.
     pub variants: Punctuated<Variant<'a>>,
 }
@@ -405,6 +412,7 @@ impl Print for Enum<'_> {
             keyword,
             name,
             generics,
+            reserved,
             variants,
             ..
         } = self;
@@ -419,7 +427,7 @@ impl Print for Enum<'_> {
         }
 
         f.write_char(' ')?;
-        variants.surround::<token::Brace>(f, level, true)?;
+        variants.surround_with::<token::Brace, _>(f, level, true, reserved)?;
         f.write_char('\n')
     }
 }
@@ -431,10 +439,12 @@ impl Display for Enum<'_> {
 }
 
 /// Single variant of an enum.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct Variant<'a> {
     /// Optional variant-level comment.
     pub comment: Comment<'a>,
+    /// Optional attributes to customize the behavior.
+    pub attributes: Attributes<'a>,
     /// Unique for this variant, within the enum it belongs to.
     pub name: Name<'a>,
     /// Fields of this variant, if any.
@@ -449,6 +459,7 @@ impl Print for Variant<'_> {
     fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         let Self {
             comment,
+            attributes,
             name,
             fields,
             id,
@@ -456,6 +467,7 @@ impl Print for Variant<'_> {
         } = self;
 
         comment.print(f, level)?;
+        attributes.print(f, level)?;
 
         Self::indent(f, level)?;
         f.write_str(name.get())?;
@@ -536,18 +548,19 @@ impl Display for TypeAlias<'_> {
 }
 
 /// Possible kinds in which the fields of a struct or enum variant can be represented.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum Fields<'a> {
     /// List of named fields.
     ///
     /// ```txt
     /// Sample {
+    ///     reserved @4;
     ///     a: u8 @1,
     ///     b: bool @2,
     ///     c: i32 @3,
     /// }
     /// ```
-    Named(token::Brace, Punctuated<NamedField<'a>>),
+    Named(token::Brace, Vec<Reserved<'a>>, Punctuated<NamedField<'a>>),
     /// List of types without an explicit name.
     ///
     /// ```txt
@@ -565,9 +578,9 @@ pub enum Fields<'a> {
 impl Print for Fields<'_> {
     fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         match self {
-            Fields::Named(_, fields) => {
+            Fields::Named(_, reserved, fields) => {
                 f.write_char(' ')?;
-                fields.surround::<token::Brace>(f, level, true)
+                fields.surround_with::<token::Brace, _>(f, level, true, reserved)
             }
             Fields::Unnamed(_, elements) => elements.surround::<token::Parenthesis>(f, 0, false),
             Fields::Unit => Ok(()),
@@ -584,22 +597,27 @@ impl Display for Fields<'_> {
 /// Single named field.
 ///
 /// ```txt
-/// field: u32 @1
-/// ┬────  ┬── ┬─
-/// │      │   ╰─── ID
-/// │      ╰─────── Type
-/// ╰────────────── Name
+/// field: u32 = 5 @1
+/// ┬────  ┬──   ┬ ┬─
+/// │      │     │ ╰─── ID
+/// │      │     ╰───── Default value
+/// │      ╰─────────── Type
+/// ╰────────────────── Name
 /// ```
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct NamedField<'a> {
     /// Optional field-level comment.
     pub comment: Comment<'a>,
+    /// Optional attributes to customize the behavior.
+    pub attributes: Attributes<'a>,
     /// Unique name for this field, within the current element.
     pub name: Name<'a>,
     /// Colon to separate the field name from the type.
     pub colon: token::Colon,
     /// Data type that defines the shape of the contained data.
     pub ty: Type<'a>,
+    /// Optional default value, that is used when the field is missing.
+    pub default: Option<(token::Equal, Literal)>,
     /// Identifier for this field, that must be unique within the current element.
     pub id: Option<Id>,
     /// Source code location.
@@ -610,18 +628,25 @@ impl Print for NamedField<'_> {
     fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         let Self {
             comment,
+            attributes,
             name,
             colon,
             ty,
+            default,
             id,
             ..
         } = self;
 
         comment.print(f, level)?;
+        attributes.print(f, level)?;
 
         Self::indent(f, level)?;
         write!(f, "{name}{colon} {ty}")?;
 
+        if let Some((equal, value)) = default {
+            write!(f, " {equal} {value}")?;
+        }
+
         if let Some(id) = id {
             write!(f, " {id}")?;
         }
@@ -645,15 +670,18 @@ impl Display for NamedField<'_> {
 /// Single unnamed field.
 ///
 /// ```txt
-/// u32 @1
-/// ┬── ┬─
-/// │   ╰─── ID
-/// ╰─────── Type
+/// u32 = 5 @1
+/// ┬──   ┬ ┬─
+/// │     │ ╰─── ID
+/// │     ╰───── Default value
+/// ╰─────────── Type
 /// ```
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct UnnamedField<'a> {
     /// Data type that defines the shape of the contained data.
     pub ty: Type<'a>,
+    /// Optional default value, that is used when the field is missing.
+    pub default: Option<(token::Equal, Literal)>,
     /// Identifier for this field, that must be unique within the current element.
     pub id: Option<Id>,
     /// Source code location.
@@ -668,9 +696,15 @@ impl Spanned for UnnamedField<'_> {
 
 impl Print for UnnamedField<'_> {
     fn print(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
-        let Self { ty, id, .. } = self;
+        let Self {
+            ty, default, id, ..
+        } = self;
         write!(f, "{ty}")?;
 
+        if let Some((equal, value)) = default {
+            write!(f, " {equal} {value}")?;
+        }
+
         if let Some(id) = id {
             write!(f, " {id}")?;
         }
@@ -685,6 +719,123 @@ impl Display for UnnamedField<'_> {
     }
 }
 
+/// Reserved IDs and names, which must not be used by any field or variant within the same
+/// element. Usually, these belong to removed fields or variants, to ensure that old data is never
+/// mistaken for something else.
+///
+/// ```txt
+/// reserved @3, @5..@9, "old_name";
+/// ┬─────── ┬─  ┬─────  ┬─────────
+/// │        │   │       ╰─── Name
+/// │        │   ╰─────────── ID range (inclusive)
+/// │        ╰─────────────── ID
+/// ╰──────────────────────── Keyword
+/// ```
+#[derive(Debug, Eq, PartialEq)]
+pub struct Reserved<'a> {
+    /// The `reserved` keyword to mark the declaration.
+    pub keyword: token::Reserved,
+    /// List of reserved IDs, ID ranges and names.
+    pub values: Punctuated<ReservedValue<'a>>,
+    /// Trailing semicolon to complete the declaration.
+    pub semicolon: token::Semicolon,
+}
+
+impl Print for Reserved<'_> {
+    fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+        let Self {
+            keyword,
+            values,
+            semicolon,
+        } = self;
+
+        keyword.print(f, level)?;
+        f.write_char(' ')?;
+
+        for (value, comma) in values {
+            write!(f, "{value}")?;
+            if let Some(comma) = comma {
+                write!(f, "{comma} ")?;
+            }
+        }
+
+        semicolon.fmt(f)
+    }
+}
+
+impl Display for Reserved<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.print(f, 0)
+    }
+}
+
+/// Single entry of a [`Reserved`] declaration.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ReservedValue<'a> {
+    /// Single ID.
+    ///
+    /// ```txt
+    /// @3
+    /// ```
+    Id(Id),
+    /// Range of IDs, including both the start and end.
+    ///
+    /// ```txt
+    /// @5..@9
+    /// ```
+    Range(Id, token::DoubleDot, Id),
+    /// Name of a field or variant.
+    ///
+    /// ```txt
+    /// "old_name"
+    /// ```
+    Name(Name<'a>),
+}
+
+impl ReservedValue<'_> {
+    /// Check whether the given ID is covered by this entry. Names never contain any ID.
+    #[must_use]
+    pub fn contains_id(&self, id: u32) -> bool {
+        match self {
+            Self::Id(value) => value.get() == id,
+            Self::Range(start, _, end) => (start.get()..=end.get()).contains(&id),
+            Self::Name(_) => false,
+        }
+    }
+
+    /// Check whether the given name is covered by this entry. IDs never contain any name.
+    #[must_use]
+    pub fn contains_name(&self, name: &str) -> bool {
+        match self {
+            Self::Id(_) | Self::Range(..) => false,
+            Self::Name(value) => value.get() == name,
+        }
+    }
+}
+
+impl Spanned for ReservedValue<'_> {
+    fn span(&self) -> Span {
+        match self {
+            Self::Id(id) => id.span(),
+            Self::Range(start, _, end) => Span {
+                start: start.span().start,
+                end: end.span().end,
+            },
+            Self::Name(name) => name.span(),
+        }
+    }
+}
+
+impl Display for ReservedValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => id.fmt(f),
+            Self::Range(start, dots, end) => write!(f, "{start}{dots}{end}"),
+            Self::Name(name) => write!(f, "\"{name}\""),
+        }
+    }
+}
+
 /// Comments above any other element.
 ///
 /// ```txt
@@ -790,17 +941,25 @@ pub struct Attribute<'a> {
     pub name: &'a str,
     /// Potential value(s) associated with the attribute.
     pub value: AttributeValue<'a>,
+    /// Source code location.
+    span: Span,
 }
 
 impl Print for Attribute<'_> {
     fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         let indent = Self::INDENT.repeat(level);
-        let Self { name, value } = self;
+        let Self { name, value, .. } = self;
 
         write!(f, "{indent}{name}{value}")
     }
 }
 
+impl Spanned for Attribute<'_> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
 impl Display for Attribute<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.print(f, 0)
@@ -939,6 +1098,28 @@ pub enum DataType<'a> {
         /// Type parameter.
         ty: Box<Type<'a>>,
     },
+    /// Key-value map of data types, ordered by its keys.
+    BTreeMap {
+        /// Source code location of the `btree_map` type name.
+        span: Span,
+        /// Angles `<`...`>` that delimit the type parameters.
+        angle: token::Angle,
+        /// First type parameter.
+        key: Box<Type<'a>>,
+        /// Separator between first and second type parameter.
+        comma: token::Comma,
+        /// Second type parameter.
+        value: Box<Type<'a>>,
+    },
+    /// Set of data types (each entry is unique), ordered by its entries.
+    BTreeSet {
+        /// Source code location of the `btree_set` type name.
+        span: Span,
+        /// Angles `<`...`>` that delimit the type parameter.
+        angle: token::Angle,
+        /// Type parameter.
+        ty: Box<Type<'a>>,
+    },
     /// Optional value.
     Option {
         /// Source code location of the `option` type name.
@@ -963,6 +1144,14 @@ pub enum DataType<'a> {
     BoxString,
     /// Boxed version of a byte vector that is immutable.
     BoxBytes,
+    /// Point in time, as seconds and nanoseconds since the Unix epoch.
+    Timestamp,
+    /// Span of time, as seconds and nanoseconds.
+    Duration,
+    /// Universally unique identifier of 16 bytes.
+    Uuid,
+    /// Decimal number with a fixed amount of fractional digits.
+    Decimal,
     /// Fixed size list of up to 12 types.
     Tuple {
         /// Parenthesis `(`...`)` that delimits the tuple.
@@ -1008,10 +1197,16 @@ impl Display for DataType<'_> {
             Self::Vec { ty, .. } => write!(f, "vec<{ty}>"),
             Self::HashMap { key, value, .. } => write!(f, "hash_map<{key}, {value}>"),
             Self::HashSet { ty, .. } => write!(f, "hash_set<{ty}>"),
+            Self::BTreeMap { key, value, .. } => write!(f, "btree_map<{key}, {value}>"),
+            Self::BTreeSet { ty, .. } => write!(f, "btree_set<{ty}>"),
             Self::Option { ty, .. } => write!(f, "option<{ty}>"),
             Self::NonZero { ty, .. } => write!(f, "non_zero<{ty}>"),
             Self::BoxString => f.write_str("box<string>"),
             Self::BoxBytes => f.write_str("box<bytes>"),
+            Self::Timestamp => f.write_str("timestamp"),
+            Self::Duration => f.write_str("duration"),
+            Self::Uuid => f.write_str("uuid"),
+            Self::Decimal => f.write_str("decimal"),
             Self::Tuple { types, .. } => types.surround::<token::Parenthesis>(f, 0, false),
             Self::Array { ty, size, .. } => write!(f, "[{ty}; {size}]"),
             Self::External(t) => t.fmt(f),
@@ -1173,6 +1368,8 @@ impl AsRef<str> for Name<'_> {
 pub struct Const<'a> {
     /// Optional element-level comment.
     pub comment: Comment<'a>,
+    /// Optional attributes to customize the behavior.
+    pub attributes: Attributes<'a>,
     /// The `const` keyword to mark the constant declaration.
     pub keyword: token::Const,
     /// Unique identifier of this constant.
@@ -1193,6 +1390,7 @@ impl Print for Const<'_> {
     fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         let Self {
             comment,
+            attributes,
             keyword,
             name,
             colon,
@@ -1203,6 +1401,7 @@ impl Print for Const<'_> {
         } = self;
 
         comment.print(f, level)?;
+        attributes.print(f, level)?;
         keyword.print(f, level)?;
 
         write!(f, " {name}{colon} {ty} {equal} {value}{semicolon}")
@@ -1215,6 +1414,172 @@ impl Display for Const<'_> {
     }
 }
 
+/// Collection of remote procedures, that can be called with a request and answer with a response.
+///
+/// ```txt
+/// /// Optional comment
+/// service Users {
+///     /// Single request and response
+///     fn get(GetUser) -> User @1;
+///     /// Stream of responses
+///     fn watch(Filter) -> stream Event @2;
+/// }
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Service<'a> {
+    /// Optional service-level comment.
+    pub comment: Comment<'a>,
+    /// Optional attributes to customize the behavior.
+    pub attributes: Attributes<'a>,
+    /// The `service` keyword to mark the service declaration.
+    pub keyword: token::Service,
+    /// Unique name for this service, within its current scope.
+    pub name: Name<'a>,
+    /// Braces `{`...`}` around the methods.
+    pub brace: token::Brace,
+    /// List of methods that the service provides.
+    pub methods: Vec<Method<'a>>,
+}
+
+impl Print for Service<'_> {
+    fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+        let Self {
+            comment,
+            attributes,
+            keyword,
+            name,
+            methods,
+            ..
+        } = self;
+
+        comment.print(f, level)?;
+        attributes.print(f, level)?;
+        keyword.print(f, level)?;
+
+        writeln!(f, " {name} {}", token::Brace::OPEN)?;
+
+        for method in methods {
+            method.print(f, level + 1)?;
+            f.write_char('\n')?;
+        }
+
+        Self::indent(f, level)?;
+        writeln!(f, "{}", token::Brace::CLOSE)
+    }
+}
+
+impl Display for Service<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.print(f, 0)
+    }
+}
+
+/// Single remote procedure of a service.
+///
+/// ```txt
+/// fn watch(Filter) -> stream Event @2;
+/// ┬─ ┬────  ┬─────    ┬─────────── ┬─
+/// │  │      │         │            ╰─── ID
+/// │  │      │         ╰──────────────── Response
+/// │  │      ╰────────────────────────── Request
+/// │  ╰───────────────────────────────── Name
+/// ╰──────────────────────────────────── Keyword
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Method<'a> {
+    /// Optional method-level comment.
+    pub comment: Comment<'a>,
+    /// Optional attributes to customize the behavior.
+    pub attributes: Attributes<'a>,
+    /// The `fn` keyword to mark the method declaration.
+    pub keyword: token::Fn,
+    /// Unique name for this method, within the service it belongs to.
+    pub name: Name<'a>,
+    /// Parenthesis `(`...`)` around the request type.
+    pub paren: token::Parenthesis,
+    /// Type of the request that the method is called with.
+    pub request: MethodType<'a>,
+    /// Arrow to separate the request from the response type.
+    pub arrow: token::Arrow,
+    /// Type of the response that the method answers with.
+    pub response: MethodType<'a>,
+    /// Identifier for this method, that must be unique within the current service.
+    pub id: Option<Id>,
+    /// Trailing semicolon to complete the definition.
+    pub semicolon: token::Semicolon,
+    /// Source code location.
+    span: Span,
+}
+
+impl Print for Method<'_> {
+    fn print(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+        let Self {
+            comment,
+            attributes,
+            keyword,
+            name,
+            request,
+            arrow,
+            response,
+            id,
+            semicolon,
+            ..
+        } = self;
+
+        comment.print(f, level)?;
+        attributes.print(f, level)?;
+        keyword.print(f, level)?;
+
+        write!(
+            f,
+            " {name}{}{request}{} {arrow} {response}",
+            token::Parenthesis::OPEN,
+            token::Parenthesis::CLOSE,
+        )?;
+        if let Some(id) = id {
+            write!(f, " {id}")?;
+        }
+        write!(f, "{semicolon}")
+    }
+}
+
+impl Spanned for Method<'_> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Display for Method<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.print(f, 0)
+    }
+}
+
+/// Request or response type of a service method, that might be a stream of multiple values.
+///
+/// ```txt
+/// stream Event
+/// ┬───── ┬────
+/// │      ╰───── Type
+/// ╰──────────── Stream marker
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct MethodType<'a> {
+    /// The `stream` keyword, if zero or more values are sent instead of exactly one.
+    pub stream: Option<token::Stream>,
+    /// Data type of the (individual) values.
+    pub ty: Type<'a>,
+}
+
+impl Display for MethodType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(stream) = self.stream {
+            write!(f, "{stream} ")?;
+        }
+        self.ty.fmt(f)
+    }
+}
+
 /// In-schema definition of a literal value, together with a span into the schema to mark where it
 /// is defined.
 #[derive(Clone, Debug, PartialEq)]
@@ -2,7 +2,7 @@ use std::ops::Range;
 
 use winnow::{
     ascii::{multispace0, newline, space0},
-    combinator::{cut_err, fail, opt, peek, preceded, repeat, terminated, trace},
+    combinator::{alt, cut_err, fail, opt, peek, preceded, repeat, terminated, trace},
     dispatch,
     error::{ErrMode, ParserError},
     prelude::*,
@@ -22,6 +22,8 @@ pub use self::{
     imports::{Cause as ParseImportCause, ParseError as ParseImportError},
     literals::{Cause as ParseLiteralCause, ParseError as ParseLiteralError},
     modules::{Cause as ParseModuleCause, ParseError as ParseModuleError},
+    reserved::{Cause as ParseReservedCause, ParseError as ParseReservedError},
+    services::{Cause as ParseServiceCause, ParseError as ParseServiceError},
     structs::{Cause as ParseStructCause, ParseError as ParseStructError},
     types::{Cause as ParseTypeCause, ParseError as ParseTypeError},
 };
@@ -42,6 +44,8 @@ mod generics;
 mod imports;
 mod literals;
 mod modules;
+mod reserved;
+mod services;
 mod structs;
 mod types; /*  */
 
@@ -82,7 +86,10 @@ fn parse_definition<'i>(input: &mut Input<'i>) -> Result<Definition<'i>, ParseDe
             dispatch! {
                 peek(any);
                 'm' => modules::parse.map(Definition::Module).map_err2(Into::into),
-                's' => structs::parse.map(Definition::Struct).map_err2(Into::into),
+                's' => alt((
+                    structs::parse.map(Definition::Struct).map_err2(Into::into),
+                    services::parse.map(Definition::Service).map_err2(Into::into),
+                )),
                 'e' => enums::parse.map(Definition::Enum).map_err2(Into::into),
                 'c' => consts::parse.map(Definition::Const).map_err2(Into::into),
                 't' => aliases::parse.map(Definition::TypeAlias).map_err2(Into::into),
@@ -4,7 +4,7 @@ use mabo_derive::{ParserError, ParserErrorCause};
 use winnow::{
     Parser,
     ascii::space0,
-    combinator::{alt, cut_err, opt, preceded, repeat, separated, terminated},
+    combinator::{alt, cut_err, delimited, opt, preceded, repeat, separated, terminated},
     stream::Location,
     token::{one_of, take_while},
 };
@@ -48,7 +48,7 @@ pub enum Cause {
 pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Attributes<'i>, ParseError> {
     let start = input.current_token_start();
 
-    repeat(0.., terminated(parse_attribute, '\n'))
+    repeat(0.., delimited(space0, parse_attribute, '\n'))
         .fold(Vec::new, |mut acc, attrs| {
             acc.extend(attrs);
             acc
@@ -70,7 +70,13 @@ fn parse_attribute<'i>(input: &mut Input<'i>) -> Result<Vec<Attribute<'i>>, Caus
             terminated(
                 separated(
                     1..,
-                    ws((parse_name, parse_value)).map(|(name, value)| Attribute { name, value }),
+                    ws((parse_name, parse_value).with_span()).map(|((name, value), span)| {
+                        Attribute {
+                            name,
+                            value,
+                            span: span.into(),
+                        }
+                    }),
                     ws(token::Comma::parser()),
                 ),
                 opt(token::Comma::parser()),
@@ -106,7 +112,13 @@ fn parse_multi_value<'i>(input: &mut Input<'i>) -> Result<Vec<Attribute<'i>>, Ca
             terminated(
                 separated(
                     1..,
-                    ws((parse_name, parse_value)).map(|(name, value)| Attribute { name, value }),
+                    ws((parse_name, parse_value).with_span()).map(|((name, value), span)| {
+                        Attribute {
+                            name,
+                            value,
+                            span: span.into(),
+                        }
+                    }),
                     ws(token::Comma::parser()),
                 ),
                 opt(token::Comma::parser()),
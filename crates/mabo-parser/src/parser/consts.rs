@@ -11,7 +11,7 @@ use winnow::{
 };
 
 use super::{Input, ParserExt, Result, literals, types};
-use crate::{Comment, Const, Name, highlight, location, token};
+use crate::{Attributes, Comment, Const, Name, highlight, location, token};
 
 /// Encountered an invalid `const` declaration.
 #[derive(Debug, ParserError)]
@@ -94,6 +94,7 @@ pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Const<'i>, ParseError>
         .map(
             |(keyword, (name, colon, ty, equal, value, semicolon))| Const {
                 comment: Comment::default(),
+                attributes: Attributes::default(),
                 keyword,
                 name,
                 colon,
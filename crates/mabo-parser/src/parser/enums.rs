@@ -4,14 +4,19 @@ use mabo_derive::{ParserError, ParserErrorCause};
 use winnow::{
     Parser,
     ascii::{alphanumeric0, space0, space1},
-    combinator::{cut_err, opt, preceded, terminated},
+    combinator::{cut_err, opt, preceded, repeat, terminated},
     error::ErrMode,
     stream::Location,
     token::one_of,
 };
 
-use super::{Input, ParserExt, Result, comments, fields, generics, ids, punctuate, surround, ws};
-use crate::{Attributes, Comment, Enum, Name, Variant, highlight, punctuated::Punctuated, token};
+use super::{
+    Input, ParserExt, Result, attributes, comments, fields, generics, ids, punctuate, reserved,
+    surround, ws,
+};
+use crate::{
+    Attributes, Comment, Enum, Name, Reserved, Variant, highlight, punctuated::Punctuated, token,
+};
 
 /// Encountered an invalid `enum` declaration.
 #[derive(Debug, ParserError)]
@@ -82,6 +87,12 @@ pub enum Cause {
     /// Invalid variant identifier.
     #[forward]
     Id(ids::ParseError),
+    /// Invalid declaration of reserved IDs or names.
+    #[forward]
+    Reserved(reserved::ParseError),
+    /// Failed to parse the attributes of a variant.
+    #[forward]
+    Attribute(attributes::ParseError),
 }
 
 pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Enum<'i>, ParseError> {
@@ -94,15 +105,18 @@ pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Enum<'i>, ParseError> {
         )),
     )
         .parse_next(input)
-        .map(|(keyword, (name, generics, (brace, variants)))| Enum {
-            comment: Comment::default(),
-            attributes: Attributes::default(),
-            keyword,
-            name,
-            generics,
-            brace,
-            variants,
-        })
+        .map(
+            |(keyword, (name, generics, (brace, (reserved, variants))))| Enum {
+                comment: Comment::default(),
+                attributes: Attributes::default(),
+                keyword,
+                name,
+                generics,
+                brace,
+                reserved,
+                variants,
+            },
+        )
         .map_err(|e| {
             e.map(|cause| ParseError {
                 at: input.current_token_start()..input.current_token_start(),
@@ -124,12 +138,15 @@ pub(super) fn parse_name<'i>(input: &mut Input<'i>) -> Result<Name<'i>, Cause> {
         })
 }
 
-fn parse_variants<'i>(
-    input: &mut Input<'i>,
-) -> Result<(token::Brace, Punctuated<Variant<'i>>), Cause> {
-    surround(punctuate(
-        (parse_variant, ws(token::Comma::parser())),
-        (parse_variant, opt(ws(token::Comma::parser()))),
+type Variants<'i> = (Vec<Reserved<'i>>, Punctuated<Variant<'i>>);
+
+fn parse_variants<'i>(input: &mut Input<'i>) -> Result<(token::Brace, Variants<'i>), Cause> {
+    surround((
+        repeat(0.., ws(reserved::parse.map_err2(Cause::from))),
+        punctuate(
+            (parse_variant, ws(token::Comma::parser())),
+            (parse_variant, opt(ws(token::Comma::parser()))),
+        ),
     ))
     .parse_next(input)
 }
@@ -137,6 +154,7 @@ fn parse_variants<'i>(
 fn parse_variant<'i>(input: &mut Input<'i>) -> Result<Variant<'i>, Cause> {
     (
         ws(comments::parse.map_err2(Cause::from)),
+        ws(attributes::parse.map_err2(Cause::from)),
         (
             preceded(space0, parse_variant_name.with_span()),
             preceded(space0, fields::parse.map_err2(Cause::from)),
@@ -145,13 +163,16 @@ fn parse_variant<'i>(input: &mut Input<'i>) -> Result<Variant<'i>, Cause> {
             .with_span(),
     )
         .parse_next(input)
-        .map(|(comment, ((name, fields, id), span))| Variant {
-            comment,
-            name: name.into(),
-            fields,
-            id,
-            span: span.into(),
-        })
+        .map(
+            |(comment, attributes, ((name, fields, id), span))| Variant {
+                comment,
+                attributes,
+                name: name.into(),
+                fields,
+                id,
+                span: span.into(),
+            },
+        )
 }
 
 fn parse_variant_name<'i>(input: &mut Input<'i>) -> Result<&'i str, Cause> {
@@ -4,16 +4,20 @@ use mabo_derive::{ParserError, ParserErrorCause};
 use winnow::{
     Parser,
     ascii::space0,
-    combinator::{opt, peek, preceded},
+    combinator::{cut_err, opt, peek, preceded, repeat},
     dispatch,
     error::ErrMode,
     stream::{Location, Stream},
     token::{any, one_of, take_while},
 };
 
-use super::{Input, ParserExt, Result, comments, ids, punctuate, surround, types, ws};
+use super::{
+    Input, ParserExt, Result, attributes, comments, ids, literals, punctuate, reserved, surround,
+    types, ws,
+};
 use crate::{
-    Fields, Name, NamedField, UnnamedField, highlight, location, punctuated::Punctuated, token,
+    Fields, Literal, Name, NamedField, Reserved, UnnamedField, highlight, location,
+    punctuated::Punctuated, token,
 };
 
 /// Encountered an invalid field declaration.
@@ -65,9 +69,32 @@ pub enum Cause {
     /// Invalid field identifier.
     #[forward]
     Id(ids::ParseError),
+    /// Invalid default value literal.
+    #[forward]
+    Literal(literals::ParseError),
+    /// Invalid declaration of reserved IDs or names.
+    #[forward]
+    Reserved(reserved::ParseError),
+    /// Reserved IDs or names were declared for unnamed fields.
+    #[err(
+        msg("Reserved declaration in unnamed fields"),
+        code(mabo::parse::fields::unnamed::reserved),
+        help(
+            "Reserved IDs and names can only be declared for named fields, in the form `{}`",
+            highlight::sample("{ reserved @1; <named>, ... }"),
+        )
+    )]
+    UnnamedReserved {
+        /// Source location of the whole declaration.
+        #[err(label("Not supported here"))]
+        at: Range<usize>,
+    },
     /// Failed parsing field comments.
     #[forward]
     Comment(comments::ParseError),
+    /// Failed parsing field attributes.
+    #[forward]
+    Attribute(attributes::ParseError),
 }
 
 pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Fields<'i>, ParseError> {
@@ -75,7 +102,7 @@ pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Fields<'i>, ParseError>
 
     dispatch!(
         peek(any);
-        '{' => parse_named.map(|(brace, fields)| Fields::Named(brace, fields)),
+        '{' => parse_named.map(|(brace, (reserved, fields))| Fields::Named(brace, reserved, fields)),
         '(' => parse_unnamed.map(|(paren, fields)| Fields::Unnamed(paren, fields)),
         _ => parse_unit.map(|()| Fields::Unit),
     )
@@ -88,12 +115,15 @@ pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Fields<'i>, ParseError>
     })
 }
 
-fn parse_named<'i>(
-    input: &mut Input<'i>,
-) -> Result<(token::Brace, Punctuated<NamedField<'i>>), Cause> {
-    surround(punctuate(
-        (parse_named_field, ws(token::Comma::parser())),
-        (parse_named_field, opt(ws(token::Comma::parser()))),
+type NamedFields<'i> = (Vec<Reserved<'i>>, Punctuated<NamedField<'i>>);
+
+fn parse_named<'i>(input: &mut Input<'i>) -> Result<(token::Brace, NamedFields<'i>), Cause> {
+    surround((
+        repeat(0.., ws(reserved::parse.map_err2(Cause::from))),
+        punctuate(
+            (parse_named_field, ws(token::Comma::parser())),
+            (parse_named_field, opt(ws(token::Comma::parser()))),
+        ),
     ))
     .parse_next(input)
 }
@@ -101,13 +131,25 @@ fn parse_named<'i>(
 fn parse_unnamed<'i>(
     input: &mut Input<'i>,
 ) -> Result<(token::Parenthesis, Punctuated<UnnamedField<'i>>), Cause> {
-    surround(punctuate(
-        (parse_unnamed_field, ws(token::Comma::parser())),
-        (parse_unnamed_field, opt(ws(token::Comma::parser()))),
+    surround(preceded(
+        parse_unnamed_reserved,
+        punctuate(
+            (parse_unnamed_field, ws(token::Comma::parser())),
+            (parse_unnamed_field, opt(ws(token::Comma::parser()))),
+        ),
     ))
     .parse_next(input)
 }
 
+/// Reject any `reserved` declaration for unnamed fields, which is parsed anyway to point out the
+/// whole of it in the error.
+fn parse_unnamed_reserved(input: &mut Input<'_>) -> Result<(), Cause> {
+    match opt(ws(reserved::parse.map_err2(Cause::from).with_span())).parse_next(input)? {
+        Some((_, at)) => Err(ErrMode::Cut(Cause::UnnamedReserved { at })),
+        None => Ok(()),
+    }
+}
+
 fn parse_unit(input: &mut Input<'_>) -> Result<(), Cause> {
     ().parse_next(input)
 }
@@ -115,12 +157,14 @@ fn parse_unit(input: &mut Input<'_>) -> Result<(), Cause> {
 fn parse_unnamed_field<'i>(input: &mut Input<'i>) -> Result<UnnamedField<'i>, Cause> {
     (
         ws(types::parse.map_err2(Cause::from)),
+        opt(parse_default),
         opt(preceded(space0, ids::parse.map_err2(Cause::from))),
     )
         .with_span()
         .parse_next(input)
-        .map(|((ty, id), span)| UnnamedField {
+        .map(|((ty, default, id), span)| UnnamedField {
             ty,
+            default,
             id,
             span: span.into(),
         })
@@ -129,23 +173,37 @@ fn parse_unnamed_field<'i>(input: &mut Input<'i>) -> Result<UnnamedField<'i>, Ca
 fn parse_named_field<'i>(input: &mut Input<'i>) -> Result<NamedField<'i>, Cause> {
     (
         ws(comments::parse.map_err2(Cause::from)),
+        ws(attributes::parse.map_err2(Cause::from)),
         (
             preceded(space0, parse_field_name),
             preceded(space0, token::Colon::parser()),
             preceded(space0, types::parse.map_err2(Cause::from)),
+            opt(parse_default),
             opt(preceded(space0, ids::parse.map_err2(Cause::from))),
         )
             .with_span(),
     )
         .parse_next(input)
-        .map(|(comment, ((name, colon, ty, id), span))| NamedField {
-            comment,
-            name,
-            colon,
-            ty,
-            id,
-            span: span.into(),
-        })
+        .map(
+            |(comment, attributes, ((name, colon, ty, default, id), span))| NamedField {
+                comment,
+                attributes,
+                name,
+                colon,
+                ty,
+                default,
+                id,
+                span: span.into(),
+            },
+        )
+}
+
+fn parse_default(input: &mut Input<'_>) -> Result<(token::Equal, Literal), Cause> {
+    (
+        preceded(space0, token::Equal::parser()),
+        cut_err(preceded(space0, literals::parse.map_err2(Cause::from))),
+    )
+        .parse_next(input)
 }
 
 fn parse_field_name<'i>(input: &mut Input<'i>) -> Result<Name<'i>, Cause> {
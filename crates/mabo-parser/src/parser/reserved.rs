@@ -0,0 +1,150 @@
+use std::ops::Range;
+
+use mabo_derive::{ParserError, ParserErrorCause};
+use winnow::{
+    Parser,
+    ascii::{space0, space1},
+    combinator::{cut_err, fail, opt, peek, preceded, terminated},
+    dispatch,
+    error::ErrMode,
+    stream::{Location, Stream},
+    token::{any, one_of, take_while},
+};
+
+use super::{Input, ParserExt, Result, ids, punctuate, ws};
+use crate::{Name, Reserved, ReservedValue, Spanned, highlight, location, token};
+
+/// Encountered an invalid `reserved` declaration.
+#[derive(Debug, ParserError)]
+#[err(
+    msg("Failed to parse reserved declaration"),
+    code(mabo::parse::reserved),
+    help(
+        "Expected reserved declaration in the form `{}`",
+        highlight::sample("reserved @1, @2..@4, \"name\";"),
+    )
+)]
+#[rename(ParseReservedError)]
+pub struct ParseError {
+    /// Source location of the whole declaration.
+    #[err(label("In this declaration"))]
+    pub at: Range<usize>,
+    /// Specific cause of the error.
+    pub cause: Cause,
+}
+
+/// Specific reason why a `reserved` declaration was invalid.
+#[derive(Debug, ParserErrorCause)]
+#[rename(ParseReservedCause)]
+pub enum Cause {
+    /// Non-specific general parser error.
+    Parser(usize),
+    /// Reserved name is not considered valid.
+    #[err(
+        msg("Invalid reserved name"),
+        code(mabo::parse::reserved::invalid_name),
+        help(
+            "Reserved names must be quoted and consist of one or more alphanumeric characters or \
+             underscores ({})",
+            highlight::value("A-Z, a-z, 0-9, _"),
+        )
+    )]
+    InvalidName {
+        /// Source location of the character.
+        #[err(label("Problematic character"))]
+        at: usize,
+    },
+    /// Range of IDs doesn't contain any value.
+    #[err(
+        msg("Empty range of reserved IDs"),
+        code(mabo::parse::reserved::empty_range),
+        help("The start of an ID range must not be greater than its end")
+    )]
+    EmptyRange {
+        /// Source location of the range.
+        #[err(label("This range"))]
+        at: Range<usize>,
+    },
+    /// Invalid reserved identifier.
+    #[forward]
+    Id(ids::ParseError),
+}
+
+pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Reserved<'i>, ParseError> {
+    let start = input.checkpoint();
+
+    (
+        terminated(
+            token::Reserved::parser(),
+            (space1, peek(one_of(['@', '"']))),
+        ),
+        cut_err((
+            punctuate(
+                (parse_value, ws(token::Comma::parser())),
+                (parse_value, opt(ws(token::Comma::parser()))),
+            ),
+            ws(token::Semicolon::parser()),
+        )),
+    )
+        .parse_next(input)
+        .map(|(keyword, (values, semicolon))| Reserved {
+            keyword,
+            values,
+            semicolon,
+        })
+        .map_err(|e| {
+            e.map(|cause| ParseError {
+                at: location::from_until(*input, &start, [';', '\n']),
+                cause,
+            })
+        })
+}
+
+fn parse_value<'i>(input: &mut Input<'i>) -> Result<ReservedValue<'i>, Cause> {
+    ws(dispatch! {
+        peek(any);
+        '@' => parse_ids,
+        '"' => parse_name.map(ReservedValue::Name),
+        _ => fail,
+    })
+    .parse_next(input)
+}
+
+fn parse_ids<'i>(input: &mut Input<'i>) -> Result<ReservedValue<'i>, Cause> {
+    let (start, end) = (
+        ids::parse.map_err2(Cause::from),
+        opt((
+            preceded(space0, token::DoubleDot::parser()),
+            cut_err(preceded(space0, ids::parse.map_err2(Cause::from))),
+        )),
+    )
+        .parse_next(input)?;
+
+    Ok(match end {
+        Some((_, end)) if start.get() > end.get() => {
+            return Err(ErrMode::Cut(Cause::EmptyRange {
+                at: start.span().start..end.span().end,
+            }));
+        }
+        Some((dots, end)) => ReservedValue::Range(start, dots, end),
+        None => ReservedValue::Id(start),
+    })
+}
+
+fn parse_name<'i>(input: &mut Input<'i>) -> Result<Name<'i>, Cause> {
+    preceded(
+        '"',
+        cut_err(terminated(
+            take_while(1.., ('a'..='z', 'A'..='Z', '0'..='9', '_')),
+            '"',
+        )),
+    )
+    .with_span()
+    .parse_next(input)
+    .map(Into::into)
+    .map_err(|e: ErrMode<_>| {
+        e.map(|()| Cause::InvalidName {
+            at: input.current_token_start(),
+        })
+    })
+}
@@ -0,0 +1,208 @@
+use std::ops::Range;
+
+use mabo_derive::{ParserError, ParserErrorCause};
+use winnow::{
+    Parser,
+    ascii::{alphanumeric0, space0, space1},
+    combinator::{cut_err, opt, preceded, repeat, terminated},
+    error::ErrMode,
+    stream::{Location, Stream},
+    token::{one_of, take_while},
+};
+
+use super::{Input, ParserExt, Result, attributes, comments, ids, surround, types, ws};
+use crate::{Attributes, Comment, Method, MethodType, Name, Service, highlight, location, token};
+
+/// Encountered an invalid `service` declaration.
+#[derive(Debug, ParserError)]
+#[err(
+    msg("Failed to parse service declaration"),
+    code(mabo::parse::service_def),
+    help(
+        "Expected service declaration in the form `{}`",
+        highlight::sample("service <Name> {...}"),
+    )
+)]
+#[rename(ParseServiceError)]
+pub struct ParseError {
+    /// Source location of the whole service.
+    #[err(label("In this declaration"))]
+    pub at: Range<usize>,
+    /// Specific cause of the error.
+    pub cause: Cause,
+}
+
+/// Specific reason why a `service` declaration was invalid.
+#[derive(Debug, ParserErrorCause)]
+#[rename(ParseServiceCause)]
+pub enum Cause {
+    /// Non-specific general parser error.
+    Parser(usize),
+    /// Defined name is not considered valid.
+    #[err(
+        msg("Invalid service name"),
+        code(mabo::parse::service_def::invalid_name),
+        help(
+            "Service names must start with an uppercase letter ({}), followed by zero or more \
+             alphanumeric characters ({})",
+            highlight::value("A-Z"),
+            highlight::value("A-Z, a-z, 0-9"),
+        )
+    )]
+    InvalidName {
+        /// Source location of the character.
+        #[err(label("Problematic character"))]
+        at: usize,
+    },
+    /// Defined method name is not considered valid.
+    #[err(
+        msg("Invalid method name"),
+        code(mabo::parse::service_def::invalid_method_name),
+        help(
+            "Method names must start with a lowercase letter ({}), followed by zero or more \
+             lowercase alphanumeric characters or underscores ({})",
+            highlight::value("a-z"),
+            highlight::value("a-z, 0-9, _"),
+        )
+    )]
+    InvalidMethodName {
+        /// Source location of the character.
+        #[err(label("Problematic character"))]
+        at: usize,
+    },
+    /// Failed to parse the comments of a method.
+    #[forward]
+    Comment(comments::ParseError),
+    /// Failed to parse the attributes of a method.
+    #[forward]
+    Attribute(attributes::ParseError),
+    /// Invalid request or response type.
+    #[forward]
+    Type(types::ParseError),
+    /// Invalid method identifier.
+    #[forward]
+    Id(ids::ParseError),
+}
+
+pub(super) fn parse<'i>(input: &mut Input<'i>) -> Result<Service<'i>, ParseError> {
+    let start = input.checkpoint();
+
+    (
+        terminated(token::Service::parser(), space1),
+        cut_err((
+            parse_name,
+            preceded(space0, surround(repeat(0.., ws(parse_method)))),
+        )),
+    )
+        .parse_next(input)
+        .map(|(keyword, (name, (brace, methods)))| Service {
+            comment: Comment::default(),
+            attributes: Attributes::default(),
+            keyword,
+            name,
+            brace,
+            methods,
+        })
+        .map_err(|e| {
+            e.map(|cause| ParseError {
+                at: location::from_until(*input, &start, ['}', '\n']),
+                cause,
+            })
+        })
+}
+
+fn parse_name<'i>(input: &mut Input<'i>) -> Result<Name<'i>, Cause> {
+    (one_of('A'..='Z'), alphanumeric0)
+        .take()
+        .with_span()
+        .parse_next(input)
+        .map(Into::into)
+        .map_err(|e: ErrMode<_>| {
+            e.map(|()| Cause::InvalidName {
+                at: input.current_token_start(),
+            })
+        })
+}
+
+fn parse_method<'i>(input: &mut Input<'i>) -> Result<Method<'i>, Cause> {
+    (
+        ws(comments::parse.map_err2(Cause::from)),
+        ws(attributes::parse.map_err2(Cause::from)),
+        (
+            preceded(space0, terminated(token::Fn::parser(), space1)),
+            cut_err((
+                parse_method_name,
+                preceded(space0, surround(ws(parse_method_type))),
+                preceded(space0, token::Arrow::parser()),
+                preceded(space0, parse_method_type),
+                opt(preceded(space0, ids::parse.map_err2(Cause::from))),
+                preceded(space0, token::Semicolon::parser()),
+            )),
+        )
+            .with_span(),
+    )
+        .parse_next(input)
+        .map(
+            |(
+                comment,
+                attributes,
+                ((keyword, (name, (paren, request), arrow, response, id, semicolon)), span),
+            )| Method {
+                comment,
+                attributes,
+                keyword,
+                name,
+                paren,
+                request,
+                arrow,
+                response,
+                id,
+                semicolon,
+                span: span.into(),
+            },
+        )
+}
+
+fn parse_method_name<'i>(input: &mut Input<'i>) -> Result<Name<'i>, Cause> {
+    (
+        one_of('a'..='z'),
+        take_while(0.., ('a'..='z', '0'..='9', '_')),
+    )
+        .take()
+        .with_span()
+        .parse_next(input)
+        .map(Into::into)
+        .map_err(|e: ErrMode<_>| {
+            e.map(|()| Cause::InvalidMethodName {
+                at: input.current_token_start(),
+            })
+        })
+}
+
+fn parse_method_type<'i>(input: &mut Input<'i>) -> Result<MethodType<'i>, Cause> {
+    (
+        opt(terminated(token::Stream::parser(), space1)),
+        types::parse.map_err2(Cause::from),
+    )
+        .parse_next(input)
+        .map(|(stream, ty)| MethodType { stream, ty })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn print_service() {
+        let err = ParseError {
+            at: (0..38),
+            cause: Cause::InvalidMethodName { at: 22 },
+        };
+
+        println!(
+            "{:?}",
+            miette::Report::from(err)
+                .with_source_code("service Sample {\n    fn Get(u32) -> u32 @1;\n}")
+        );
+    }
+}
@@ -88,6 +88,10 @@ fn parse_basic<'i>(input: &mut Input<'i>) -> Result<DataType<'i>, Cause> {
             "&string" => empty.value(DataType::StringRef),
             "bytes" => empty.value(DataType::Bytes),
             "&bytes" => empty.value(DataType::BytesRef),
+            "timestamp" => empty.value(DataType::Timestamp),
+            "duration" => empty.value(DataType::Duration),
+            "uuid" => empty.value(DataType::Uuid),
+            "decimal" => empty.value(DataType::Decimal),
             _ => fail,
         },
         literal("box<string>").value(DataType::BoxString),
@@ -146,6 +150,18 @@ fn parse_generic<'i>(input: &mut Input<'i>) -> Result<DataType<'i>, Cause> {
                 angle,
                 ty: Box::new(ty),
             }),
+        ("btree_map", ref span) => parse_pair(|angle, key, comma, value| DataType::BTreeMap {
+                span: span.into(),
+                angle,
+                key: Box::new(key),
+                comma,
+                value: Box::new(value),
+            }),
+        ("btree_set", ref span) => parse_single(|angle, ty| DataType::BTreeSet {
+                span: span.into(),
+                angle,
+                ty: Box::new(ty),
+            }),
         ("option", ref span) => parse_single(|angle, ty| DataType::Option {
                 span: span.into(),
                 angle,
@@ -69,12 +69,29 @@ impl<T: Print, P: Punctuation> Punctuated<T, P> {
         f: &mut fmt::Formatter<'_>,
         level: usize,
         newline: bool,
+    ) -> fmt::Result {
+        self.surround_with::<D, T>(f, level, newline, &[])
+    }
+
+    /// Same as [`Self::surround`], but print the given leading elements right after the opening
+    /// delimiter, each one separated by a newline (or space).
+    pub(crate) fn surround_with<D: Delimiter, H: Print>(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        level: usize,
+        newline: bool,
+        leading: &[H],
     ) -> fmt::Result {
         f.write_char(D::OPEN)?;
         if newline {
             f.write_char('\n')?;
         }
 
+        for value in leading {
+            value.print(f, level + 1)?;
+            f.write_char(if newline { '\n' } else { ' ' })?;
+        }
+
         for (value, _) in &self.values {
             value.print(f, level + 1)?;
             f.write_str(P::VALUE)?;
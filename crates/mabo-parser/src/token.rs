@@ -9,7 +9,7 @@ use mabo_derive::Debug;
 use winnow::{
     Parser,
     error::ParserError,
-    stream::{Compare, Location, Stream, StreamIsPartial},
+    stream::{self, Compare, Location, StreamIsPartial},
 };
 
 use crate::{Print, Span, Spanned};
@@ -57,7 +57,7 @@ macro_rules! define_keywords {
                 #[inline]
                 pub(crate) fn parser<'a, I, E>() -> impl Parser<I, Self, E> + use<I, E>
                 where
-                    I: Compare<&'a str> + Location + Stream + StreamIsPartial,
+                    I: Compare<&'a str> + Location + stream::Stream + StreamIsPartial,
                     E: ParserError<I>,
                 {
                     |i: &mut I| Self::NAME.span().output_into().parse_next(i)
@@ -123,7 +123,7 @@ macro_rules! define_punctuation {
                 #[inline]
                 pub(crate) fn parser<'a, I, E>() -> impl Parser<I, Self, E> + use<I, E>
                 where
-                    I: Compare<&'a str> + Location + Stream + StreamIsPartial,
+                    I: Compare<&'a str> + Location + stream::Stream + StreamIsPartial,
                     E: ParserError<I>,
                 {
                     |i: &mut I| Self::VALUE.span().output_into().parse_next(i)
@@ -227,6 +227,14 @@ define_keywords! {
     Type "type"
     /// The `use` keyword.
     Use "use"
+    /// The `reserved` keyword.
+    Reserved "reserved"
+    /// The `service` keyword.
+    Service "service"
+    /// The `fn` keyword.
+    Fn "fn"
+    /// The `stream` keyword.
+    Stream "stream"
 }
 
 define_punctuation! {
@@ -242,6 +250,10 @@ define_punctuation! {
     DoubleColon "::"
     /// Equal sign `=` separator, used in type aliases.
     Equal "="
+    /// Double dot `..` separator, used for ranges of reserved IDs.
+    DoubleDot ".."
+    /// Arrow `->` separator, between the request and response type of a service method.
+    Arrow "->"
 }
 
 define_delimiters! {
@@ -1,5 +1,10 @@
 #![expect(missing_docs)]
 
 fn main() -> mabo_build::Result<()> {
-    mabo_build::Compiler::default().compile(env!("CARGO_MANIFEST_DIR"))
+    mabo_build::Compiler::default()
+        .with_canonical_encoding(true)
+        .with_views(true)
+        .with_decode_merge(true)
+        .with_validate_on_decode(true)
+        .compile(env!("CARGO_MANIFEST_DIR"))
 }
@@ -41,6 +41,10 @@ mod schemas {
         mabo::include!("const_string");
     }
 
+    mod deprecated {
+        mabo::include!("deprecated");
+    }
+
     mod enum_basic {
         mabo::include!("enum_basic");
     }
@@ -57,6 +61,10 @@ mod schemas {
         mabo::include!("enum_min_ws");
     }
 
+    mod enum_reserved {
+        mabo::include!("enum_reserved");
+    }
+
     mod import_basic {
         mabo::include!("import_basic");
     }
@@ -81,10 +89,20 @@ mod schemas {
         mabo::include!("schema_basic");
     }
 
+    // The service is only called from the tests.
+    #[allow(dead_code)]
+    pub(crate) mod service_basic {
+        mabo::include!("service_basic");
+    }
+
     mod struct_basic {
         mabo::include!("struct_basic");
     }
 
+    mod struct_defaults {
+        mabo::include!("struct_defaults");
+    }
+
     mod struct_generics {
         mabo::include!("struct_generics");
     }
@@ -97,10 +115,18 @@ mod schemas {
         mabo::include!("struct_min_ws");
     }
 
+    mod struct_reserved {
+        mabo::include!("struct_reserved");
+    }
+
     mod struct_tuple {
         mabo::include!("struct_tuple");
     }
 
+    mod struct_validate {
+        mabo::include!("struct_validate");
+    }
+
     mod types_basic {
         mabo::include!("types_basic");
     }
@@ -120,20 +146,40 @@ mod schemas {
     mod types_ref {
         mabo::include!("types_ref");
     }
+
+    mod types_well_known {
+        mabo::include!("types_well_known");
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fmt::Debug;
 
-    use mabo::{Decode, Encode};
+    use mabo::{
+        BorrowDecode, Decode, Encode,
+        buf::{self, DecodeMerge, DecodeOptions, Error, Limits, Size},
+    };
+
+    use super::{evolution, sample, schemas::service_basic};
+
+    fn roundtrip_borrowed<'a, T: Debug + PartialEq + BorrowDecode<'a> + Encode>(
+        value: &T,
+        buf: &'a mut Vec<u8>,
+    ) {
+        value.encode(buf);
+        println!("{}: {buf:?}", std::any::type_name::<T>());
+        assert_eq!(value.size(), buf.len());
 
-    use super::{evolution, sample};
+        let value2 = T::borrow_decode(&mut &**buf).unwrap();
+        assert_eq!(*value, value2);
+    }
 
     fn roundtrip<T: Debug + PartialEq + Decode + Encode>(value: &T) {
         let mut buf = Vec::new();
         value.encode(&mut buf);
         println!("{}: {buf:?}", std::any::type_name::<T>());
+        assert_eq!(value.size(), buf.len());
 
         let value2 = T::decode(&mut &*buf).unwrap();
         assert_eq!(*value, value2);
@@ -154,6 +200,67 @@ mod tests {
         assert_eq!(5, value.field1);
     }
 
+    #[test]
+    fn evolution_preserved() {
+        let value = evolution::Version2 {
+            field1: 5,
+            field2: "Test".to_owned(),
+        };
+
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+
+        let mut preserved = evolution::Version1Preserved::decode(&mut &*buf).unwrap();
+        assert_eq!(5, preserved.field1);
+        assert_eq!(1, preserved.unknown_fields.len());
+
+        preserved.field1 = 6;
+        buf.clear();
+        preserved.encode(&mut buf);
+        assert_eq!(preserved.size(), buf.len());
+
+        let value2 = evolution::Version2::decode(&mut &*buf).unwrap();
+        assert_eq!(6, value2.field1);
+        assert_eq!(value.field2, value2.field2);
+    }
+
+    #[test]
+    fn evolution_enum() {
+        let mut buf = Vec::new();
+        evolution::EnumVersion2::Two("Test".to_owned()).encode(&mut buf);
+
+        assert!(matches!(
+            evolution::EnumVersion1::decode(&mut &*buf)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::UnknownVariant(2)),
+        ));
+    }
+
+    #[test]
+    fn evolution_enum_preserved() {
+        for value in [
+            evolution::EnumVersion2::Two("Test".to_owned()),
+            evolution::EnumVersion2::Three,
+        ] {
+            let mut buf = Vec::new();
+            value.encode(&mut buf);
+
+            let preserved = evolution::EnumVersion1Preserved::decode(&mut &*buf).unwrap();
+            assert!(matches!(
+                preserved,
+                evolution::EnumVersion1Preserved::Unknown { id: 2 | 3, .. },
+            ));
+
+            buf.clear();
+            preserved.encode(&mut buf);
+            assert_eq!(preserved.size(), buf.len());
+
+            let value2 = evolution::EnumVersion2::decode(&mut &*buf).unwrap();
+            assert_eq!(value, value2);
+        }
+    }
+
     #[test]
     fn sample() {
         roundtrip(&sample::Sample {
@@ -163,6 +270,25 @@ mod tests {
         });
     }
 
+    #[test]
+    fn encode_to_slice() {
+        let value = sample::Sample2::Fields {
+            name: "this".into(),
+            valid: true,
+            dates: vec![(2023, 1, 1), (2023, 10, 5)],
+        };
+        let size = value.size();
+
+        let mut buf = [0; 64];
+        assert_eq!(Ok(size), value.encode_to_slice(&mut buf));
+        assert_eq!(value, sample::Sample2::decode(&mut &buf[..size]).unwrap());
+
+        let mut buf = [0; 8];
+        let err = value.encode_to_slice(&mut buf).unwrap_err();
+        assert_eq!((size, 8), (err.required, err.available));
+        assert_eq!([0; 8], buf);
+    }
+
     #[test]
     fn sample2_unit() {
         roundtrip(&sample::Sample2::Unit);
@@ -230,4 +356,810 @@ mod tests {
             vec_maybe: vec![None, None],
         });
     }
+
+    #[test]
+    fn specials_defaults() {
+        let mut buf = Vec::new();
+        buf::encode_u32(&mut buf, buf::END_MARKER);
+
+        let value = sample::specials::Retry::decode(&mut &*buf).unwrap();
+        assert_eq!(sample::specials::Retry::default(), value);
+        assert_eq!(3, value.attempts);
+        assert_eq!("default", value.label);
+
+        roundtrip(&sample::specials::Retry {
+            attempts: 0,
+            backoff: 0.0,
+            label: String::new(),
+        });
+    }
+
+    #[test]
+    fn specials_defaults_canonical() {
+        use mabo::buf::verify_canonical;
+
+        use super::sample::specials::Retry;
+
+        // Fields that hold their default value are still part of the canonical encoding.
+        let value = Retry::default();
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        assert_eq!(value.size(), buf.len());
+        assert_eq!(value, buf::decode_strict::<Retry>(&buf).unwrap());
+        assert_eq!(value, verify_canonical::<Retry>(&buf).unwrap());
+
+        // Missing fields are filled in with their default value, even in strict mode.
+        let mut missing = Vec::new();
+        buf::encode_u32(&mut missing, buf::END_MARKER);
+        assert_eq!(value, Retry::decode(&mut &missing[..]).unwrap());
+        assert_eq!(value, buf::decode_strict::<Retry>(&missing).unwrap());
+        assert!(matches!(
+            verify_canonical::<Retry>(&missing),
+            Err(Error::NonCanonical)
+        ));
+    }
+
+    #[test]
+    fn specials_defaults_nested() {
+        let value = sample::specials::Policy::default();
+        assert_eq!(sample::specials::Retry::default(), value.retry);
+        assert_eq!(None, value.fallback);
+        assert!(value.enabled);
+    }
+
+    #[test]
+    fn specials_well_known() {
+        roundtrip(&sample::specials::Event {
+            at: std::time::UNIX_EPOCH + std::time::Duration::new(1_700_000_000, 5),
+            took: std::time::Duration::from_millis(1500),
+            key: [7; 16],
+            amount: mabo::Decimal::new(-1999, 2),
+        });
+    }
+
+    #[test]
+    fn specials_validate() {
+        use mabo::validate::{Rule, Validate, Violation, Violations};
+
+        let mut account = sample::specials::Account {
+            name: "mabo_user".to_owned(),
+            age: 30,
+            emails: vec!["user@example.com".to_owned()],
+            nickname: Some("mabo".to_owned()),
+        };
+        assert_eq!(Ok(()), account.validate());
+        roundtrip(&account);
+
+        account.name = "Mabo User".to_owned();
+        account.age = 12;
+        account.emails.clear();
+        account.nickname = None;
+        assert_eq!(
+            Err(Violations(vec![
+                Violation {
+                    field: "name",
+                    variant: None,
+                    rule: Rule::Pattern("^[a-z0-9_]+$"),
+                },
+                Violation {
+                    field: "age",
+                    variant: None,
+                    rule: Rule::Min("13"),
+                },
+                Violation {
+                    field: "emails",
+                    variant: None,
+                    rule: Rule::NonEmpty,
+                },
+            ])),
+            account.validate(),
+        );
+
+        let command = sample::specials::Command::Wait { seconds: 90.0 };
+        assert_eq!(
+            "field `seconds` of variant `Wait` must be at most 60",
+            command.validate().unwrap_err().to_string(),
+        );
+        assert_eq!(Ok(()), sample::specials::Command::Stop.validate());
+    }
+
+    #[test]
+    fn specials_validate_on_decode() {
+        let mut buf = Vec::new();
+        sample::specials::Account {
+            name: "ab".to_owned(),
+            age: 30,
+            emails: vec!["user@example.com".to_owned()],
+            nickname: None,
+        }
+        .encode(&mut buf);
+
+        let result = sample::specials::Account::decode(&mut &*buf);
+        let Err(Error::Validation(violations)) = result.as_ref().map_err(Error::kind) else {
+            panic!("expected validation error, got {result:?}");
+        };
+        assert_eq!(
+            "field `name` must have a length of at least 3",
+            violations.to_string(),
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        use std::collections::{HashMap, HashSet};
+
+        use mabo::buf::verify_canonical;
+
+        use super::sample::specials::Lookup;
+
+        let value = Lookup {
+            entries: (0..50)
+                .map(|i| (i.to_string(), i))
+                .collect::<HashMap<_, _>>(),
+            tags: (0..50).collect::<HashSet<_>>(),
+        };
+        let copy = Lookup {
+            entries: (0..50).rev().map(|i| (i.to_string(), i)).collect(),
+            tags: (0..50).rev().collect(),
+        };
+
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        let mut buf2 = Vec::new();
+        copy.encode(&mut buf2);
+        assert_eq!(buf, buf2);
+        assert_eq!(value.size(), buf.len());
+        assert_eq!(value, verify_canonical::<Lookup>(&buf).unwrap());
+
+        // Swap the first two tags, which are single byte varints at the end of the payload.
+        let tags = buf.len() - 51;
+        buf.swap(tags, tags + 1);
+        assert_eq!(value, Lookup::decode(&mut &*buf).unwrap());
+        assert!(matches!(
+            verify_canonical::<Lookup>(&buf),
+            Err(Error::NonCanonical)
+        ));
+    }
+
+    #[test]
+    fn refs_borrowed() {
+        roundtrip_borrowed(
+            &sample::refs::Borrowed {
+                name: "borrowed",
+                raw: &[1, 2, 3],
+                tags: vec!["a", "b"],
+                inner: sample::refs::Inner {
+                    label: Some("label"),
+                    owned: "owned".to_owned(),
+                },
+            },
+            &mut Vec::new(),
+        );
+    }
+
+    #[test]
+    fn refs_message() {
+        roundtrip_borrowed(&sample::refs::Message::Text("hello"), &mut Vec::new());
+        roundtrip_borrowed(&sample::refs::Message::Empty, &mut Vec::new());
+    }
+
+    #[test]
+    fn dynamic() {
+        fn check(root: &str, value: &impl Encode) -> mabo::Value {
+            let schema = mabo_parser::Schema::parse(include_str!("sample.mabo"), None).unwrap();
+            let schema = mabo_compiler::simplify_schema(&schema);
+            let schemas = [("sample", &schema)];
+            let registry = mabo::dynamic::Registry::new(&schemas);
+
+            let mut buf = Vec::new();
+            value.encode(&mut buf);
+
+            let dynamic = registry.decode("sample", root, &mut &*buf).unwrap();
+            let mut buf2 = Vec::new();
+            registry
+                .encode("sample", root, &dynamic, &mut buf2)
+                .unwrap();
+            assert_eq!(buf, buf2);
+
+            dynamic
+        }
+
+        let value = check(
+            "Sample",
+            &sample::Sample {
+                a: 5,
+                b: true,
+                c: ("Test".into(), -2),
+            },
+        );
+        assert_eq!(Some(&mabo::Value::U32(5)), value.field("a"));
+        assert_eq!(
+            Some(&mabo::Value::Tuple(vec![
+                mabo::Value::String("Test".into()),
+                mabo::Value::I16(-2),
+            ])),
+            value.field("c"),
+        );
+
+        check("Sample2", &sample::Sample2::Tuple(7, 8));
+        check("Sample3", &sample::Sample3(true, (vec![1, 2, 3], -500_000)));
+        check(
+            "SampleAlias",
+            &sample::SampleAlias {
+                a: 50,
+                b: false,
+                c: (String::new(), -10),
+            },
+        );
+        check(
+            "specials::SomeOptions",
+            &sample::specials::SomeOptions {
+                maybe_int: Some(5),
+                maybe_text: None,
+                maybe_tuple: Some((20, 30)),
+                nested: Some(None),
+                vec_maybe: vec![Some(true), None],
+            },
+        );
+    }
+
+    #[test]
+    fn dynamic_defaults() {
+        let schema = mabo_parser::Schema::parse(include_str!("sample.mabo"), None).unwrap();
+        let schema = mabo_compiler::simplify_schema(&schema);
+        let schemas = [("sample", &schema)];
+        let registry = mabo::dynamic::Registry::new(&schemas);
+
+        // Only the `label` field, all others are missing.
+        let mut buf = Vec::new();
+        buf::encode_field(
+            &mut buf,
+            mabo::FieldId::new(3, mabo::FieldEncoding::LengthPrefixed),
+            |w| buf::encode_string(w, "custom"),
+        );
+        buf::encode_u32(&mut buf, buf::END_MARKER);
+
+        let value = sample::specials::Retry::decode(&mut &*buf).unwrap();
+        let dynamic = registry
+            .decode("sample", "specials::Retry", &mut &*buf)
+            .unwrap();
+
+        assert_eq!(3, value.attempts);
+        assert_eq!(Some(&mabo::Value::U32(3)), dynamic.field("attempts"));
+        assert_eq!(Some(&mabo::Value::F64(1.5)), dynamic.field("backoff"));
+        assert_eq!(
+            Some(&mabo::Value::String(value.label)),
+            dynamic.field("label")
+        );
+    }
+
+    #[test]
+    fn raw() {
+        let mut buf = Vec::new();
+        sample::Sample {
+            a: 5,
+            b: true,
+            c: ("Test".into(), -2),
+        }
+        .encode(&mut buf);
+
+        let message = mabo::raw::decode_raw(&buf).unwrap();
+
+        assert_eq!(None, message.variant);
+        assert_eq!(
+            vec![1, 2, 3],
+            message.fields.iter().map(|f| f.id).collect::<Vec<_>>()
+        );
+        assert_eq!(mabo::raw::RawValue::Varint(5), message.fields[0].value);
+
+        let mut buf = Vec::new();
+        sample::Sample2::Tuple(7, 8).encode(&mut buf);
+
+        let message = mabo::raw::decode_raw(&buf).unwrap();
+
+        assert!(message.variant.is_some());
+        assert_eq!(2, message.fields.len());
+    }
+
+    #[test]
+    fn serde() {
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Sample {
+            a: u32,
+            b: bool,
+            c: (String, i16),
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        enum Sample2 {
+            Unit,
+            Tuple(u32, u32),
+            Fields {
+                name: String,
+                valid: bool,
+                dates: Vec<(u16, u8, u8)>,
+            },
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Sample3(bool, (Vec<u8>, i64));
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        #[expect(clippy::option_option)]
+        struct SomeOptions {
+            maybe_int: Option<u32>,
+            maybe_text: Option<String>,
+            #[serde(rename = "maybe_tuple@3")]
+            maybe_tuple: Option<(u8, u8)>,
+            nested: Option<Option<u8>>,
+            vec_maybe: Vec<Option<bool>>,
+        }
+
+        fn check<'de, T, S>(buf: &'de mut Vec<u8>, value: &T, mirror: &S)
+        where
+            T: Encode,
+            S: std::fmt::Debug + PartialEq + serde::Deserialize<'de> + serde::Serialize,
+        {
+            value.encode(buf);
+            assert_eq!(*buf, mabo::serde::to_vec(mirror).unwrap());
+            assert_eq!(*mirror, mabo::serde::from_slice::<S>(buf).unwrap());
+        }
+
+        check(
+            &mut Vec::new(),
+            &sample::Sample {
+                a: 5,
+                b: true,
+                c: ("Test".into(), -2),
+            },
+            &Sample {
+                a: 5,
+                b: true,
+                c: ("Test".into(), -2),
+            },
+        );
+        check(&mut Vec::new(), &sample::Sample2::Unit, &Sample2::Unit);
+        check(
+            &mut Vec::new(),
+            &sample::Sample2::Tuple(7, 8),
+            &Sample2::Tuple(7, 8),
+        );
+        check(
+            &mut Vec::new(),
+            &sample::Sample2::Fields {
+                name: "this".into(),
+                valid: true,
+                dates: vec![(2023, 1, 1)],
+            },
+            &Sample2::Fields {
+                name: "this".into(),
+                valid: true,
+                dates: vec![(2023, 1, 1)],
+            },
+        );
+        check(
+            &mut Vec::new(),
+            &sample::Sample3(true, (vec![1, 2, 3], -500_000)),
+            &Sample3(true, (vec![1, 2, 3], -500_000)),
+        );
+        check(
+            &mut Vec::new(),
+            &sample::specials::SomeOptions {
+                maybe_int: Some(5),
+                maybe_text: None,
+                maybe_tuple: Some((20, 30)),
+                nested: Some(None),
+                vec_maybe: vec![Some(true), None],
+            },
+            &SomeOptions {
+                maybe_int: Some(5),
+                maybe_text: None,
+                maybe_tuple: Some((20, 30)),
+                nested: Some(None),
+                vec_maybe: vec![Some(true), None],
+            },
+        );
+    }
+
+    #[test]
+    fn error_context() {
+        let mut buf = Vec::new();
+        sample::Sample2::Fields {
+            name: "test".to_owned(),
+            valid: true,
+            dates: vec![(2023, 1, 1), (2024, 2, 2)],
+        }
+        .encode(&mut buf);
+        // Turn the year of the second date into an invalid varint.
+        let year = buf.len() - 5;
+        buf[year + 1] = 0xff;
+        buf[year + 2] = 0xff;
+
+        let err = sample::Sample2::decode(&mut &*buf).unwrap_err();
+        let context = err.context().unwrap();
+
+        assert!(matches!(err.kind(), Error::DecodeInt(_)));
+        assert_eq!("Sample2::Fields.dates[1]", context.path.to_string());
+        assert_eq!(Some(year), context.offset);
+        assert_eq!(
+            format!("failed to decode `Sample2::Fields.dates[1]` at byte offset {year}"),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn limits_depth() {
+        let mut buf = Vec::new();
+        let value = sample::gens::SampleGen2::Value(sample::SampleAlias {
+            a: 1,
+            b: true,
+            c: (String::new(), 2),
+        });
+        value.encode(&mut buf);
+
+        let options = DecodeOptions::new().with_max_depth(2);
+        let value2 = sample::gens::SampleGen2::<sample::SampleAlias>::decode_with(
+            &mut &*buf,
+            &mut options.into(),
+        )
+        .unwrap();
+        assert_eq!(value, value2);
+
+        let options = DecodeOptions::new().with_max_depth(1);
+        assert!(matches!(
+            sample::gens::SampleGen2::<sample::SampleAlias>::decode_with(
+                &mut &*buf,
+                &mut options.into()
+            )
+            .as_ref()
+            .map_err(Error::kind),
+            Err(Error::DepthExceeded),
+        ));
+    }
+
+    #[test]
+    fn limits_length() {
+        let mut buf = Vec::new();
+        sample::specials::SomeOptions {
+            maybe_int: None,
+            maybe_text: None,
+            maybe_tuple: None,
+            nested: None,
+            vec_maybe: vec![None; 5],
+        }
+        .encode(&mut buf);
+
+        let mut limits = Limits::new(DecodeOptions::new().with_max_length(4));
+        assert!(matches!(
+            sample::specials::SomeOptions::decode_with(&mut &*buf, &mut limits)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::LengthExceeded),
+        ));
+    }
+
+    #[test]
+    fn view() {
+        let mut buf = Vec::new();
+        sample::Sample {
+            a: 5,
+            b: true,
+            c: ("test".to_owned(), -3),
+        }
+        .encode(&mut buf);
+
+        let view = sample::SampleView::new(&buf).unwrap();
+        assert_eq!(5, view.a().unwrap());
+        assert!(view.b().unwrap());
+        assert_eq!(("test".to_owned(), -3), view.c().unwrap());
+
+        let mut buf = Vec::new();
+        sample::specials::SomeOptions {
+            maybe_int: Some(5),
+            maybe_text: None,
+            maybe_tuple: None,
+            nested: Some(None),
+            vec_maybe: vec![Some(true)],
+        }
+        .encode(&mut buf);
+
+        let view = sample::specials::SomeOptionsView::new(&buf).unwrap();
+        assert_eq!(Some(5), view.maybe_int().unwrap());
+        assert_eq!(None, view.maybe_text().unwrap());
+        assert_eq!(Some(None), view.nested().unwrap());
+        assert_eq!(vec![Some(true)], view.vec_maybe().unwrap());
+    }
+
+    #[test]
+    fn view_borrowed() {
+        let inner = sample::refs::Inner {
+            label: Some("label"),
+            owned: "owned".to_owned(),
+        };
+        let mut buf = Vec::new();
+        sample::refs::Borrowed {
+            name: "borrowed",
+            raw: &[1, 2, 3],
+            tags: vec!["a", "b"],
+            inner: inner.clone(),
+        }
+        .encode(&mut buf);
+
+        let view = sample::refs::BorrowedView::new(&buf).unwrap();
+        assert_eq!("borrowed", view.name().unwrap());
+        assert_eq!(vec!["a", "b"], view.tags().unwrap());
+        assert_eq!(inner, view.inner().unwrap());
+    }
+
+    #[test]
+    fn view_missing_field() {
+        let mut buf = Vec::new();
+        evolution::Version1 { field1: 5 }.encode(&mut buf);
+
+        let view = evolution::Version2View::new(&buf).unwrap();
+        assert_eq!(5, view.field1().unwrap());
+        assert!(matches!(
+            view.field2().as_ref().map_err(Error::kind),
+            Err(Error::MissingField { id: 2, .. }),
+        ));
+    }
+
+    #[test]
+    fn decode_merge() {
+        let mut profile = sample::specials::Profile {
+            name: "name".to_owned(),
+            bio: Some("bio".to_owned()),
+            tags: vec!["a".to_owned()],
+            settings: sample::specials::Settings {
+                theme: "dark".to_owned(),
+                flags: [1].into(),
+            },
+        };
+
+        let mut buf = Vec::new();
+        sample::specials::ProfileUpdate {
+            tags: vec!["b".to_owned()],
+            settings: Some(sample::specials::SettingsUpdate {
+                flags: [2, 3].into(),
+            }),
+        }
+        .encode(&mut buf);
+        profile.decode_merge(&mut &*buf).unwrap();
+
+        assert_eq!("name", profile.name);
+        assert_eq!(Some("bio"), profile.bio.as_deref());
+        assert_eq!(vec!["a", "b"], profile.tags);
+        assert_eq!("dark", profile.settings.theme);
+        assert_eq!(
+            vec![&1, &2, &3],
+            profile.settings.flags.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn decode_merge_replace() {
+        let mut value = sample::Sample {
+            a: 1,
+            b: false,
+            c: ("old".to_owned(), 1),
+        };
+        let update = sample::Sample {
+            a: 2,
+            b: true,
+            c: ("new".to_owned(), 2),
+        };
+
+        let mut buf = Vec::new();
+        update.encode(&mut buf);
+        value.decode_merge(&mut &*buf).unwrap();
+        assert_eq!(update, value);
+
+        let mut value = sample::Sample2::Unit;
+        buf.clear();
+        sample::Sample2::Tuple(1, 2).encode(&mut buf);
+        value.decode_merge(&mut &*buf).unwrap();
+        assert_eq!(sample::Sample2::Tuple(1, 2), value);
+    }
+
+    #[test]
+    fn limits_bytes() {
+        let mut buf = Vec::new();
+        sample::Sample2::Fields {
+            name: "a".repeat(100),
+            valid: true,
+            dates: Vec::new(),
+        }
+        .encode(&mut buf);
+
+        let mut limits = Limits::new(DecodeOptions::new().with_max_bytes(99));
+        assert!(matches!(
+            sample::Sample2::decode_with(&mut &*buf, &mut limits)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::AllocationExceeded),
+        ));
+    }
+
+    #[test]
+    fn decode_strict() {
+        let mut buf = Vec::new();
+        evolution::Version1 { field1: 5 }.encode(&mut buf);
+        assert_eq!(
+            5,
+            buf::decode_strict::<evolution::Version1>(&buf)
+                .unwrap()
+                .field1
+        );
+
+        // The same field again, followed by the end marker.
+        let mut duplicate = buf.clone();
+        duplicate.insert(duplicate.len() - 1, buf[0]);
+        duplicate.insert(duplicate.len() - 1, 6);
+        assert_eq!(
+            6,
+            evolution::Version1::decode(&mut &*duplicate)
+                .unwrap()
+                .field1
+        );
+        assert!(matches!(
+            buf::decode_strict::<evolution::Version1>(&duplicate)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::DuplicateField(1)),
+        ));
+
+        let mut trailing = buf.clone();
+        trailing.push(0);
+        assert!(matches!(
+            buf::decode_strict::<evolution::Version1>(&trailing)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::TrailingData(1)),
+        ));
+
+        // The value `5` with an additional, empty continuation byte.
+        let non_minimal = [buf[0], 0x85, 0x00, buf[2]];
+        assert!(matches!(
+            buf::decode_strict::<evolution::Version1>(&non_minimal)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::NonMinimalVarint),
+        ));
+    }
+
+    #[test]
+    fn decode_strict_unknown_field() {
+        let mut buf = Vec::new();
+        evolution::Version2 {
+            field1: 5,
+            field2: "Test".to_owned(),
+        }
+        .encode(&mut buf);
+
+        assert!(matches!(
+            buf::decode_strict::<evolution::Version1>(&buf)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::UnknownField(2)),
+        ));
+        assert!(matches!(
+            buf::decode_strict::<evolution::Version1Preserved>(&buf)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::UnknownField(2)),
+        ));
+    }
+
+    #[test]
+    fn decode_strict_unknown_variant() {
+        for (value, id) in [
+            (evolution::EnumVersion2::Two("Test".to_owned()), 2),
+            (evolution::EnumVersion2::Three, 3),
+        ] {
+            let mut buf = Vec::new();
+            value.encode(&mut buf);
+
+            assert!(matches!(
+                buf::decode_strict::<evolution::EnumVersion1Preserved>(&buf)
+                    .as_ref()
+                    .map_err(Error::kind),
+                Err(Error::UnknownVariant(v)) if *v == id,
+            ));
+        }
+    }
+
+    #[test]
+    fn service() {
+        use std::{
+            pin::pin,
+            task::{Context, Poll, Waker},
+        };
+
+        use mabo::service::{self, BoxStream, Handler, Transport};
+        use service_basic::{GetUser, User, Users, UsersClient, UsersServer};
+
+        struct Store;
+
+        impl Users for Store {
+            async fn get(&self, request: GetUser) -> Result<User, service::Error> {
+                Ok(User {
+                    user_id: request.user_id,
+                    name: "Test".to_owned(),
+                })
+            }
+
+            async fn watch(&self, request: u64) -> Result<BoxStream<User>, service::Error> {
+                Ok(service::iter((1..=request).map(|user_id| User {
+                    user_id,
+                    name: "Test".to_owned(),
+                })))
+            }
+
+            async fn import(&self, mut requests: BoxStream<User>) -> Result<u64, service::Error> {
+                let mut count = 0;
+                while let Some(user) = service::next(&mut requests).await {
+                    user?;
+                    count += 1;
+                }
+                Ok(count)
+            }
+
+            async fn sync(
+                &self,
+                requests: BoxStream<User>,
+            ) -> Result<BoxStream<User>, service::Error> {
+                Ok(requests)
+            }
+        }
+
+        /// Transport that passes all calls directly to a server in the same process.
+        struct Local<H>(H);
+
+        impl<H: Handler> Transport for Local<H> {
+            async fn call(
+                &self,
+                method: &'static service::Method,
+                requests: BoxStream<buf::Bytes>,
+            ) -> Result<BoxStream<buf::Bytes>, service::Error> {
+                self.0.handle(method.id, requests).await
+            }
+        }
+
+        fn poll<F: Future>(future: F) -> F::Output {
+            match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+                Poll::Ready(output) => output,
+                Poll::Pending => panic!("future is not ready"),
+            }
+        }
+
+        fn collect(mut stream: BoxStream<User>) -> Vec<u64> {
+            std::iter::from_fn(|| poll(service::next(&mut stream)))
+                .map(|user| user.unwrap().user_id)
+                .collect()
+        }
+
+        let user = |user_id| User {
+            user_id,
+            name: "Test".to_owned(),
+        };
+        let client = UsersClient::new(Local(UsersServer::new(Store)));
+
+        let found = poll(client.get(GetUser { user_id: 5 })).unwrap();
+        assert_eq!(user(5), found);
+
+        let watched = poll(client.watch(3)).unwrap();
+        assert_eq!(vec![1, 2, 3], collect(watched));
+
+        #[expect(deprecated)]
+        let imported = poll(client.import(service::iter([user(1), user(2)]))).unwrap();
+        assert_eq!(2, imported);
+
+        let synced = poll(client.sync(service::iter([user(4), user(2)]))).unwrap();
+        assert_eq!(vec![4, 2], collect(synced));
+
+        assert!(matches!(
+            poll(UsersServer::new(Store).handle(9, service::iter(None))),
+            Err(service::Error::UnknownMethod(9)),
+        ));
+    }
 }
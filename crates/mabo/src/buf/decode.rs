@@ -1,15 +1,27 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Debug,
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Debug, Display},
     hash::Hash,
+    time::Duration,
 };
 
 pub use bytes::{Buf, Bytes};
 
-use crate::{FieldEncoding, FieldId, NonZero, NonZeroBytes, NonZeroString, VariantId, varint};
+use super::{DecodeOptions, Encode, Limits};
+use crate::{
+    Decimal, FieldEncoding, FieldId, NonZero, NonZeroBytes, NonZeroString, UnknownField,
+    UnknownFields, VariantEncoding, VariantId,
+    collections::{HashMap, HashSet},
+    varint,
+};
 
 /// Result type alias for the decoding process, which defaults to the [`Error`] type for errors.
-pub type Result<T, E = Error> = std::result::Result<T, E>;
+pub type Result<T, E = Error> = core::result::Result<T, E>;
 
 /// Error that can happen while trying to decode a Mabo payload.
 #[derive(Debug, thiserror::Error)]
@@ -22,7 +34,10 @@ pub enum Error {
     DecodeInt(#[from] varint::DecodeIntError),
     /// A string value was not encoded in valid UTF-8.
     #[error("string is not valid UTF-8")]
-    NonUtf8(#[from] std::string::FromUtf8Error),
+    NonUtf8(#[from] alloc::string::FromUtf8Error),
+    /// A borrowed string value was not encoded in valid UTF-8.
+    #[error("borrowed string is not valid UTF-8")]
+    NonUtf8Borrowed(#[from] core::str::Utf8Error),
     /// The field of a struct or enum non-optional in the schema, but is missing from the payload.
     #[error("required field is missing from the payload")]
     MissingField {
@@ -40,6 +55,246 @@ pub enum Error {
     /// The value of a non-zero field was actually zero.
     #[error("non-zero value was found to be zero")]
     Zero,
+    /// The value is outside the valid range of its type, like a `timestamp` with more than a
+    /// second worth of nanoseconds, or a `uuid` that isn't exactly 16 bytes long.
+    #[error("value is outside the valid range of its type")]
+    InvalidValue,
+    /// The nesting of structs and enums is deeper than allowed by the [`DecodeOptions`].
+    ///
+    /// [`DecodeOptions`]: super::DecodeOptions
+    #[error("maximum nesting depth exceeded")]
+    DepthExceeded,
+    /// A collection contains more elements than allowed by the [`DecodeOptions`].
+    ///
+    /// [`DecodeOptions`]: super::DecodeOptions
+    #[error("maximum collection length exceeded")]
+    LengthExceeded,
+    /// The decoded value needs more memory than allowed by the [`DecodeOptions`].
+    ///
+    /// [`DecodeOptions`]: super::DecodeOptions
+    #[error("maximum allocation size exceeded")]
+    AllocationExceeded,
+    /// The payload decoded fine, but is not in its canonical form. See [`verify_canonical`].
+    #[error("payload is not canonically encoded")]
+    NonCanonical,
+    /// The same field of a struct or enum variant was found more than once, while decoding in
+    /// strict mode.
+    #[error("encountered a duplicate field")]
+    DuplicateField(u32),
+    /// A field was found that does not exist in the schema, while decoding in strict mode.
+    #[error("encountered an unknown field")]
+    UnknownField(u32),
+    /// The same key of a map or element of a set was found more than once, while decoding in
+    /// strict mode.
+    #[error("encountered a duplicate map key or set element")]
+    DuplicateElement,
+    /// The payload contains the given amount of bytes after the decoded value. See
+    /// [`decode_strict`]. Also reported for length-prefixed values like `timestamp`, that have data
+    /// left over within their length, while decoding in strict mode.
+    #[error("encountered trailing data after the value")]
+    TrailingData(usize),
+    /// A _Varint_ integer was encoded with more bytes than needed, while decoding in strict mode.
+    #[error("varint integer is not minimally encoded")]
+    NonMinimalVarint,
+    /// The decoded value violates the constraints declared in its schema. See
+    /// [`validate`](crate::validate).
+    #[error("decoded value failed validation: {0}")]
+    Validation(crate::validate::Violations),
+    /// Any of the other errors, together with its location inside the payload.
+    #[error(transparent)]
+    Context(Box<ErrorContext>),
+}
+
+impl From<varint::StrictDecodeIntError> for Error {
+    fn from(value: varint::StrictDecodeIntError) -> Self {
+        match value {
+            varint::StrictDecodeIntError::Decode(e) => Self::DecodeInt(e),
+            varint::StrictDecodeIntError::NonMinimal => Self::NonMinimalVarint,
+        }
+    }
+}
+
+impl Error {
+    /// Get the original error, without the context about its location.
+    #[must_use]
+    pub fn kind(&self) -> &Self {
+        match self {
+            Self::Context(context) => &context.source,
+            _ => self,
+        }
+    }
+
+    /// Get the location of the error inside the payload, if known.
+    #[must_use]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::Context(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Add the struct or enum variant field, that failed to decode, to the error's path.
+    #[must_use]
+    pub fn with_field(self, id: u32, name: Option<&'static str>) -> Self {
+        self.with_segment(PathSegment::Field { id, name })
+    }
+
+    /// Add the enum variant, that failed to decode, to the error's path.
+    #[must_use]
+    pub fn with_variant(self, name: &'static str) -> Self {
+        self.with_segment(PathSegment::Variant(name))
+    }
+
+    /// Add the index of the collection element, that failed to decode, to the error's path.
+    #[must_use]
+    pub fn with_index(self, index: usize) -> Self {
+        self.with_segment(PathSegment::Index(index))
+    }
+
+    /// Set the name of the outermost type that was decoded, which is the start of the error's
+    /// path.
+    #[must_use]
+    pub fn with_type(self, name: &'static str) -> Self {
+        self.map_context(|context| context.path.root = Some(name))
+    }
+
+    /// Set the byte offset into the payload, where decoding stopped, unless it is already known.
+    #[must_use]
+    pub fn with_offset(self, offset: usize) -> Self {
+        self.map_context(|context| {
+            context.offset.get_or_insert(offset);
+        })
+    }
+
+    fn with_segment(self, segment: PathSegment) -> Self {
+        self.map_context(|context| context.path.segments.push(segment))
+    }
+
+    fn map_context(self, f: impl FnOnce(&mut ErrorContext)) -> Self {
+        let mut context = match self {
+            Self::Context(context) => context,
+            source => Box::new(ErrorContext {
+                path: FieldPath::default(),
+                offset: None,
+                source,
+            }),
+        };
+
+        f(&mut context);
+        Self::Context(context)
+    }
+
+    /// Add the context, that is only known to the outermost decoder of a payload.
+    fn with_root<T>(self, offset: usize) -> Self {
+        let name = core::any::type_name::<T>();
+        let name = name.split(['<', '(', '[']).next().unwrap_or_default();
+        let name = name.rsplit_once("::").map_or(name, |(_, name)| name);
+
+        let error = self.with_offset(offset);
+        if name.is_empty() {
+            error
+        } else {
+            error.with_type(name)
+        }
+    }
+}
+
+/// Location inside of a payload, where a decoding error happened.
+#[derive(Debug)]
+pub struct ErrorContext {
+    /// Path through the nested types, that leads to the value that failed to decode.
+    pub path: FieldPath,
+    /// Byte offset into the payload, where decoding stopped.
+    ///
+    /// This is only known if the value was decoded as a whole, like with [`Decode::decode`], as
+    /// nested decoders don't know where the payload started.
+    pub offset: Option<usize>,
+    /// The original error.
+    pub source: Error,
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failed to decode")?;
+        if !self.path.is_empty() {
+            write!(f, " `{}`", self.path)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at byte offset {offset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ErrorContext {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Path through nested types, like `Order.items[3].price`, that leads to a specific value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FieldPath {
+    root: Option<&'static str>,
+    /// Segments in reverse order, as they're collected while the decoders unwind.
+    segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    /// Name of the outermost type.
+    #[must_use]
+    pub const fn root(&self) -> Option<&'static str> {
+        self.root
+    }
+
+    /// Iterate over the segments of the path, starting from the outermost type.
+    #[must_use]
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &PathSegment> {
+        self.segments.iter().rev()
+    }
+
+    /// Check whether the path is empty, meaning that nothing about the location is known.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none() && self.segments.is_empty()
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(root) = self.root {
+            f.write_str(root)?;
+        }
+
+        for segment in self.segments() {
+            match segment {
+                PathSegment::Field {
+                    name: Some(name), ..
+                } => write!(f, ".{name}")?,
+                PathSegment::Field { id, name: None } => write!(f, ".{id}")?,
+                PathSegment::Variant(name) => write!(f, "::{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Single step of a [`FieldPath`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PathSegment {
+    /// Field of a struct or enum variant.
+    Field {
+        /// Identifier of the field.
+        id: u32,
+        /// Name of the field (if it is a named field).
+        name: Option<&'static str>,
+    },
+    /// Variant of an enum.
+    Variant(&'static str),
+    /// Element of a collection, or entry of a map.
+    Index(usize),
 }
 
 /// Special field identifier that marks the end of a struct or enum variant.
@@ -55,12 +310,21 @@ macro_rules! ensure_size {
 
 /// Decode a Mabo `bool` (`true` or `false`) value.
 ///
+/// Any non-zero byte is treated as `true`, unless strict decoding is enabled, where only `0` and
+/// `1` are accepted.
+///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value.
-pub fn decode_bool(r: &mut impl Buf) -> Result<bool> {
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or it is
+/// neither `0` nor `1` while decoding in strict mode.
+pub fn decode_bool(r: &mut impl Buf, limits: &Limits) -> Result<bool> {
     ensure_size!(r, 1);
-    Ok(r.get_u8() != 0)
+    match r.get_u8() {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ if limits.is_strict() => Err(Error::InvalidValue),
+        _ => Ok(true),
+    }
 }
 
 /// Decode a Mabo `u8` integer.
@@ -91,9 +355,14 @@ macro_rules! decode_int {
             /// # Errors
             ///
             /// Will return `Err` if the buffer does not have enough remaining data to read the
-            /// value, or the _Varint_ decoding fails due to a missing end marker.
-            pub fn [<decode_ $ty>](r: &mut impl Buf) -> Result<$ty> {
-                let (value, consumed) = varint::[<decode_ $ty>](r.chunk())?;
+            /// value, the _Varint_ decoding fails due to a missing end marker, or it isn't
+            /// minimally encoded while decoding in strict mode.
+            pub fn [<decode_ $ty>](r: &mut impl Buf, limits: &Limits) -> Result<$ty> {
+                let (value, consumed) = if limits.is_strict() {
+                    varint::[<decode_ $ty _strict>](r.chunk())?
+                } else {
+                    varint::[<decode_ $ty>](r.chunk())?
+                };
                 r.advance(consumed);
                 Ok(value)
             }
@@ -131,20 +400,22 @@ pub fn decode_f64(r: &mut impl Buf) -> Result<f64> {
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// string is not valid UTF-8.
-pub fn decode_string(r: &mut impl Buf) -> Result<String> {
-    String::from_utf8(decode_bytes_std(r)?).map_err(Into::into)
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// string is not valid UTF-8, or it exceeds the allocation limit.
+pub fn decode_string(r: &mut impl Buf, limits: &mut Limits) -> Result<String> {
+    String::from_utf8(decode_bytes_std(r, limits)?).map_err(Into::into)
 }
 
 /// Decode a Mabo `bytes` raw byte array (represented as default Rust byte vector).
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value.
-pub fn decode_bytes_std(r: &mut impl Buf) -> Result<Vec<u8>> {
-    let len = decode_u64(r)?;
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or it
+/// exceeds the allocation limit.
+pub fn decode_bytes_std(r: &mut impl Buf, limits: &mut Limits) -> Result<Vec<u8>> {
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
+    limits.allocate(len as usize)?;
 
     Ok(r.copy_to_bytes(len as usize).to_vec())
 }
@@ -153,33 +424,161 @@ pub fn decode_bytes_std(r: &mut impl Buf) -> Result<Vec<u8>> {
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value.
-pub fn decode_bytes_bytes(r: &mut impl Buf) -> Result<Bytes> {
-    let len = decode_u64(r)?;
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or it
+/// exceeds the allocation limit.
+pub fn decode_bytes_bytes(r: &mut impl Buf, limits: &mut Limits) -> Result<Bytes> {
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
+    limits.allocate(len as usize)?;
 
     Ok(r.copy_to_bytes(len as usize))
 }
 
-/// Decode a Mabo `vec<T>` vector value.
+/// Decode a Mabo `timestamp` (represented as [`std::time::SystemTime`] type).
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
+/// point in time is invalid or can't be represented.
+#[cfg(feature = "std")]
+pub fn decode_timestamp_std(r: &mut impl Buf, limits: &Limits) -> Result<std::time::SystemTime> {
+    let (seconds, nanos) = decode_timestamp(r, limits)?;
+    let epoch = std::time::UNIX_EPOCH;
+    let time = if seconds < 0 {
+        epoch.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    } else {
+        epoch.checked_add(Duration::from_secs(seconds.unsigned_abs()))
+    };
+
+    time.and_then(|time| time.checked_add(Duration::from_nanos(nanos.into())))
+        .ok_or(Error::InvalidValue)
+}
+
+/// Decode a Mabo `timestamp` (represented as [`chrono::DateTime`] type in UTC).
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
+/// point in time is invalid or can't be represented.
+#[cfg(feature = "chrono")]
+pub fn decode_timestamp_chrono(
+    r: &mut impl Buf,
+    limits: &Limits,
+) -> Result<chrono::DateTime<chrono::Utc>> {
+    let (seconds, nanos) = decode_timestamp(r, limits)?;
+    chrono::DateTime::from_timestamp(seconds, nanos).ok_or(Error::InvalidValue)
+}
+
+/// Decode a Mabo `timestamp` as seconds and nanoseconds since the Unix epoch, the same way as an
+/// `(i64, u32)` tuple.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
+/// nanoseconds make up more than a second.
+pub fn decode_timestamp(r: &mut impl Buf, limits: &Limits) -> Result<(i64, u32)> {
+    decode_length_prefixed(r, limits, |r| {
+        Ok((decode_i64(r, limits)?, decode_nanos(r, limits)?))
+    })
+}
+
+/// Decode a value that is made up of several parts, within the bounds of its length prefix. Any
+/// trailing data after the parts is skipped, in case a newer version extended the value, unless
+/// strict decoding is enabled.
+fn decode_length_prefixed<R, T>(
+    r: &mut R,
+    limits: &Limits,
+    decode: impl FnOnce(&mut bytes::buf::Take<&mut R>) -> Result<T>,
+) -> Result<T>
+where
+    R: Buf,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_size!(r, len as usize);
+
+    let mut r = r.take(len as usize);
+    let value = decode(&mut r)?;
+    if limits.is_strict() && r.has_remaining() {
+        return Err(Error::TrailingData(r.remaining()));
+    }
+    r.advance(r.remaining());
+
+    Ok(value)
+}
+
+fn decode_nanos(r: &mut impl Buf, limits: &Limits) -> Result<u32> {
+    match decode_u32(r, limits)? {
+        nanos @ 0..1_000_000_000 => Ok(nanos),
+        _ => Err(Error::InvalidValue),
+    }
+}
+
+/// Decode a Mabo `duration`, the same way as an `(u64, u32)` tuple of seconds and nanoseconds.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
+/// nanoseconds make up more than a second.
+pub fn decode_duration(r: &mut impl Buf, limits: &Limits) -> Result<Duration> {
+    decode_length_prefixed(r, limits, |r| {
+        Ok(Duration::new(
+            decode_u64(r, limits)?,
+            decode_nanos(r, limits)?,
+        ))
+    })
+}
+
+/// Decode a Mabo `uuid`, the same way as `bytes` that are always 16 bytes long.
 ///
 /// # Errors
 ///
 /// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// `T` type fails to decode.
-pub fn decode_vec<R, T, D>(r: &mut R, decode: D) -> Result<Vec<T>>
+/// value isn't exactly 16 bytes long.
+pub fn decode_uuid(r: &mut impl Buf, limits: &Limits) -> Result<[u8; 16]> {
+    if decode_u64(r, limits)? != 16 {
+        return Err(Error::InvalidValue);
+    }
+    ensure_size!(r, 16);
+
+    let mut value = [0; 16];
+    r.copy_to_slice(&mut value);
+    Ok(value)
+}
+
+/// Decode a Mabo `decimal` number, the same way as an `(i128, u32)` tuple of mantissa and scale.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value.
+pub fn decode_decimal(r: &mut impl Buf, limits: &Limits) -> Result<Decimal> {
+    decode_length_prefixed(r, limits, |r| {
+        Ok(Decimal::new(
+            decode_i128(r, limits)?,
+            decode_u32(r, limits)?,
+        ))
+    })
+}
+
+/// Decode a Mabo `vec<T>` vector value.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the vector exceeds the decoding limits.
+pub fn decode_vec<R, T, D>(r: &mut R, limits: &mut Limits, decode: D) -> Result<Vec<T>>
 where
     R: Buf,
-    D: Fn(&mut bytes::buf::Take<&mut R>) -> Result<T>,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
 {
-    let len = decode_u64(r)?;
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
 
     let mut vec = Vec::new();
     let mut r = r.take(len as usize);
 
     while r.has_remaining() {
-        vec.push(decode(&mut r)?);
+        limits.push::<T>(vec.len())?;
+        vec.push(decode(&mut r, limits).map_err(|e| e.with_index(vec.len()))?);
     }
 
     Ok(vec)
@@ -189,27 +588,71 @@ where
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// `K`/`V` type fails to decode.
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// `K`/`V` type fails to decode, or the map exceeds the decoding limits.
 pub fn decode_hash_map<R, K, V, DK, DV>(
     r: &mut R,
+    limits: &mut Limits,
     decode_key: DK,
     decode_value: DV,
 ) -> Result<HashMap<K, V>>
 where
     R: Buf,
     K: Hash + Eq,
-    DK: Fn(&mut bytes::buf::Take<&mut R>) -> Result<K>,
-    DV: Fn(&mut bytes::buf::Take<&mut R>) -> Result<V>,
+    DK: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<K>,
+    DV: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<V>,
 {
-    let len = decode_u64(r)?;
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
 
     let mut map = HashMap::new();
     let mut r = r.take(len as usize);
 
     while r.has_remaining() {
-        map.insert(decode_key(&mut r)?, decode_value(&mut r)?);
+        limits.push::<(K, V)>(map.len())?;
+        let index = map.len();
+        let key = decode_key(&mut r, limits).map_err(|e| e.with_index(index))?;
+        let value = decode_value(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(map.insert(key, value).is_none())
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(map)
+}
+
+/// Decode a Mabo `btree_map<K, V>` ordered map value.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// `K`/`V` type fails to decode, or the map exceeds the decoding limits.
+pub fn decode_btree_map<R, K, V, DK, DV>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode_key: DK,
+    decode_value: DV,
+) -> Result<BTreeMap<K, V>>
+where
+    R: Buf,
+    K: Ord,
+    DK: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<K>,
+    DV: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<V>,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_size!(r, len as usize);
+
+    let mut map = BTreeMap::new();
+    let mut r = r.take(len as usize);
+
+    while r.has_remaining() {
+        limits.push::<(K, V)>(map.len())?;
+        let index = map.len();
+        let key = decode_key(&mut r, limits).map_err(|e| e.with_index(index))?;
+        let value = decode_value(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(map.insert(key, value).is_none())
+            .map_err(|e| e.with_index(index))?;
     }
 
     Ok(map)
@@ -219,22 +662,57 @@ where
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// `T` type fails to decode.
-pub fn decode_hash_set<R, T, D>(r: &mut R, decode: D) -> Result<HashSet<T>>
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the set exceeds the decoding limits.
+pub fn decode_hash_set<R, T, D>(r: &mut R, limits: &mut Limits, decode: D) -> Result<HashSet<T>>
 where
     R: Buf,
     T: Hash + Eq,
-    D: Fn(&mut bytes::buf::Take<&mut R>) -> Result<T>,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
 {
-    let len = decode_u64(r)?;
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
 
     let mut set = HashSet::new();
     let mut r = r.take(len as usize);
 
     while r.has_remaining() {
-        set.insert(decode(&mut r)?);
+        limits.push::<T>(set.len())?;
+        let index = set.len();
+        let value = decode(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(set.insert(value))
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(set)
+}
+
+/// Decode a Mabo `btree_set<T>` ordered set value.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the set exceeds the decoding limits.
+pub fn decode_btree_set<R, T, D>(r: &mut R, limits: &mut Limits, decode: D) -> Result<BTreeSet<T>>
+where
+    R: Buf,
+    T: Ord,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_size!(r, len as usize);
+
+    let mut set = BTreeSet::new();
+    let mut r = r.take(len as usize);
+
+    while r.has_remaining() {
+        limits.push::<T>(set.len())?;
+        let index = set.len();
+        let value = decode(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(set.insert(value))
+            .map_err(|e| e.with_index(index))?;
     }
 
     Ok(set)
@@ -244,197 +722,484 @@ where
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// `T` type fails to decode.
-pub fn decode_option<R, T, D>(r: &mut R, decode: D) -> Result<Option<T>>
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// `T` type fails to decode, or the presence marker is neither `0` nor `1` while decoding in strict
+/// mode.
+pub fn decode_option<R, T, D>(r: &mut R, limits: &mut Limits, decode: D) -> Result<Option<T>>
 where
     R: Buf,
-    D: Fn(&mut R) -> Result<T>,
+    D: Fn(&mut R, &mut Limits) -> Result<T>,
 {
-    let some = decode_u8(r)? == 1;
-    if some { decode(r).map(Some) } else { Ok(None) }
+    if decode_presence(r, limits)? {
+        decode(r, limits).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Decode the marker in front of an `option<T>`, that tells whether a value follows. Only `1` marks
+/// a present value, and anything else besides `0` is rejected in strict mode.
+pub(crate) fn decode_presence(r: &mut impl Buf, limits: &Limits) -> Result<bool> {
+    match decode_u8(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ if limits.is_strict() => Err(Error::InvalidValue),
+        _ => Ok(false),
+    }
 }
 
 /// Decode a Mabo `[T; N]` array value.
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// `T` type fails to decode.
-#[expect(clippy::missing_panics_doc)]
-pub fn decode_array<const N: usize, R, T, D>(r: &mut R, decode: D) -> Result<[T; N]>
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, the array exceeds the decoding limits, or it contains less than `N`
+/// values.
+pub fn decode_array<const N: usize, R, T, D>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode: D,
+) -> Result<[T; N]>
 where
     R: Buf,
     T: Debug,
-    D: Fn(&mut bytes::buf::Take<&mut R>) -> Result<T>,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
 {
-    let len = decode_u64(r)?;
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
 
     let mut vec = Vec::new();
     let mut r = r.take(len as usize);
 
     while r.has_remaining() && vec.len() < N {
-        vec.push(decode(&mut r)?);
+        limits.allocate(size_of::<T>())?;
+        vec.push(decode(&mut r, limits).map_err(|e| e.with_index(vec.len()))?);
     }
 
     // skip any remaining values, in case the old array definition was larger.
     r.advance(r.remaining());
 
-    // SAFETY: we can unwrap here, because we ensured the Vec exactly matches
-    // with the length of the array.
-    Ok(vec.try_into().unwrap())
-}
-
-macro_rules! ensure_not_empty {
-    ($size:ident) => {
-        if $size == 0 {
-            return Err(Error::Zero);
-        }
-    };
-}
-
-macro_rules! decode_non_zero_int {
-    ($ty:ty) => {
-        paste::paste! {
-            #[doc = "Decode a Mabo `non_zero<" $ty ">` integer as [`NonZero" $ty:upper "`]."]
-            #[doc = "\n\n[`NonZero" $ty:upper "`]: core::num::NonZero" $ty:upper]
-            ///
-            /// # Errors
-            ///
-            /// Will return `Err` if the buffer does not have enough remaining data to read the
-            /// value, or the integer value is zero.
-            pub fn [<decode_non_zero_ $ty>](
-                r: &mut impl Buf,
-            ) -> Result<std::num::[<NonZero $ty:upper>]> {
-                std::num::[<NonZero $ty:upper>]::new([<decode_ $ty>](r)?)
-                    .ok_or_else(|| Error::Zero)
-            }
-        }
-    };
-    ($($ty:ty),+ $(,)?) => {
-        $(decode_non_zero_int!($ty);)+
-    };
-}
-
-decode_non_zero_int!(u8, u16, u32, u64, u128);
-decode_non_zero_int!(i8, i16, i32, i64, i128);
-
-/// Decode a Mabo `non_zero<string>`.
-///
-/// # Errors
-///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
-/// string is not valid UTF-8, or the string is empty.
-#[expect(clippy::missing_panics_doc)]
-pub fn decode_non_zero_string(r: &mut impl Buf) -> Result<NonZeroString> {
-    String::from_utf8(decode_non_zero_bytes_std(r)?.into_inner())
-        .map(|v| NonZeroString::new(v).unwrap())
-        .map_err(Into::into)
+    vec.try_into().map_err(|_| Error::InsufficientData)
 }
 
-/// Decode a Mabo `non_zero<bytes>` (represented as [`NonZeroBytes`]).
+/// Decode a UTF-8 encoded Mabo `&string`, borrowing the data from the input instead of copying it.
 ///
 /// # Errors
 ///
 /// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// byte array is empty.
-#[expect(clippy::missing_panics_doc)]
-pub fn decode_non_zero_bytes_std(r: &mut impl Buf) -> Result<NonZeroBytes> {
-    let len = decode_u64(r)?;
-    ensure_not_empty!(len);
-    ensure_size!(r, len as usize);
-
-    Ok(NonZero::<Vec<_>>::new(r.copy_to_bytes(len as usize).to_vec()).unwrap())
+/// string is not valid UTF-8.
+pub fn decode_string_borrowed<'de>(r: &mut &'de [u8], limits: &Limits) -> Result<&'de str> {
+    core::str::from_utf8(decode_bytes_borrowed(r, limits)?).map_err(Into::into)
 }
 
-/// Decode a Mabo `non_zero<bytes>` (represented as [`NonZero`]<[`bytes::Bytes`]>).
+/// Decode a Mabo `&bytes` raw byte array, borrowing the data from the input instead of copying it.
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
-/// byte array is empty.
-#[expect(clippy::missing_panics_doc)]
-pub fn decode_non_zero_bytes_bytes(r: &mut impl Buf) -> Result<NonZero<Bytes>> {
-    let len = decode_u64(r)?;
-    ensure_not_empty!(len);
+/// Will return `Err` if the buffer does not have enough remaining data to read the value.
+pub fn decode_bytes_borrowed<'de>(r: &mut &'de [u8], limits: &Limits) -> Result<&'de [u8]> {
+    let len = decode_u64(r, limits)?;
     ensure_size!(r, len as usize);
 
-    Ok(NonZero::<Bytes>::new(r.copy_to_bytes(len as usize)).unwrap())
+    let (value, rest) = r.split_at(len as usize);
+    *r = rest;
+
+    Ok(value)
 }
 
-/// Decode a Mabo `non_zero<vec<T>>`.
+/// Decode a Mabo `vec<T>` vector value, where the `T` type can borrow from the input.
 ///
 /// # Errors
 ///
-/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
-/// collection is empty, or the `T` type fails to decode.
-#[expect(clippy::missing_panics_doc)]
-pub fn decode_non_zero_vec<R, T, D>(r: &mut R, decode: D) -> Result<NonZero<Vec<T>>>
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the vector exceeds the decoding limits.
+pub fn decode_vec_borrowed<'de, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<Vec<T>>
 where
-    R: Buf,
-    D: Fn(&mut bytes::buf::Take<&mut R>) -> Result<T>,
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
 {
-    let len = decode_u64(r)?;
-    ensure_not_empty!(len);
-    ensure_size!(r, len as usize);
-
+    let mut r = decode_bytes_borrowed(r, limits)?;
     let mut vec = Vec::new();
-    let mut r = r.take(len as usize);
 
     while r.has_remaining() {
-        vec.push(decode(&mut r)?);
+        limits.push::<T>(vec.len())?;
+        vec.push(decode(&mut r, limits).map_err(|e| e.with_index(vec.len()))?);
     }
 
-    Ok(NonZero::<Vec<_>>::new(vec).unwrap())
+    Ok(vec)
 }
 
-/// Decode a Mabo `non_zero<hash_map<K, V>>`.
+/// Decode a Mabo `hash_map<K, V>` hash map value, where the `K`/`V` types can borrow from the
+/// input.
 ///
 /// # Errors
 ///
 /// Will return `Err` if the buffer does not have enough remaining data to read the value, the
-/// collection is empty, or the `K`/`V` type fails to decode.
-#[expect(clippy::missing_panics_doc)]
-pub fn decode_non_zero_hash_map<R, K, V, DK, DV>(
-    r: &mut R,
+/// `K`/`V` type fails to decode, or the map exceeds the decoding limits.
+pub fn decode_hash_map_borrowed<'de, K, V, DK, DV>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
     decode_key: DK,
     decode_value: DV,
-) -> Result<NonZero<HashMap<K, V>>>
+) -> Result<HashMap<K, V>>
 where
-    R: Buf,
     K: Hash + Eq,
-    DK: Fn(&mut bytes::buf::Take<&mut R>) -> Result<K>,
-    DV: Fn(&mut bytes::buf::Take<&mut R>) -> Result<V>,
+    DK: Fn(&mut &'de [u8], &mut Limits) -> Result<K>,
+    DV: Fn(&mut &'de [u8], &mut Limits) -> Result<V>,
 {
-    let len = decode_u64(r)?;
-    ensure_not_empty!(len);
+    let mut r = decode_bytes_borrowed(r, limits)?;
+    let mut map = HashMap::new();
+
+    while r.has_remaining() {
+        limits.push::<(K, V)>(map.len())?;
+        let index = map.len();
+        let key = decode_key(&mut r, limits).map_err(|e| e.with_index(index))?;
+        let value = decode_value(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(map.insert(key, value).is_none())
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(map)
+}
+
+/// Decode a Mabo `btree_map<K, V>` ordered map value, where the `K`/`V` types can borrow from the
+/// input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// `K`/`V` type fails to decode, or the map exceeds the decoding limits.
+pub fn decode_btree_map_borrowed<'de, K, V, DK, DV>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode_key: DK,
+    decode_value: DV,
+) -> Result<BTreeMap<K, V>>
+where
+    K: Ord,
+    DK: Fn(&mut &'de [u8], &mut Limits) -> Result<K>,
+    DV: Fn(&mut &'de [u8], &mut Limits) -> Result<V>,
+{
+    let mut r = decode_bytes_borrowed(r, limits)?;
+    let mut map = BTreeMap::new();
+
+    while r.has_remaining() {
+        limits.push::<(K, V)>(map.len())?;
+        let index = map.len();
+        let key = decode_key(&mut r, limits).map_err(|e| e.with_index(index))?;
+        let value = decode_value(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(map.insert(key, value).is_none())
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(map)
+}
+
+/// Decode a Mabo `hash_set<T>` hash set value, where the `T` type can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the set exceeds the decoding limits.
+pub fn decode_hash_set_borrowed<'de, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<HashSet<T>>
+where
+    T: Hash + Eq,
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
+{
+    let mut r = decode_bytes_borrowed(r, limits)?;
+    let mut set = HashSet::new();
+
+    while r.has_remaining() {
+        limits.push::<T>(set.len())?;
+        let index = set.len();
+        let value = decode(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(set.insert(value))
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(set)
+}
+
+/// Decode a Mabo `btree_set<T>` ordered set value, where the `T` type can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the set exceeds the decoding limits.
+pub fn decode_btree_set_borrowed<'de, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<BTreeSet<T>>
+where
+    T: Ord,
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
+{
+    let mut r = decode_bytes_borrowed(r, limits)?;
+    let mut set = BTreeSet::new();
+
+    while r.has_remaining() {
+        limits.push::<T>(set.len())?;
+        let index = set.len();
+        let value = decode(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(set.insert(value))
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(set)
+}
+
+/// Decode a Mabo `[T; N]` array value, where the `T` type can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, the array exceeds the decoding limits, or it contains less than `N`
+/// values.
+pub fn decode_array_borrowed<'de, const N: usize, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<[T; N]>
+where
+    T: Debug,
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
+{
+    let mut r = decode_bytes_borrowed(r, limits)?;
+    let mut vec = Vec::new();
+
+    // any remaining values are skipped, in case the old array definition was larger.
+    while r.has_remaining() && vec.len() < N {
+        limits.allocate(size_of::<T>())?;
+        vec.push(decode(&mut r, limits).map_err(|e| e.with_index(vec.len()))?);
+    }
+
+    vec.try_into().map_err(|_| Error::InsufficientData)
+}
+
+macro_rules! ensure_not_empty {
+    ($size:ident) => {
+        if $size == 0 {
+            return Err(Error::Zero);
+        }
+    };
+}
+
+macro_rules! decode_non_zero_int {
+    ($ty:ty $(, $limits:ident)?) => {
+        paste::paste! {
+            #[doc = "Decode a Mabo `non_zero<" $ty ">` integer as [`NonZero" $ty:upper "`]."]
+            #[doc = "\n\n[`NonZero" $ty:upper "`]: core::num::NonZero" $ty:upper]
+            ///
+            /// # Errors
+            ///
+            /// Will return `Err` if the buffer does not have enough remaining data to read the
+            /// value, or the integer value is zero.
+            pub fn [<decode_non_zero_ $ty>](
+                r: &mut impl Buf,
+                $($limits: &Limits,)?
+            ) -> Result<core::num::[<NonZero $ty:upper>]> {
+                core::num::[<NonZero $ty:upper>]::new([<decode_ $ty>](r, $($limits)?)?)
+                    .ok_or_else(|| Error::Zero)
+            }
+        }
+    };
+    (limits: $($ty:ty),+ $(,)?) => {
+        $(decode_non_zero_int!($ty, limits);)+
+    };
+}
+
+decode_non_zero_int!(u8);
+decode_non_zero_int!(i8);
+decode_non_zero_int!(limits: u16, u32, u64, u128);
+decode_non_zero_int!(limits: i16, i32, i64, i128);
+
+/// Decode a Mabo `non_zero<string>`.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// string is not valid UTF-8, or the string is empty.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_string(r: &mut impl Buf, limits: &mut Limits) -> Result<NonZeroString> {
+    String::from_utf8(decode_non_zero_bytes_std(r, limits)?.into_inner())
+        .map(|v| NonZeroString::new(v).unwrap())
+        .map_err(Into::into)
+}
+
+/// Decode a Mabo `non_zero<bytes>` (represented as [`NonZeroBytes`]).
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
+/// byte array is empty.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_bytes_std(r: &mut impl Buf, limits: &mut Limits) -> Result<NonZeroBytes> {
+    let len = decode_u64(r, limits)?;
+    ensure_not_empty!(len);
+    ensure_size!(r, len as usize);
+    limits.allocate(len as usize)?;
+
+    Ok(NonZero::<Vec<_>>::new(r.copy_to_bytes(len as usize).to_vec()).unwrap())
+}
+
+/// Decode a Mabo `non_zero<bytes>` (represented as [`NonZero`]<[`bytes::Bytes`]>).
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
+/// byte array is empty.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_bytes_bytes(
+    r: &mut impl Buf,
+    limits: &mut Limits,
+) -> Result<NonZero<Bytes>> {
+    let len = decode_u64(r, limits)?;
+    ensure_not_empty!(len);
+    ensure_size!(r, len as usize);
+    limits.allocate(len as usize)?;
+
+    Ok(NonZero::<Bytes>::new(r.copy_to_bytes(len as usize)).unwrap())
+}
+
+/// Decode a Mabo `non_zero<vec<T>>`.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `T` type fails to decode.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_vec<R, T, D>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode: D,
+) -> Result<NonZero<Vec<T>>>
+where
+    R: Buf,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_not_empty!(len);
+    ensure_size!(r, len as usize);
+
+    let mut vec = Vec::new();
+    let mut r = r.take(len as usize);
+
+    while r.has_remaining() {
+        limits.push::<T>(vec.len())?;
+        vec.push(decode(&mut r, limits).map_err(|e| e.with_index(vec.len()))?);
+    }
+
+    Ok(NonZero::<Vec<_>>::new(vec).unwrap())
+}
+
+/// Decode a Mabo `non_zero<hash_map<K, V>>`.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `K`/`V` type fails to decode.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_hash_map<R, K, V, DK, DV>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode_key: DK,
+    decode_value: DV,
+) -> Result<NonZero<HashMap<K, V>>>
+where
+    R: Buf,
+    K: Hash + Eq,
+    DK: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<K>,
+    DV: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<V>,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_not_empty!(len);
     ensure_size!(r, len as usize);
 
     let mut map = HashMap::new();
     let mut r = r.take(len as usize);
 
     while r.has_remaining() {
-        map.insert(decode_key(&mut r)?, decode_value(&mut r)?);
+        limits.push::<(K, V)>(map.len())?;
+        let index = map.len();
+        let key = decode_key(&mut r, limits).map_err(|e| e.with_index(index))?;
+        let value = decode_value(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(map.insert(key, value).is_none())
+            .map_err(|e| e.with_index(index))?;
     }
 
     Ok(NonZero::<HashMap<_, _>>::new(map).unwrap())
 }
 
+/// Decode a Mabo `non_zero<btree_map<K, V>>`.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `K`/`V` type fails to decode.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_btree_map<R, K, V, DK, DV>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode_key: DK,
+    decode_value: DV,
+) -> Result<NonZero<BTreeMap<K, V>>>
+where
+    R: Buf,
+    K: Ord,
+    DK: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<K>,
+    DV: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<V>,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_not_empty!(len);
+    ensure_size!(r, len as usize);
+
+    let mut map = BTreeMap::new();
+    let mut r = r.take(len as usize);
+
+    while r.has_remaining() {
+        limits.push::<(K, V)>(map.len())?;
+        let index = map.len();
+        let key = decode_key(&mut r, limits).map_err(|e| e.with_index(index))?;
+        let value = decode_value(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(map.insert(key, value).is_none())
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(NonZero::<BTreeMap<_, _>>::new(map).unwrap())
+}
+
 /// Decode a Mabo `non_zero<hash_set<T>>`.
 ///
 /// # Errors
 ///
 /// Will return `Err` if the buffer does not have enough remaining data to read the value, the
-/// collection is empty, or the `T` type fails to decode.
+/// collection is empty or exceeds the decoding limits, or the `T` type fails to decode.
 #[expect(clippy::missing_panics_doc)]
-pub fn decode_non_zero_hash_set<R, T, D>(r: &mut R, decode: D) -> Result<NonZero<HashSet<T>>>
+pub fn decode_non_zero_hash_set<R, T, D>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode: D,
+) -> Result<NonZero<HashSet<T>>>
 where
     R: Buf,
     T: Hash + Eq,
-    D: Fn(&mut bytes::buf::Take<&mut R>) -> Result<T>,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
 {
-    let len = decode_u64(r)?;
+    let len = decode_u64(r, limits)?;
     ensure_not_empty!(len);
     ensure_size!(r, len as usize);
 
@@ -442,12 +1207,151 @@ where
     let mut r = r.take(len as usize);
 
     while r.has_remaining() {
-        set.insert(decode(&mut r)?);
+        limits.push::<T>(set.len())?;
+        let index = set.len();
+        let value = decode(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(set.insert(value))
+            .map_err(|e| e.with_index(index))?;
     }
 
     Ok(NonZero::<HashSet<_>>::new(set).unwrap())
 }
 
+/// Decode a Mabo `non_zero<btree_set<T>>`.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `T` type fails to decode.
+#[expect(clippy::missing_panics_doc)]
+pub fn decode_non_zero_btree_set<R, T, D>(
+    r: &mut R,
+    limits: &mut Limits,
+    decode: D,
+) -> Result<NonZero<BTreeSet<T>>>
+where
+    R: Buf,
+    T: Ord,
+    D: Fn(&mut bytes::buf::Take<&mut R>, &mut Limits) -> Result<T>,
+{
+    let len = decode_u64(r, limits)?;
+    ensure_not_empty!(len);
+    ensure_size!(r, len as usize);
+
+    let mut set = BTreeSet::new();
+    let mut r = r.take(len as usize);
+
+    while r.has_remaining() {
+        limits.push::<T>(set.len())?;
+        let index = set.len();
+        let value = decode(&mut r, limits).map_err(|e| e.with_index(index))?;
+        limits
+            .ensure_inserted(set.insert(value))
+            .map_err(|e| e.with_index(index))?;
+    }
+
+    Ok(NonZero::<BTreeSet<_>>::new(set).unwrap())
+}
+
+/// Decode a Mabo `non_zero<vec<T>>`, where the `T` type can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `T` type fails to decode.
+pub fn decode_non_zero_vec_borrowed<'de, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<NonZero<Vec<T>>>
+where
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
+{
+    decode_vec_borrowed(r, limits, decode)
+        .and_then(|vec| NonZero::<Vec<_>>::new(vec).ok_or(Error::Zero))
+}
+
+/// Decode a Mabo `non_zero<hash_map<K, V>>`, where the `K`/`V` types can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `K`/`V` type fails to decode.
+pub fn decode_non_zero_hash_map_borrowed<'de, K, V, DK, DV>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode_key: DK,
+    decode_value: DV,
+) -> Result<NonZero<HashMap<K, V>>>
+where
+    K: Hash + Eq,
+    DK: Fn(&mut &'de [u8], &mut Limits) -> Result<K>,
+    DV: Fn(&mut &'de [u8], &mut Limits) -> Result<V>,
+{
+    decode_hash_map_borrowed(r, limits, decode_key, decode_value)
+        .and_then(|map| NonZero::<HashMap<_, _>>::new(map).ok_or(Error::Zero))
+}
+
+/// Decode a Mabo `non_zero<btree_map<K, V>>`, where the `K`/`V` types can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `K`/`V` type fails to decode.
+pub fn decode_non_zero_btree_map_borrowed<'de, K, V, DK, DV>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode_key: DK,
+    decode_value: DV,
+) -> Result<NonZero<BTreeMap<K, V>>>
+where
+    K: Ord,
+    DK: Fn(&mut &'de [u8], &mut Limits) -> Result<K>,
+    DV: Fn(&mut &'de [u8], &mut Limits) -> Result<V>,
+{
+    decode_btree_map_borrowed(r, limits, decode_key, decode_value)
+        .and_then(|map| NonZero::<BTreeMap<_, _>>::new(map).ok_or(Error::Zero))
+}
+
+/// Decode a Mabo `non_zero<hash_set<T>>`, where the `T` type can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `T` type fails to decode.
+pub fn decode_non_zero_hash_set_borrowed<'de, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<NonZero<HashSet<T>>>
+where
+    T: Hash + Eq,
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
+{
+    decode_hash_set_borrowed(r, limits, decode)
+        .and_then(|set| NonZero::<HashSet<_>>::new(set).ok_or(Error::Zero))
+}
+
+/// Decode a Mabo `non_zero<btree_set<T>>`, where the `T` type can borrow from the input.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// collection is empty or exceeds the decoding limits, or the `T` type fails to decode.
+pub fn decode_non_zero_btree_set_borrowed<'de, T, D>(
+    r: &mut &'de [u8],
+    limits: &mut Limits,
+    decode: D,
+) -> Result<NonZero<BTreeSet<T>>>
+where
+    T: Ord,
+    D: Fn(&mut &'de [u8], &mut Limits) -> Result<T>,
+{
+    decode_btree_set_borrowed(r, limits, decode)
+        .and_then(|set| NonZero::<BTreeSet<_>>::new(set).ok_or(Error::Zero))
+}
+
 /// Decode a Mabo field identifier.
 ///
 /// # Errors
@@ -455,8 +1359,8 @@ where
 /// Will return `Err` if the buffer does not have enough remaining data to read the value, the
 /// value fails to decode or the identifier has an invalid field encoding.
 #[inline]
-pub fn decode_id(r: &mut impl Buf) -> Result<FieldId> {
-    decode_u32(r).and_then(|id| FieldId::from_u32(id).ok_or(Error::UnknownEncoding(id)))
+pub fn decode_id(r: &mut impl Buf, limits: &Limits) -> Result<FieldId> {
+    decode_u32(r, limits).and_then(|id| FieldId::from_u32(id).ok_or(Error::UnknownEncoding(id)))
 }
 
 /// Decode a Mabo enum variant identifier.
@@ -466,8 +1370,8 @@ pub fn decode_id(r: &mut impl Buf) -> Result<FieldId> {
 /// Will return `Err` if the buffer does not have enough remaining data to read the value, or the
 /// value fails to decode.
 #[inline]
-pub fn decode_variant_id(r: &mut impl Buf) -> Result<VariantId> {
-    decode_u32(r).map(VariantId::new)
+pub fn decode_variant_id(r: &mut impl Buf, limits: &Limits) -> Result<VariantId> {
+    decode_u32(r, limits).map(VariantId::from_u32)
 }
 
 /// Decode a field, but skip over the value instead of fully decoding it.
@@ -477,7 +1381,7 @@ pub fn decode_variant_id(r: &mut impl Buf) -> Result<VariantId> {
 /// Will return `Err` if the buffer does not have enough remaining data to skip over the value, or
 /// the decoding of data in fails in the process. For example to skip a _Varint_, it still needs to
 /// decoded partially to find its end.
-pub fn decode_skip(r: &mut impl Buf, encoding: FieldEncoding) -> Result<()> {
+pub fn decode_skip(r: &mut impl Buf, limits: &Limits, encoding: FieldEncoding) -> Result<()> {
     match encoding {
         FieldEncoding::Varint => loop {
             ensure_size!(r, 1);
@@ -486,7 +1390,7 @@ pub fn decode_skip(r: &mut impl Buf, encoding: FieldEncoding) -> Result<()> {
             }
         },
         FieldEncoding::LengthPrefixed => {
-            let len = decode_u64(r)? as usize;
+            let len = decode_u64(r, limits)? as usize;
             ensure_size!(r, len);
             r.advance(len);
             Ok(())
@@ -509,16 +1413,253 @@ pub fn decode_skip(r: &mut impl Buf, encoding: FieldEncoding) -> Result<()> {
     }
 }
 
+/// Skip over a field that is unknown to the schema, unless strict decoding is enabled, in which
+/// case unknown fields are rejected.
+///
+/// # Errors
+///
+/// Will return `Err` if the skipping fails (same as in [`decode_skip`]), or strict decoding is
+/// enabled.
+#[inline]
+pub fn decode_skip_field(r: &mut impl Buf, limits: &Limits, id: FieldId) -> Result<()> {
+    if limits.is_strict() {
+        return Err(Error::UnknownField(id.value));
+    }
+
+    decode_skip(r, limits, id.encoding)
+}
+
+/// Decode a field that is unknown to the schema, but keep its raw data instead of skipping over it.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// decoding of data in fails in the process (same as in [`decode_skip`]), the data exceeds the
+/// allocation limit, or strict decoding is enabled.
+pub fn decode_unknown_field(
+    r: &mut impl Buf,
+    limits: &mut Limits,
+    id: FieldId,
+    fields: &mut UnknownFields,
+) -> Result<()> {
+    if limits.is_strict() {
+        return Err(Error::UnknownField(id.value));
+    }
+
+    let data = decode_raw_data(r, limits, id.encoding)?;
+    fields.push(UnknownField { id, data });
+    Ok(())
+}
+
+/// Decode an enum variant that is unknown to the schema, keeping the raw data of all its fields,
+/// including the end marker. Unit variants don't have any content and result in empty data.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the
+/// decoding of data in fails in the process (same as in [`decode_skip`]), the data exceeds the
+/// allocation limit, or strict decoding is enabled.
+pub fn decode_unknown_variant(
+    r: &mut impl Buf,
+    limits: &mut Limits,
+    id: VariantId,
+) -> Result<Bytes> {
+    if limits.is_strict() {
+        return Err(Error::UnknownVariant(id.value));
+    }
+
+    if id.encoding == VariantEncoding::Unit {
+        return Ok(Bytes::new());
+    }
+
+    let mut data = Vec::new();
+
+    loop {
+        let id = decode_id(r, limits)?;
+        super::encode_id(&mut data, id);
+
+        if id.value == END_MARKER {
+            break;
+        }
+
+        data.extend_from_slice(&decode_raw_data(r, limits, id.encoding)?);
+    }
+
+    Ok(Bytes::from(data))
+}
+
+/// Read the raw data of a single field, as it appears after its identifier.
+fn decode_raw_data(
+    r: &mut impl Buf,
+    limits: &mut Limits,
+    encoding: FieldEncoding,
+) -> Result<Bytes> {
+    Ok(match encoding {
+        FieldEncoding::Varint => {
+            let mut data = Vec::new();
+            loop {
+                ensure_size!(r, 1);
+                let byte = r.get_u8();
+                data.push(byte);
+
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+
+            limits.allocate(data.len())?;
+            Bytes::from(data)
+        }
+        FieldEncoding::LengthPrefixed => {
+            let len = decode_u64(r, limits)?;
+            ensure_size!(r, len as usize);
+
+            let mut data = Vec::new();
+            super::encode_u64(&mut data, len);
+            limits.allocate(data.len() + len as usize)?;
+
+            data.extend_from_slice(&r.copy_to_bytes(len as usize));
+            Bytes::from(data)
+        }
+        FieldEncoding::Fixed1 | FieldEncoding::Fixed4 | FieldEncoding::Fixed8 => {
+            let len = match encoding {
+                FieldEncoding::Fixed1 => 1,
+                FieldEncoding::Fixed4 => 4,
+                _ => 8,
+            };
+
+            ensure_size!(r, len);
+            limits.allocate(len)?;
+            r.copy_to_bytes(len)
+        }
+    })
+}
+
+/// Decode a single value, that is prefixed with its encoded byte size, as written by
+/// [`encode_length_delimited`](super::encode_length_delimited).
+///
+/// The value can never read past its length prefix. Any data of the frame, that the value didn't
+/// consume, is skipped so the buffer is positioned at the start of the next value.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the whole frame, or
+/// the value itself fails to decode.
+pub fn decode_length_delimited<T: Decode>(r: &mut impl Buf) -> Result<T> {
+    decode_length_delimited_with(r, &mut Limits::default())
+}
+
+/// Decode a single value, that is prefixed with its encoded byte size, while ensuring the given
+/// [`Limits`] are respected. See [`decode_length_delimited`] for details.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the whole frame, or
+/// the value itself fails to decode.
+pub fn decode_length_delimited_with<T: Decode>(r: &mut impl Buf, limits: &mut Limits) -> Result<T> {
+    let len = decode_u64(r, limits)? as usize;
+    ensure_size!(r, len);
+
+    let mut r = r.take(len);
+    let value = decode_root(&mut r, limits)?;
+
+    let remaining = r.remaining();
+    r.advance(remaining);
+
+    Ok(value)
+}
+
+/// Decode a value from a payload, and verify that the payload is in its canonical form.
+///
+/// A payload is canonical, if encoding the decoded value again results in the exact same bytes.
+/// Among others, this rejects payloads with trailing data, fields that are unknown to the schema, or
+/// `hash_map` and `hash_set` elements that aren't sorted by their encoded bytes.
+///
+/// The latter only works if the code for `T` was generated with canonical encoding enabled, as the
+/// value would otherwise not be encoded canonically itself.
+///
+/// # Errors
+///
+/// Will return `Err` if the value fails to decode, or the payload is not canonical.
+pub fn verify_canonical<T: Decode + Encode>(data: &[u8]) -> Result<T> {
+    let value = T::decode(&mut &*data)?;
+
+    let mut buf = Vec::with_capacity(data.len());
+    value.encode(&mut buf);
+
+    if buf == data {
+        Ok(value)
+    } else {
+        Err(Error::NonCanonical)
+    }
+}
+
+/// Decode a value from a payload in strict mode, which rejects most ways of encoding the same value
+/// with different bytes.
+///
+/// Besides the checks enabled by [`DecodeOptions::with_strict`], this rejects any data after the
+/// value. Fields with a default value may still be missing from the payload.
+///
+/// Contrary to [`verify_canonical`], the payload isn't encoded again and the order of `hash_map`
+/// and `hash_set` elements is not checked.
+///
+/// # Errors
+///
+/// Will return `Err` if the value fails to decode, or any of the strict checks fails.
+#[inline]
+pub fn decode_strict<T: Decode>(data: &[u8]) -> Result<T> {
+    decode_strict_with(data, DecodeOptions::new())
+}
+
+/// Decode a value from a payload in strict mode (see [`decode_strict`]), while ensuring the
+/// limits from the given options are respected.
+///
+/// # Errors
+///
+/// Will return `Err` if the value fails to decode, any of the limits is exceeded, or any of the
+/// strict checks fails.
+pub fn decode_strict_with<T: Decode>(data: &[u8], options: DecodeOptions) -> Result<T> {
+    let r = &mut &*data;
+    let mut limits = Limits::new(options.with_strict(true));
+    let value = decode_root::<T>(r, &mut limits)?;
+
+    if r.has_remaining() {
+        return Err(Error::TrailingData(r.len()).with_root::<T>(data.len() - r.len()));
+    }
+
+    Ok(value)
+}
+
+/// Decode a value, that makes up a whole payload, and add the type name and byte offset to any
+/// error.
+pub(crate) fn decode_root<T: Decode>(r: &mut impl Buf, limits: &mut Limits) -> Result<T> {
+    let start = r.remaining();
+    T::decode_with(r, limits).map_err(|e| e.with_root::<T>(start - r.remaining()))
+}
+
 /// Values that can decode themselves from Mabo encoded data.
 pub trait Decode: Sized {
-    /// Read the encoded data from the provided buffer.
+    /// Read the encoded data from the provided buffer, without any [`Limits`] in place.
     ///
     /// # Errors
     ///
     /// Will return `Err` if the buffer does not have enough remaining data to read the value, or,
     /// depending on the defined data structure, due to several possible issues that can arise when
     /// trying to decode.
-    fn decode(r: &mut impl Buf) -> Result<Self>;
+    #[inline]
+    fn decode(r: &mut impl Buf) -> Result<Self> {
+        decode_root(r, &mut Limits::default())
+    }
+
+    /// Read the encoded data from the provided buffer, while ensuring the given [`Limits`] are
+    /// respected.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the buffer does not have enough remaining data to read the value, any
+    /// of the limits is exceeded, or, depending on the defined data structure, due to several
+    /// possible issues that can arise when trying to decode.
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self>;
 }
 
 macro_rules! forward {
@@ -526,40 +1667,90 @@ macro_rules! forward {
         paste::paste! {
             impl Decode for $ty {
                 #[inline(always)]
-                fn decode(r: &mut impl Buf) -> Result<Self> {
+                fn decode_with(r: &mut impl Buf, _: &mut Limits) -> Result<Self> {
                     [<decode_ $ty>](r)
                 }
             }
         }
     };
+    ($ty:ty, limits) => {
+        paste::paste! {
+            impl Decode for $ty {
+                #[inline(always)]
+                fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+                    [<decode_ $ty>](r, limits)
+                }
+            }
+        }
+    };
+    (limits: $($ty:ty),+ $(,)?) => {
+        $(forward!($ty, limits);)+
+    };
     ($($ty:ty),+ $(,)?) => {
         $(forward!($ty);)+
     };
 }
 
-forward!(bool);
-forward!(u8, u16, u32, u64, u128);
-forward!(i8, i16, i32, i64, i128);
-forward!(f32, f64);
+forward!(u8, i8, f32, f64);
+forward!(limits: bool, u16, u32, u64, u128);
+forward!(limits: i16, i32, i64, i128);
 
 impl Decode for String {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_string(r)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_string(r, limits)
     }
 }
 
 impl Decode for Box<str> {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_string(r).map(String::into_boxed_str)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_string(r, limits).map(String::into_boxed_str)
     }
 }
 
 impl Decode for Box<[u8]> {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_bytes_std(r).map(Vec::into_boxed_slice)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_bytes_std(r, limits).map(Vec::into_boxed_slice)
+    }
+}
+
+impl Decode for Duration {
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_duration(r, limits)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Decode for std::time::SystemTime {
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_timestamp_std(r, limits)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Decode for chrono::DateTime<chrono::Utc> {
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_timestamp_chrono(r, limits)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Decode for uuid::Uuid {
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_uuid(r, limits).map(Self::from_bytes)
+    }
+}
+
+impl Decode for Decimal {
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_decimal(r, limits)
     }
 }
 
@@ -568,8 +1759,8 @@ where
     T: Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_vec(r, |r| T::decode(r))
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_vec(r, limits, |r, limits| T::decode_with(r, limits))
     }
 }
 
@@ -579,8 +1770,29 @@ where
     V: Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_hash_map(r, |r| K::decode(r), |r| V::decode(r))
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_hash_map(
+            r,
+            limits,
+            |r, limits| K::decode_with(r, limits),
+            |r, limits| V::decode_with(r, limits),
+        )
+    }
+}
+
+impl<K, V> Decode for BTreeMap<K, V>
+where
+    K: Ord + Decode,
+    V: Decode,
+{
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_btree_map(
+            r,
+            limits,
+            |r, limits| K::decode_with(r, limits),
+            |r, limits| V::decode_with(r, limits),
+        )
     }
 }
 
@@ -589,8 +1801,18 @@ where
     T: Hash + Eq + Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_hash_set(r, |r| T::decode(r))
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_hash_set(r, limits, |r, limits| T::decode_with(r, limits))
+    }
+}
+
+impl<T> Decode for BTreeSet<T>
+where
+    T: Ord + Decode,
+{
+    #[inline(always)]
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_btree_set(r, limits, |r, limits| T::decode_with(r, limits))
     }
 }
 
@@ -599,8 +1821,8 @@ where
     T: Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_option(r, T::decode)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_option(r, limits, T::decode_with)
     }
 }
 
@@ -609,37 +1831,218 @@ where
     T: Debug + Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        decode_array(r, |r| T::decode(r))
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        decode_array(r, limits, |r, limits| T::decode_with(r, limits))
     }
 }
 
-impl<T> Decode for std::borrow::Cow<'_, T>
+impl<T> Decode for alloc::borrow::Cow<'_, T>
 where
     T: Copy + Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        T::decode(r).map(std::borrow::Cow::Owned)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        T::decode_with(r, limits).map(alloc::borrow::Cow::Owned)
     }
 }
 
-impl<T> Decode for std::rc::Rc<T>
+impl<T> Decode for alloc::rc::Rc<T>
 where
     T: Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        T::decode(r).map(std::rc::Rc::new)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        T::decode_with(r, limits).map(alloc::rc::Rc::new)
     }
 }
 
-impl<T> Decode for std::sync::Arc<T>
+impl<T> Decode for alloc::sync::Arc<T>
 where
     T: Decode,
 {
     #[inline(always)]
-    fn decode(r: &mut impl Buf) -> Result<Self> {
-        T::decode(r).map(std::sync::Arc::new)
+    fn decode_with(r: &mut impl Buf, limits: &mut Limits) -> Result<Self> {
+        T::decode_with(r, limits).map(alloc::sync::Arc::new)
+    }
+}
+
+/// Append the decoded elements of a vector, map or set to the current ones, the way that
+/// [`DecodeMerge`] handles collections.
+#[inline(always)]
+pub fn merge_extend<C>(current: &mut C, decoded: C)
+where
+    C: IntoIterator + Extend<C::Item>,
+{
+    current.extend(decoded);
+}
+
+/// Values that can decode Mabo encoded data into an existing instance, instead of creating a new
+/// one.
+///
+/// This follows the merge semantics known from Protobuf. Fields that are present in the data
+/// replace the current value, except for vectors, maps and sets, which get the decoded elements
+/// appended, and nested structs, which are merged recursively. Fields that are absent from the
+/// data are left untouched. That allows to apply partial updates, by only encoding the fields that
+/// changed.
+pub trait DecodeMerge: Decode {
+    /// Merge the encoded data from the provided buffer into this value, without any [`Limits`]
+    /// in place.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the buffer does not have enough remaining data to read the value, or,
+    /// depending on the defined data structure, due to several possible issues that can arise when
+    /// trying to decode. The value may be partially updated in that case.
+    #[inline]
+    fn decode_merge(&mut self, r: &mut impl Buf) -> Result<()> {
+        let start = r.remaining();
+        self.decode_merge_with(r, &mut Limits::default())
+            .map_err(|e| e.with_root::<Self>(start - r.remaining()))
+    }
+
+    /// Merge the encoded data from the provided buffer into this value, while ensuring the given
+    /// [`Limits`] are respected.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the buffer does not have enough remaining data to read the value, any
+    /// of the limits is exceeded, or, depending on the defined data structure, due to several
+    /// possible issues that can arise when trying to decode. The value may be partially updated in
+    /// that case.
+    fn decode_merge_with(&mut self, r: &mut impl Buf, limits: &mut Limits) -> Result<()>;
+}
+
+macro_rules! merge_replace {
+    ($ty:ty) => {
+        impl DecodeMerge for $ty {
+            #[inline(always)]
+            fn decode_merge_with(&mut self, r: &mut impl Buf, limits: &mut Limits) -> Result<()> {
+                *self = Self::decode_with(r, limits)?;
+                Ok(())
+            }
+        }
+    };
+    ($($ty:ty),+ $(,)?) => {
+        $(merge_replace!($ty);)+
+    };
+}
+
+merge_replace!(bool);
+merge_replace!(u8, u16, u32, u64, u128);
+merge_replace!(i8, i16, i32, i64, i128);
+merge_replace!(f32, f64);
+merge_replace!(String, Box<str>, Box<[u8]>);
+merge_replace!(Duration, Decimal);
+#[cfg(feature = "std")]
+merge_replace!(std::time::SystemTime);
+#[cfg(feature = "chrono")]
+merge_replace!(chrono::DateTime<chrono::Utc>);
+#[cfg(feature = "uuid")]
+merge_replace!(uuid::Uuid);
+
+impl<const N: usize, T> DecodeMerge for [T; N]
+where
+    T: Debug + Decode,
+{
+    #[inline(always)]
+    fn decode_merge_with(&mut self, r: &mut impl Buf, limits: &mut Limits) -> Result<()> {
+        *self = Self::decode_with(r, limits)?;
+        Ok(())
+    }
+}
+
+macro_rules! merge_collection {
+    ($name:ident < $($gens:ident),+ > $(where $($bounds:tt)+)?) => {
+        impl<$($gens),+> DecodeMerge for $name<$($gens),+>
+        where
+            $($gens: Decode,)+
+            $($($bounds)+)?
+        {
+            #[inline(always)]
+            fn decode_merge_with(&mut self, r: &mut impl Buf, limits: &mut Limits) -> Result<()> {
+                merge_extend(self, Self::decode_with(r, limits)?);
+                Ok(())
+            }
+        }
+    };
+}
+
+merge_collection!(Vec<T>);
+merge_collection!(HashMap<K, V> where K: Hash + Eq,);
+merge_collection!(BTreeMap<K, V> where K: Ord,);
+merge_collection!(HashSet<T> where T: Hash + Eq,);
+merge_collection!(BTreeSet<T> where T: Ord,);
+
+impl<T> DecodeMerge for Option<T>
+where
+    T: DecodeMerge,
+{
+    fn decode_merge_with(&mut self, r: &mut impl Buf, limits: &mut Limits) -> Result<()> {
+        if decode_presence(r, limits)? {
+            match self {
+                Some(value) => value.decode_merge_with(r, limits)?,
+                None => *self = Some(T::decode_with(r, limits)?),
+            }
+        } else {
+            *self = None;
+        }
+
+        Ok(())
+    }
+}
+
+/// Values that can decode themselves from Mabo encoded data, while borrowing parts of the data
+/// directly from the input instead of copying them.
+///
+/// This is automatically implemented for all types that implement [`Decode`], as owned values can
+/// always be decoded from a borrowed input as well.
+pub trait BorrowDecode<'de>: Sized {
+    /// Read the encoded data from the provided byte slice, advancing it past the decoded value.
+    /// No [`Limits`] are in place.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the buffer does not have enough remaining data to read the value, or,
+    /// depending on the defined data structure, due to several possible issues that can arise when
+    /// trying to decode.
+    #[inline]
+    fn borrow_decode(r: &mut &'de [u8]) -> Result<Self> {
+        let start = r.len();
+        Self::borrow_decode_with(r, &mut Limits::default())
+            .map_err(|e| e.with_root::<Self>(start - r.len()))
+    }
+
+    /// Read the encoded data from the provided byte slice, advancing it past the decoded value,
+    /// while ensuring the given [`Limits`] are respected.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the buffer does not have enough remaining data to read the value, any
+    /// of the limits is exceeded, or, depending on the defined data structure, due to several
+    /// possible issues that can arise when trying to decode.
+    fn borrow_decode_with(r: &mut &'de [u8], limits: &mut Limits) -> Result<Self>;
+}
+
+impl<'de, T> BorrowDecode<'de> for T
+where
+    T: Decode,
+{
+    #[inline(always)]
+    fn borrow_decode_with(r: &mut &'de [u8], limits: &mut Limits) -> Result<Self> {
+        T::decode_with(r, limits)
+    }
+}
+
+impl<'de> BorrowDecode<'de> for &'de str {
+    #[inline(always)]
+    fn borrow_decode_with(r: &mut &'de [u8], limits: &mut Limits) -> Result<Self> {
+        decode_string_borrowed(r, limits)
+    }
+}
+
+impl<'de> BorrowDecode<'de> for &'de [u8] {
+    #[inline(always)]
+    fn borrow_decode_with(r: &mut &'de [u8], limits: &mut Limits) -> Result<Self> {
+        decode_bytes_borrowed(r, limits)
     }
 }
@@ -1,8 +1,20 @@
-use std::collections::{HashMap, HashSet};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+
+use core::time::Duration;
 
 pub use bytes::{BufMut, Bytes};
 
-use crate::{FieldId, NonZero, VariantId, varint};
+use super::SizeCache;
+use crate::{
+    Decimal, FieldId, NonZero, UnknownFields, VariantEncoding, VariantId,
+    collections::{HashMap, HashSet},
+    varint,
+};
 
 /// Encode a Mabo `bool` (`true` or `false`) value.
 pub fn encode_bool(w: &mut impl BufMut, value: bool) {
@@ -63,6 +75,57 @@ pub fn encode_bytes_bytes(w: &mut impl BufMut, value: &Bytes) {
     encode_bytes_std(w, value);
 }
 
+/// Encode a Mabo `timestamp` (represented as [`std::time::SystemTime`] type).
+#[cfg(feature = "std")]
+pub fn encode_timestamp_std(w: &mut impl BufMut, value: &std::time::SystemTime) {
+    let (seconds, nanos) = super::size::timestamp_std_parts(value);
+    encode_timestamp(w, seconds, nanos);
+}
+
+/// Encode a Mabo `timestamp` (represented as [`chrono::DateTime`] type in UTC).
+#[cfg(feature = "chrono")]
+pub fn encode_timestamp_chrono(w: &mut impl BufMut, value: &chrono::DateTime<chrono::Utc>) {
+    let (seconds, nanos) = super::size::timestamp_chrono_parts(value);
+    encode_timestamp(w, seconds, nanos);
+}
+
+/// Encode a Mabo `timestamp`, given as seconds and nanoseconds since the Unix epoch, the same way
+/// as an `(i64, u32)` tuple.
+pub fn encode_timestamp(w: &mut impl BufMut, seconds: i64, nanos: u32) {
+    encode_u64(
+        w,
+        (super::size_i64(seconds) + super::size_u32(nanos)) as u64,
+    );
+    encode_i64(w, seconds);
+    encode_u32(w, nanos);
+}
+
+/// Encode a Mabo `duration`, the same way as an `(u64, u32)` tuple of seconds and nanoseconds.
+pub fn encode_duration(w: &mut impl BufMut, value: &Duration) {
+    let (seconds, nanos) = (value.as_secs(), value.subsec_nanos());
+    encode_u64(
+        w,
+        (super::size_u64(seconds) + super::size_u32(nanos)) as u64,
+    );
+    encode_u64(w, seconds);
+    encode_u32(w, nanos);
+}
+
+/// Encode a Mabo `uuid`, the same way as `bytes` that are always 16 bytes long.
+pub fn encode_uuid(w: &mut impl BufMut, value: &[u8; 16]) {
+    encode_bytes_std(w, value);
+}
+
+/// Encode a Mabo `decimal` number, the same way as an `(i128, u32)` tuple of mantissa and scale.
+pub fn encode_decimal(w: &mut impl BufMut, value: &Decimal) {
+    encode_u64(
+        w,
+        (super::size_i128(value.mantissa) + super::size_u32(value.scale)) as u64,
+    );
+    encode_i128(w, value.mantissa);
+    encode_u32(w, value.scale);
+}
+
 /// Encode a Mabo `vec<T>` vector value.
 pub fn encode_vec<W, T, S, E>(w: &mut W, vec: &[T], size: S, encode: E)
 where
@@ -105,6 +168,34 @@ pub fn encode_hash_map<W, K, V, SK, SV, EK, EV>(
     }
 }
 
+/// Encode a Mabo `btree_map<K, V>` ordered map value.
+pub fn encode_btree_map<W, K, V, SK, SV, EK, EV>(
+    w: &mut W,
+    map: &BTreeMap<K, V>,
+    size_key: SK,
+    size_value: SV,
+    encode_key: EK,
+    encode_value: EV,
+) where
+    W: BufMut,
+    SK: Fn(&K) -> usize,
+    SV: Fn(&V) -> usize,
+    EK: Fn(&mut W, &K),
+    EV: Fn(&mut W, &V),
+{
+    encode_u64(
+        w,
+        map.iter()
+            .map(|(k, v)| size_key(k) + size_value(v))
+            .sum::<usize>() as u64,
+    );
+
+    for (key, value) in map {
+        encode_key(w, key);
+        encode_value(w, value);
+    }
+}
+
 /// Encode a Mabo `hash_set<T>` hash set value.
 pub fn encode_hash_set<W, T, S, E>(w: &mut W, set: &HashSet<T>, size: S, encode: E)
 where
@@ -119,11 +210,244 @@ where
     }
 }
 
+/// Encode a Mabo `btree_set<T>` ordered set value.
+pub fn encode_btree_set<W, T, S, E>(w: &mut W, set: &BTreeSet<T>, size: S, encode: E)
+where
+    W: BufMut,
+    S: Fn(&T) -> usize,
+    E: Fn(&mut W, &T),
+{
+    encode_u64(w, set.iter().map(size).sum::<usize>() as u64);
+
+    for value in set {
+        encode(w, value);
+    }
+}
+
+/// Encode a Mabo `hash_map<K, V>` hash map value in its canonical form.
+///
+/// Hash maps iterate their entries in random order, which would otherwise lead to different
+/// encodings of the same value. Here, the entries are sorted by the encoded bytes of their keys
+/// instead, so equal maps always produce the exact same bytes. This comes at the cost of an
+/// additional allocation for each key.
+pub fn encode_hash_map_canonical<W, K, V, SK, SV, EK, EV>(
+    w: &mut W,
+    map: &HashMap<K, V>,
+    size_key: SK,
+    size_value: SV,
+    encode_key: EK,
+    encode_value: EV,
+) where
+    W: BufMut,
+    SK: Fn(&K) -> usize,
+    SV: Fn(&V) -> usize,
+    EK: Fn(&mut Vec<u8>, &K),
+    EV: Fn(&mut W, &V),
+{
+    let mut size = 0;
+    let mut entries = map
+        .iter()
+        .map(|(key, value)| {
+            let mut buf = Vec::with_capacity(size_key(key));
+            encode_key(&mut buf, key);
+            size += buf.len() + size_value(value);
+            (buf, value)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+    encode_u64(w, size as u64);
+
+    for (key, value) in entries {
+        w.put_slice(&key);
+        encode_value(w, value);
+    }
+}
+
+/// Encode a Mabo `hash_set<T>` hash set value in its canonical form.
+///
+/// Like [`encode_hash_map_canonical`], the elements are sorted by their encoded bytes, so equal
+/// sets always produce the exact same bytes.
+pub fn encode_hash_set_canonical<W, T, S, E>(w: &mut W, set: &HashSet<T>, size: S, encode: E)
+where
+    W: BufMut,
+    S: Fn(&T) -> usize,
+    E: Fn(&mut Vec<u8>, &T),
+{
+    let mut elements = set
+        .iter()
+        .map(|value| {
+            let mut buf = Vec::with_capacity(size(value));
+            encode(&mut buf, value);
+            buf
+        })
+        .collect::<Vec<_>>();
+    elements.sort_unstable();
+
+    encode_u64(w, elements.iter().map(Vec::len).sum::<usize>() as u64);
+
+    for value in elements {
+        w.put_slice(&value);
+    }
+}
+
+/// Encode a Mabo `vec<T>` vector value, with its content size taken from the cache.
+pub fn encode_vec_cached<W, T, E>(w: &mut W, sizes: &mut SizeCache, vec: &[T], encode: E)
+where
+    W: BufMut,
+    E: Fn(&mut W, &mut SizeCache, &T),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    for value in vec {
+        encode(w, sizes, value);
+    }
+}
+
+/// Encode a Mabo `hash_map<K, V>` hash map value, with its content size taken from the cache.
+pub fn encode_hash_map_cached<W, K, V, EK, EV>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    map: &HashMap<K, V>,
+    encode_key: EK,
+    encode_value: EV,
+) where
+    W: BufMut,
+    EK: Fn(&mut W, &mut SizeCache, &K),
+    EV: Fn(&mut W, &mut SizeCache, &V),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    for (key, value) in map {
+        encode_key(w, sizes, key);
+        encode_value(w, sizes, value);
+    }
+}
+
+/// Encode a Mabo `btree_map<K, V>` ordered map value, with its content size taken from the cache.
+pub fn encode_btree_map_cached<W, K, V, EK, EV>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    map: &BTreeMap<K, V>,
+    encode_key: EK,
+    encode_value: EV,
+) where
+    W: BufMut,
+    EK: Fn(&mut W, &mut SizeCache, &K),
+    EV: Fn(&mut W, &mut SizeCache, &V),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    for (key, value) in map {
+        encode_key(w, sizes, key);
+        encode_value(w, sizes, value);
+    }
+}
+
+/// Encode a Mabo `hash_set<T>` hash set value, with its content size taken from the cache.
+pub fn encode_hash_set_cached<W, T, E>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    set: &HashSet<T>,
+    encode: E,
+) where
+    W: BufMut,
+    E: Fn(&mut W, &mut SizeCache, &T),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    for value in set {
+        encode(w, sizes, value);
+    }
+}
+
+/// Encode a Mabo `btree_set<T>` ordered set value, with its content size taken from the cache.
+pub fn encode_btree_set_cached<W, T, E>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    set: &BTreeSet<T>,
+    encode: E,
+) where
+    W: BufMut,
+    E: Fn(&mut W, &mut SizeCache, &T),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    for value in set {
+        encode(w, sizes, value);
+    }
+}
+
+/// Encode a Mabo `hash_map<K, V>` hash map value in its canonical form, with its content size
+/// taken from the cache.
+///
+/// Unlike [`encode_hash_map_canonical`], the values are encoded into a separate buffer as well,
+/// as the sizes of their nested values must be taken from the cache in iteration order, before
+/// the entries are sorted.
+pub fn encode_hash_map_canonical_cached<W, K, V, EK, EV>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    map: &HashMap<K, V>,
+    encode_key: EK,
+    encode_value: EV,
+) where
+    W: BufMut,
+    EK: Fn(&mut Vec<u8>, &mut SizeCache, &K),
+    EV: Fn(&mut Vec<u8>, &mut SizeCache, &V),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    let mut entries = map
+        .iter()
+        .map(|(key, value)| {
+            let mut key_buf = Vec::new();
+            encode_key(&mut key_buf, sizes, key);
+            let mut value_buf = Vec::new();
+            encode_value(&mut value_buf, sizes, value);
+            (key_buf, value_buf)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+    for (key, value) in entries {
+        w.put_slice(&key);
+        w.put_slice(&value);
+    }
+}
+
+/// Encode a Mabo `hash_set<T>` hash set value in its canonical form, with its content size taken
+/// from the cache.
+pub fn encode_hash_set_canonical_cached<W, T, E>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    set: &HashSet<T>,
+    encode: E,
+) where
+    W: BufMut,
+    E: Fn(&mut Vec<u8>, &mut SizeCache, &T),
+{
+    encode_u64(w, sizes.take() as u64);
+
+    let mut elements = set
+        .iter()
+        .map(|value| {
+            let mut buf = Vec::new();
+            encode(&mut buf, sizes, value);
+            buf
+        })
+        .collect::<Vec<_>>();
+    elements.sort_unstable();
+
+    for value in elements {
+        w.put_slice(&value);
+    }
+}
+
 /// Encode a Mabo `option<T>` option value.
 pub fn encode_option<W, T, E>(w: &mut W, option: &Option<T>, encode: E)
 where
     W: BufMut,
-    E: Fn(&mut W, &T),
+    E: FnOnce(&mut W, &T),
 {
     if let Some(value) = option {
         w.put_u8(1);
@@ -147,18 +471,42 @@ where
     }
 }
 
+/// Encode a Mabo `[T; N]` array value, with its content size taken from the cache.
+pub fn encode_array_cached<const N: usize, W, T, E>(
+    w: &mut W,
+    sizes: &mut SizeCache,
+    array: &[T; N],
+    encode: E,
+) where
+    W: BufMut,
+    E: Fn(&mut W, &mut SizeCache, &T),
+{
+    encode_vec_cached(w, sizes, array, encode);
+}
+
 /// Encode a Mabo `(T1, T2, ...)` tuple value.
 #[inline(always)]
 pub fn encode_tuple<W, S, E>(w: &mut W, size: S, encode: E)
 where
     W: BufMut,
-    S: Fn() -> usize,
-    E: Fn(&mut W),
+    S: FnOnce() -> usize,
+    E: FnOnce(&mut W),
 {
     encode_u64(w, size() as u64);
     encode(w);
 }
 
+/// Encode a Mabo `(T1, T2, ...)` tuple value, with its content size taken from the cache.
+#[inline(always)]
+pub fn encode_tuple_cached<W, E>(w: &mut W, sizes: &mut SizeCache, encode: E)
+where
+    W: BufMut,
+    E: FnOnce(&mut W, &mut SizeCache),
+{
+    encode_u64(w, sizes.take() as u64);
+    encode(w, sizes);
+}
+
 /// Encode a Mabo field identifier.
 #[inline(always)]
 pub fn encode_id(w: &mut impl BufMut, id: FieldId) {
@@ -168,7 +516,7 @@ pub fn encode_id(w: &mut impl BufMut, id: FieldId) {
 /// Encode a Mabo enum variant identifier.
 #[inline(always)]
 pub fn encode_variant_id(w: &mut impl BufMut, id: VariantId) {
-    encode_u32(w, id.value);
+    encode_u32(w, id.into_u32());
 }
 
 /// Encode a required Mabo struct or enum field.
@@ -176,7 +524,7 @@ pub fn encode_variant_id(w: &mut impl BufMut, id: VariantId) {
 pub fn encode_field<W, E>(w: &mut W, id: FieldId, encode: E)
 where
     W: BufMut,
-    E: Fn(&mut W),
+    E: FnOnce(&mut W),
 {
     encode_id(w, id);
     encode(w);
@@ -187,7 +535,7 @@ where
 pub fn encode_field_option<W, T, E>(w: &mut W, id: FieldId, option: &Option<T>, encode: E)
 where
     W: BufMut,
-    E: Fn(&mut W, &T),
+    E: FnOnce(&mut W, &T),
 {
     if let Some(value) = option {
         encode_id(w, id);
@@ -195,10 +543,124 @@ where
     }
 }
 
+/// Encode all fields, that were unknown to the schema while decoding, back into their original
+/// raw form.
+pub fn encode_unknown_fields(w: &mut impl BufMut, fields: &UnknownFields) {
+    for field in fields {
+        encode_id(w, field.id);
+        w.put_slice(&field.data);
+    }
+}
+
+/// Encode an enum variant, that was unknown to the schema while decoding, back into its original
+/// raw form.
+pub fn encode_unknown_variant(w: &mut impl BufMut, id: u32, data: &Bytes) {
+    let encoding = if data.is_empty() {
+        VariantEncoding::Unit
+    } else {
+        VariantEncoding::Fields
+    };
+
+    encode_variant_id(w, VariantId::new(id, encoding));
+    w.put_slice(data);
+}
+
+/// Encode a single value, prefixed with its encoded byte size.
+///
+/// This allows to put several values into the same buffer, one after another, and later decode
+/// them individually with [`decode_length_delimited`](super::decode_length_delimited).
+pub fn encode_length_delimited(w: &mut impl BufMut, value: &impl Encode) {
+    let mut sizes = SizeCache::new();
+    encode_u64(w, value.size_cached(&mut sizes) as u64);
+    value.encode_cached(w, &mut sizes);
+}
+
+/// Encode a single value, by calculating the sizes of all its length-prefixed parts in one pass
+/// up front, instead of recalculating them for every level of nesting.
+///
+/// This is the usual implementation of [`Encode::encode`] for types that override
+/// [`Encode::encode_cached`].
+pub fn encode_sized<T>(w: &mut impl BufMut, value: &T)
+where
+    T: Encode + ?Sized,
+{
+    let mut sizes = SizeCache::new();
+    value.size_cached(&mut sizes);
+    value.encode_cached(w, &mut sizes);
+}
+
+/// Error that occurs when the encoded form of a value doesn't fit into the target buffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("encoding requires {required} bytes, but only {available} are available")]
+pub struct CapacityError {
+    /// Amount of bytes that the encoded value occupies.
+    pub required: usize,
+    /// Amount of bytes that were left in the target buffer.
+    pub available: usize,
+}
+
 /// Values that can encode themselves in the Mabo format.
 pub trait Encode: super::Size {
     /// Write the encoded data in the provided buffer.
+    ///
+    /// # Panics
+    ///
+    /// The buffer may panic if it can't hold all the encoded data, which is the case for
+    /// fixed-size buffers like `&mut [u8]`. Use [`Self::try_encode`] or
+    /// [`Self::encode_to_slice`] to get an error instead.
     fn encode(&self, w: &mut impl BufMut);
+
+    /// Write the encoded data in the provided buffer, taking the content size of each
+    /// length-prefixed value from the cache, instead of calculating it again.
+    ///
+    /// The cache must have been filled by calling [`Size::size_cached`](super::Size::size_cached)
+    /// on the same value. Values without any length-prefixed parts don't use the cache, which is
+    /// what the default implementation does.
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, _sizes: &mut SizeCache) {
+        self.encode(w);
+    }
+
+    /// Write the encoded data in the provided buffer, after checking that it has enough space
+    /// left for it.
+    ///
+    /// Nothing is written if the encoded value doesn't fit into the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the [`size`](super::Size::size) of the encoded value is larger than
+    /// the remaining capacity of the buffer.
+    fn try_encode(&self, w: &mut impl BufMut) -> Result<(), CapacityError> {
+        let mut sizes = SizeCache::new();
+        let required = self.size_cached(&mut sizes);
+        let available = w.remaining_mut();
+        if required > available {
+            return Err(CapacityError {
+                required,
+                available,
+            });
+        }
+
+        self.encode_cached(w, &mut sizes);
+        Ok(())
+    }
+
+    /// Write the encoded data to the start of the given slice, and return the amount of bytes
+    /// written.
+    ///
+    /// This never allocates, and makes the encoding usable with stack buffers or pre-allocated
+    /// regions of a ring buffer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the encoded value doesn't fit into the slice. In that case, the slice
+    /// is left untouched.
+    fn encode_to_slice(&self, buf: &mut [u8]) -> Result<usize, CapacityError> {
+        let capacity = buf.len();
+        let mut w = buf;
+        self.try_encode(&mut w)?;
+        Ok(capacity - w.len())
+    }
 }
 
 macro_rules! forward {
@@ -243,13 +705,56 @@ impl Encode for Box<[u8]> {
     }
 }
 
+impl Encode for Duration {
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_duration(w, self);
+    }
+}
+
+#[cfg(feature = "std")]
+impl Encode for std::time::SystemTime {
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_timestamp_std(w, self);
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Encode for chrono::DateTime<chrono::Utc> {
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_timestamp_chrono(w, self);
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Encode for uuid::Uuid {
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_uuid(w, self.as_bytes());
+    }
+}
+
+impl Encode for Decimal {
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_decimal(w, self);
+    }
+}
+
 impl<T> Encode for Vec<T>
 where
     T: Encode,
 {
     #[inline(always)]
     fn encode(&self, w: &mut impl BufMut) {
-        encode_vec(w, self, T::size, |w, v| v.encode(w));
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_vec_cached(w, sizes, self, |w, sizes, v| v.encode_cached(w, sizes));
     }
 }
 
@@ -259,7 +764,12 @@ where
 {
     #[inline(always)]
     fn encode(&self, w: &mut impl BufMut) {
-        encode_vec(w, self, T::size, |w, v| v.encode(w));
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_vec_cached(w, sizes, self, |w, sizes, v| v.encode_cached(w, sizes));
     }
 }
 
@@ -270,13 +780,39 @@ where
 {
     #[inline(always)]
     fn encode(&self, w: &mut impl BufMut) {
-        encode_hash_map(
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_hash_map_cached(
             w,
+            sizes,
             self,
-            K::size,
-            V::size,
-            |w, k| k.encode(w),
-            |w, v| v.encode(w),
+            |w, sizes, k| k.encode_cached(w, sizes),
+            |w, sizes, v| v.encode_cached(w, sizes),
+        );
+    }
+}
+
+impl<K, V> Encode for BTreeMap<K, V>
+where
+    K: Encode,
+    V: Encode,
+{
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_btree_map_cached(
+            w,
+            sizes,
+            self,
+            |w, sizes, k| k.encode_cached(w, sizes),
+            |w, sizes, v| v.encode_cached(w, sizes),
         );
     }
 }
@@ -287,7 +823,27 @@ where
 {
     #[inline(always)]
     fn encode(&self, w: &mut impl BufMut) {
-        encode_hash_set(w, self, T::size, |w, v| v.encode(w));
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_hash_set_cached(w, sizes, self, |w, sizes, v| v.encode_cached(w, sizes));
+    }
+}
+
+impl<T> Encode for BTreeSet<T>
+where
+    T: Encode,
+{
+    #[inline(always)]
+    fn encode(&self, w: &mut impl BufMut) {
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_btree_set_cached(w, sizes, self, |w, sizes, v| v.encode_cached(w, sizes));
     }
 }
 
@@ -299,6 +855,11 @@ where
     fn encode(&self, w: &mut impl BufMut) {
         encode_option(w, self, |w, v| v.encode(w));
     }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_option(w, self, |w, v| v.encode_cached(w, sizes));
+    }
 }
 
 impl<const N: usize, T> Encode for [T; N]
@@ -307,7 +868,12 @@ where
 {
     #[inline(always)]
     fn encode(&self, w: &mut impl BufMut) {
-        encode_array(w, self, T::size, |w, v| v.encode(w));
+        encode_sized(w, self);
+    }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        encode_array_cached(w, sizes, self, |w, sizes, v| v.encode_cached(w, sizes));
     }
 }
 
@@ -319,9 +885,14 @@ where
     fn encode(&self, w: &mut impl BufMut) {
         self.0.encode(w);
     }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        self.0.encode_cached(w, sizes);
+    }
 }
 
-impl<T> Encode for std::borrow::Cow<'_, T>
+impl<T> Encode for alloc::borrow::Cow<'_, T>
 where
     T: Clone + Encode,
 {
@@ -329,9 +900,14 @@ where
     fn encode(&self, w: &mut impl BufMut) {
         T::encode(self, w);
     }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        T::encode_cached(self, w, sizes);
+    }
 }
 
-impl<T> Encode for std::rc::Rc<T>
+impl<T> Encode for alloc::rc::Rc<T>
 where
     T: Encode,
 {
@@ -339,9 +915,14 @@ where
     fn encode(&self, w: &mut impl BufMut) {
         T::encode(self, w);
     }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        T::encode_cached(self, w, sizes);
+    }
 }
 
-impl<T> Encode for std::sync::Arc<T>
+impl<T> Encode for alloc::sync::Arc<T>
 where
     T: Encode,
 {
@@ -349,4 +930,9 @@ where
     fn encode(&self, w: &mut impl BufMut) {
         T::encode(self, w);
     }
+
+    #[inline(always)]
+    fn encode_cached(&self, w: &mut impl BufMut, sizes: &mut SizeCache) {
+        T::encode_cached(self, w, sizes);
+    }
 }
@@ -0,0 +1,203 @@
+use super::{Error, Result};
+
+/// Options to restrict the resources that the decoding of a single value may use.
+///
+/// This is mostly useful when decoding data from untrusted sources, to avoid excessive memory
+/// usage or stack overflows caused by maliciously crafted payloads. By default, all limits are
+/// disabled and strict decoding is turned off.
+///
+/// # Example
+///
+/// ```
+/// use mabo::buf::{Decode, DecodeOptions, Limits};
+///
+/// let options = DecodeOptions::new()
+///     .with_max_depth(32)
+///     .with_max_length(1000)
+///     .with_max_bytes(64 * 1024);
+///
+/// let value = Vec::<u32>::decode_with(&mut &[0_u8][..], &mut Limits::new(options)).unwrap();
+/// assert!(value.is_empty());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodeOptions {
+    depth: usize,
+    length: usize,
+    bytes: usize,
+    strict: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecodeOptions {
+    /// Create a new instance of the options, with all limits disabled.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            depth: usize::MAX,
+            length: usize::MAX,
+            bytes: usize::MAX,
+            strict: false,
+        }
+    }
+
+    /// Limit the maximum nesting depth of structs and enums.
+    #[must_use]
+    pub const fn with_max_depth(mut self, value: usize) -> Self {
+        self.depth = value;
+        self
+    }
+
+    /// Limit the maximum amount of elements in a single collection (`vec`, `hash_map` and
+    /// `hash_set`).
+    #[must_use]
+    pub const fn with_max_length(mut self, value: usize) -> Self {
+        self.length = value;
+        self
+    }
+
+    /// Limit the total amount of bytes that may be allocated for the decoded value. This counts
+    /// the content of strings and byte arrays, as well as the in-memory size of collection
+    /// elements.
+    #[must_use]
+    pub const fn with_max_bytes(mut self, value: usize) -> Self {
+        self.bytes = value;
+        self
+    }
+
+    /// Reject payloads that repeat the same field of a struct or enum variant, contain fields that
+    /// are unknown to the schema, or repeat the same key of a map or element of a set. _Varint_
+    /// integers must use the least amount of bytes, and `bool` values as well as the markers of
+    /// `option<T>` values must be either `0` or `1`.
+    ///
+    /// Usually these are accepted, where the last occurrence of a field or key wins. See
+    /// [`decode_strict`](super::decode_strict) for decoding a whole payload in strict mode.
+    #[must_use]
+    pub const fn with_strict(mut self, value: bool) -> Self {
+        self.strict = value;
+        self
+    }
+}
+
+/// Tracking state of the [`DecodeOptions`] during the decoding of a single value.
+///
+/// It is passed through all the decoding functions, which report the nesting depth and
+/// allocations to it. Once any limit is exceeded, the decoding is aborted with an error.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    options: DecodeOptions,
+    depth: usize,
+    bytes: usize,
+}
+
+impl From<DecodeOptions> for Limits {
+    fn from(options: DecodeOptions) -> Self {
+        Self::new(options)
+    }
+}
+
+impl Limits {
+    /// Create a new tracking state for the given options.
+    #[must_use]
+    pub const fn new(options: DecodeOptions) -> Self {
+        Self {
+            options,
+            depth: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Run the given decoding function one nesting level deeper.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the maximum nesting depth is exceeded, or the decoding function itself
+    /// fails.
+    #[inline]
+    pub fn nested<T>(&mut self, decode: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= self.options.depth {
+            return Err(Error::DepthExceeded);
+        }
+
+        self.depth += 1;
+        let value = decode(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    /// Ensure, that a collection with the given amount of elements is allowed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the length exceeds the maximum collection length.
+    #[inline]
+    pub fn ensure_length(&self, len: usize) -> Result<()> {
+        if len > self.options.length {
+            return Err(Error::LengthExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Register an allocation of the given amount of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the allocation exceeds the remaining byte budget.
+    #[inline]
+    pub fn allocate(&mut self, bytes: usize) -> Result<()> {
+        match self.bytes.checked_add(bytes) {
+            Some(total) if total <= self.options.bytes => {
+                self.bytes = total;
+                Ok(())
+            }
+            _ => Err(Error::AllocationExceeded),
+        }
+    }
+
+    /// Whether strict decoding is enabled in the [`DecodeOptions`].
+    #[inline]
+    #[must_use]
+    pub const fn is_strict(&self) -> bool {
+        self.options.strict
+    }
+
+    /// Ensure, that the field with the given identifier wasn't decoded before, in case strict
+    /// decoding is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if strict decoding is enabled and the field is already `present`.
+    #[inline]
+    pub fn ensure_new_field(&self, id: u32, present: bool) -> Result<()> {
+        if present && self.options.strict {
+            return Err(Error::DuplicateField(id));
+        }
+
+        Ok(())
+    }
+
+    /// Ensure, that a collection element was actually added to a map or set, instead of replacing
+    /// an existing one, in case strict decoding is enabled.
+    #[inline]
+    pub(crate) fn ensure_inserted(&self, inserted: bool) -> Result<()> {
+        if !inserted && self.options.strict {
+            return Err(Error::DuplicateElement);
+        }
+
+        Ok(())
+    }
+
+    /// Register a new element, that is added to a collection which already contains `len`
+    /// elements.
+    #[inline]
+    pub(crate) fn push<T>(&mut self, len: usize) -> Result<()> {
+        self.ensure_length(len + 1)?;
+        self.allocate(size_of::<T>())
+    }
+}
@@ -2,15 +2,22 @@
 
 pub use decode::*;
 pub use encode::*;
+pub use limits::*;
+pub use packed::*;
 pub use size::*;
 
 mod decode;
 mod encode;
+mod limits;
+mod packed;
 mod size;
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, HashSet};
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fmt::Debug,
+    };
 
     use super::*;
 
@@ -18,7 +25,7 @@ mod tests {
     fn non_zero_string_valid() {
         let mut buf = Vec::new();
         encode_string(&mut buf, "test");
-        assert!(decode_non_zero_string(&mut &*buf).is_ok());
+        assert!(decode_non_zero_string(&mut &*buf, &mut Limits::default()).is_ok());
     }
 
     #[test]
@@ -26,7 +33,7 @@ mod tests {
         let mut buf = Vec::new();
         encode_string(&mut buf, "");
         assert!(matches!(
-            decode_non_zero_string(&mut &*buf),
+            decode_non_zero_string(&mut &*buf, &mut Limits::default()),
             Err(Error::Zero),
         ));
     }
@@ -35,7 +42,7 @@ mod tests {
     fn non_zero_bytes_std_valid() {
         let mut buf = Vec::new();
         encode_bytes_std(&mut buf, &[1, 2, 3]);
-        assert!(decode_non_zero_bytes_std(&mut &*buf).is_ok());
+        assert!(decode_non_zero_bytes_std(&mut &*buf, &mut Limits::default()).is_ok());
     }
 
     #[test]
@@ -43,7 +50,7 @@ mod tests {
         let mut buf = Vec::new();
         encode_bytes_std(&mut buf, &[]);
         assert!(matches!(
-            decode_non_zero_bytes_std(&mut &*buf),
+            decode_non_zero_bytes_std(&mut &*buf, &mut Limits::default()),
             Err(Error::Zero),
         ));
     }
@@ -52,7 +59,7 @@ mod tests {
     fn non_zero_bytes_bytes_valid() {
         let mut buf = Vec::new();
         encode_bytes_bytes(&mut buf, &Bytes::from_static(&[1, 2, 3]));
-        assert!(decode_non_zero_bytes_bytes(&mut &*buf).is_ok());
+        assert!(decode_non_zero_bytes_bytes(&mut &*buf, &mut Limits::default()).is_ok());
     }
 
     #[test]
@@ -60,7 +67,7 @@ mod tests {
         let mut buf = Vec::new();
         encode_bytes_bytes(&mut buf, &Bytes::from_static(&[]));
         assert!(matches!(
-            decode_non_zero_bytes_bytes(&mut &*buf),
+            decode_non_zero_bytes_bytes(&mut &*buf, &mut Limits::default()),
             Err(Error::Zero),
         ));
     }
@@ -74,7 +81,12 @@ mod tests {
             |v| size_u32(*v),
             |w, v| encode_u32(w, *v),
         );
-        assert!(decode_non_zero_vec(&mut &*buf, |r| decode_u32(r)).is_ok());
+        assert!(
+            decode_non_zero_vec(&mut &*buf, &mut Limits::default(), |r, limits| decode_u32(
+                r, limits
+            ))
+            .is_ok()
+        );
     }
 
     #[test]
@@ -82,7 +94,9 @@ mod tests {
         let mut buf = Vec::new();
         encode_vec(&mut buf, &[], |v| size_u32(*v), |w, v| encode_u32(w, *v));
         assert!(matches!(
-            decode_non_zero_vec(&mut &*buf, |r| decode_u32(r)),
+            decode_non_zero_vec(&mut &*buf, &mut Limits::default(), |r, limits| decode_u32(
+                r, limits
+            )),
             Err(Error::Zero),
         ));
     }
@@ -99,7 +113,13 @@ mod tests {
             |w, v| encode_bool(w, *v),
         );
         assert!(
-            decode_non_zero_hash_map(&mut &*buf, |r| decode_u32(r), |r| decode_bool(r)).is_ok()
+            decode_non_zero_hash_map(
+                &mut &*buf,
+                &mut Limits::default(),
+                |r, limits| decode_u32(r, limits),
+                |r, limits| decode_bool(r, limits)
+            )
+            .is_ok()
         );
     }
 
@@ -115,7 +135,12 @@ mod tests {
             |w, v| encode_bool(w, *v),
         );
         assert!(matches!(
-            decode_non_zero_hash_map(&mut &*buf, |r| decode_u32(r), |r| decode_bool(r)),
+            decode_non_zero_hash_map(
+                &mut &*buf,
+                &mut Limits::default(),
+                |r, limits| decode_u32(r, limits),
+                |r, limits| decode_bool(r, limits)
+            ),
             Err(Error::Zero),
         ));
     }
@@ -131,7 +156,12 @@ mod tests {
                 encode_u32(w, *v);
             },
         );
-        assert!(decode_non_zero_hash_set(&mut &*buf, |r| decode_u32(r)).is_ok());
+        assert!(
+            decode_non_zero_hash_set(&mut &*buf, &mut Limits::default(), |r, limits| decode_u32(
+                r, limits
+            ))
+            .is_ok()
+        );
     }
 
     #[test]
@@ -144,8 +174,632 @@ mod tests {
             |w, v| encode_u32(w, *v),
         );
         assert!(matches!(
-            decode_non_zero_hash_set(&mut &*buf, |r| decode_u32(r)),
+            decode_non_zero_hash_set(&mut &*buf, &mut Limits::default(), |r, limits| decode_u32(
+                r, limits
+            )),
+            Err(Error::Zero),
+        ));
+    }
+
+    #[test]
+    fn non_zero_btree_map_valid() {
+        let mut buf = Vec::new();
+        encode_btree_map(
+            &mut buf,
+            &BTreeMap::from_iter([(1, true), (2, false)]),
+            |k| size_u32(*k),
+            |v| size_bool(*v),
+            |w, k| encode_u32(w, *k),
+            |w, v| encode_bool(w, *v),
+        );
+        assert!(
+            decode_non_zero_btree_map(
+                &mut &*buf,
+                &mut Limits::default(),
+                |r, limits| decode_u32(r, limits),
+                |r, limits| decode_bool(r, limits)
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn non_zero_btree_map_invalid() {
+        let mut buf = Vec::new();
+        encode_btree_map(
+            &mut buf,
+            &BTreeMap::new(),
+            |k| size_u32(*k),
+            |v| size_bool(*v),
+            |w, k| encode_u32(w, *k),
+            |w, v| encode_bool(w, *v),
+        );
+        assert!(matches!(
+            decode_non_zero_btree_map(
+                &mut &*buf,
+                &mut Limits::default(),
+                |r, limits| decode_u32(r, limits),
+                |r, limits| decode_bool(r, limits)
+            ),
             Err(Error::Zero),
         ));
     }
+
+    #[test]
+    fn packed_matches_generic() {
+        fn check<T: Packed + Decode + Debug + PartialEq>(
+            values: &[T],
+            size: impl Fn(&T) -> usize,
+            encode: impl Fn(&mut Vec<u8>, &T),
+        ) {
+            let mut generic = Vec::new();
+            encode_vec(&mut generic, values, size, encode);
+
+            let mut packed = Vec::new();
+            encode_vec_packed(&mut packed, values);
+            assert_eq!(generic, packed);
+            assert_eq!(generic.len(), size_vec_packed(values));
+
+            let decoded = decode_vec_packed::<T>(&mut &*packed, &mut Limits::default()).unwrap();
+            assert_eq!(values, decoded);
+
+            let decoded = decode_vec(&mut &*packed, &mut Limits::default(), |r, limits| {
+                T::decode_with(r, limits)
+            })
+            .unwrap();
+            assert_eq!(values, decoded);
+        }
+
+        check(
+            &[true, false, true],
+            |v| size_bool(*v),
+            |w, v| encode_bool(w, *v),
+        );
+        check(&[0, 1, 255_u8], |v| size_u8(*v), |w, v| encode_u8(w, *v));
+        check(
+            &[i8::MIN, 0, i8::MAX],
+            |v| size_i8(*v),
+            |w, v| encode_i8(w, *v),
+        );
+        check(
+            &[-1.5, 0.0, 1e9_f32],
+            |v| size_f32(*v),
+            |w, v| encode_f32(w, *v),
+        );
+        check(
+            &[-1.5, 0.0, 1e300_f64],
+            |v| size_f64(*v),
+            |w, v| encode_f64(w, *v),
+        );
+        check(
+            &[0, 300, u16::MAX],
+            |v| size_u16(*v),
+            |w, v| encode_u16(w, *v),
+        );
+        check(
+            &[0, 300, u32::MAX],
+            |v| size_u32(*v),
+            |w, v| encode_u32(w, *v),
+        );
+        check(
+            &[0, 300, u64::MAX],
+            |v| size_u64(*v),
+            |w, v| encode_u64(w, *v),
+        );
+        check(
+            &[0, 300, u128::MAX],
+            |v| size_u128(*v),
+            |w, v| encode_u128(w, *v),
+        );
+        check(
+            &[i16::MIN, -1, i16::MAX],
+            |v| size_i16(*v),
+            |w, v| encode_i16(w, *v),
+        );
+        check(
+            &[i32::MIN, -1, i32::MAX],
+            |v| size_i32(*v),
+            |w, v| encode_i32(w, *v),
+        );
+        check(
+            &[i64::MIN, -1, i64::MAX],
+            |v| size_i64(*v),
+            |w, v| encode_i64(w, *v),
+        );
+        check(
+            &[i128::MIN, -1, i128::MAX],
+            |v| size_i128(*v),
+            |w, v| encode_i128(w, *v),
+        );
+
+        let large = (0..10_000_u32).map(|v| v * 997).collect::<Vec<_>>();
+        check(&large, |v| size_u32(*v), |w, v| encode_u32(w, *v));
+        let large = (0..10_000_u16).map(f64::from).collect::<Vec<_>>();
+        check(&large, |v| size_f64(*v), |w, v| encode_f64(w, *v));
+    }
+
+    #[test]
+    fn short_array() {
+        assert!(matches!(
+            <[String; 2]>::decode(&mut &[0][..])
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::InsufficientData),
+        ));
+    }
+
+    #[test]
+    fn short_array_borrowed() {
+        let mut buf = Vec::new();
+        encode_array(
+            &mut buf,
+            &["a"],
+            |v| size_string(v),
+            |w, v| encode_string(w, v),
+        );
+        assert!(matches!(
+            decode_array_borrowed::<2, &str, _>(&mut &*buf, &mut Limits::default(), |r, limits| {
+                decode_string_borrowed(r, limits)
+            }),
+            Err(Error::InsufficientData),
+        ));
+    }
+
+    #[test]
+    fn packed_array() {
+        let mut buf = Vec::new();
+        encode_array_packed(&mut buf, &[1_u32, 2, 3]);
+        assert_eq!(
+            [1, 2],
+            decode_array_packed::<2, u32>(&mut &*buf, &mut Limits::default()).unwrap()
+        );
+        assert!(matches!(
+            decode_array_packed::<4, u32>(&mut &*buf, &mut Limits::default()),
+            Err(Error::InsufficientData),
+        ));
+    }
+
+    #[test]
+    fn packed_invalid_length() {
+        let mut buf = Vec::new();
+        encode_bytes_std(&mut buf, &[0, 0, 0, 0, 0]);
+        assert!(matches!(
+            decode_vec_packed::<f32>(&mut &*buf, &mut Limits::default()),
+            Err(Error::InsufficientData),
+        ));
+    }
+
+    #[test]
+    fn packed_limits() {
+        let mut buf = Vec::new();
+        encode_vec_packed(&mut buf, &[1.0_f64; 10]);
+        let mut limits = Limits::new(DecodeOptions::new().with_max_length(5));
+        assert!(matches!(
+            decode_vec_packed::<f64>(&mut &*buf, &mut limits),
+            Err(Error::LengthExceeded),
+        ));
+    }
+
+    #[test]
+    fn limits_length_exceeded() {
+        let mut buf = Vec::new();
+        encode_vec(
+            &mut buf,
+            &[1_u32, 2, 3],
+            |v| size_u32(*v),
+            |w, v| encode_u32(w, *v),
+        );
+
+        let mut limits = Limits::new(DecodeOptions::new().with_max_length(3));
+        assert!(Vec::<u32>::decode_with(&mut &*buf, &mut limits).is_ok());
+
+        let mut limits = Limits::new(DecodeOptions::new().with_max_length(2));
+        assert!(matches!(
+            Vec::<u32>::decode_with(&mut &*buf, &mut limits),
+            Err(Error::LengthExceeded),
+        ));
+    }
+
+    #[test]
+    fn limits_allocation_exceeded() {
+        let mut buf = Vec::new();
+        encode_string(&mut buf, "test");
+        encode_string(&mut buf, "test");
+
+        let mut limits = Limits::new(DecodeOptions::new().with_max_bytes(6));
+        assert!(String::decode_with(&mut &*buf, &mut limits).is_ok());
+        assert!(matches!(
+            String::decode_with(&mut &buf[5..], &mut limits),
+            Err(Error::AllocationExceeded),
+        ));
+    }
+
+    #[test]
+    fn limits_depth_exceeded() {
+        let mut limits = Limits::new(DecodeOptions::new().with_max_depth(2));
+        assert!(limits.nested(|l| l.nested(|_| Ok(()))).is_ok());
+        assert!(matches!(
+            limits.nested(|l| l.nested(|l| l.nested(|_| Ok(())))),
+            Err(Error::DepthExceeded),
+        ));
+        assert!(limits.nested(|_| Ok(())).is_ok());
+    }
+
+    #[test]
+    fn length_delimited() {
+        let mut buf = Vec::new();
+        encode_length_delimited(&mut buf, &vec![1_u32, 2, 3]);
+        encode_length_delimited(&mut buf, &"test".to_owned());
+        assert_eq!(
+            size_length_delimited(&vec![1_u32, 2, 3]) + size_length_delimited(&"test".to_owned()),
+            buf.len(),
+        );
+
+        let mut r = &*buf;
+        assert_eq!(
+            vec![1, 2, 3],
+            decode_length_delimited::<Vec<u32>>(&mut r).unwrap()
+        );
+        assert_eq!("test", decode_length_delimited::<String>(&mut r).unwrap());
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn encode_to_slice() {
+        let value = vec![1_u32, 2, 300];
+        let mut buf = [0; 8];
+        assert_eq!(Ok(5), value.encode_to_slice(&mut buf));
+        assert_eq!(value, Vec::<u32>::decode(&mut &buf[..5]).unwrap());
+    }
+
+    #[test]
+    fn encode_to_slice_too_small() {
+        let mut buf = [0xff; 4];
+        assert_eq!(
+            Err(CapacityError {
+                required: 5,
+                available: 4,
+            }),
+            vec![1_u32, 2, 300].encode_to_slice(&mut buf),
+        );
+        assert_eq!([0xff; 4], buf);
+    }
+
+    #[test]
+    fn try_encode() {
+        let mut buf = Vec::new();
+        assert!("test".to_owned().try_encode(&mut buf).is_ok());
+        assert_eq!(5, buf.len());
+
+        let mut buf = [0; 2];
+        assert!(matches!(
+            "test".to_owned().try_encode(&mut &mut buf[..]),
+            Err(CapacityError {
+                required: 5,
+                available: 2,
+            }),
+        ));
+    }
+
+    #[test]
+    fn size_cache_matches_uncached() {
+        let value = vec![
+            vec![vec!["a".to_owned()], vec![]],
+            vec![vec!["bc".to_owned(), "def".to_owned()]],
+        ];
+
+        let mut expected = Vec::new();
+        encode_vec(
+            &mut expected,
+            &value,
+            |v| size_vec(v, |v| size_vec(v, |v| size_string(v))),
+            |w, v| {
+                encode_vec(
+                    w,
+                    v,
+                    |v| size_vec(v, |v| size_string(v)),
+                    |w, v| encode_vec(w, v, |v| size_string(v), |w, v| encode_string(w, v)),
+                );
+            },
+        );
+
+        let mut sizes = SizeCache::new();
+        assert_eq!(expected.len(), value.size_cached(&mut sizes));
+        assert_eq!(expected.len(), value.size());
+
+        let mut buf = Vec::new();
+        value.encode_cached(&mut buf, &mut sizes);
+        assert_eq!(expected, buf);
+
+        buf.clear();
+        value.encode(&mut buf);
+        assert_eq!(expected, buf);
+    }
+
+    #[test]
+    fn size_cache_reuse() {
+        let mut sizes = SizeCache::new();
+        let mut buf = Vec::new();
+
+        for value in [vec![vec![1_u32, 2]], vec![vec![], vec![300]]] {
+            sizes.clear();
+            buf.clear();
+            value.size_cached(&mut sizes);
+            value.encode_cached(&mut buf, &mut sizes);
+            assert_eq!(value.size(), buf.len());
+            assert_eq!(
+                value,
+                decode_vec(&mut &*buf, &mut Limits::default(), |r, limits| {
+                    decode_vec(r, limits, |r, limits| decode_u32(r, limits))
+                })
+                .unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn hash_map_canonical_cached() {
+        let map = (0..100_u32)
+            .map(|k| (k, vec![vec![k; (k % 3) as usize]]))
+            .collect::<HashMap<_, _>>();
+
+        let mut expected = Vec::new();
+        encode_hash_map_canonical(
+            &mut expected,
+            &map,
+            |k| size_u32(*k),
+            Size::size,
+            |w, k| encode_u32(w, *k),
+            |w, v| v.encode(w),
+        );
+
+        let mut sizes = SizeCache::new();
+        size_hash_map_cached(
+            &mut sizes,
+            &map,
+            |_, k| size_u32(*k),
+            |sizes, v| v.size_cached(sizes),
+        );
+
+        let mut buf = Vec::new();
+        encode_hash_map_canonical_cached(
+            &mut buf,
+            &mut sizes,
+            &map,
+            |w, _, k| encode_u32(w, *k),
+            |w, sizes, v| v.encode_cached(w, sizes),
+        );
+        assert_eq!(expected, buf);
+    }
+
+    #[test]
+    fn decode_merge() {
+        let mut buf = Vec::new();
+        vec![3_u32, 4].encode(&mut buf);
+        let mut vec = vec![1_u32, 2];
+        vec.decode_merge(&mut &*buf).unwrap();
+        assert_eq!(vec![1, 2, 3, 4], vec);
+
+        buf.clear();
+        Some(vec![2_u32]).encode(&mut buf);
+        let mut option = Some(vec![1_u32]);
+        option.decode_merge(&mut &*buf).unwrap();
+        assert_eq!(Some(vec![1, 2]), option);
+
+        buf.clear();
+        None::<Vec<u32>>.encode(&mut buf);
+        option.decode_merge(&mut &*buf).unwrap();
+        assert_eq!(None, option);
+    }
+
+    #[test]
+    fn decode_strict_duplicate_element() {
+        let mut buf = Vec::new();
+        vec![1_u32, 1].encode(&mut buf);
+
+        assert_eq!(1, HashSet::<u32>::decode(&mut &*buf).unwrap().len());
+        assert!(matches!(
+            decode_strict::<HashSet<u32>>(&buf)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::DuplicateElement),
+        ));
+        assert!(matches!(
+            decode_strict::<BTreeMap<u32, u32>>(&[4, 1, 2, 1, 3])
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::DuplicateElement),
+        ));
+    }
+
+    #[test]
+    fn decode_strict_length_prefix() {
+        let mut buf = Vec::new();
+        vec![1_u32, 2].encode(&mut buf);
+        assert_eq!(vec![1, 2], decode_strict::<Vec<u32>>(&buf).unwrap());
+
+        // The length prefix `2` with an additional, empty continuation byte.
+        let non_minimal = [0x82, 0x00, buf[1], buf[2]];
+        assert!(matches!(
+            decode_strict::<Vec<u32>>(&non_minimal)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::NonMinimalVarint),
+        ));
+    }
+
+    #[test]
+    fn decode_strict_markers() {
+        assert!(bool::decode(&mut &[2][..]).unwrap());
+        assert!(matches!(
+            decode_strict::<bool>(&[2]).as_ref().map_err(Error::kind),
+            Err(Error::InvalidValue),
+        ));
+
+        assert_eq!(None, Option::<u8>::decode(&mut &[2, 1][..]).unwrap());
+        assert!(matches!(
+            decode_strict::<Option<u8>>(&[2, 1])
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::InvalidValue),
+        ));
+    }
+
+    #[test]
+    fn decode_strict_well_known() {
+        // newer versions may append more parts, which are rejected in strict mode.
+        let buf = [4, 2, 3, 7, 7];
+        assert_eq!(
+            core::time::Duration::new(2, 3),
+            core::time::Duration::decode(&mut &buf[..]).unwrap()
+        );
+        assert!(matches!(
+            decode_strict::<core::time::Duration>(&buf)
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::TrailingData(2)),
+        ));
+
+        // the seconds `2` with an additional, empty continuation byte.
+        assert!(matches!(
+            decode_strict::<core::time::Duration>(&[3, 0x82, 0x00, 3])
+                .as_ref()
+                .map_err(Error::kind),
+            Err(Error::NonMinimalVarint),
+        ));
+    }
+
+    #[test]
+    fn decode_strict_defaults() {
+        /// Struct with a single `attempts: u32 = 3 @1` field.
+        #[derive(Debug, PartialEq)]
+        struct Retry(u32);
+
+        impl Decode for Retry {
+            fn decode_with(r: &mut impl bytes::Buf, limits: &mut Limits) -> Result<Self> {
+                let mut attempts = None;
+                loop {
+                    let id = decode_id(r, limits)?;
+                    match id.value {
+                        END_MARKER => break,
+                        1 => attempts = Some(decode_u32(r, limits)?),
+                        _ => decode_skip_field(r, limits, id)?,
+                    }
+                }
+
+                Ok(Self(attempts.unwrap_or(3)))
+            }
+        }
+
+        let mut buf = Vec::new();
+        encode_field(
+            &mut buf,
+            crate::FieldId::new(1, crate::FieldEncoding::Varint),
+            |w| {
+                encode_u32(w, 3);
+            },
+        );
+        encode_u32(&mut buf, END_MARKER);
+        assert_eq!(Retry(3), decode_strict::<Retry>(&buf).unwrap());
+
+        // The field is missing, but its default value is filled in.
+        let missing = [buf[2]];
+        assert_eq!(Retry(3), decode_strict::<Retry>(&missing).unwrap());
+    }
+
+    #[test]
+    fn timestamp_before_epoch() {
+        let value = std::time::UNIX_EPOCH - std::time::Duration::new(1, 250);
+        let mut buf = Vec::new();
+        encode_timestamp_std(&mut buf, &value);
+        assert_eq!(size_timestamp_std(&value), buf.len());
+        assert_eq!(
+            (-2, 999_999_750),
+            decode_timestamp(&mut &*buf, &Limits::default()).unwrap()
+        );
+        assert_eq!(
+            value,
+            decode_timestamp_std(&mut &*buf, &Limits::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn timestamp_invalid_nanos() {
+        let mut buf = Vec::new();
+        encode_timestamp(&mut buf, 0, 1_000_000_000);
+        assert!(matches!(
+            decode_timestamp(&mut &*buf, &Limits::default()),
+            Err(Error::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn well_known_roundtrip() {
+        let duration = core::time::Duration::new(90, 500);
+        let mut buf = Vec::new();
+        encode_duration(&mut buf, &duration);
+        assert_eq!(size_duration(&duration), buf.len());
+        assert_eq!(
+            duration,
+            decode_duration(&mut &*buf, &Limits::default()).unwrap()
+        );
+
+        let decimal = crate::Decimal::new(-12_345, 3);
+        buf.clear();
+        encode_decimal(&mut buf, &decimal);
+        assert_eq!(size_decimal(&decimal), buf.len());
+        assert_eq!(
+            decimal,
+            decode_decimal(&mut &*buf, &Limits::default()).unwrap()
+        );
+
+        let uuid = [0xab; 16];
+        buf.clear();
+        encode_uuid(&mut buf, &uuid);
+        assert_eq!(size_uuid(&uuid), buf.len());
+        assert_eq!(uuid, decode_uuid(&mut &*buf, &Limits::default()).unwrap());
+    }
+
+    #[test]
+    fn well_known_length_prefix() {
+        // newer versions may append more parts, which are skipped.
+        let buf = [4, 2, 3, 7, 7, 1];
+        assert_eq!(
+            (1, 3),
+            decode_timestamp(&mut &buf[..], &Limits::default()).unwrap()
+        );
+        assert_eq!(
+            core::time::Duration::new(2, 3),
+            decode_duration(&mut &buf[..], &Limits::default()).unwrap()
+        );
+        assert_eq!(
+            crate::Decimal::new(1, 3),
+            decode_decimal(&mut &buf[..], &Limits::default()).unwrap()
+        );
+
+        // parts must not be read beyond the declared length.
+        let buf = [1, 2, 3];
+        assert!(matches!(
+            decode_timestamp(&mut &buf[..], &Limits::default()),
+            Err(Error::DecodeInt(_))
+        ));
+        assert!(matches!(
+            decode_duration(&mut &buf[..], &Limits::default()),
+            Err(Error::DecodeInt(_))
+        ));
+        assert!(matches!(
+            decode_decimal(&mut &buf[..], &Limits::default()),
+            Err(Error::DecodeInt(_))
+        ));
+    }
+
+    #[test]
+    fn uuid_invalid_length() {
+        let mut buf = Vec::new();
+        encode_bytes_std(&mut buf, &[1, 2, 3]);
+        assert!(matches!(
+            decode_uuid(&mut &*buf, &Limits::default()),
+            Err(Error::InvalidValue)
+        ));
+    }
 }
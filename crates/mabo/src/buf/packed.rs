@@ -0,0 +1,248 @@
+use alloc::vec::Vec;
+
+use bytes::{Buf, BufMut};
+
+use super::{Error, Limits, Result, decode_u64, encode_u64, size_u64};
+use crate::{NonZero, varint};
+
+/// Size of the stack buffer that elements are collected in, before they're written out to the
+/// destination buffer in one go.
+const BATCH_SIZE: usize = 256;
+
+/// Primitive element type of a `vec<T>` or `[T; N]`, that is en- and decoded as a whole slice.
+///
+/// The wire format of a packed vector or array is exactly the same as the one written by
+/// [`encode_vec`](super::encode_vec) and [`encode_array`](super::encode_array). Only the way of
+/// getting there differs, as the content is handled by a single call for the whole slice, instead
+/// of calling a closure for each element.
+///
+/// It is implemented for all fixed-width types (`bool`, `u8`, `i8`, `f32` and `f64`), and the
+/// _Varint_ encoded integers. Only `u8` values are copied as they are, all other types still
+/// convert each element into its wire representation. The encoded elements are collected in a
+/// stack buffer, which is written to the destination once it's full.
+pub trait Packed: Sized {
+    /// Calculate the encoded size of all the values, without the length prefix.
+    fn size_packed(values: &[Self]) -> usize;
+
+    /// Encode all the values in sequence, without the length prefix.
+    fn encode_packed(w: &mut impl BufMut, values: &[Self]);
+
+    /// Decode all values from the given data, which is exactly the content of one vector or array.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data doesn't contain a whole number of values, or the values
+    /// exceed the decoding limits.
+    fn decode_packed(data: &[u8], limits: &mut Limits) -> Result<Vec<Self>>;
+}
+
+fn encode_fixed<T: Copy, const W: usize>(
+    w: &mut impl BufMut,
+    values: &[T],
+    to_bytes: impl Fn(T) -> [u8; W],
+) {
+    let mut buf = [0; BATCH_SIZE];
+
+    for chunk in values.chunks(BATCH_SIZE / W) {
+        for (dst, value) in buf.chunks_exact_mut(W).zip(chunk) {
+            dst.copy_from_slice(&to_bytes(*value));
+        }
+        w.put_slice(&buf[..chunk.len() * W]);
+    }
+}
+
+fn decode_fixed<T, const W: usize>(
+    data: &[u8],
+    limits: &mut Limits,
+    from_bytes: impl Fn([u8; W]) -> T,
+) -> Result<Vec<T>> {
+    if !data.len().is_multiple_of(W) {
+        return Err(Error::InsufficientData);
+    }
+
+    let len = data.len() / W;
+    limits.ensure_length(len)?;
+    limits.allocate(len * size_of::<T>())?;
+
+    Ok(data
+        .chunks_exact(W)
+        .map(|chunk| {
+            let mut bytes = [0; W];
+            bytes.copy_from_slice(chunk);
+            from_bytes(bytes)
+        })
+        .collect())
+}
+
+impl Packed for u8 {
+    #[inline]
+    fn size_packed(values: &[Self]) -> usize {
+        values.len()
+    }
+
+    #[inline]
+    fn encode_packed(w: &mut impl BufMut, values: &[Self]) {
+        w.put_slice(values);
+    }
+
+    fn decode_packed(data: &[u8], limits: &mut Limits) -> Result<Vec<Self>> {
+        limits.ensure_length(data.len())?;
+        limits.allocate(data.len())?;
+        Ok(data.to_vec())
+    }
+}
+
+macro_rules! packed_fixed {
+    ($ty:ty => $width:literal, $to_bytes:expr, $from_bytes:expr) => {
+        impl Packed for $ty {
+            #[inline]
+            fn size_packed(values: &[Self]) -> usize {
+                values.len() * $width
+            }
+
+            fn encode_packed(w: &mut impl BufMut, values: &[Self]) {
+                encode_fixed::<_, $width>(w, values, $to_bytes);
+            }
+
+            fn decode_packed(data: &[u8], limits: &mut Limits) -> Result<Vec<Self>> {
+                decode_fixed::<_, $width>(data, limits, $from_bytes)
+            }
+        }
+    };
+}
+
+packed_fixed!(bool => 1, |v| [v.into()], |[b]| b != 0);
+packed_fixed!(i8 => 1, i8::to_be_bytes, i8::from_be_bytes);
+packed_fixed!(f32 => 4, f32::to_be_bytes, f32::from_be_bytes);
+packed_fixed!(f64 => 8, f64::to_be_bytes, f64::from_be_bytes);
+
+macro_rules! packed_varint {
+    ($ty:ty) => {
+        paste::paste! {
+            impl Packed for $ty {
+                fn size_packed(values: &[Self]) -> usize {
+                    values.iter().map(|v| varint::[<size_ $ty>](*v)).sum()
+                }
+
+                fn encode_packed(w: &mut impl BufMut, values: &[Self]) {
+                    let mut buf = [0; BATCH_SIZE];
+                    let mut pos = 0;
+
+                    for value in values {
+                        let (bytes, len) = varint::[<encode_ $ty>](*value);
+                        if pos + len > BATCH_SIZE {
+                            w.put_slice(&buf[..pos]);
+                            pos = 0;
+                        }
+                        buf[pos..pos + len].copy_from_slice(&bytes[..len]);
+                        pos += len;
+                    }
+
+                    w.put_slice(&buf[..pos]);
+                }
+
+                fn decode_packed(mut data: &[u8], limits: &mut Limits) -> Result<Vec<Self>> {
+                    let mut vec = Vec::new();
+
+                    while !data.is_empty() {
+                        limits.push::<Self>(vec.len())?;
+                        let (value, consumed) = varint::[<decode_ $ty>](data)
+                            .map_err(|e| Error::from(e).with_index(vec.len()))?;
+                        vec.push(value);
+                        data = &data[consumed..];
+                    }
+
+                    Ok(vec)
+                }
+            }
+        }
+    };
+    ($($ty:ty),+ $(,)?) => {
+        $(packed_varint!($ty);)+
+    };
+}
+
+packed_varint!(u16, u32, u64, u128);
+packed_varint!(i16, i32, i64, i128);
+
+/// Calculate the size of a Mabo `vec<T>` vector value, with a [`Packed`] element type.
+pub fn size_vec_packed<T: Packed>(vec: &[T]) -> usize {
+    let size = T::size_packed(vec);
+    size_u64(size as u64) + size
+}
+
+/// Calculate the size of a Mabo `[T; N]` array value, with a [`Packed`] element type.
+pub fn size_array_packed<const N: usize, T: Packed>(array: &[T; N]) -> usize {
+    size_vec_packed(array)
+}
+
+/// Encode a Mabo `vec<T>` vector value, with a [`Packed`] element type.
+pub fn encode_vec_packed<T: Packed>(w: &mut impl BufMut, vec: &[T]) {
+    encode_u64(w, T::size_packed(vec) as u64);
+    T::encode_packed(w, vec);
+}
+
+/// Encode a Mabo `[T; N]` array value, with a [`Packed`] element type.
+pub fn encode_array_packed<const N: usize, T: Packed>(w: &mut impl BufMut, array: &[T; N]) {
+    encode_vec_packed(w, array);
+}
+
+/// Decode a Mabo `vec<T>` vector value, with a [`Packed`] element type.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, or the vector exceeds the decoding limits.
+pub fn decode_vec_packed<T: Packed>(r: &mut impl Buf, limits: &mut Limits) -> Result<Vec<T>> {
+    let len = decode_u64(r, limits)? as usize;
+    if r.remaining() < len {
+        return Err(Error::InsufficientData);
+    }
+
+    // Decode straight from the buffer if the content is contiguous, which is always the case for
+    // byte slices. Otherwise, it is collected into a single chunk first.
+    if r.chunk().len() >= len {
+        let vec = T::decode_packed(&r.chunk()[..len], limits)?;
+        r.advance(len);
+        Ok(vec)
+    } else {
+        T::decode_packed(&r.copy_to_bytes(len), limits)
+    }
+}
+
+/// Decode a Mabo `non_zero<vec<T>>` vector value, with a [`Packed`] element type.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, the vector exceeds the decoding limits, or it is empty.
+pub fn decode_non_zero_vec_packed<T: Packed>(
+    r: &mut impl Buf,
+    limits: &mut Limits,
+) -> Result<NonZero<Vec<T>>> {
+    let vec = decode_vec_packed(r, limits)?;
+    if vec.is_empty() {
+        return Err(Error::Zero);
+    }
+
+    Ok(NonZero(vec))
+}
+
+/// Decode a Mabo `[T; N]` array value, with a [`Packed`] element type.
+///
+/// # Errors
+///
+/// Will return `Err` if the buffer does not have enough remaining data to read the value, the `T`
+/// type fails to decode, the array exceeds the decoding limits, or it contains less than `N`
+/// elements.
+pub fn decode_array_packed<const N: usize, T: Packed>(
+    r: &mut impl Buf,
+    limits: &mut Limits,
+) -> Result<[T; N]> {
+    let mut vec = decode_vec_packed(r, limits)?;
+
+    // skip any remaining values, in case the old array definition was larger.
+    vec.truncate(N);
+
+    vec.try_into().map_err(|_| Error::InsufficientData)
+}
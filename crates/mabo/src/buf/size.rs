@@ -1,8 +1,19 @@
-use std::collections::{HashMap, HashSet};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+
+use core::time::Duration;
 
 use bytes::Bytes;
 
-use crate::{NonZero, varint};
+use crate::{
+    Decimal, NonZero, UnknownFields,
+    collections::{HashMap, HashSet},
+    varint,
+};
 
 /// Size of the special field identifier that marks the end of a struct or enum variant.
 pub const END_MARKER_SIZE: usize = 1;
@@ -68,12 +79,88 @@ pub const fn size_bytes_bytes(value: &Bytes) -> usize {
     size_u64(value.len() as u64) + value.len()
 }
 
+/// Calculate the size of a Mabo `timestamp` (represented as [`std::time::SystemTime`] type).
+#[cfg(feature = "std")]
+#[must_use]
+pub fn size_timestamp_std(value: &std::time::SystemTime) -> usize {
+    let (seconds, nanos) = timestamp_std_parts(value);
+    size_timestamp(seconds, nanos)
+}
+
+/// Calculate the size of a Mabo `timestamp` (represented as [`chrono::DateTime`] type in UTC).
+#[cfg(feature = "chrono")]
+#[must_use]
+pub fn size_timestamp_chrono(value: &chrono::DateTime<chrono::Utc>) -> usize {
+    let (seconds, nanos) = timestamp_chrono_parts(value);
+    size_timestamp(seconds, nanos)
+}
+
+/// Calculate the size of a Mabo `timestamp`, given as seconds and nanoseconds since the Unix
+/// epoch.
+#[must_use]
+pub const fn size_timestamp(seconds: i64, nanos: u32) -> usize {
+    let len = size_i64(seconds) + size_u32(nanos);
+    size_u64(len as u64) + len
+}
+
+/// Split the time into seconds and nanoseconds since the Unix epoch, where the nanoseconds always
+/// count forward in time, even for points in time before the epoch.
+#[cfg(feature = "std")]
+pub(crate) fn timestamp_std_parts(value: &std::time::SystemTime) -> (i64, u32) {
+    match value.duration_since(std::time::UNIX_EPOCH) {
+        Ok(after) => (
+            i64::try_from(after.as_secs()).unwrap_or(i64::MAX),
+            after.subsec_nanos(),
+        ),
+        Err(e) => {
+            let before = e.duration();
+            let seconds = i64::try_from(before.as_secs()).map_or(i64::MIN, |s| -s);
+            match before.subsec_nanos() {
+                0 => (seconds, 0),
+                nanos => (seconds.saturating_sub(1), 1_000_000_000 - nanos),
+            }
+        }
+    }
+}
+
+/// Split the time into seconds and nanoseconds since the Unix epoch. Leap seconds are folded into
+/// the last nanosecond before them.
+#[cfg(feature = "chrono")]
+pub(crate) fn timestamp_chrono_parts(value: &chrono::DateTime<chrono::Utc>) -> (i64, u32) {
+    (
+        value.timestamp(),
+        value.timestamp_subsec_nanos().min(999_999_999),
+    )
+}
+
+/// Calculate the size of a Mabo `duration`.
+#[must_use]
+pub const fn size_duration(value: &Duration) -> usize {
+    let len = size_u64(value.as_secs()) + size_u32(value.subsec_nanos());
+    size_u64(len as u64) + len
+}
+
+/// Calculate the size of a Mabo `uuid`, which is always the same, regardless of the value itself.
+#[inline(always)]
+#[must_use]
+pub const fn size_uuid(_: &[u8; 16]) -> usize {
+    17
+}
+
+/// Calculate the size of a Mabo `decimal` number.
+#[must_use]
+pub const fn size_decimal(value: &Decimal) -> usize {
+    let len = size_i128(value.mantissa) + size_u32(value.scale);
+    size_u64(len as u64) + len
+}
+
 /// Calculate the size of a Mabo `vec<T>` vector value.
 pub fn size_vec<T, S>(vec: &[T], size: S) -> usize
 where
     S: Fn(&T) -> usize,
 {
-    size_u64(vec.len() as u64) + vec.iter().map(size).sum::<usize>()
+    let size = vec.iter().map(size).sum::<usize>();
+    size_u64(size as u64) + size
 }
 
 /// Calculate the size of a Mabo `hash_map<K, V>` hash map value.
@@ -82,11 +169,24 @@ where
     SK: Fn(&K) -> usize,
     SV: Fn(&V) -> usize,
 {
-    size_u64(map.len() as u64)
-        + map
-            .iter()
-            .map(|(key, value)| size_key(key) + size_value(value))
-            .sum::<usize>()
+    let size = map
+        .iter()
+        .map(|(key, value)| size_key(key) + size_value(value))
+        .sum::<usize>();
+    size_u64(size as u64) + size
+}
+
+/// Calculate the size of a Mabo `btree_map<K, V>` ordered map value.
+pub fn size_btree_map<K, V, SK, SV>(map: &BTreeMap<K, V>, size_key: SK, size_value: SV) -> usize
+where
+    SK: Fn(&K) -> usize,
+    SV: Fn(&V) -> usize,
+{
+    let size = map
+        .iter()
+        .map(|(key, value)| size_key(key) + size_value(value))
+        .sum::<usize>();
+    size_u64(size as u64) + size
 }
 
 /// Calculate the size of a Mabo `hash_set<T>` hash set value.
@@ -94,23 +194,130 @@ pub fn size_hash_set<T, S>(set: &HashSet<T>, size: S) -> usize
 where
     S: Fn(&T) -> usize,
 {
-    size_u64(set.len() as u64) + set.iter().map(size).sum::<usize>()
+    let size = set.iter().map(size).sum::<usize>();
+    size_u64(size as u64) + size
+}
+
+/// Calculate the size of a Mabo `btree_set<T>` ordered set value.
+pub fn size_btree_set<T, S>(set: &BTreeSet<T>, size: S) -> usize
+where
+    S: Fn(&T) -> usize,
+{
+    let size = set.iter().map(size).sum::<usize>();
+    size_u64(size as u64) + size
 }
 
 /// Calculate the size of a Mabo `option<T>` option value.
 pub fn size_option<T, S>(option: Option<&T>, size: S) -> usize
 where
-    S: Fn(&T) -> usize,
+    S: FnOnce(&T) -> usize,
 {
     size_u8(0) + option.map_or(0, size)
 }
 
+/// Calculate the size of a Mabo `(T1, T2, ...)` tuple value.
+#[inline(always)]
+pub fn size_tuple<S>(size: S) -> usize
+where
+    S: FnOnce() -> usize,
+{
+    let size = size();
+    size_u64(size as u64) + size
+}
+
 /// Calculate the size of a Mabo `[T; N]` array value.
 pub fn size_array<const N: usize, T, S>(array: &[T; N], size: S) -> usize
 where
     S: Fn(&T) -> usize,
 {
-    size_u64(N as u64) + array.iter().map(size).sum::<usize>()
+    let size = array.iter().map(size).sum::<usize>();
+    size_u64(size as u64) + size
+}
+
+/// Calculate the size of a Mabo `vec<T>` vector value, and record its content size in the cache.
+pub fn size_vec_cached<T, S>(sizes: &mut SizeCache, vec: &[T], size: S) -> usize
+where
+    S: Fn(&mut SizeCache, &T) -> usize,
+{
+    sizes.record(|sizes| vec.iter().map(|value| size(sizes, value)).sum())
+}
+
+/// Calculate the size of a Mabo `hash_map<K, V>` hash map value, and record its content size in
+/// the cache.
+pub fn size_hash_map_cached<K, V, SK, SV>(
+    sizes: &mut SizeCache,
+    map: &HashMap<K, V>,
+    size_key: SK,
+    size_value: SV,
+) -> usize
+where
+    SK: Fn(&mut SizeCache, &K) -> usize,
+    SV: Fn(&mut SizeCache, &V) -> usize,
+{
+    sizes.record(|sizes| {
+        map.iter()
+            .map(|(key, value)| size_key(sizes, key) + size_value(sizes, value))
+            .sum()
+    })
+}
+
+/// Calculate the size of a Mabo `btree_map<K, V>` ordered map value, and record its content size
+/// in the cache.
+pub fn size_btree_map_cached<K, V, SK, SV>(
+    sizes: &mut SizeCache,
+    map: &BTreeMap<K, V>,
+    size_key: SK,
+    size_value: SV,
+) -> usize
+where
+    SK: Fn(&mut SizeCache, &K) -> usize,
+    SV: Fn(&mut SizeCache, &V) -> usize,
+{
+    sizes.record(|sizes| {
+        map.iter()
+            .map(|(key, value)| size_key(sizes, key) + size_value(sizes, value))
+            .sum()
+    })
+}
+
+/// Calculate the size of a Mabo `hash_set<T>` hash set value, and record its content size in the
+/// cache.
+pub fn size_hash_set_cached<T, S>(sizes: &mut SizeCache, set: &HashSet<T>, size: S) -> usize
+where
+    S: Fn(&mut SizeCache, &T) -> usize,
+{
+    sizes.record(|sizes| set.iter().map(|value| size(sizes, value)).sum())
+}
+
+/// Calculate the size of a Mabo `btree_set<T>` ordered set value, and record its content size in
+/// the cache.
+pub fn size_btree_set_cached<T, S>(sizes: &mut SizeCache, set: &BTreeSet<T>, size: S) -> usize
+where
+    S: Fn(&mut SizeCache, &T) -> usize,
+{
+    sizes.record(|sizes| set.iter().map(|value| size(sizes, value)).sum())
+}
+
+/// Calculate the size of a Mabo `(T1, T2, ...)` tuple value, and record its content size in the
+/// cache.
+#[inline(always)]
+pub fn size_tuple_cached<S>(sizes: &mut SizeCache, size: S) -> usize
+where
+    S: FnOnce(&mut SizeCache) -> usize,
+{
+    sizes.record(size)
+}
+
+/// Calculate the size of a Mabo `[T; N]` array value, and record its content size in the cache.
+pub fn size_array_cached<const N: usize, T, S>(
+    sizes: &mut SizeCache,
+    array: &[T; N],
+    size: S,
+) -> usize
+where
+    S: Fn(&mut SizeCache, &T) -> usize,
+{
+    size_vec_cached(sizes, array, size)
 }
 
 /// Calculate the size of a Mabo field identifier.
@@ -124,14 +331,14 @@ pub fn size_field_id(id: u32) -> usize {
 #[inline(always)]
 #[must_use]
 pub fn size_variant_id(id: u32) -> usize {
-    size_u32(id)
+    size_u32(id << 1)
 }
 
 /// Calculate the size of a required Mabo struct or enum field.
 #[inline(always)]
 pub fn size_field<S>(id: u32, size: S) -> usize
 where
-    S: Fn() -> usize,
+    S: FnOnce() -> usize,
 {
     size_field_id(id) + size()
 }
@@ -140,15 +347,130 @@ where
 #[inline(always)]
 pub fn size_field_option<T, S>(id: u32, option: Option<&T>, size: S) -> usize
 where
-    S: Fn(&T) -> usize,
+    S: FnOnce(&T) -> usize,
 {
     option.map_or(0, |value| size_field_id(id) + size(value))
 }
 
+/// Calculate the size of all fields, that were unknown to the schema while decoding.
+#[must_use]
+pub fn size_unknown_fields(fields: &UnknownFields) -> usize {
+    fields
+        .iter()
+        .map(|field| size_u32(field.id.into_u32()) + field.data.len())
+        .sum()
+}
+
+/// Calculate the size of an enum variant, that was unknown to the schema while decoding.
+#[must_use]
+pub fn size_unknown_variant(id: u32, data: &Bytes) -> usize {
+    size_variant_id(id) + data.len()
+}
+
+/// Calculate the size of a single value, including its length prefix, as written by
+/// [`encode_length_delimited`](super::encode_length_delimited).
+pub fn size_length_delimited(value: &impl Size) -> usize {
+    let size = value.size();
+    size_u64(size as u64) + size
+}
+
+/// Content sizes of all length-prefixed values inside of a single value, that is about to be
+/// encoded.
+///
+/// Vectors, maps, sets, arrays and tuples are prefixed with the byte size of their content, which
+/// has to be known before the content itself is written. Calculating it on the spot means that
+/// each nested value is sized once for every level it is nested in, which grows quadratically
+/// with the nesting depth. Instead, [`Size::size_cached`] records each content size in the order
+/// the values are visited, and [`Encode::encode_cached`](super::Encode::encode_cached) takes them
+/// back out in the very same order while writing.
+///
+/// The cache can be reused for several values by [clearing](Self::clear) it in between.
+#[derive(Clone, Debug, Default)]
+pub struct SizeCache {
+    sizes: Vec<usize>,
+    next: usize,
+    disabled: bool,
+}
+
+impl SizeCache {
+    /// Create a new, empty cache.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sizes: Vec::new(),
+            next: 0,
+            disabled: false,
+        }
+    }
+
+    /// Create a cache that doesn't record anything, for when only the total size of a value is
+    /// of interest.
+    #[inline]
+    #[must_use]
+    pub const fn disabled() -> Self {
+        Self {
+            sizes: Vec::new(),
+            next: 0,
+            disabled: true,
+        }
+    }
+
+    /// Calculate the content size of a length-prefixed value with the given closure, and record
+    /// it in the cache. The returned size includes the length prefix.
+    ///
+    /// The slot for the value is reserved before the closure runs, so the sizes stay in the
+    /// order in which the prefixes are written.
+    pub fn record(&mut self, size: impl FnOnce(&mut Self) -> usize) -> usize {
+        let size = if self.disabled {
+            size(self)
+        } else {
+            let slot = self.sizes.len();
+            self.sizes.push(0);
+            let size = size(self);
+            self.sizes[slot] = size;
+            size
+        };
+
+        size_u64(size as u64) + size
+    }
+
+    /// Take the next content size out of the cache.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if all recorded sizes were already taken, which means that the value was
+    /// encoded differently than it was sized.
+    #[inline]
+    pub fn take(&mut self) -> usize {
+        let size = self.sizes[self.next];
+        self.next += 1;
+        size
+    }
+
+    /// Remove all recorded sizes, but keep the allocated memory for the next value.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.sizes.clear();
+        self.next = 0;
+    }
+}
+
 /// Values that are able to calculate their encoded byte size, without actually encoding.
 pub trait Size {
     /// Calculate the encoded byte size.
     fn size(&self) -> usize;
+
+    /// Calculate the encoded byte size, and record the content size of each length-prefixed value
+    /// along the way, for a following call to
+    /// [`Encode::encode_cached`](super::Encode::encode_cached).
+    ///
+    /// Values without any length-prefixed parts don't need to record anything, which is what the
+    /// default implementation does.
+    #[inline(always)]
+    fn size_cached(&self, _sizes: &mut SizeCache) -> usize {
+        self.size()
+    }
 }
 
 macro_rules! forward {
@@ -193,13 +515,56 @@ impl Size for Box<[u8]> {
     }
 }
 
+impl Size for Duration {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        size_duration(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Size for std::time::SystemTime {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        size_timestamp_std(self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Size for chrono::DateTime<chrono::Utc> {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        size_timestamp_chrono(self)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Size for uuid::Uuid {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        size_uuid(self.as_bytes())
+    }
+}
+
+impl Size for Decimal {
+    #[inline(always)]
+    fn size(&self) -> usize {
+        size_decimal(self)
+    }
+}
+
 impl<T> Size for Vec<T>
 where
     T: Size,
 {
     #[inline(always)]
     fn size(&self) -> usize {
-        size_vec(self, Size::size)
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_vec_cached(sizes, self, |sizes, v| v.size_cached(sizes))
     }
 }
 
@@ -209,7 +574,12 @@ where
 {
     #[inline(always)]
     fn size(&self) -> usize {
-        size_vec(self, Size::size)
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_vec_cached(sizes, self, |sizes, v| v.size_cached(sizes))
     }
 }
 
@@ -220,7 +590,38 @@ where
 {
     #[inline(always)]
     fn size(&self) -> usize {
-        size_hash_map(self, Size::size, Size::size)
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_hash_map_cached(
+            sizes,
+            self,
+            |sizes, k| k.size_cached(sizes),
+            |sizes, v| v.size_cached(sizes),
+        )
+    }
+}
+
+impl<K, V> Size for BTreeMap<K, V>
+where
+    K: Size,
+    V: Size,
+{
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_btree_map_cached(
+            sizes,
+            self,
+            |sizes, k| k.size_cached(sizes),
+            |sizes, v| v.size_cached(sizes),
+        )
     }
 }
 
@@ -230,7 +631,27 @@ where
 {
     #[inline(always)]
     fn size(&self) -> usize {
-        size_hash_set(self, Size::size)
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_hash_set_cached(sizes, self, |sizes, v| v.size_cached(sizes))
+    }
+}
+
+impl<T> Size for BTreeSet<T>
+where
+    T: Size,
+{
+    #[inline(always)]
+    fn size(&self) -> usize {
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_btree_set_cached(sizes, self, |sizes, v| v.size_cached(sizes))
     }
 }
 
@@ -242,6 +663,11 @@ where
     fn size(&self) -> usize {
         size_option(self.as_ref(), Size::size)
     }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_option(self.as_ref(), |v| v.size_cached(sizes))
+    }
 }
 
 impl<const N: usize, T> Size for [T; N]
@@ -250,7 +676,12 @@ where
 {
     #[inline(always)]
     fn size(&self) -> usize {
-        size_array(self, Size::size)
+        self.size_cached(&mut SizeCache::disabled())
+    }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        size_array_cached(sizes, self, |sizes, v| v.size_cached(sizes))
     }
 }
 
@@ -262,9 +693,14 @@ where
     fn size(&self) -> usize {
         self.0.size()
     }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        self.0.size_cached(sizes)
+    }
 }
 
-impl<T> Size for std::borrow::Cow<'_, T>
+impl<T> Size for alloc::borrow::Cow<'_, T>
 where
     T: Clone + Size,
 {
@@ -272,9 +708,14 @@ where
     fn size(&self) -> usize {
         T::size(self)
     }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        T::size_cached(self, sizes)
+    }
 }
 
-impl<T> Size for std::rc::Rc<T>
+impl<T> Size for alloc::rc::Rc<T>
 where
     T: Size,
 {
@@ -282,9 +723,14 @@ where
     fn size(&self) -> usize {
         T::size(self)
     }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        T::size_cached(self, sizes)
+    }
 }
 
-impl<T> Size for std::sync::Arc<T>
+impl<T> Size for alloc::sync::Arc<T>
 where
     T: Size,
 {
@@ -292,4 +738,9 @@ where
     fn size(&self) -> usize {
         T::size(self)
     }
+
+    #[inline(always)]
+    fn size_cached(&self, sizes: &mut SizeCache) -> usize {
+        T::size_cached(self, sizes)
+    }
 }
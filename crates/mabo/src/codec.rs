@@ -0,0 +1,257 @@
+//! Integration with the [`tokio`] runtime, to send and receive length-delimited messages over
+//! asynchronous streams like TCP sockets.
+//!
+//! The framing is the same as for the synchronous [`MessageReader`](crate::io::MessageReader) and
+//! [`MessageWriter`](crate::io::MessageWriter), so both sides can be mixed freely.
+//!
+//! # Example
+//!
+//! ```
+//! use mabo::codec::MaboCodec;
+//! use tokio_util::codec::{Decoder, Encoder};
+//!
+//! let mut codec = MaboCodec::<u32>::new();
+//! let mut buf = bytes::BytesMut::new();
+//! codec.encode(5, &mut buf).unwrap();
+//!
+//! let mut partial = buf.split_to(1);
+//! assert_eq!(None, codec.decode(&mut partial).unwrap());
+//!
+//! partial.unsplit(buf);
+//! assert_eq!(Some(5), codec.decode(&mut partial).unwrap());
+//! ```
+
+use std::{fmt, io, marker::PhantomData};
+
+use bytes::{Buf, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{
+    Decode,
+    buf::{self, DecodeOptions, Encode, Limits},
+    io::{DEFAULT_MAX_FRAME_SIZE, Error, MAX_PREFIX_SIZE, Result, check_frame_size},
+    varint,
+};
+
+/// Codec for [`tokio_util::codec`] that en- and decodes `T` as length-delimited messages.
+///
+/// Decoding only happens once the whole frame was received, so partial frames are kept in the
+/// buffer until more data arrives.
+pub struct MaboCodec<T> {
+    max_frame_size: usize,
+    options: DecodeOptions,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> MaboCodec<T> {
+    /// Create a new codec with the [`DEFAULT_MAX_FRAME_SIZE`] and no further decoding limits.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            options: DecodeOptions::new(),
+            _type: PhantomData,
+        }
+    }
+
+    /// Limit the maximum size of a single frame, for both the en- and decoding.
+    #[must_use]
+    pub const fn with_max_frame_size(mut self, value: usize) -> Self {
+        self.max_frame_size = value;
+        self
+    }
+
+    /// Apply the given decoding limits to each message.
+    #[must_use]
+    pub const fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<T> Clone for MaboCodec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MaboCodec<T> {}
+
+impl<T> Default for MaboCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for MaboCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaboCodec")
+            .field("max_frame_size", &self.max_frame_size)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl<T: Decode> Decoder for MaboCodec<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        let (size, consumed) = match varint::decode_u64(src) {
+            Ok(value) => value,
+            Err(_) if src.len() < MAX_PREFIX_SIZE => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let size = check_frame_size(size, self.max_frame_size)?;
+        if src.len() < consumed + size {
+            src.reserve(consumed + size - src.len());
+            return Ok(None);
+        }
+
+        src.advance(consumed);
+        let mut frame = src.split_to(size).freeze();
+
+        buf::decode_root(&mut frame, &mut Limits::new(self.options))
+            .map(Some)
+            .map_err(Into::into)
+    }
+}
+
+impl<T: Encode> Encoder<T> for MaboCodec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
+        self.encode(&item, dst)
+    }
+}
+
+impl<T: Encode> Encoder<&T> for MaboCodec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, item: &T, dst: &mut BytesMut) -> Result<()> {
+        let size = check_frame_size(item.size() as u64, self.max_frame_size)?;
+
+        dst.reserve(buf::size_u64(size as u64) + size);
+        buf::encode_length_delimited(dst, item);
+        Ok(())
+    }
+}
+
+/// Read a single length-delimited message from an asynchronous reader.
+///
+/// Returns `None` if the reader reached its end, right at the start of a frame.
+///
+/// # Errors
+///
+/// Will return `Err` if reading from the reader fails, the reader ends in the middle of a frame,
+/// the frame exceeds the maximum frame size, or the message itself fails to decode.
+pub async fn read_message<T, R>(r: &mut R, max_frame_size: usize) -> Result<Option<T>>
+where
+    T: Decode,
+    R: AsyncRead + Unpin,
+{
+    let mut prefix = [0; MAX_PREFIX_SIZE];
+    let mut size = None;
+
+    for i in 0..MAX_PREFIX_SIZE {
+        prefix[i] = match r.read_u8().await {
+            Ok(byte) => byte,
+            Err(e) if i == 0 && e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        if prefix[i] & 0x80 == 0 {
+            size = Some(varint::decode_u64(&prefix[..=i])?.0);
+            break;
+        }
+    }
+
+    let size = check_frame_size(size.ok_or(varint::DecodeIntError)?, max_frame_size)?;
+
+    let mut frame = vec![0; size];
+    r.read_exact(&mut frame).await?;
+
+    T::decode(&mut &frame[..]).map(Some).map_err(Into::into)
+}
+
+/// Write a single message as length-delimited frame into an asynchronous writer.
+///
+/// # Errors
+///
+/// Will return `Err` if the message exceeds the maximum frame size, or writing to the writer
+/// fails.
+pub async fn write_message<W>(w: &mut W, value: &impl Encode, max_frame_size: usize) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let size = check_frame_size(value.size() as u64, max_frame_size)?;
+
+    let mut frame = Vec::with_capacity(buf::size_u64(size as u64) + size);
+    buf::encode_length_delimited(&mut frame, value);
+
+    w.write_all(&frame).await.map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_partial() {
+        let mut codec = MaboCodec::<String>::new();
+        let mut full = BytesMut::new();
+        codec.encode("first".to_owned(), &mut full).unwrap();
+        codec.encode("second".to_owned(), &mut full).unwrap();
+
+        let mut src = BytesMut::new();
+        let mut messages = Vec::new();
+
+        for byte in full {
+            src.extend_from_slice(&[byte]);
+            if let Some(message) = codec.decode(&mut src).unwrap() {
+                messages.push(message);
+            }
+        }
+
+        assert_eq!(vec!["first", "second"], messages);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn decode_frame_too_large() {
+        let mut src = BytesMut::new();
+        MaboCodec::new()
+            .encode("too long".to_owned(), &mut src)
+            .unwrap();
+
+        assert!(matches!(
+            MaboCodec::<String>::new()
+                .with_max_frame_size(4)
+                .decode(&mut src),
+            Err(Error::FrameTooLarge { size: 9, max: 4 }),
+        ));
+    }
+
+    #[tokio::test]
+    async fn roundtrip_async() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+
+        write_message(&mut client, &1_u32, DEFAULT_MAX_FRAME_SIZE)
+            .await
+            .unwrap();
+        write_message(&mut client, &"test".to_owned(), DEFAULT_MAX_FRAME_SIZE)
+            .await
+            .unwrap();
+        drop(client);
+
+        let first = read_message::<u32, _>(&mut server, DEFAULT_MAX_FRAME_SIZE).await;
+        let second = read_message::<String, _>(&mut server, DEFAULT_MAX_FRAME_SIZE).await;
+        let end = read_message::<u32, _>(&mut server, DEFAULT_MAX_FRAME_SIZE).await;
+
+        assert_eq!(Some(1), first.unwrap());
+        assert_eq!(Some("test".to_owned()), second.unwrap());
+        assert_eq!(None, end.unwrap());
+    }
+}
@@ -0,0 +1,979 @@
+//! Schema driven en- and decoding of [`Value`]s, without any generated code.
+//!
+//! Types are resolved in the same way as the [`mabo_compiler::resolve`] step does it: Types are
+//! first looked up in the current module (or the module path they are prefixed with), and then
+//! through the imports of that module in any of the other schemas.
+//!
+//! # Example
+//!
+//! ```
+//! use mabo::{Value, dynamic::Registry};
+//!
+//! let schema = mabo_parser::Schema::parse("struct Sample { value: u32 @1 }", None).unwrap();
+//! let schema = mabo_compiler::simplify_schema(&schema);
+//! let schemas = [("sample", &schema)];
+//! let registry = Registry::new(&schemas);
+//!
+//! let value = registry.decode("sample", "Sample", &mut &[9, 5, 0][..]).unwrap();
+//! assert_eq!(Some(&Value::U32(5)), value.field("value"));
+//! ```
+
+use std::rc::Rc;
+
+use bytes::{Buf, BufMut};
+use mabo_compiler::simplify::{
+    Definition, ExternalType, FieldKind, Fields, Literal, Schema, Struct, Type,
+    Variant as SchemaVariant,
+};
+
+use crate::{
+    FieldEncoding, FieldId, VariantEncoding, VariantId,
+    buf::{self, END_MARKER, Limits},
+    value::{Field, Value, Variant},
+};
+
+/// Result type alias for the dynamic en- and decoding, which defaults to the [`Error`] type for
+/// errors.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error that can happen while en- or decoding a dynamic [`Value`].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The payload itself failed to decode.
+    #[error("failed to decode the payload")]
+    Decode(#[from] buf::Error),
+    /// None of the registered schemas has the requested name.
+    #[error("schema `{0}` is not registered")]
+    MissingSchema(String),
+    /// A type that is referenced in a schema couldn't be resolved.
+    #[error("type `{0}` could not be resolved")]
+    MissingType(String),
+    /// A value doesn't match the type that is defined in the schema.
+    #[error("value doesn't match the schema type `{expected}`")]
+    TypeMismatch {
+        /// Textual representation of the expected schema type.
+        expected: String,
+    },
+    /// A required field is missing, either from the payload or from the value to encode.
+    #[error("required field {id} is missing")]
+    MissingField {
+        /// Identifier of the field.
+        id: u32,
+        /// Name of the field (if it is a named field).
+        name: Option<String>,
+    },
+    /// An enum variant doesn't exist in the schema.
+    #[error("enum variant {0} is not defined in the schema")]
+    UnknownVariant(u32),
+}
+
+/// Collection of compiled schemas, that allows to en- and decode [`Value`]s for any of the types
+/// defined in them.
+///
+/// The schemas are identified by name, in the same way as for
+/// [`mabo_compiler::resolve_schemas`], so imports between them can be resolved.
+#[derive(Clone, Copy)]
+pub struct Registry<'a> {
+    schemas: &'a [(&'a str, &'a Schema<'a>)],
+}
+
+/// Reader that is passed through the decoding, as the nesting of buffers would otherwise result in
+/// infinitely recursive generic types.
+type Reader<'r> = &'r mut dyn Buf;
+
+/// Location of a definition, and the concrete types for any of its generic type arguments.
+#[derive(Clone)]
+struct Scope<'a> {
+    schema: usize,
+    path: Rc<[&'a str]>,
+    generics: Rc<[Generic<'a>]>,
+}
+
+/// Concrete type for a generic type argument, together with the scope it was defined in.
+struct Generic<'a> {
+    name: &'a str,
+    ty: &'a Type<'a>,
+    scope: Scope<'a>,
+}
+
+/// Definition that was found in one of the schemas, together with its location.
+struct Found<'a> {
+    resolved: Resolved<'a>,
+    schema: usize,
+    path: Vec<&'a str>,
+    generics: &'a [&'a str],
+}
+
+impl<'a> Found<'a> {
+    /// Create the scope for the content of the definition, with the given concrete types for its
+    /// generic type arguments.
+    fn into_scope(
+        self,
+        generics: impl IntoIterator<Item = (&'a Type<'a>, Scope<'a>)>,
+    ) -> (Resolved<'a>, Scope<'a>) {
+        let scope = Scope {
+            schema: self.schema,
+            path: self.path.into(),
+            generics: self
+                .generics
+                .iter()
+                .zip(generics)
+                .map(|(name, (ty, scope))| Generic { name, ty, scope })
+                .collect::<Vec<_>>()
+                .into(),
+        };
+
+        (self.resolved, scope)
+    }
+}
+
+/// Definition that a type resolved to.
+#[derive(Clone, Copy)]
+enum Resolved<'a> {
+    Struct(&'a Struct<'a>),
+    Enum(&'a [SchemaVariant<'a>]),
+    Alias(&'a Type<'a>),
+}
+
+impl<'a> Registry<'a> {
+    /// Create a new registry from the given list of schemas, identified by their name.
+    #[must_use]
+    pub const fn new(schemas: &'a [(&'a str, &'a Schema<'a>)]) -> Self {
+        Self { schemas }
+    }
+
+    /// Decode a value of the `root` type, that is defined in the given `schema`, without any
+    /// [`Limits`] in place.
+    ///
+    /// The root type can be prefixed with a module path like `module::Type`, relative to the
+    /// schema root.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the schema or root type doesn't exist, any type within can't be
+    /// resolved, or the payload fails to decode.
+    pub fn decode(&self, schema: &str, root: &str, r: &mut impl Buf) -> Result<Value> {
+        self.decode_with(schema, root, r, &mut Limits::default())
+    }
+
+    /// Decode a value of the `root` type, while ensuring the given [`Limits`] are respected. See
+    /// [`Self::decode`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the schema or root type doesn't exist, any type within can't be
+    /// resolved, or the payload fails to decode.
+    pub fn decode_with(
+        &self,
+        schema: &str,
+        root: &str,
+        r: &mut impl Buf,
+        limits: &mut Limits,
+    ) -> Result<Value> {
+        let (resolved, scope) = self.resolve_root(schema, root)?;
+        let mut r: Reader<'_> = r;
+        self.decode_resolved(&scope, resolved, &mut r, limits)
+    }
+
+    /// Encode a value of the `root` type, that is defined in the given `schema`. See
+    /// [`Self::decode`] for details about the root type.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the schema or root type doesn't exist, any type within can't be
+    /// resolved, or the value doesn't match the types defined in the schema.
+    pub fn encode(
+        &self,
+        schema: &str,
+        root: &str,
+        value: &Value,
+        w: &mut impl BufMut,
+    ) -> Result<()> {
+        let (resolved, scope) = self.resolve_root(schema, root)?;
+        let mut buf = Vec::new();
+        self.encode_resolved(&scope, resolved, value, &mut buf)?;
+
+        w.put_slice(&buf);
+        Ok(())
+    }
+
+    fn resolve_root(&self, schema: &str, root: &str) -> Result<(Resolved<'a>, Scope<'a>)> {
+        let index = self
+            .schemas
+            .iter()
+            .position(|(name, _)| *name == schema)
+            .ok_or_else(|| Error::MissingSchema(schema.to_owned()))?;
+
+        let mut path = root.split("::").collect::<Vec<_>>();
+        let name = path.pop().unwrap_or_default();
+
+        self.find(index, &[], &path, name)
+            .map(|found| found.into_scope([]))
+            .ok_or_else(|| Error::MissingType(root.to_owned()))
+    }
+
+    /// Find the definitions of a module, by following the path from the schema root.
+    fn module(&self, schema: usize, path: &[&str]) -> Option<(&'a [Definition<'a>], Vec<&'a str>)> {
+        let mut definitions = &*self.schemas[schema].1.definitions;
+        let mut names = Vec::with_capacity(path.len());
+
+        for segment in path {
+            let module = definitions.iter().find_map(|def| match def {
+                Definition::Module(m) if m.name == *segment => Some(m),
+                _ => None,
+            })?;
+
+            definitions = &module.definitions;
+            names.push(module.name);
+        }
+
+        Some((definitions, names))
+    }
+
+    /// Find a definition by its (optional) module path and name, relative to the given module.
+    /// Types that can't be found locally are looked up through the imports of the module.
+    fn find(
+        &self,
+        schema: usize,
+        module: &[&'a str],
+        path: &[&str],
+        name: &str,
+    ) -> Option<Found<'a>> {
+        let full_path = module.iter().chain(path).copied().collect::<Vec<_>>();
+
+        if let Some(found) = self.find_local(schema, &full_path, name) {
+            return Some(found);
+        }
+
+        let (definitions, _) = self.module(schema, module)?;
+
+        definitions.iter().find_map(|def| {
+            let Definition::Import(import) = def else {
+                return None;
+            };
+
+            let (root, segments) = import.segments.split_first()?;
+            let schema = self.schemas.iter().position(|(name, _)| name == root)?;
+
+            match &import.element {
+                Some(element) if path.is_empty() && **element == *name => {
+                    self.find_local(schema, segments, name)
+                }
+                Some(_) => None,
+                None => {
+                    let (first, rest) = path.split_first()?;
+                    if import.segments.last() != Some(first) {
+                        return None;
+                    }
+
+                    let full_path = segments.iter().chain(rest).copied().collect::<Vec<_>>();
+                    self.find_local(schema, &full_path, name)
+                }
+            }
+        })
+    }
+
+    /// Find a definition in the exact module, without considering any imports.
+    fn find_local(&self, schema: usize, path: &[&str], name: &str) -> Option<Found<'a>> {
+        let (definitions, path) = self.module(schema, path)?;
+
+        let (resolved, generics) = definitions.iter().find_map(|def| match def {
+            Definition::Struct(s) if s.name == name => Some((Resolved::Struct(s), &*s.generics)),
+            Definition::Enum(e) if e.name == name => {
+                Some((Resolved::Enum(&e.variants), &*e.generics))
+            }
+            Definition::TypeAlias(a) if a.name == name => {
+                Some((Resolved::Alias(&a.target), &*a.generics))
+            }
+            _ => None,
+        })?;
+
+        Some(Found {
+            resolved,
+            schema,
+            path,
+            generics,
+        })
+    }
+
+    /// Resolve an external type, which is either a generic type argument of the current scope, or
+    /// a definition in any of the schemas.
+    fn resolve(&self, scope: &Scope<'a>, ty: &'a ExternalType<'a>) -> Result<Target<'a>> {
+        if ty.path.is_empty()
+            && ty.generics.is_empty()
+            && let Some(generic) = scope.generics.iter().find(|g| g.name == ty.name)
+        {
+            return Ok(Target::Type(generic.ty, generic.scope.clone()));
+        }
+
+        let found = self
+            .find(scope.schema, &scope.path, &ty.path, ty.name)
+            .ok_or_else(|| {
+                Error::MissingType(
+                    ty.path
+                        .iter()
+                        .chain(std::iter::once(&ty.name))
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join("::"),
+                )
+            })?;
+
+        let (resolved, target) = found.into_scope(ty.generics.iter().map(|ty| (ty, scope.clone())));
+
+        Ok(Target::Definition(resolved, target))
+    }
+
+    fn decode_resolved(
+        &self,
+        scope: &Scope<'a>,
+        resolved: Resolved<'a>,
+        r: &mut Reader<'_>,
+        limits: &mut Limits,
+    ) -> Result<Value> {
+        match resolved {
+            Resolved::Struct(s) => self
+                .decode_fields(scope, &s.fields, r, limits)
+                .map(Value::Struct),
+            Resolved::Enum(variants) => {
+                let id = buf::decode_variant_id(r, limits)?.value;
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.id == id)
+                    .ok_or(buf::Error::UnknownVariant(id))?;
+
+                Ok(Value::Enum(Variant {
+                    id,
+                    name: variant.name.to_owned(),
+                    fields: self.decode_fields(scope, &variant.fields, r, limits)?,
+                }))
+            }
+            Resolved::Alias(ty) => self.decode_type(scope, ty, r, limits, true),
+        }
+    }
+
+    fn decode_fields(
+        &self,
+        scope: &Scope<'a>,
+        fields: &'a Fields<'a>,
+        r: &mut Reader<'_>,
+        limits: &mut Limits,
+    ) -> Result<Vec<Field>> {
+        if fields.kind == FieldKind::Unit {
+            return Ok(Vec::new());
+        }
+
+        limits.nested(|limits| Ok(self.decode_fields_nested(scope, fields, r, limits)))?
+    }
+
+    fn decode_fields_nested(
+        &self,
+        scope: &Scope<'a>,
+        fields: &'a Fields<'a>,
+        r: &mut Reader<'_>,
+        limits: &mut Limits,
+    ) -> Result<Vec<Field>> {
+        let mut values = vec![None; fields.fields.len()];
+
+        loop {
+            let id = buf::decode_id(r, limits)?;
+            if id.value == END_MARKER {
+                break;
+            }
+
+            let Some(index) = fields.fields.iter().position(|f| f.id == id.value) else {
+                buf::decode_skip(r, limits, id.encoding)?;
+                continue;
+            };
+
+            values[index] = Some(match &fields.fields[index].ty {
+                Type::Option(ty) => {
+                    Value::Option(Some(self.decode_type(scope, ty, r, limits, true)?.into()))
+                }
+                ty => self.decode_type(scope, ty, r, limits, true)?,
+            });
+        }
+
+        fields
+            .fields
+            .iter()
+            .zip(values)
+            .map(|(field, value)| {
+                let name = field_name(fields, &field.name);
+                let value = match (value, &field.ty) {
+                    (Some(value), _) => value,
+                    (None, Type::Option(_)) => Value::Option(None),
+                    (None, ty) => match field.default.as_ref().and_then(|d| default_value(ty, d)) {
+                        Some(value) => value,
+                        None => return Err(Error::MissingField { id: field.id, name }),
+                    },
+                };
+
+                Ok(Field {
+                    id: field.id,
+                    name,
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    fn decode_type(
+        &self,
+        scope: &Scope<'a>,
+        ty: &'a Type<'a>,
+        r: &mut Reader<'_>,
+        limits: &mut Limits,
+        root: bool,
+    ) -> Result<Value> {
+        Ok(match ty {
+            Type::Bool => Value::Bool(buf::decode_bool(r, limits)?),
+            Type::U8 => Value::U8(buf::decode_u8(r)?),
+            Type::U16 => Value::U16(buf::decode_u16(r, limits)?),
+            Type::U32 => Value::U32(buf::decode_u32(r, limits)?),
+            Type::U64 => Value::U64(buf::decode_u64(r, limits)?),
+            Type::U128 => Value::U128(buf::decode_u128(r, limits)?),
+            Type::I8 => Value::I8(buf::decode_i8(r)?),
+            Type::I16 => Value::I16(buf::decode_i16(r, limits)?),
+            Type::I32 => Value::I32(buf::decode_i32(r, limits)?),
+            Type::I64 => Value::I64(buf::decode_i64(r, limits)?),
+            Type::I128 => Value::I128(buf::decode_i128(r, limits)?),
+            Type::F32 => Value::F32(buf::decode_f32(r)?),
+            Type::F64 => Value::F64(buf::decode_f64(r)?),
+            Type::String | Type::StringRef | Type::BoxString => {
+                Value::String(buf::decode_string(r, limits)?)
+            }
+            Type::Bytes | Type::BytesRef | Type::BoxBytes => {
+                Value::Bytes(buf::decode_bytes_std(r, limits)?)
+            }
+            Type::Timestamp => {
+                let (seconds, nanos) = buf::decode_timestamp(r, limits)?;
+                Value::Timestamp { seconds, nanos }
+            }
+            Type::Duration => Value::Duration(buf::decode_duration(r, limits)?),
+            Type::Uuid => Value::Uuid(buf::decode_uuid(r, limits)?),
+            Type::Decimal => Value::Decimal(buf::decode_decimal(r, limits)?),
+            Type::Vec(ty) | Type::HashSet(ty) | Type::BTreeSet(ty) => {
+                Value::Vec(decode_list(r, limits, usize::MAX, |r, limits, len| {
+                    limits.push::<Value>(len)?;
+                    self.decode_type(scope, ty, r, limits, false)
+                })?)
+            }
+            Type::HashMap(kv) | Type::BTreeMap(kv) => {
+                Value::Map(decode_list(r, limits, usize::MAX, |r, limits, len| {
+                    limits.push::<(Value, Value)>(len)?;
+                    Ok((
+                        self.decode_type(scope, &kv.0, r, limits, false)?,
+                        self.decode_type(scope, &kv.1, r, limits, false)?,
+                    ))
+                })?)
+            }
+            Type::Option(ty) => Value::Option(if buf::decode_bool(r, limits)? {
+                Some(self.decode_type(scope, ty, r, limits, false)?.into())
+            } else {
+                None
+            }),
+            Type::NonZero(ty) => {
+                let value = self.decode_type(scope, ty, r, limits, root)?;
+                if is_zero(&value) {
+                    return Err(buf::Error::Zero.into());
+                }
+                value
+            }
+            Type::Tuple(types) => {
+                if root {
+                    buf::decode_u64(r, limits)?;
+                }
+
+                Value::Tuple(
+                    types
+                        .iter()
+                        .map(|ty| self.decode_type(scope, ty, r, limits, false))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            Type::Array(ty, size) => {
+                let size = *size as usize;
+                let values = decode_list(r, limits, size, |r, limits, _| {
+                    limits.allocate(size_of::<Value>())?;
+                    self.decode_type(scope, ty, r, limits, false)
+                })?;
+
+                if values.len() < size {
+                    return Err(buf::Error::InsufficientData.into());
+                }
+
+                Value::Vec(values)
+            }
+            Type::External(ty) => match self.resolve(scope, ty)? {
+                Target::Type(ty, scope) => self.decode_type(&scope, ty, r, limits, root)?,
+                Target::Definition(resolved, scope) => {
+                    self.decode_resolved(&scope, resolved, r, limits)?
+                }
+            },
+        })
+    }
+
+    fn encode_resolved(
+        &self,
+        scope: &Scope<'a>,
+        resolved: Resolved<'a>,
+        value: &Value,
+        w: &mut Vec<u8>,
+    ) -> Result<()> {
+        match (resolved, value) {
+            (Resolved::Struct(s), Value::Struct(values)) => {
+                self.encode_fields(scope, &s.fields, values, w)
+            }
+            (Resolved::Enum(variants), Value::Enum(value)) => {
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.id == value.id)
+                    .ok_or(Error::UnknownVariant(value.id))?;
+                let encoding = if variant.fields.kind == FieldKind::Unit {
+                    VariantEncoding::Unit
+                } else {
+                    VariantEncoding::Fields
+                };
+
+                buf::encode_variant_id(w, VariantId::new(variant.id, encoding));
+                self.encode_fields(scope, &variant.fields, &value.fields, w)
+            }
+            (Resolved::Alias(ty), value) => self.encode_type(scope, ty, value, w, true),
+            (Resolved::Struct(_), _) => Err(mismatch("struct")),
+            (Resolved::Enum(_), _) => Err(mismatch("enum")),
+        }
+    }
+
+    fn encode_fields(
+        &self,
+        scope: &Scope<'a>,
+        fields: &'a Fields<'a>,
+        values: &[Field],
+        w: &mut Vec<u8>,
+    ) -> Result<()> {
+        if fields.kind == FieldKind::Unit {
+            return Ok(());
+        }
+
+        for field in &fields.fields {
+            let value = values
+                .iter()
+                .find(|value| value.id == field.id)
+                .map(|value| &value.value);
+
+            match (&field.ty, value) {
+                (Type::Option(ty), Some(Value::Option(Some(value)))) => {
+                    buf::encode_id(w, FieldId::new(field.id, encoding(ty)));
+                    self.encode_type(scope, ty, value, w, true)?;
+                }
+                (Type::Option(_), Some(Value::Option(None)) | None) => {}
+                (_, Some(value)) => {
+                    buf::encode_id(w, FieldId::new(field.id, encoding(&field.ty)));
+                    self.encode_type(scope, &field.ty, value, w, true)?;
+                }
+                (ty, None) => {
+                    let value = field
+                        .default
+                        .as_ref()
+                        .and_then(|d| default_value(ty, d))
+                        .ok_or_else(|| Error::MissingField {
+                            id: field.id,
+                            name: field_name(fields, &field.name),
+                        })?;
+
+                    buf::encode_id(w, FieldId::new(field.id, encoding(ty)));
+                    self.encode_type(scope, ty, &value, w, true)?;
+                }
+            }
+        }
+
+        buf::encode_u32(w, END_MARKER);
+        Ok(())
+    }
+
+    fn encode_type(
+        &self,
+        scope: &Scope<'a>,
+        ty: &'a Type<'a>,
+        value: &Value,
+        w: &mut Vec<u8>,
+        root: bool,
+    ) -> Result<()> {
+        match (ty, value) {
+            (Type::Bool, Value::Bool(v)) => buf::encode_bool(w, *v),
+            (Type::U8, Value::U8(v)) => buf::encode_u8(w, *v),
+            (Type::U16, Value::U16(v)) => buf::encode_u16(w, *v),
+            (Type::U32, Value::U32(v)) => buf::encode_u32(w, *v),
+            (Type::U64, Value::U64(v)) => buf::encode_u64(w, *v),
+            (Type::U128, Value::U128(v)) => buf::encode_u128(w, *v),
+            (Type::I8, Value::I8(v)) => buf::encode_i8(w, *v),
+            (Type::I16, Value::I16(v)) => buf::encode_i16(w, *v),
+            (Type::I32, Value::I32(v)) => buf::encode_i32(w, *v),
+            (Type::I64, Value::I64(v)) => buf::encode_i64(w, *v),
+            (Type::I128, Value::I128(v)) => buf::encode_i128(w, *v),
+            (Type::F32, Value::F32(v)) => buf::encode_f32(w, *v),
+            (Type::F64, Value::F64(v)) => buf::encode_f64(w, *v),
+            (Type::String | Type::StringRef | Type::BoxString, Value::String(v)) => {
+                buf::encode_string(w, v);
+            }
+            (Type::Bytes | Type::BytesRef | Type::BoxBytes, Value::Bytes(v)) => {
+                buf::encode_bytes_std(w, v);
+            }
+            (Type::Timestamp, Value::Timestamp { seconds, nanos }) => {
+                buf::encode_timestamp(w, *seconds, *nanos);
+            }
+            (Type::Duration, Value::Duration(v)) => buf::encode_duration(w, v),
+            (Type::Uuid, Value::Uuid(v)) => buf::encode_uuid(w, v),
+            (Type::Decimal, Value::Decimal(v)) => buf::encode_decimal(w, v),
+            (Type::Vec(ty) | Type::HashSet(ty) | Type::BTreeSet(ty), Value::Vec(values)) => {
+                encode_prefixed(w, |w| {
+                    values
+                        .iter()
+                        .try_for_each(|value| self.encode_type(scope, ty, value, w, false))
+                })?;
+            }
+            (Type::Array(ty, size), Value::Vec(values)) if values.len() == *size as usize => {
+                encode_prefixed(w, |w| {
+                    values
+                        .iter()
+                        .try_for_each(|value| self.encode_type(scope, ty, value, w, false))
+                })?;
+            }
+            (Type::HashMap(kv) | Type::BTreeMap(kv), Value::Map(values)) => {
+                encode_prefixed(w, |w| {
+                    values.iter().try_for_each(|(key, value)| {
+                        self.encode_type(scope, &kv.0, key, w, false)?;
+                        self.encode_type(scope, &kv.1, value, w, false)
+                    })
+                })?;
+            }
+            (Type::Option(_), Value::Option(None)) => buf::encode_u8(w, 0),
+            (Type::Option(ty), Value::Option(Some(value))) => {
+                buf::encode_u8(w, 1);
+                self.encode_type(scope, ty, value, w, false)?;
+            }
+            (Type::NonZero(ty), value) => self.encode_type(scope, ty, value, w, root)?,
+            (Type::Tuple(types), Value::Tuple(values)) if types.len() == values.len() => {
+                let encode = |w: &mut Vec<u8>| {
+                    types
+                        .iter()
+                        .zip(values)
+                        .try_for_each(|(ty, value)| self.encode_type(scope, ty, value, w, false))
+                };
+
+                if root {
+                    encode_prefixed(w, encode)?;
+                } else {
+                    encode(w)?;
+                }
+            }
+            (Type::External(ty), value) => match self.resolve(scope, ty)? {
+                Target::Type(ty, scope) => self.encode_type(&scope, ty, value, w, root)?,
+                Target::Definition(resolved, scope) => {
+                    self.encode_resolved(&scope, resolved, value, w)?;
+                }
+            },
+            (ty, _) => return Err(mismatch(format!("{ty:?}"))),
+        }
+
+        Ok(())
+    }
+}
+
+/// Target that an external type resolves to.
+enum Target<'a> {
+    /// Concrete type of a generic type argument.
+    Type(&'a Type<'a>, Scope<'a>),
+    /// Definition with its generic type arguments filled in.
+    Definition(Resolved<'a>, Scope<'a>),
+}
+
+/// Decode the length-prefixed elements of a collection. Any elements past the `max` amount are
+/// skipped.
+fn decode_list<T>(
+    r: &mut Reader<'_>,
+    limits: &mut Limits,
+    max: usize,
+    mut decode: impl FnMut(&mut Reader<'_>, &mut Limits, usize) -> Result<T>,
+) -> Result<Vec<T>> {
+    let len = buf::decode_u64(r, limits)? as usize;
+    if r.remaining() < len {
+        return Err(buf::Error::InsufficientData.into());
+    }
+
+    let mut values = Vec::new();
+    let mut take = r.take(len);
+    let mut r: Reader<'_> = &mut take;
+
+    while r.has_remaining() && values.len() < max {
+        values.push(decode(&mut r, limits, values.len())?);
+    }
+
+    let remaining = r.remaining();
+    r.advance(remaining);
+
+    Ok(values)
+}
+
+/// Encode the elements of a collection, prefixed with their total byte size.
+fn encode_prefixed(w: &mut Vec<u8>, encode: impl FnOnce(&mut Vec<u8>) -> Result<()>) -> Result<()> {
+    let mut buf = Vec::new();
+    encode(&mut buf)?;
+
+    buf::encode_bytes_std(w, &buf);
+    Ok(())
+}
+
+/// Field encoding as it is used for the field identifiers, which only depends on the type as it
+/// is written in the schema.
+fn encoding(ty: &Type<'_>) -> FieldEncoding {
+    match ty {
+        Type::Bool | Type::U8 | Type::I8 => FieldEncoding::Fixed1,
+        Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::U128
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::I128 => FieldEncoding::Varint,
+        Type::F32 => FieldEncoding::Fixed4,
+        Type::F64 => FieldEncoding::Fixed8,
+        Type::NonZero(ty) => encoding(ty),
+        Type::String
+        | Type::StringRef
+        | Type::Bytes
+        | Type::BytesRef
+        | Type::Vec(_)
+        | Type::HashMap(_)
+        | Type::HashSet(_)
+        | Type::BTreeMap(_)
+        | Type::BTreeSet(_)
+        | Type::Option(_)
+        | Type::BoxString
+        | Type::BoxBytes
+        | Type::Timestamp
+        | Type::Duration
+        | Type::Uuid
+        | Type::Decimal
+        | Type::Tuple(_)
+        | Type::Array(_, _)
+        | Type::External(_) => FieldEncoding::LengthPrefixed,
+    }
+}
+
+fn field_name(fields: &Fields<'_>, name: &str) -> Option<String> {
+    (fields.kind == FieldKind::Named).then(|| name.to_owned())
+}
+
+/// Convert the default value of a field into a value of the field's type, which is used for fields
+/// that are missing.
+#[expect(clippy::cast_possible_truncation)]
+fn default_value(ty: &Type<'_>, literal: &Literal) -> Option<Value> {
+    Some(match (ty, literal) {
+        (Type::Bool, Literal::Bool(v)) => Value::Bool(*v),
+        (Type::U8, Literal::Int(v)) => Value::U8((*v).try_into().ok()?),
+        (Type::U16, Literal::Int(v)) => Value::U16((*v).try_into().ok()?),
+        (Type::U32, Literal::Int(v)) => Value::U32((*v).try_into().ok()?),
+        (Type::U64, Literal::Int(v)) => Value::U64((*v).try_into().ok()?),
+        (Type::U128, Literal::Int(v)) => Value::U128((*v).try_into().ok()?),
+        (Type::I8, Literal::Int(v)) => Value::I8((*v).try_into().ok()?),
+        (Type::I16, Literal::Int(v)) => Value::I16((*v).try_into().ok()?),
+        (Type::I32, Literal::Int(v)) => Value::I32((*v).try_into().ok()?),
+        (Type::I64, Literal::Int(v)) => Value::I64((*v).try_into().ok()?),
+        (Type::I128, Literal::Int(v)) => Value::I128(*v),
+        (Type::F32, Literal::Float(v)) => Value::F32(*v as f32),
+        (Type::F64, Literal::Float(v)) => Value::F64(*v),
+        (Type::String | Type::StringRef | Type::BoxString, Literal::String(v)) => {
+            Value::String(v.to_string())
+        }
+        (Type::Bytes | Type::BytesRef | Type::BoxBytes, Literal::Bytes(v)) => {
+            Value::Bytes(v.to_vec())
+        }
+        _ => return None,
+    })
+}
+
+fn is_zero(value: &Value) -> bool {
+    match value {
+        Value::U8(v) => *v == 0,
+        Value::U16(v) => *v == 0,
+        Value::U32(v) => *v == 0,
+        Value::U64(v) => *v == 0,
+        Value::U128(v) => *v == 0,
+        Value::I8(v) => *v == 0,
+        Value::I16(v) => *v == 0,
+        Value::I32(v) => *v == 0,
+        Value::I64(v) => *v == 0,
+        Value::I128(v) => *v == 0,
+        Value::String(v) => v.is_empty(),
+        Value::Bytes(v) => v.is_empty(),
+        Value::Vec(v) => v.is_empty(),
+        Value::Map(v) => v.is_empty(),
+        _ => false,
+    }
+}
+
+fn mismatch(expected: impl Into<String>) -> Error {
+    Error::TypeMismatch {
+        expected: expected.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(schemas: &[(&str, &str)], root: &str, value: &Value) {
+        let parsed = schemas
+            .iter()
+            .map(|(_, schema)| mabo_parser::Schema::parse(schema, None).unwrap())
+            .collect::<Vec<_>>();
+        let simplified = parsed
+            .iter()
+            .map(mabo_compiler::simplify_schema)
+            .collect::<Vec<_>>();
+        let schemas = schemas
+            .iter()
+            .zip(&simplified)
+            .map(|((name, _), schema)| (*name, schema))
+            .collect::<Vec<_>>();
+        let registry = Registry::new(&schemas);
+
+        let mut buf = Vec::new();
+        registry
+            .encode(schemas[0].0, root, value, &mut buf)
+            .unwrap();
+
+        let decoded = registry.decode(schemas[0].0, root, &mut &*buf).unwrap();
+        assert_eq!(*value, decoded);
+    }
+
+    fn field(id: u32, name: &str, value: Value) -> Field {
+        Field {
+            id,
+            name: Some(name.to_owned()),
+            value,
+        }
+    }
+
+    #[test]
+    fn generics_and_aliases() {
+        let schema = "
+            struct Outer {
+                inner: Alias @1,
+            }
+
+            type Alias = module::Generic<u32>;
+
+            mod module {
+                struct Generic<T> {
+                    value: T @1,
+                    nested: option<Wrapper<T>> @2,
+                }
+
+                enum Wrapper<T> {
+                    Value(T @1) @1,
+                }
+            }
+        ";
+
+        roundtrip(
+            &[("test", schema)],
+            "Outer",
+            &Value::Struct(vec![field(
+                1,
+                "inner",
+                Value::Struct(vec![
+                    field(1, "value", Value::U32(5)),
+                    field(
+                        2,
+                        "nested",
+                        Value::Option(Some(
+                            Value::Enum(Variant {
+                                id: 1,
+                                name: "Value".to_owned(),
+                                fields: vec![Field {
+                                    id: 1,
+                                    name: None,
+                                    value: Value::U32(6),
+                                }],
+                            })
+                            .into(),
+                        )),
+                    ),
+                ]),
+            )]),
+        );
+    }
+
+    #[test]
+    fn imports() {
+        let schema = "
+            use other::module;
+            use other::module::Imported;
+
+            struct Sample {
+                first: module::Imported @1,
+                second: Imported @2,
+            }
+        ";
+        let other = "
+            mod module {
+                struct Imported {
+                    value: string @1,
+                }
+            }
+        ";
+
+        let imported = Value::Struct(vec![field(1, "value", Value::String("test".to_owned()))]);
+
+        roundtrip(
+            &[("test", schema), ("other", other)],
+            "Sample",
+            &Value::Struct(vec![
+                field(1, "first", imported.clone()),
+                field(2, "second", imported),
+            ]),
+        );
+    }
+
+    #[test]
+    fn defaults() {
+        let schema = mabo_parser::Schema::parse(
+            r#"struct Sample { retries: u32 = 3 @1, name: string = "test" @2 }"#,
+            None,
+        )
+        .unwrap();
+        let schema = mabo_compiler::simplify_schema(&schema);
+        let schemas = [("test", &schema)];
+        let registry = Registry::new(&schemas);
+
+        let value = registry.decode("test", "Sample", &mut &[0][..]).unwrap();
+        assert_eq!(Some(&Value::U32(3)), value.field("retries"));
+        assert_eq!(Some(&Value::String("test".to_owned())), value.field("name"));
+
+        let mut buf = Vec::new();
+        registry
+            .encode("test", "Sample", &Value::Struct(Vec::new()), &mut buf)
+            .unwrap();
+        assert_eq!(
+            value,
+            registry.decode("test", "Sample", &mut &*buf).unwrap()
+        );
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let schema = mabo_parser::Schema::parse("struct Sample { value: u32 @1 }", None).unwrap();
+        let schema = mabo_compiler::simplify_schema(&schema);
+        let schemas = [("test", &schema)];
+
+        let value = Value::Struct(vec![field(1, "value", Value::Bool(true))]);
+        assert!(matches!(
+            Registry::new(&schemas).encode("test", "Sample", &value, &mut Vec::new()),
+            Err(Error::TypeMismatch { .. }),
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+//! Reading and writing of multiple length-delimited messages from and to I/O streams.
+//!
+//! Each message is written as a single frame, that consists of the encoded message size (as
+//! _Varint_ encoded `u64`) followed by the encoded message itself. This is the same format as
+//! produced by [`encode_length_delimited`](crate::buf::encode_length_delimited), which allows to
+//! put any amount of messages into a single file or socket.
+//!
+//! # Example
+//!
+//! ```
+//! use mabo::io::{MessageReader, MessageWriter};
+//!
+//! let mut writer = MessageWriter::new(Vec::new());
+//! writer.write(&1_u32).unwrap();
+//! writer.write(&2_u32).unwrap();
+//!
+//! let data = writer.into_inner();
+//! let mut reader = MessageReader::new(&data[..]);
+//!
+//! assert_eq!(Some(1_u32), reader.read().unwrap());
+//! assert_eq!(Some(2_u32), reader.read().unwrap());
+//! assert_eq!(None, reader.read::<u32>().unwrap());
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::{
+    Decode,
+    buf::{self, DecodeOptions, Encode, Limits},
+    varint,
+};
+
+/// Default maximum size of a single frame, which is 4 MiB.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Maximum amount of bytes that the _Varint_ encoded size of a frame can occupy.
+pub(crate) const MAX_PREFIX_SIZE: usize = 10;
+
+/// Result type alias for reading and writing messages, which defaults to the [`Error`] type for
+/// errors.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error that can happen while reading or writing messages.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The underlying reader or writer failed.
+    #[error("I/O error while reading or writing a message")]
+    Io(#[from] io::Error),
+    /// A message failed to decode.
+    #[error("failed to decode the message")]
+    Decode(#[from] buf::Error),
+    /// The size of a frame is invalid.
+    #[error("failed to decode the frame size")]
+    InvalidFrameSize(#[from] varint::DecodeIntError),
+    /// A frame is larger than the configured maximum frame size.
+    #[error("frame size of {size} bytes exceeds the maximum of {max} bytes")]
+    FrameTooLarge {
+        /// Size of the frame.
+        size: u64,
+        /// Maximum allowed frame size.
+        max: usize,
+    },
+}
+
+/// Ensure the frame size doesn't exceed the maximum, and convert it for further use.
+pub(crate) fn check_frame_size(size: u64, max: usize) -> Result<usize> {
+    match usize::try_from(size) {
+        Ok(value) if value <= max => Ok(value),
+        _ => Err(Error::FrameTooLarge { size, max }),
+    }
+}
+
+/// Reader for a sequence of length-delimited messages from any [`Read`] source.
+#[derive(Debug)]
+pub struct MessageReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    max_frame_size: usize,
+    options: DecodeOptions,
+}
+
+impl<R: Read> MessageReader<R> {
+    /// Create a new reader with the [`DEFAULT_MAX_FRAME_SIZE`] and no further decoding limits.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            options: DecodeOptions::default(),
+        }
+    }
+
+    /// Limit the maximum size of a single frame. Larger frames are rejected before any of its
+    /// content is read.
+    #[must_use]
+    pub fn with_max_frame_size(mut self, value: usize) -> Self {
+        self.max_frame_size = value;
+        self
+    }
+
+    /// Apply the given decoding limits to each message.
+    #[must_use]
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Read the next message from the underlying reader.
+    ///
+    /// Returns `None` if the reader reached its end, right at the start of a frame.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if reading from the underlying reader fails, the reader ends in the
+    /// middle of a frame, the frame exceeds the maximum frame size, or the message itself fails to
+    /// decode.
+    pub fn read<T: Decode>(&mut self) -> Result<Option<T>> {
+        let Some(size) = self.read_size()? else {
+            return Ok(None);
+        };
+
+        let size = check_frame_size(size, self.max_frame_size)?;
+
+        self.buf.clear();
+        self.buf.resize(size, 0);
+        self.reader.read_exact(&mut self.buf)?;
+
+        let mut r = &self.buf[..];
+        let value = buf::decode_root(&mut r, &mut Limits::new(self.options))?;
+
+        Ok(Some(value))
+    }
+
+    /// Iterate over all remaining messages of the underlying reader.
+    pub fn messages<T: Decode>(&mut self) -> impl Iterator<Item = Result<T>> + '_ {
+        std::iter::from_fn(|| self.read().transpose())
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Get a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwrap this reader, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the _Varint_ encoded frame size, one byte at a time, as the underlying reader can't
+    /// be peeked into.
+    fn read_size(&mut self) -> Result<Option<u64>> {
+        let mut prefix = [0; MAX_PREFIX_SIZE];
+
+        for i in 0..MAX_PREFIX_SIZE {
+            if let Err(e) = self.reader.read_exact(&mut prefix[i..=i]) {
+                return if i == 0 && e.kind() == io::ErrorKind::UnexpectedEof {
+                    Ok(None)
+                } else {
+                    Err(e.into())
+                };
+            }
+
+            if prefix[i] & 0x80 == 0 {
+                let (size, _) = varint::decode_u64(&prefix[..=i])?;
+                return Ok(Some(size));
+            }
+        }
+
+        Err(varint::DecodeIntError.into())
+    }
+}
+
+/// Writer for a sequence of length-delimited messages into any [`Write`] destination.
+#[derive(Debug)]
+pub struct MessageWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+    max_frame_size: usize,
+}
+
+impl<W: Write> MessageWriter<W> {
+    /// Create a new writer with the [`DEFAULT_MAX_FRAME_SIZE`].
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::new(),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+
+    /// Limit the maximum size of a single frame. Larger messages are rejected before anything is
+    /// written.
+    #[must_use]
+    pub fn with_max_frame_size(mut self, value: usize) -> Self {
+        self.max_frame_size = value;
+        self
+    }
+
+    /// Write a single message as length-delimited frame into the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the message exceeds the maximum frame size, or writing to the
+    /// underlying writer fails.
+    pub fn write(&mut self, value: &impl Encode) -> Result<()> {
+        let size = check_frame_size(value.size() as u64, self.max_frame_size)?;
+
+        self.buf.clear();
+        self.buf.reserve(buf::size_u64(size as u64) + size);
+        buf::encode_length_delimited(&mut self.buf, value);
+
+        self.writer.write_all(&self.buf)?;
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if flushing the underlying writer fails.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(Into::into)
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Get a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwrap this writer, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut writer = MessageWriter::new(Vec::new());
+        writer.write(&"first".to_owned()).unwrap();
+        writer.write(&"second".to_owned()).unwrap();
+
+        let data = writer.into_inner();
+        let mut reader = MessageReader::new(&data[..]);
+        let messages = reader.messages::<String>().collect::<Result<Vec<_>>>();
+
+        assert_eq!(vec!["first", "second"], messages.unwrap());
+    }
+
+    #[test]
+    fn frame_too_large() {
+        let mut writer = MessageWriter::new(Vec::new()).with_max_frame_size(4);
+        assert!(matches!(
+            writer.write(&"too long".to_owned()),
+            Err(Error::FrameTooLarge { size: 9, max: 4 }),
+        ));
+        assert!(writer.get_ref().is_empty());
+
+        let mut writer = MessageWriter::new(Vec::new());
+        writer.write(&"too long".to_owned()).unwrap();
+
+        let data = writer.into_inner();
+        let mut reader = MessageReader::new(&data[..]).with_max_frame_size(4);
+        assert!(matches!(
+            reader.read::<String>(),
+            Err(Error::FrameTooLarge { size: 9, max: 4 }),
+        ));
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let mut writer = MessageWriter::new(Vec::new());
+        writer.write(&"message".to_owned()).unwrap();
+
+        let data = writer.into_inner();
+        let mut reader = MessageReader::new(&data[..data.len() - 1]);
+        assert!(matches!(reader.read::<String>(), Err(Error::Io(_))));
+    }
+}
@@ -1,26 +1,71 @@
 //! Runtime support crate for the Mabo encoding format.
+//!
+//! The crate works in `no_std` environments, as long as an allocator is available. Disabling the
+//! default `std` feature switches over to the `alloc` crate, and uses [`hashbrown`] for the
+//! [`collections::HashMap`] and [`collections::HashSet`] types.
 
-#![expect(
-    clippy::cast_possible_truncation,
-    clippy::implicit_hasher,
-    clippy::inline_always
-)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![expect(clippy::cast_possible_truncation, clippy::inline_always)]
+#![cfg_attr(feature = "std", expect(clippy::implicit_hasher))]
 
-use std::{
-    collections::{HashMap, HashSet},
-    ops::Deref,
+extern crate alloc;
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
 };
+use core::ops::Deref;
+
+use collections::{HashMap, HashSet};
 
-pub use buf::{Buf, BufMut, Bytes, Decode, Encode};
+pub use buf::{BorrowDecode, Buf, BufMut, Bytes, Decode, Encode};
+#[cfg(feature = "chrono")]
+pub use chrono;
+#[cfg(feature = "uuid")]
+pub use uuid;
+pub use value::Value;
 
 pub mod buf;
+#[cfg(feature = "tokio")]
+pub mod codec;
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod raw;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod service;
+pub mod validate;
+pub mod value;
 pub mod varint;
 
+/// Version of the wire format that this crate reads and writes.
+///
+/// It is bumped whenever the encoding changes in a way that makes data incompatible with earlier
+/// versions. Version `2` combines the variant number with a [`VariantEncoding`] marker in each
+/// [`VariantId`], where version `1` wrote the plain variant number.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// Map and set types that back Mabo's `hash_map`, `hash_set`, `btree_map` and `btree_set` types.
+///
+/// The hash collections are the standard library ones with the `std` feature enabled, and the ones
+/// from [`hashbrown`] otherwise. Generated code that targets `no_std` refers to them through this
+/// module.
+pub mod collections {
+    pub use alloc::collections::{BTreeMap, BTreeSet};
+    #[cfg(not(feature = "std"))]
+    pub use hashbrown::{HashMap, HashSet};
+    #[cfg(feature = "std")]
+    pub use std::collections::{HashMap, HashSet};
+}
+
 /// Identifier for a single struct or enum variant field.
 ///
 /// This type contains the actual identifier, plus additional information that is encoded together
 /// with it. It allows for convenient en- and decoding of the information.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct FieldId {
     /// The real decoded field identifier.
     pub value: u32,
@@ -58,7 +103,7 @@ impl FieldId {
 }
 
 /// Minimum detail about how a field is encoded, which allows to skip over a field if it's unknown.
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(u32)]
 pub enum FieldEncoding {
     /// Variable-length integer.
@@ -91,23 +136,124 @@ impl FieldEncoding {
 
 /// Identifier for a single enum variant.
 ///
-/// Currently, this is identical to the raw value it contains, but might be extended to encode
-/// additional information like the [`FieldId`] in the future.
-#[derive(Clone, Copy)]
+/// Similar to the [`FieldId`], this contains the actual identifier, plus additional information
+/// about the encoding of the variant's content. It allows decoders to skip over (or capture) the
+/// content of variants that they don't know.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct VariantId {
     /// The real decoded variant identifier.
     pub value: u32,
+    /// Encoding information for variant skipping.
+    pub encoding: VariantEncoding,
 }
 
 impl VariantId {
     /// Create a new instance of a variant identifier.
     #[inline]
     #[must_use]
-    pub fn new(value: u32) -> Self {
-        Self { value }
+    pub const fn new(value: u32, encoding: VariantEncoding) -> Self {
+        Self { value, encoding }
+    }
+
+    /// Convert from a raw `u32` into the variant identifier.
+    #[inline]
+    #[must_use]
+    pub const fn from_u32(value: u32) -> Self {
+        Self {
+            value: value >> 1,
+            encoding: VariantEncoding::from_u32(value),
+        }
+    }
+
+    /// Convert the variant identifier into a raw `u32`, which contains all its information.
+    #[inline]
+    #[must_use]
+    pub const fn into_u32(self) -> u32 {
+        (self.value << 1) | self.encoding as u32
+    }
+}
+
+/// Minimum detail about how an enum variant is encoded, which allows to skip over a variant if
+/// it's unknown.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum VariantEncoding {
+    /// Unit variant without any content.
+    Unit = 0,
+    /// Variant with named or unnamed fields, terminated by an end marker.
+    Fields = 1,
+}
+
+impl VariantEncoding {
+    /// Extract the variant encoding from a raw variant identifier.
+    #[must_use]
+    pub const fn from_u32(value: u32) -> Self {
+        if value & 1 == 0 {
+            Self::Unit
+        } else {
+            Self::Fields
+        }
     }
 }
 
+/// Fields that were found while decoding a struct, but are not known to its schema definition.
+///
+/// Generated structs only carry this container if the preservation of unknown fields is enabled,
+/// either for all structs in the code generator, or individually with the `#[preserve_unknown]`
+/// attribute. The fields are kept in the order they were found and written back when encoding the
+/// struct again, so data from newer schema versions isn't lost when passing through services that
+/// use an older version.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UnknownFields(Vec<UnknownField>);
+
+impl UnknownFields {
+    /// Create a new, empty container.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Add another field to the end of the container.
+    #[inline]
+    pub fn push(&mut self, field: UnknownField) {
+        self.0.push(field);
+    }
+
+    /// Remove all fields from the container.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl Deref for UnknownFields {
+    type Target = [UnknownField];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a UnknownFields {
+    type IntoIter = core::slice::Iter<'a, UnknownField>;
+    type Item = &'a UnknownField;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Single field, that is unknown to the schema, in its raw encoded form.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownField {
+    /// Identifier of the field, including the encoding of its data.
+    pub id: FieldId,
+    /// Raw encoded data of the field, exactly as it appears after the identifier. For
+    /// [`FieldEncoding::LengthPrefixed`] fields, this includes the length prefix.
+    pub data: Bytes,
+}
+
 /// Convenience macro to include generated Rust code for Mabo schemas.
 ///
 /// By default build scripts write output files into a special directory provided by Cargo as
@@ -132,6 +278,52 @@ macro_rules! include {
     };
 }
 
+/// Decimal number with a fixed amount of fractional digits, that backs Mabo's `decimal` type.
+///
+/// The number is represented as an integer `mantissa`, scaled down by a power of ten. For example,
+/// `12.345` has the mantissa `12345` and the scale `3`. The representation is not normalized,
+/// meaning that `1.0` and `1.00` are different values.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Decimal {
+    /// Unscaled integer value of the number.
+    pub mantissa: i128,
+    /// Amount of fractional digits, as power of ten to scale down the mantissa by.
+    pub scale: u32,
+}
+
+impl Decimal {
+    /// Create a new decimal number from its unscaled value and scale.
+    ///
+    /// ```
+    /// let value = mabo::Decimal::new(12345, 3);
+    /// assert_eq!("12.345", value.to_string());
+    /// ```
+    #[must_use]
+    pub const fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+}
+
+impl core::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let digits = alloc::format!(
+            "{:0>width$}",
+            self.mantissa.unsigned_abs(),
+            width = self.scale as usize + 1
+        );
+        let (int, frac) = digits.split_at(digits.len() - self.scale as usize);
+
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+        f.write_str(int)?;
+        if !frac.is_empty() {
+            write!(f, ".{frac}")?;
+        }
+        Ok(())
+    }
+}
+
 /// A container that guarantees that the contained type is not zero or not empty.
 ///
 /// What's exactly meant by _non-zero_ depends on the type itself. For example, integers define this
@@ -183,6 +375,8 @@ non_zero_collection!(Vec<T>);
 non_zero_collection!(Bytes);
 non_zero_collection!(HashMap<K, V>);
 non_zero_collection!(HashSet<T>);
+non_zero_collection!(BTreeMap<K, V>);
+non_zero_collection!(BTreeSet<T>);
 
 /// String (Mabo's `non_zero<string>`) that is guaranteed to not be empty.
 pub type NonZeroString = NonZero<String>;
@@ -194,3 +388,7 @@ pub type NonZeroVec<T> = NonZero<Vec<T>>;
 pub type NonZeroHashMap<K, V> = NonZero<HashMap<K, V>>;
 /// Hash set (Mabo's `non_zero<hash_set<T>>`) that is guaranteed to not be empty.
 pub type NonZeroHashSet<T> = NonZero<HashSet<T>>;
+/// Ordered map (Mabo's `non_zero<btree_map<K ,V>>`) that is guaranteed to not be empty.
+pub type NonZeroBTreeMap<K, V> = NonZero<BTreeMap<K, V>>;
+/// Ordered set (Mabo's `non_zero<btree_set<T>>`) that is guaranteed to not be empty.
+pub type NonZeroBTreeSet<T> = NonZero<BTreeSet<T>>;
@@ -0,0 +1,651 @@
+//! Schemaless inspection of encoded payloads, for debugging purposes.
+//!
+//! The wire format carries just enough information to find the boundaries of each field, without
+//! knowing the schema the payload was encoded with. [`decode_raw`] uses this to turn any payload
+//! into a tree of field identifiers, encodings, byte offsets and raw values. It is the equivalent
+//! of `protoc --decode_raw` for Mabo.
+//!
+//! Length-prefixed fields are ambiguous without a schema. They might contain strings, bytes,
+//! collections, or nested structs and enums (which are written without a length prefix). The
+//! inspector tries these interpretations in order and picks the first one that allows the rest of
+//! the payload to be decoded as well. The result is therefore a best guess, not a guarantee.
+//!
+//! # Example
+//!
+//! ```
+//! use mabo::raw::{RawValue, decode_raw};
+//!
+//! // Struct with field 1 as `u32` (value 5) and field 2 as `string` (value "hi").
+//! let message = decode_raw(&[0x08, 0x05, 0x11, 0x02, b'h', b'i', 0x00]).unwrap();
+//!
+//! assert_eq!(RawValue::Varint(5), message.fields[0].value);
+//! assert_eq!(RawValue::Bytes(b"hi"), message.fields[1].value);
+//! println!("{message}");
+//! ```
+
+use alloc::{vec, vec::Vec};
+use core::fmt::{self, Display};
+
+use crate::{
+    FieldEncoding, FieldId, VariantEncoding, VariantId,
+    buf::{self, END_MARKER},
+    collections::HashSet,
+    varint,
+};
+
+/// Maximum nesting depth of structs and enums, that the inspector follows.
+const MAX_DEPTH: usize = 64;
+
+/// Result type alias for the raw inspection, which defaults to the [`Error`] type for errors.
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// Error that can happen while inspecting a payload.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Data at the given offset could not be decoded under any interpretation.
+    #[error("failed to decode the payload at offset {offset}")]
+    Decode {
+        /// Byte offset into the payload, where decoding failed.
+        offset: usize,
+        /// Underlying cause of the failure.
+        #[source]
+        source: buf::Error,
+    },
+    /// The payload contains additional data after the end of the message.
+    #[error("unexpected trailing data at offset {0}")]
+    TrailingData(usize),
+}
+
+impl Error {
+    /// Byte offset into the payload, where the error occurred.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        match self {
+            Self::Decode { offset, .. } | Self::TrailingData(offset) => *offset,
+        }
+    }
+}
+
+/// Decoded top-level message, either a struct or an enum.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RawMessage<'a> {
+    /// Identifier of the variant, if the payload appears to be an enum.
+    pub variant: Option<u32>,
+    /// Fields of the struct or enum variant, in the order they appear in the payload.
+    pub fields: Vec<RawField<'a>>,
+}
+
+/// Single field, as found in the payload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RawField<'a> {
+    /// Identifier of the field.
+    pub id: u32,
+    /// Encoding of the field's value.
+    pub encoding: FieldEncoding,
+    /// Byte offset of the field identifier, relative to the start of the payload.
+    pub offset: usize,
+    /// Raw value of the field.
+    pub value: RawValue<'a>,
+}
+
+/// Raw value of a field, interpreted only by its encoding.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RawValue<'a> {
+    /// _Varint_ encoded integer, without the `ZigZag` decoding for signed integers applied.
+    Varint(u128),
+    /// 1-byte value like a `bool`, `u8` or `i8`.
+    Fixed1(u8),
+    /// 4-byte value like a `f32`, in big-endian byte order.
+    Fixed4([u8; 4]),
+    /// 8-byte value like a `f64`, in big-endian byte order.
+    Fixed8([u8; 8]),
+    /// Length-prefixed data that doesn't look like a message, like strings, bytes or collections.
+    Bytes(&'a [u8]),
+    /// Length-prefixed data that can be decoded as a struct.
+    Message(Vec<RawField<'a>>),
+    /// Struct that is embedded directly, without length prefix.
+    Struct(Vec<RawField<'a>>),
+    /// Enum variant that is embedded directly, without length prefix. Unit variants have no
+    /// fields.
+    Variant {
+        /// Identifier of the variant.
+        id: u32,
+        /// Fields of the variant, in the order they appear in the payload.
+        fields: Vec<RawField<'a>>,
+    },
+}
+
+impl RawValue<'_> {
+    /// Interpret a _Varint_ as signed integer, by reversing its `ZigZag` encoding.
+    #[expect(clippy::cast_possible_wrap)]
+    #[must_use]
+    pub const fn as_signed(&self) -> Option<i128> {
+        match *self {
+            Self::Varint(value) => Some(((value >> 1) as i128) ^ -((value & 0b1) as i128)),
+            _ => None,
+        }
+    }
+}
+
+/// Inspect an encoded payload without knowing its schema.
+///
+/// The payload is first interpreted as struct and, if that fails, as enum. An empty payload is
+/// considered a unit struct.
+///
+/// # Errors
+///
+/// Will return `Err` if the payload can't be decoded under any interpretation. The error carries
+/// the furthest byte offset the inspector could reach, which usually points close to the location
+/// of corrupted data.
+pub fn decode_raw(data: &[u8]) -> Result<RawMessage<'_>> {
+    if data.is_empty() {
+        return Ok(RawMessage {
+            variant: None,
+            fields: Vec::new(),
+        });
+    }
+
+    let mut inspector = Inspector {
+        data,
+        error: None,
+        failed: HashSet::new(),
+    };
+
+    if let Some((fields, _)) = inspector.fields(0, data.len(), true, 0) {
+        return Ok(RawMessage {
+            variant: None,
+            fields,
+        });
+    }
+
+    if let Some((id, pos)) = inspector.variant_id(0, data.len()) {
+        let fields = match id.encoding {
+            VariantEncoding::Unit if pos == data.len() => Some(Vec::new()),
+            VariantEncoding::Unit => {
+                inspector.fail(pos, Error::TrailingData(pos));
+                None
+            }
+            VariantEncoding::Fields => inspector
+                .fields(pos, data.len(), true, 0)
+                .map(|(fields, _)| fields),
+        };
+
+        if let Some(fields) = fields {
+            return Ok(RawMessage {
+                variant: Some(id.value),
+                fields,
+            });
+        }
+    }
+
+    Err(inspector.error.unwrap_or(Error::TrailingData(0)))
+}
+
+/// Walks over the payload, keeping track of the most relevant error.
+struct Inspector<'a> {
+    data: &'a [u8],
+    /// Error with the largest offset that was encountered so far.
+    error: Option<Error>,
+    /// Field sequences, identified by their start and end position, that are known to not decode,
+    /// which avoids exponential runtime while backtracking.
+    failed: HashSet<(usize, usize, bool)>,
+}
+
+/// Single field while decoding a field sequence, with all the possible interpretations of its
+/// value, and the one that is currently tried.
+struct Candidate<'a> {
+    offset: usize,
+    values: Vec<(RawField<'a>, usize)>,
+    current: usize,
+}
+
+impl<'a> Inspector<'a> {
+    fn fail(&mut self, offset: usize, error: Error) {
+        if self.error.as_ref().is_none_or(|e| e.offset() <= offset) {
+            self.error = Some(error);
+        }
+    }
+
+    fn fail_decode(&mut self, offset: usize, source: buf::Error) {
+        self.fail(offset, Error::Decode { offset, source });
+    }
+
+    fn varint<T>(
+        &mut self,
+        pos: usize,
+        end: usize,
+        decode: impl Fn(&[u8]) -> Result<(T, usize), varint::DecodeIntError>,
+    ) -> Option<(T, usize)> {
+        match decode(&self.data[pos..end]) {
+            Ok((value, consumed)) => Some((value, pos + consumed)),
+            Err(_) if pos == end => {
+                self.fail_decode(pos, buf::Error::InsufficientData);
+                None
+            }
+            Err(e) => {
+                self.fail_decode(pos, e.into());
+                None
+            }
+        }
+    }
+
+    fn variant_id(&mut self, pos: usize, end: usize) -> Option<(VariantId, usize)> {
+        self.varint(pos, end, varint::decode_u32)
+            .map(|(id, pos)| (VariantId::from_u32(id), pos))
+    }
+
+    fn fixed<const N: usize>(&mut self, pos: usize, end: usize) -> Option<([u8; N], usize)> {
+        if let Some(value) = self.data[pos..end].first_chunk() {
+            Some((*value, pos + N))
+        } else {
+            self.fail_decode(pos, buf::Error::InsufficientData);
+            None
+        }
+    }
+
+    /// Decode a sequence of fields, terminated by an end marker, and return the fields together
+    /// with the position right after the end marker. If `exact` is set, the end marker must be the
+    /// very last byte before `end`.
+    ///
+    /// This explores the possible interpretations of each field's value depth-first and backtracks
+    /// if the remaining fields can't be decoded.
+    fn fields(
+        &mut self,
+        start: usize,
+        end: usize,
+        exact: bool,
+        depth: usize,
+    ) -> Option<(Vec<RawField<'a>>, usize)> {
+        if depth > MAX_DEPTH {
+            self.fail_decode(start, buf::Error::DepthExceeded);
+            return None;
+        }
+
+        let mut stack = Vec::<Candidate<'a>>::new();
+        let mut pos = start;
+
+        loop {
+            if self.failed.contains(&(pos, end, exact)) {
+                // Skip right to backtracking.
+            } else if self.data.get(pos) == Some(&(END_MARKER as u8)) && pos < end {
+                if !exact || pos + 1 == end {
+                    let fields = stack
+                        .into_iter()
+                        .map(|mut candidate| candidate.values.swap_remove(candidate.current).0)
+                        .collect();
+                    return Some((fields, pos + 1));
+                }
+
+                self.fail(pos + 1, Error::TrailingData(pos + 1));
+            } else {
+                let values = self.field(pos, end, depth);
+                if !values.is_empty() {
+                    pos = values[0].1;
+                    stack.push(Candidate {
+                        offset: values[0].0.offset,
+                        values,
+                        current: 0,
+                    });
+                    continue;
+                }
+            }
+
+            self.failed.insert((pos, end, exact));
+
+            loop {
+                let candidate = stack.last_mut()?;
+                if candidate.current + 1 < candidate.values.len() {
+                    candidate.current += 1;
+                    pos = candidate.values[candidate.current].1;
+                    break;
+                }
+
+                pos = candidate.offset;
+                stack.pop();
+                self.failed.insert((pos, end, exact));
+            }
+        }
+    }
+
+    /// Decode a single field and return all possible interpretations of its value, together with
+    /// the position right after the value.
+    fn field(&mut self, offset: usize, end: usize, depth: usize) -> Vec<(RawField<'a>, usize)> {
+        let Some((raw_id, pos)) = self.varint(offset, end, varint::decode_u32) else {
+            return Vec::new();
+        };
+        let Some(id) = FieldId::from_u32(raw_id) else {
+            self.fail_decode(offset, buf::Error::UnknownEncoding(raw_id));
+            return Vec::new();
+        };
+
+        let field = |value| RawField {
+            id: id.value,
+            encoding: id.encoding,
+            offset,
+            value,
+        };
+
+        let single = |value: Option<(RawValue<'a>, usize)>| {
+            value
+                .map(|(value, pos)| vec![(field(value), pos)])
+                .unwrap_or_default()
+        };
+
+        match id.encoding {
+            FieldEncoding::Varint => single(
+                self.varint(pos, end, varint::decode_u128)
+                    .map(|(v, p)| (RawValue::Varint(v), p)),
+            ),
+            FieldEncoding::Fixed1 => single(
+                self.fixed(pos, end)
+                    .map(|([v], p)| (RawValue::Fixed1(v), p)),
+            ),
+            FieldEncoding::Fixed4 => {
+                single(self.fixed(pos, end).map(|(v, p)| (RawValue::Fixed4(v), p)))
+            }
+            FieldEncoding::Fixed8 => {
+                single(self.fixed(pos, end).map(|(v, p)| (RawValue::Fixed8(v), p)))
+            }
+            FieldEncoding::LengthPrefixed => self
+                .length_prefixed(pos, end, depth)
+                .into_iter()
+                .map(|(value, pos)| (field(value), pos))
+                .collect(),
+        }
+    }
+
+    /// Collect the possible interpretations of a length-prefixed value, in the order of their
+    /// likeliness.
+    fn length_prefixed(
+        &mut self,
+        pos: usize,
+        end: usize,
+        depth: usize,
+    ) -> Vec<(RawValue<'a>, usize)> {
+        let mut values = Vec::new();
+
+        if let Some((len, start)) = self.varint(pos, end, varint::decode_u64) {
+            match usize::try_from(len) {
+                Ok(len) if len <= end - start => {
+                    let data_end = start + len;
+                    let value = match self.fields(start, data_end, true, depth + 1) {
+                        Some((fields, _)) if !fields.is_empty() => RawValue::Message(fields),
+                        _ => RawValue::Bytes(&self.data[start..data_end]),
+                    };
+                    values.push((value, data_end));
+                }
+                _ => self.fail_decode(start, buf::Error::InsufficientData),
+            }
+        }
+
+        if let Some((fields, next)) = self.fields(pos, end, false, depth + 1) {
+            values.push((RawValue::Struct(fields), next));
+        }
+
+        if let Some((id, start)) = self.variant_id(pos, end) {
+            match id.encoding {
+                VariantEncoding::Unit => values.push((
+                    RawValue::Variant {
+                        id: id.value,
+                        fields: Vec::new(),
+                    },
+                    start,
+                )),
+                VariantEncoding::Fields => {
+                    if let Some((fields, next)) = self.fields(start, end, false, depth + 1) {
+                        values.push((
+                            RawValue::Variant {
+                                id: id.value,
+                                fields,
+                            },
+                            next,
+                        ));
+                    }
+                }
+            }
+        }
+
+        values
+    }
+}
+
+impl Display for RawMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.variant {
+            Some(id) => {
+                writeln!(f, "variant {id} {{")?;
+                write_fields(f, &self.fields, 1)?;
+                writeln!(f, "}}")
+            }
+            None => write_fields(f, &self.fields, 0),
+        }
+    }
+}
+
+fn write_fields(f: &mut fmt::Formatter<'_>, fields: &[RawField<'_>], indent: usize) -> fmt::Result {
+    for field in fields {
+        write!(
+            f,
+            "{:indent$}[{}] {} {}: ",
+            "",
+            field.offset,
+            field.id,
+            encoding_name(field.encoding),
+            indent = indent * 2,
+        )?;
+
+        let nested = match &field.value {
+            RawValue::Varint(value) => {
+                writeln!(
+                    f,
+                    "{value} (zigzag {})",
+                    field.value.as_signed().unwrap_or_default()
+                )?;
+                continue;
+            }
+            RawValue::Fixed1(value) => {
+                writeln!(f, "{value:#04x}")?;
+                continue;
+            }
+            RawValue::Fixed4(value) => {
+                writeln!(f, "{} (f32 {})", Hex(value), f32::from_be_bytes(*value))?;
+                continue;
+            }
+            RawValue::Fixed8(value) => {
+                writeln!(f, "{} (f64 {})", Hex(value), f64::from_be_bytes(*value))?;
+                continue;
+            }
+            RawValue::Bytes(value) => {
+                match core::str::from_utf8(value) {
+                    Ok(s) if !s.chars().any(char::is_control) => writeln!(f, "{s:?}")?,
+                    _ => writeln!(f, "{}", Hex(value))?,
+                }
+                continue;
+            }
+            RawValue::Message(fields) => {
+                writeln!(f, "message {{")?;
+                fields
+            }
+            RawValue::Struct(fields) => {
+                writeln!(f, "struct {{")?;
+                fields
+            }
+            RawValue::Variant { id, fields } => {
+                writeln!(f, "variant {id} {{")?;
+                fields
+            }
+        };
+
+        write_fields(f, nested, indent + 1)?;
+        writeln!(f, "{:indent$}}}", "", indent = indent * 2)?;
+    }
+
+    Ok(())
+}
+
+const fn encoding_name(encoding: FieldEncoding) -> &'static str {
+    match encoding {
+        FieldEncoding::Varint => "varint",
+        FieldEncoding::LengthPrefixed => "length-prefixed",
+        FieldEncoding::Fixed1 => "fixed1",
+        FieldEncoding::Fixed4 => "fixed4",
+        FieldEncoding::Fixed8 => "fixed8",
+    }
+}
+
+/// Formats raw bytes as space separated hex values.
+struct Hex<'a>(&'a [u8]);
+
+impl Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{b:02x}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buf::{self, Encode};
+
+    fn field(id: u32, encoding: FieldEncoding, offset: usize, value: RawValue<'_>) -> RawField<'_> {
+        RawField {
+            id,
+            encoding,
+            offset,
+            value,
+        }
+    }
+
+    fn encode_id(buf: &mut Vec<u8>, id: u32, encoding: FieldEncoding) {
+        buf::encode_id(buf, FieldId::new(id, encoding));
+    }
+
+    #[test]
+    fn primitives() {
+        let mut data = Vec::new();
+        encode_id(&mut data, 1, FieldEncoding::Varint);
+        buf::encode_i32(&mut data, -3);
+        encode_id(&mut data, 2, FieldEncoding::Fixed1);
+        buf::encode_bool(&mut data, true);
+        encode_id(&mut data, 3, FieldEncoding::Fixed8);
+        buf::encode_f64(&mut data, 1.5);
+        encode_id(&mut data, 4, FieldEncoding::LengthPrefixed);
+        buf::encode_string(&mut data, "test");
+        buf::encode_u32(&mut data, END_MARKER);
+
+        let message = decode_raw(&data).unwrap();
+
+        assert_eq!(None, message.variant);
+        assert_eq!(
+            vec![
+                field(1, FieldEncoding::Varint, 0, RawValue::Varint(5)),
+                field(2, FieldEncoding::Fixed1, 2, RawValue::Fixed1(1)),
+                field(
+                    3,
+                    FieldEncoding::Fixed8,
+                    4,
+                    RawValue::Fixed8(1.5_f64.to_be_bytes())
+                ),
+                field(
+                    4,
+                    FieldEncoding::LengthPrefixed,
+                    13,
+                    RawValue::Bytes(b"test")
+                ),
+            ],
+            message.fields
+        );
+        assert_eq!(Some(-3), message.fields[0].value.as_signed());
+    }
+
+    #[test]
+    fn nested() {
+        let mut inner = Vec::new();
+        encode_id(&mut inner, 1, FieldEncoding::Varint);
+        buf::encode_u32(&mut inner, 7);
+        buf::encode_u32(&mut inner, END_MARKER);
+
+        let mut data = Vec::new();
+        // Nested struct, embedded without length prefix.
+        encode_id(&mut data, 1, FieldEncoding::LengthPrefixed);
+        data.extend_from_slice(&inner);
+        // Unit enum variant.
+        encode_id(&mut data, 2, FieldEncoding::LengthPrefixed);
+        buf::encode_variant_id(&mut data, VariantId::new(3, VariantEncoding::Unit));
+        // Collection that contains a single struct.
+        encode_id(&mut data, 3, FieldEncoding::LengthPrefixed);
+        buf::encode_bytes_std(&mut data, &inner);
+        buf::encode_u32(&mut data, END_MARKER);
+
+        let message = decode_raw(&data).unwrap();
+        let inner = vec![field(1, FieldEncoding::Varint, 1, RawValue::Varint(7))];
+
+        assert_eq!(
+            vec![
+                field(
+                    1,
+                    FieldEncoding::LengthPrefixed,
+                    0,
+                    RawValue::Struct(inner.clone())
+                ),
+                field(
+                    2,
+                    FieldEncoding::LengthPrefixed,
+                    4,
+                    RawValue::Variant {
+                        id: 3,
+                        fields: Vec::new()
+                    }
+                ),
+                field(
+                    3,
+                    FieldEncoding::LengthPrefixed,
+                    6,
+                    RawValue::Message(vec![field(
+                        1,
+                        FieldEncoding::Varint,
+                        8,
+                        RawValue::Varint(7)
+                    )])
+                ),
+            ],
+            message.fields
+        );
+    }
+
+    #[test]
+    fn enum_variant() {
+        let mut data = Vec::new();
+        buf::encode_variant_id(&mut data, VariantId::new(2, VariantEncoding::Fields));
+        encode_id(&mut data, 1, FieldEncoding::Fixed1);
+        true.encode(&mut data);
+        buf::encode_u32(&mut data, END_MARKER);
+
+        let message = decode_raw(&data).unwrap();
+
+        assert_eq!(Some(2), message.variant);
+        assert_eq!(
+            vec![field(1, FieldEncoding::Fixed1, 1, RawValue::Fixed1(1))],
+            message.fields
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut data = Vec::new();
+        encode_id(&mut data, 1, FieldEncoding::Varint);
+        buf::encode_u32(&mut data, 5);
+        data.push(0xff);
+
+        let err = decode_raw(&data).unwrap_err();
+
+        assert_eq!(2, err.offset());
+    }
+}
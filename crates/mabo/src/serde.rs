@@ -0,0 +1,1317 @@
+//! Integration with [`serde`], to en- and decode any type that implements [`Serialize`] or
+//! [`Deserialize`] in the Mabo wire format, without generating code from a schema.
+//!
+//! # Compatibility with generated code
+//!
+//! The data model of Serde maps onto Mabo's types as follows, which produces the same payload as
+//! code generated from an equivalent schema:
+//!
+//! - Booleans, integers, floats, strings and byte arrays map to the Mabo type of the same name.
+//!   A `char` is encoded like an `u32`.
+//! - Sequences (like `Vec<T>` or `HashSet<T>`) map to `vec<T>` or `hash_set<T>` and maps to
+//!   `hash_map<K, V>`. A `Vec<u8>` is compatible with `bytes` as well.
+//! - Tuples map to Mabo tuples. Fixed-size arrays are treated as tuples by Serde, and are only
+//!   compatible with Mabo's `[T; N]` when used directly as field type.
+//! - Structs, tuple structs and unit structs map to Mabo structs. A newtype struct is treated as
+//!   tuple struct with a single field, unless it's marked as `#[serde(transparent)]`.
+//! - Enums map to Mabo enums, with all their variant kinds.
+//! - `Option<T>` maps to `option<T>`. If used directly as field type, an absent value omits the
+//!   field entirely, same as in generated code.
+//!
+//! # Identifiers
+//!
+//! Field and variant identifiers are derived from the declaration order, starting at `1`. The
+//! identifier can be set explicitly by adding an `@<id>` suffix to the name, like
+//! `#[serde(rename = "name@5")]`. Same as in schemas, the following fields continue counting from
+//! there. Variants without explicit identifier always use their position instead.
+//!
+//! Explicit identifiers are recommended, as re-ordering fields or skipping them with
+//! `#[serde(skip_serializing)]` would otherwise change the identifiers of others.
+//!
+//! # Example
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Sample {
+//!     name: String,
+//!     #[serde(rename = "age@5")]
+//!     age: u8,
+//!     tags: Option<Vec<String>>,
+//! }
+//!
+//! let sample = Sample {
+//!     name: "test".to_owned(),
+//!     age: 30,
+//!     tags: None,
+//! };
+//!
+//! let buf = mabo::serde::to_vec(&sample).unwrap();
+//! assert_eq!(sample, mabo::serde::from_slice(&buf).unwrap());
+//! ```
+
+use std::fmt::Display;
+
+use ::serde::{
+    Deserialize, Serialize,
+    de::{self, DeserializeSeed, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, ser,
+};
+
+use crate::{
+    FieldEncoding, FieldId, VariantEncoding, VariantId,
+    buf::{self, BufMut, DecodeOptions, END_MARKER, Limits},
+};
+
+/// Result type alias for the Serde integration, which defaults to the [`Error`] type for errors.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error that can happen while serializing or deserializing values.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Custom error message, produced by the [`Serialize`] or [`Deserialize`] implementation.
+    #[error("{0}")]
+    Custom(String),
+    /// The underlying decoding of the payload failed.
+    #[error("failed to decode the value")]
+    Decode(#[from] buf::Error),
+    /// The type tried to deserialize without giving any hint about the expected data type.
+    #[error("the Mabo format is not self-describing")]
+    NotSelfDescribing,
+    /// The identifier suffix in the name of a field or variant is invalid.
+    #[error("invalid identifier in name `{0}`")]
+    InvalidId(String),
+    /// A collection element didn't consume any data, which would result in an endless loop.
+    #[error("collection elements must not be empty")]
+    EmptyElement,
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Serialize the value into a new byte vector.
+///
+/// # Errors
+///
+/// Will return `Err` if the value's [`Serialize`] implementation fails, or a field or variant name
+/// contains an invalid identifier.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut buf = Vec::new();
+    value.serialize(Serializer::new(&mut buf, Level::Nested))?;
+    Ok(buf)
+}
+
+/// Serialize the value into the given writer.
+///
+/// # Errors
+///
+/// Will return `Err` if the value's [`Serialize`] implementation fails, or a field or variant name
+/// contains an invalid identifier.
+pub fn to_writer<T>(w: &mut impl BufMut, value: &T) -> Result<()>
+where
+    T: Serialize + ?Sized,
+{
+    w.put_slice(&to_vec(value)?);
+    Ok(())
+}
+
+/// Deserialize a value from the given data, borrowing from it where possible.
+///
+/// # Errors
+///
+/// Will return `Err` if the data doesn't contain a valid payload for the value.
+pub fn from_slice<'de, T>(data: &'de [u8]) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    from_slice_with(data, &mut Limits::new(DecodeOptions::new()))
+}
+
+/// Deserialize a value from the given data, while enforcing the given decoding limits.
+///
+/// # Errors
+///
+/// Will return `Err` if the data doesn't contain a valid payload for the value, or it exceeds
+/// the decoding limits.
+pub fn from_slice_with<'de, T>(mut data: &'de [u8], limits: &mut Limits) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(Deserializer {
+        input: &mut data,
+        limits,
+        level: Level::Nested,
+    })
+}
+
+/// Position of a value, which changes the encoding of some types.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Level {
+    /// Direct value of a field, where an absent option omits the whole field and tuples are
+    /// prefixed with their size.
+    Field,
+    /// Present value of an optional field, where tuples are prefixed with their size.
+    Optional,
+    /// Any other value, like the elements of collections.
+    Nested,
+}
+
+/// Extract the identifier from the `@<id>` suffix of a name, or fall back to the given default.
+fn parse_id(name: &str, default: u32) -> Result<u32> {
+    match name.rsplit_once('@') {
+        Some((_, id)) => id.parse().map_err(|_| Error::InvalidId(name.to_owned())),
+        None => Ok(default),
+    }
+}
+
+/// Find the name of the field, that belongs to the given identifier. Same as in schemas, fields
+/// without explicit identifier continue counting from the previous field.
+fn find_field(names: &'static [&'static str], id: u32) -> Result<Option<&'static str>> {
+    let mut next = 1;
+    for name in names {
+        let current = parse_id(name, next)?;
+        if current == id {
+            return Ok(Some(name));
+        }
+        next = current.saturating_add(1);
+    }
+
+    Ok(None)
+}
+
+/// Find the name of the variant, that belongs to the given identifier. Variants without explicit
+/// identifier always use their position, as Serde doesn't provide the other variants' names
+/// during serialization.
+fn find_variant(names: &'static [&'static str], id: u32) -> Result<Option<&'static str>> {
+    for (index, name) in (1..).zip(names) {
+        if parse_id(name, index)? == id {
+            return Ok(Some(name));
+        }
+    }
+
+    Ok(None)
+}
+
+struct Serializer<'a> {
+    out: &'a mut Vec<u8>,
+    level: Level,
+}
+
+impl<'a> Serializer<'a> {
+    fn new(out: &'a mut Vec<u8>, level: Level) -> Self {
+        Self { out, level }
+    }
+}
+
+/// The output of serializing a single value is the field encoding that must be used for it, or
+/// `None` if the value was omitted, because it is an absent optional field.
+type Encoded = Option<FieldEncoding>;
+
+macro_rules! serialize_int {
+    ($($ty:ty => $encoding:ident),+ $(,)?) => {
+        paste::paste! {$(
+            fn [<serialize_ $ty>](self, v: $ty) -> Result<Self::Ok> {
+                buf::[<encode_ $ty>](self.out, v);
+                Ok(Some(FieldEncoding::$encoding))
+            }
+        )+}
+    };
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = Encoded;
+    type Error = Error;
+    type SerializeSeq = Collection<'a>;
+    type SerializeTuple = Collection<'a>;
+    type SerializeTupleStruct = Fields<'a>;
+    type SerializeTupleVariant = Fields<'a>;
+    type SerializeMap = Collection<'a>;
+    type SerializeStruct = Fields<'a>;
+    type SerializeStructVariant = Fields<'a>;
+
+    serialize_int!(
+        bool => Fixed1,
+        u8 => Fixed1,
+        u16 => Varint,
+        u32 => Varint,
+        u64 => Varint,
+        u128 => Varint,
+        i8 => Fixed1,
+        i16 => Varint,
+        i32 => Varint,
+        i64 => Varint,
+        i128 => Varint,
+        f32 => Fixed4,
+        f64 => Fixed8,
+    );
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.serialize_u32(v.into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        buf::encode_string(self.out, v);
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        buf::encode_bytes_std(self.out, v);
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        if self.level == Level::Field {
+            return Ok(None);
+        }
+
+        buf::encode_u8(self.out, 0);
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        if self.level == Level::Field {
+            return value.serialize(Serializer::new(self.out, Level::Optional));
+        }
+
+        buf::encode_u8(self.out, 1);
+        value.serialize(Serializer::new(self.out, Level::Nested))?;
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        let id = parse_id(variant, variant_index + 1)?;
+        buf::encode_variant_id(self.out, VariantId::new(id, VariantEncoding::Unit));
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut fields = Fields::new(self.out);
+        ser::SerializeTupleStruct::serialize_field(&mut fields, value)?;
+        ser::SerializeTupleStruct::end(fields)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut fields = self.serialize_tuple_variant("", variant_index, variant, 1)?;
+        ser::SerializeTupleVariant::serialize_field(&mut fields, value)?;
+        ser::SerializeTupleVariant::end(fields)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(Collection::new(self.out, true))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Ok(Collection::new(self.out, self.level != Level::Nested))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Ok(Fields::new(self.out))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        let id = parse_id(variant, variant_index + 1)?;
+        buf::encode_variant_id(self.out, VariantId::new(id, VariantEncoding::Fields));
+        Ok(Fields::new(self.out))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(Collection::new(self.out, true))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(Fields::new(self.out))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializer for collections and tuples, which are (optionally) prefixed with their byte size.
+struct Collection<'a> {
+    out: &'a mut Vec<u8>,
+    buf: Vec<u8>,
+    prefixed: bool,
+}
+
+impl<'a> Collection<'a> {
+    fn new(out: &'a mut Vec<u8>, prefixed: bool) -> Self {
+        Self {
+            out,
+            buf: Vec::new(),
+            prefixed,
+        }
+    }
+
+    fn element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value
+            .serialize(Serializer::new(&mut self.buf, Level::Nested))
+            .map(|_| ())
+    }
+
+    fn finish(self) {
+        if self.prefixed {
+            buf::encode_u64(self.out, self.buf.len() as u64);
+        }
+        self.out.extend_from_slice(&self.buf);
+    }
+}
+
+impl ser::SerializeSeq for Collection<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+impl ser::SerializeTuple for Collection<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+impl ser::SerializeMap for Collection<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+/// Serializer for the fields of structs and enum variants, which are terminated by an end marker.
+struct Fields<'a> {
+    out: &'a mut Vec<u8>,
+    next_id: u32,
+}
+
+impl<'a> Fields<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self { out, next_id: 1 }
+    }
+
+    fn field<T>(&mut self, name: Option<&str>, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let id = parse_id(name.unwrap_or_default(), self.next_id)?;
+        self.next_id = id.saturating_add(1);
+
+        let mut buf = Vec::new();
+        if let Some(encoding) = value.serialize(Serializer::new(&mut buf, Level::Field))? {
+            buf::encode_id(self.out, FieldId::new(id, encoding));
+            self.out.extend_from_slice(&buf);
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) {
+        buf::encode_u32(self.out, END_MARKER);
+    }
+}
+
+impl ser::SerializeTupleStruct for Fields<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.field(None, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+impl ser::SerializeTupleVariant for Fields<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.field(None, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+impl ser::SerializeStruct for Fields<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.field(Some(key), value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.next_id = parse_id(key, self.next_id)?.saturating_add(1);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+impl ser::SerializeStructVariant for Fields<'_> {
+    type Ok = Encoded;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.field(Some(key), value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.next_id = parse_id(key, self.next_id)?.saturating_add(1);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish();
+        Ok(Some(FieldEncoding::LengthPrefixed))
+    }
+}
+
+struct Deserializer<'a, 'de> {
+    input: &'a mut &'de [u8],
+    limits: &'a mut Limits,
+    level: Level,
+}
+
+macro_rules! deserialize_int {
+    ($($ty:ty),+ $(,)?) => {
+        paste::paste! {$(
+            fn [<deserialize_ $ty>]<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.[<visit_ $ty>](<$ty as buf::Decode>::decode_with(self.input, self.limits)?)
+            }
+        )+}
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_, 'de> {
+    type Error = Error;
+
+    deserialize_int!(
+        bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64
+    );
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::NotSelfDescribing)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let value = buf::decode_u32(self.input, self.limits)?;
+        match char::from_u32(value) {
+            Some(c) => visitor.visit_char(c),
+            None => Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(value.into()),
+                &"a unicode scalar value",
+            )),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let value = buf::decode_string_borrowed(self.input, self.limits)?;
+        self.limits.allocate(value.len())?;
+        visitor.visit_borrowed_str(value)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let value = buf::decode_bytes_borrowed(self.input, self.limits)?;
+        self.limits.allocate(value.len())?;
+        visitor.visit_borrowed_bytes(value)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.level == Level::Field {
+            return visitor.visit_some(Deserializer {
+                level: Level::Optional,
+                ..self
+            });
+        }
+
+        if buf::decode_presence(self.input, self.limits)? {
+            visitor.visit_some(Deserializer {
+                level: Level::Nested,
+                ..self
+            })
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.limits.nested(|limits| {
+            let mut fields = TupleFields::new(self.input, limits, 1, None);
+            Ok(fields
+                .next_element(NewtypeSeed(visitor))
+                .and_then(|value| fields.finish().map(|()| value)))
+        })?
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut input = buf::decode_bytes_borrowed(self.input, self.limits)?;
+        visitor.visit_seq(Elements::new(&mut input, self.limits, None))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.level != Level::Nested {
+            buf::decode_u64(self.input, self.limits)?;
+        }
+
+        visitor.visit_seq(Elements::new(self.input, self.limits, Some(len)))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.limits
+            .nested(|limits| Ok(TupleFields::new(self.input, limits, len, None).visit(visitor)))?
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut input = buf::decode_bytes_borrowed(self.input, self.limits)?;
+        visitor.visit_map(Elements::new(&mut input, self.limits, None))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.limits.nested(|limits| {
+            Ok(visitor.visit_map(StructFields {
+                input: self.input,
+                limits,
+                fields,
+                ended: false,
+            }))
+        })?
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.limits.nested(|limits| {
+            Ok(visitor.visit_enum(Enum {
+                input: self.input,
+                limits,
+                variants,
+                encoding: VariantEncoding::Unit,
+            }))
+        })?
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::NotSelfDescribing)
+    }
+
+    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::NotSelfDescribing)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Access to the elements of collections and tuples.
+struct Elements<'a, 'de> {
+    input: &'a mut &'de [u8],
+    limits: &'a mut Limits,
+    /// Fixed amount of elements, or `None` to read elements until the input is exhausted.
+    len: Option<usize>,
+    count: usize,
+}
+
+impl<'a, 'de> Elements<'a, 'de> {
+    fn new(input: &'a mut &'de [u8], limits: &'a mut Limits, len: Option<usize>) -> Self {
+        Self {
+            input,
+            limits,
+            len,
+            count: 0,
+        }
+    }
+
+    fn element<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.len {
+            Some(len) if self.count >= len => return Ok(None),
+            Some(_) => {}
+            None if self.input.is_empty() => return Ok(None),
+            None => self.limits.ensure_length(self.count + 1)?,
+        }
+
+        let remaining = self.input.len();
+        let value = seed.deserialize(Deserializer {
+            input: self.input,
+            limits: self.limits,
+            level: Level::Nested,
+        })?;
+
+        if self.len.is_none() && self.input.len() == remaining {
+            return Err(Error::EmptyElement);
+        }
+
+        self.count += 1;
+        Ok(Some(value))
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.element(seed)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Elements<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.element(seed)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer {
+            input: self.input,
+            limits: self.limits,
+            level: Level::Nested,
+        })
+    }
+}
+
+/// Access to the named fields of structs and enum variants.
+struct StructFields<'a, 'de> {
+    input: &'a mut &'de [u8],
+    limits: &'a mut Limits,
+    fields: &'static [&'static str],
+    ended: bool,
+}
+
+impl<'de> de::MapAccess<'de> for StructFields<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        while !self.ended {
+            let id = buf::decode_id(self.input, self.limits)?;
+            if id.value == END_MARKER {
+                self.ended = true;
+                break;
+            }
+
+            match find_field(self.fields, id.value)? {
+                Some(name) => {
+                    return seed
+                        .deserialize(IntoDeserializer::<Error>::into_deserializer(name))
+                        .map(Some);
+                }
+                None => buf::decode_skip(self.input, self.limits, id.encoding)?,
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer {
+            input: self.input,
+            limits: self.limits,
+            level: Level::Field,
+        })
+    }
+}
+
+/// Access to the unnamed fields of structs and enum variants.
+///
+/// Fields are expected in the order of their identifiers, but can be absent if they're optional.
+struct TupleFields<'a, 'de> {
+    input: &'a mut &'de [u8],
+    limits: &'a mut Limits,
+    len: usize,
+    index: u32,
+    /// Identifier that was already read, but not yet processed.
+    next: Option<FieldId>,
+}
+
+impl<'a, 'de> TupleFields<'a, 'de> {
+    fn new(
+        input: &'a mut &'de [u8],
+        limits: &'a mut Limits,
+        len: usize,
+        next: Option<FieldId>,
+    ) -> Self {
+        Self {
+            input,
+            limits,
+            len,
+            index: 0,
+            next,
+        }
+    }
+
+    fn next_id(&mut self) -> Result<FieldId> {
+        match self.next {
+            Some(id) => Ok(id),
+            None => Ok(*self.next.insert(buf::decode_id(self.input, self.limits)?)),
+        }
+    }
+
+    fn next_element<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.index += 1;
+
+        loop {
+            let id = self.next_id()?;
+            if id.value == END_MARKER || id.value > self.index {
+                return seed.deserialize(Missing(self.index));
+            }
+
+            self.next = None;
+
+            if id.value == self.index {
+                return seed.deserialize(Deserializer {
+                    input: self.input,
+                    limits: self.limits,
+                    level: Level::Field,
+                });
+            }
+
+            buf::decode_skip(self.input, self.limits, id.encoding)?;
+        }
+    }
+
+    /// Skip over any remaining fields, including the end marker.
+    fn finish(&mut self) -> Result<()> {
+        loop {
+            let id = self.next_id()?;
+            self.next = None;
+
+            if id.value == END_MARKER {
+                return Ok(());
+            }
+
+            buf::decode_skip(self.input, self.limits, id.encoding)?;
+        }
+    }
+
+    fn visit<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let value = visitor.visit_seq(&mut self)?;
+        self.finish()?;
+        Ok(value)
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for TupleFields<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.index as usize >= self.len {
+            return Ok(None);
+        }
+
+        self.next_element(seed).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index as usize)
+    }
+}
+
+/// Deserializes a newtype struct from the content of its single field.
+struct NewtypeSeed<V>(V);
+
+impl<'de, V> DeserializeSeed<'de> for NewtypeSeed<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0.visit_newtype_struct(deserializer)
+    }
+}
+
+/// Placeholder for an unnamed field that is absent from the payload, which is only valid for
+/// optional fields.
+struct Missing(u32);
+
+impl<'de> de::Deserializer<'de> for Missing {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(buf::Error::MissingField {
+            id: self.0,
+            name: None,
+        }
+        .into())
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Access to the variant of an enum.
+struct Enum<'a, 'de> {
+    input: &'a mut &'de [u8],
+    limits: &'a mut Limits,
+    variants: &'static [&'static str],
+    encoding: VariantEncoding,
+}
+
+impl<'a, 'de> Enum<'a, 'de> {
+    /// Identifier to start the field decoding with, which immediately ends it for unit variants.
+    fn first_id(&self) -> Option<FieldId> {
+        (self.encoding == VariantEncoding::Unit)
+            .then_some(FieldId::new(END_MARKER, FieldEncoding::Varint))
+    }
+
+    fn tuple_fields(self, len: usize) -> TupleFields<'a, 'de> {
+        let next = self.first_id();
+        TupleFields::new(self.input, self.limits, len, next)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for Enum<'_, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let id = buf::decode_variant_id(self.input, self.limits)?;
+        let name =
+            find_variant(self.variants, id.value)?.ok_or(buf::Error::UnknownVariant(id.value))?;
+
+        self.encoding = id.encoding;
+        Ok((
+            seed.deserialize(IntoDeserializer::<Error>::into_deserializer(name))?,
+            self,
+        ))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Enum<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        self.tuple_fields(0).finish()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut fields = self.tuple_fields(1);
+        let value = fields.next_element(seed)?;
+        fields.finish()?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.tuple_fields(len).visit(visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let ended = self.encoding == VariantEncoding::Unit;
+        visitor.visit_map(StructFields {
+            input: self.input,
+            limits: self.limits,
+            fields,
+            ended,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fmt};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::Encode;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[expect(clippy::option_option)]
+    struct Sample<'a> {
+        a: u32,
+        b: bool,
+        c: (String, i16),
+        #[serde(rename = "d@5")]
+        d: Option<Option<u8>>,
+        #[serde(borrow)]
+        e: Vec<&'a str>,
+        f: HashMap<u8, f64>,
+        g: Option<(u16, u16)>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum Variants {
+        Unit,
+        Newtype(u32),
+        Tuple(Option<u32>, i64),
+        #[serde(rename = "Named@7")]
+        Named {
+            value: String,
+        },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Wrapper(Variants);
+
+    fn roundtrip<'de, T>(buf: &'de mut Vec<u8>, value: &T)
+    where
+        T: fmt::Debug + PartialEq + Deserialize<'de> + Serialize,
+    {
+        *buf = to_vec(value).unwrap();
+        assert_eq!(*value, from_slice::<T>(buf).unwrap());
+    }
+
+    #[test]
+    fn roundtrip_struct() {
+        let mut buf = Vec::new();
+        roundtrip(
+            &mut buf,
+            &Sample {
+                a: 5,
+                b: true,
+                c: ("test".to_owned(), -2),
+                d: Some(None),
+                e: vec!["a", "b"],
+                f: HashMap::from([(1, 1.5)]),
+                g: None,
+            },
+        );
+    }
+
+    #[test]
+    fn roundtrip_enum() {
+        for value in [
+            Variants::Unit,
+            Variants::Newtype(5),
+            Variants::Tuple(None, -5),
+            Variants::Tuple(Some(1), 2),
+            Variants::Named {
+                value: "test".to_owned(),
+            },
+        ] {
+            let mut buf = Vec::new();
+            roundtrip(&mut buf, &Wrapper(value));
+        }
+    }
+
+    #[test]
+    fn explicit_id() {
+        let buf = to_vec(&Variants::Named {
+            value: String::new(),
+        })
+        .unwrap();
+
+        let mut expect = Vec::new();
+        buf::encode_variant_id(&mut expect, VariantId::new(7, VariantEncoding::Fields));
+        buf::encode_id(&mut expect, FieldId::new(1, FieldEncoding::LengthPrefixed));
+        buf::encode_string(&mut expect, "");
+        buf::encode_u32(&mut expect, END_MARKER);
+
+        assert_eq!(expect, buf);
+    }
+
+    #[test]
+    fn compatible_primitives() {
+        let value = (
+            vec![Some(1_u16), None],
+            HashMap::from([("key".to_owned(), vec![1_u8, 2])]),
+        );
+
+        let mut expect = Vec::new();
+        value.0.encode(&mut expect);
+        value.1.encode(&mut expect);
+
+        assert_eq!(expect, to_vec(&value).unwrap());
+    }
+
+    #[test]
+    fn skip_unknown_fields() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Old {
+            a: u32,
+        }
+
+        let buf = to_vec(&Sample {
+            a: 1,
+            b: false,
+            c: (String::new(), 0),
+            d: None,
+            e: Vec::new(),
+            f: HashMap::new(),
+            g: Some((1, 2)),
+        })
+        .unwrap();
+
+        assert_eq!(Old { a: 1 }, from_slice(&buf).unwrap());
+    }
+
+    #[test]
+    fn missing_field() {
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Required(Option<u32>, u32);
+
+        let mut buf = Vec::new();
+        buf::encode_u32(&mut buf, END_MARKER);
+
+        assert!(matches!(
+            from_slice::<Required>(&buf),
+            Err(Error::Decode(buf::Error::MissingField {
+                id: 2,
+                name: None
+            }))
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! Transport-agnostic building blocks for the services that are declared in a schema.
+//!
+//! The code generator creates a trait for every service, together with a client that implements
+//! the trait by sending encoded messages through a [`Transport`], and a server that implements
+//! [`Handler`] by decoding incoming messages and dispatching them to any implementation of the
+//! trait.
+//!
+//! Moving the messages between client and server, like over HTTP or a plain TCP connection, is up
+//! to the transport. Every call sends a stream of encoded requests and receives a stream of encoded
+//! responses. Methods without the `stream` marker simply send exactly one message.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    future::{Future, poll_fn},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{Bytes, Decode, Encode};
+
+/// Description of a single method of a service, which transports can use to route a call.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Method {
+    /// Name of the service that the method belongs to.
+    pub service: &'static str,
+    /// Name of the method itself.
+    pub name: &'static str,
+    /// Identifier of the method, which is unique within the service.
+    pub id: u32,
+    /// Whether the client sends a stream of requests, instead of exactly one.
+    pub request_stream: bool,
+    /// Whether the server answers with a stream of responses, instead of exactly one.
+    pub response_stream: bool,
+}
+
+/// Possible errors that can occur during a call to a service method.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A request or response message failed to decode.
+    #[error("failed to decode a message")]
+    Decode(#[from] crate::buf::Error),
+    /// The called method doesn't exist in the service.
+    #[error("unknown method with ID {0}")]
+    UnknownMethod(u32),
+    /// The stream of messages ended, before the single message that the method expects.
+    #[error("expected exactly one message, but the stream ended")]
+    MissingMessage,
+    /// Failure in the transport, or in the implementation of the service itself.
+    #[error(transparent)]
+    Other(Box<dyn core::error::Error + Send + Sync>),
+}
+
+/// Sequence of values that become available over time, like the requests or responses of a
+/// streaming method.
+///
+/// The trait has the same shape as the `Stream` trait of the `futures` crate, so adapting between
+/// both is straightforward.
+pub trait Stream {
+    /// Type of the values in the stream.
+    type Item;
+
+    /// Try to get the next value, registering the current task for wakeup if it isn't available
+    /// yet. Returns `Poll::Ready(None)` once the stream is exhausted.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+/// Type-erased stream of values, each of which might have failed.
+pub type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>>;
+
+/// Connection to a remote service, that moves the encoded messages of a call back and forth.
+pub trait Transport: Send + Sync {
+    /// Call the method with a stream of encoded requests, and receive the stream of encoded
+    /// responses.
+    fn call(
+        &self,
+        method: &'static Method,
+        requests: BoxStream<Bytes>,
+    ) -> impl Future<Output = Result<BoxStream<Bytes>, Error>> + Send;
+}
+
+/// Server side of a service, that decodes the requests of a call and dispatches them to the
+/// service implementation.
+pub trait Handler: Send + Sync {
+    /// Descriptions of all methods that the service provides.
+    const METHODS: &'static [Method];
+
+    /// Handle a call of the method with the given ID, and answer with a stream of encoded
+    /// responses.
+    fn handle(
+        &self,
+        method: u32,
+        requests: BoxStream<Bytes>,
+    ) -> impl Future<Output = Result<BoxStream<Bytes>, Error>> + Send;
+}
+
+/// Wait for the next value of the stream.
+pub async fn next<T>(stream: &mut BoxStream<T>) -> Option<Result<T, Error>> {
+    poll_fn(|cx| stream.as_mut().poll_next(cx)).await
+}
+
+/// Create a stream from the values of an iterator, which are all available right away.
+#[must_use]
+pub fn iter<I>(values: I) -> BoxStream<I::Item>
+where
+    I: IntoIterator,
+    I::IntoIter: Send + Unpin + 'static,
+{
+    Box::pin(Iter(values.into_iter()))
+}
+
+/// Create a stream that yields exactly one value.
+#[must_use]
+pub fn once<T: Send + Unpin + 'static>(value: T) -> BoxStream<T> {
+    iter(Some(value))
+}
+
+/// Encode a value as a single message.
+#[must_use]
+pub fn encode<T: Encode>(value: &T) -> Bytes {
+    let mut buf = Vec::with_capacity(value.size());
+    value.encode(&mut buf);
+    buf.into()
+}
+
+/// Decode a value from a single message.
+///
+/// # Errors
+///
+/// Will return `Err` if the message isn't a valid encoding of the value.
+pub fn decode<T: Decode>(mut message: Bytes) -> Result<T, Error> {
+    T::decode(&mut message).map_err(Into::into)
+}
+
+/// Encode each value of the stream as a separate message.
+#[must_use]
+pub fn encode_stream<T: Encode + Send + 'static>(values: BoxStream<T>) -> BoxStream<Bytes> {
+    Box::pin(Map(values, |value: T| Ok(encode(&value))))
+}
+
+/// Decode each message of the stream as a separate value.
+#[must_use]
+pub fn decode_stream<T: Decode + Send + 'static>(messages: BoxStream<Bytes>) -> BoxStream<T> {
+    Box::pin(Map(messages, decode))
+}
+
+/// Take the only message of the stream, for methods that expect exactly one. Any further messages
+/// are ignored.
+///
+/// # Errors
+///
+/// Will return `Err` if the stream fails, or ends without any message.
+pub async fn single(mut messages: BoxStream<Bytes>) -> Result<Bytes, Error> {
+    next(&mut messages)
+        .await
+        .unwrap_or(Err(Error::MissingMessage))
+}
+
+struct Iter<I>(I);
+
+impl<I: Iterator + Unpin> Stream for Iter<I> {
+    type Item = Result<I::Item, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().0.next().map(Ok))
+    }
+}
+
+struct Map<T, F>(BoxStream<T>, F);
+
+impl<T, U, F> Stream for Map<T, F>
+where
+    F: FnMut(T) -> Result<U, Error> + Unpin,
+{
+    type Item = Result<U, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let Self(stream, map) = self.get_mut();
+        stream
+            .as_mut()
+            .poll_next(cx)
+            .map(|item| item.map(|value| value.and_then(&mut *map)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::task::Waker;
+
+    use super::*;
+
+    fn poll<F: Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is not ready"),
+        }
+    }
+
+    #[test]
+    fn roundtrip_stream() {
+        let messages = encode_stream(iter([1_u32, 2, 3]));
+        let mut values = decode_stream::<u32>(messages);
+
+        assert_eq!(Some(1), poll(next(&mut values)).transpose().unwrap());
+        assert_eq!(Some(2), poll(next(&mut values)).transpose().unwrap());
+        assert_eq!(Some(3), poll(next(&mut values)).transpose().unwrap());
+        assert_eq!(None, poll(next(&mut values)).transpose().unwrap());
+    }
+
+    #[test]
+    fn single_missing() {
+        let result = poll(single(iter(None)));
+        assert!(matches!(result, Err(Error::MissingMessage)));
+    }
+}
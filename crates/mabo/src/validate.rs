@@ -0,0 +1,169 @@
+//! Checks for the constraints that are declared with the `#[validate(...)]` attribute on the fields
+//! of a schema.
+//!
+//! The code generator implements [`Validate`] for every struct and enum that declares any rules.
+//! It can optionally run the checks right after decoding, in which case any violations are
+//! reported as [`buf::Error::Validation`](crate::buf::Error::Validation).
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+/// Check the constraints of a value, that were declared in its schema.
+pub trait Validate {
+    /// Check all declared constraints of the value's fields.
+    ///
+    /// Nested structs and enums are not checked as part of this value. They have their own
+    /// implementation, which is called when decoding them with validation enabled.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with every constraint that the value doesn't fulfill.
+    fn validate(&self) -> Result<(), Violations>;
+}
+
+/// List of all constraints that a value failed to fulfill.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Violations(pub Vec<Violation>);
+
+impl Violations {
+    /// Create a new, empty list of violations.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Record a violation of the rule for the given field, unless the value is `valid`.
+    #[inline]
+    pub fn check(
+        &mut self,
+        valid: bool,
+        field: &'static str,
+        variant: Option<&'static str>,
+        rule: Rule,
+    ) {
+        if !valid {
+            self.0.push(Violation {
+                field,
+                variant,
+                rule,
+            });
+        }
+    }
+
+    /// Turn the list into a result, which is only `Ok` if there are no violations.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there is at least one violation.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.0.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl Display for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, violation) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::error::Error for Violations {}
+
+/// Single constraint that a field of a value failed to fulfill.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    /// Name of the field.
+    pub field: &'static str,
+    /// Name of the enum variant that the field belongs to, if it's not a struct field.
+    pub variant: Option<&'static str>,
+    /// The rule that was violated.
+    pub rule: Rule,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "field `{}`", self.field)?;
+        if let Some(variant) = self.variant {
+            write!(f, " of variant `{variant}`")?;
+        }
+
+        match self.rule {
+            Rule::Min(value) => write!(f, " must be at least {value}"),
+            Rule::Max(value) => write!(f, " must be at most {value}"),
+            Rule::MinLen(len) => write!(f, " must have a length of at least {len}"),
+            Rule::MaxLen(len) => write!(f, " must have a length of at most {len}"),
+            Rule::Pattern(pattern) => write!(f, " must match the pattern `{pattern}`"),
+            Rule::NonEmpty => f.write_str(" must not be empty"),
+        }
+    }
+}
+
+/// Possible rules of the `#[validate(...)]` attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rule {
+    /// Smallest allowed value of a number, as written in the schema.
+    Min(&'static str),
+    /// Largest allowed value of a number, as written in the schema.
+    Max(&'static str),
+    /// Smallest allowed length of a string (in characters), bytes or collection.
+    MinLen(u64),
+    /// Largest allowed length of a string (in characters), bytes or collection.
+    MaxLen(u64),
+    /// Regular expression that a string must match.
+    Pattern(&'static str),
+    /// String, bytes or collection must not be empty.
+    NonEmpty,
+}
+
+/// Check the constraints of a freshly decoded value, which is used by the generated code if
+/// validation on decode is enabled.
+///
+/// # Errors
+///
+/// Will return `Err` if the value violates any of its constraints.
+#[inline]
+pub fn validate_decoded<T: Validate>(value: T) -> crate::buf::Result<T> {
+    value
+        .validate()
+        .map(|()| value)
+        .map_err(crate::buf::Error::Validation)
+}
+
+/// Lazily compiled regular expression, that backs the `pattern` rule in generated code.
+#[cfg(feature = "regex")]
+pub struct Pattern {
+    pattern: &'static str,
+    regex: std::sync::OnceLock<regex::Regex>,
+}
+
+#[cfg(feature = "regex")]
+impl Pattern {
+    /// Create a new pattern, which is compiled on first use.
+    #[must_use]
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Whether the pattern matches anywhere in the value. Use `^` and `$` anchors to match the
+    /// full value.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern is not a valid regular expression. Patterns in a schema are
+    /// already checked by the compiler.
+    #[must_use]
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex
+            .get_or_init(|| regex::Regex::new(self.pattern).expect("invalid validation pattern"))
+            .is_match(value)
+    }
+}
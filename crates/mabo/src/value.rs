@@ -0,0 +1,115 @@
+//! Dynamically typed representation of Mabo values, for cases where no code was generated from a
+//! schema.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::time::Duration;
+
+use crate::Decimal;
+
+/// Single dynamically typed value, that can represent any data type of a Mabo schema.
+///
+/// Several schema types share the same representation, as the schema is always needed to en- or
+/// decode a value anyway:
+///
+/// - `vec<T>`, `hash_set<T>`, `btree_set<T>` and `[T; N]` are all represented as [`Value::Vec`].
+/// - `hash_map<K, V>` and `btree_map<K, V>` are both represented as [`Value::Map`].
+/// - `string`, `&string` and `box<string>` are all represented as [`Value::String`].
+/// - `bytes`, `&bytes` and `box<bytes>` are all represented as [`Value::Bytes`].
+/// - `non_zero<T>` is represented the same as its inner type `T`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Boolean `true` or `false`.
+    Bool(bool),
+    /// 8-bit unsigned integer.
+    U8(u8),
+    /// 16-bit unsigned integer.
+    U16(u16),
+    /// 32-bit unsigned integer.
+    U32(u32),
+    /// 64-bit unsigned integer.
+    U64(u64),
+    /// 128-bit unsigned integer.
+    U128(u128),
+    /// 8-bit signed integer.
+    I8(i8),
+    /// 16-bit signed integer.
+    I16(i16),
+    /// 32-bit signed integer.
+    I32(i32),
+    /// 64-bit signed integer.
+    I64(i64),
+    /// 128-bit signed integer.
+    I128(i128),
+    /// 32-bit floating point number.
+    F32(f32),
+    /// 64-bit floating point number.
+    F64(f64),
+    /// UTF-8 encoded string.
+    String(String),
+    /// Raw byte array.
+    Bytes(Vec<u8>),
+    /// Point in time.
+    Timestamp {
+        /// Seconds since the Unix epoch.
+        seconds: i64,
+        /// Nanoseconds on top of the seconds, always less than a full second.
+        nanos: u32,
+    },
+    /// Span of time.
+    Duration(Duration),
+    /// Universally unique identifier.
+    Uuid([u8; 16]),
+    /// Decimal number with a fixed amount of fractional digits.
+    Decimal(Decimal),
+    /// List of values, all of the same type.
+    Vec(Vec<Value>),
+    /// Key-value pairs of a map, in the order they were decoded.
+    Map(Vec<(Value, Value)>),
+    /// Optional value.
+    Option(Option<Box<Value>>),
+    /// Fixed size list of values with different types.
+    Tuple(Vec<Value>),
+    /// Fields of a struct.
+    Struct(Vec<Field>),
+    /// Single variant of an enum.
+    Enum(Variant),
+}
+
+/// Single field of a struct or enum variant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    /// Identifier of the field.
+    pub id: u32,
+    /// Name of the field, if it is a named field.
+    pub name: Option<String>,
+    /// The field's value.
+    ///
+    /// Optional fields are always represented as [`Value::Option`] and may be omitted entirely
+    /// when encoding.
+    pub value: Value,
+}
+
+/// Variant of an enum, together with its fields.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variant {
+    /// Identifier of the variant.
+    pub id: u32,
+    /// Name of the variant.
+    pub name: String,
+    /// Fields of the variant, which is empty for unit variants.
+    pub fields: Vec<Field>,
+}
+
+impl Value {
+    /// Get the field with the given name, if this is a struct.
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Self::Struct(fields) => fields
+                .iter()
+                .find(|field| field.name.as_deref() == Some(name))
+                .map(|field| &field.value),
+            _ => None,
+        }
+    }
+}
@@ -51,14 +51,14 @@ zigzag!(
 /// Calculate the maximum amount of bytes that an integer might require to be encoded as _varint_.
 #[inline]
 const fn max_size<T>() -> usize {
-    (std::mem::size_of::<T>() * 8).div_ceil(7)
+    (core::mem::size_of::<T>() * 8).div_ceil(7)
 }
 
 #[inline]
 const fn size<T>(leading_zeros: usize) -> usize {
     max(
         1,
-        (std::mem::size_of::<T>() * 8 - leading_zeros).div_ceil(7),
+        (core::mem::size_of::<T>() * 8 - leading_zeros).div_ceil(7),
     )
 }
 
@@ -110,6 +110,20 @@ macro_rules! varint {
                 Err(DecodeIntError)
             }
 
+            #[doc = "Decode a _Varint_ back to a `" $ty "`, like [`decode_" $ty "`], but reject it if"]
+            /// it uses more bytes than needed.
+            ///
+            /// # Errors
+            ///
+            /// Will return `Err` if the raw bytes don't contain an end marker within the possible
+            /// maximum byte count valid for the integer, or the integer isn't minimally encoded.
+            #[inline]
+            pub fn [<decode_ $ty _strict>](buf: &[u8]) -> Result<($ty, usize), StrictDecodeIntError> {
+                let (value, consumed) = [<decode_ $ty>](buf)?;
+                ensure_minimal(buf, consumed)?;
+                Ok((value, consumed))
+            }
+
             #[doc = "Calculate the byte size of a `" $ty "` encoded as _Varint_."]
             #[inline]
             #[must_use]
@@ -135,6 +149,20 @@ macro_rules! varint {
                 [<decode_ $ty>](buf).map(|(v, b)| ([<zigzag_decode_ $signed>](v), b))
             }
 
+            #[doc = "Decode a _Varint_ back to a `" $signed "`, like [`decode_" $signed "`], but reject it"]
+            /// if it uses more bytes than needed.
+            ///
+            /// # Errors
+            ///
+            /// Will return `Err` if the raw bytes don't contain an end marker within the possible
+            /// maximum byte count valid for the integer, or the integer isn't minimally encoded.
+            #[inline]
+            pub fn [<decode_ $signed _strict>](
+                buf: &[u8],
+            ) -> Result<($signed, usize), StrictDecodeIntError> {
+                [<decode_ $ty _strict>](buf).map(|(v, b)| ([<zigzag_decode_ $signed>](v), b))
+            }
+
             #[doc = "Calculate the byte size of a `" $signed "` encoded as _Varint_."]
             #[inline]
             #[must_use]
@@ -170,6 +198,21 @@ macro_rules! varint {
                     }
                 }
 
+                #[test]
+                fn [<strict_ $ty>]() {
+                    let (buf, size) = [<encode_ $ty>]($ty::MAX);
+                    assert!([<decode_ $ty _strict>](&buf[..size]).is_ok());
+                    assert!([<decode_ $ty _strict>](&[0]).is_ok());
+                    assert!(matches!(
+                        [<decode_ $ty _strict>](&[0x81, 0]),
+                        Err(StrictDecodeIntError::NonMinimal)
+                    ));
+                    assert!(matches!(
+                        [<decode_ $signed _strict>](&[0x80, 0x80, 0]),
+                        Err(StrictDecodeIntError::NonMinimal)
+                    ));
+                }
+
                 #[test]
                 fn [<sizecheck_ $ty>]() {
                     assert_eq!(1, [<size_ $ty>]($ty::MIN));
@@ -197,6 +240,29 @@ varint!((u16, i16), (u32, i32), (u64, i64), (u128, i128));
 #[error("input was lacking a final marker for the end of the integer data")]
 pub struct DecodeIntError;
 
+/// Ensure that the last byte of a _Varint_, that spans more than one byte, carries any bits of the
+/// integer. Otherwise, the same integer could have been encoded with fewer bytes.
+#[inline]
+fn ensure_minimal(buf: &[u8], consumed: usize) -> Result<(), StrictDecodeIntError> {
+    if consumed > 1 && buf[consumed - 1] == 0 {
+        Err(StrictDecodeIntError::NonMinimal)
+    } else {
+        Ok(())
+    }
+}
+
+/// Error that can happen when trying to decode a _Varint_ in strict mode, where only the shortest
+/// possible encoding of an integer is accepted.
+#[derive(Debug, Error)]
+pub enum StrictDecodeIntError {
+    /// The integer failed to decode in the first place.
+    #[error(transparent)]
+    Decode(#[from] DecodeIntError),
+    /// The integer was encoded with more bytes than needed.
+    #[error("integer data was not minimally encoded")]
+    NonMinimal,
+}
+
 #[cfg(test)]
 mod tests {
     #[test]